@@ -68,6 +68,9 @@ pub struct TaskPlan {
     pub runtime_recommendation: String,
     /// Human-readable time estimate (e.g., "~3 minutes").
     pub estimated_duration: String,
+    /// Template the plan was loaded from, recorded on the session for insights.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
 }
 
 /// Error returned when task analysis fails.
@@ -353,6 +356,7 @@ fn build_team_plan(task: &str, runtime: &str) -> TaskPlan {
         task_graph: nodes,
         runtime_recommendation: runtime.to_string(),
         estimated_duration: format!("~{estimated_minutes} minutes"),
+        template_id: None,
     }
 }
 
@@ -414,6 +418,7 @@ pub fn build_solo_plan(task: &str, runtime: &str) -> TaskPlan {
         }],
         runtime_recommendation: runtime.to_string(),
         estimated_duration: "~1 minute".to_string(),
+        template_id: None,
     }
 }
 
@@ -485,6 +490,7 @@ mod tests {
             task_graph: vec![],
             runtime_recommendation: "claude-code".to_string(),
             estimated_duration: "~3 minutes".to_string(),
+            template_id: None,
         };
         let json = serde_json::to_string(&plan).unwrap();
         assert!(json.contains("\"agentCount\""));
@@ -528,6 +534,7 @@ mod tests {
             ],
             runtime_recommendation: "claude-code".to_string(),
             estimated_duration: "~5 minutes".to_string(),
+            template_id: None,
         };
 
         let json = serde_json::to_string(&plan).unwrap();
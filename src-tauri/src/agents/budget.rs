@@ -0,0 +1,463 @@
+// Budget enforcement — spending caps per session, per project per day and month, and
+// across all projects, with warnings as spend approaches a cap.
+//
+// Finished sessions count with their recorded `cost_estimate`. Running sessions, print-mode
+// and PTY, count with live spend from their usage events, priced with the model pricing
+// table (see pricing.rs). Claude reports a print run's usage only in its final result, so
+// until then its spend is estimated from the usage of each streamed message.
+// commands/budget.rs refuses to start runs once a cap is reached and stops running
+// sessions that reach one.
+
+use crate::agents::events::ElfEvent;
+use crate::agents::pricing::{PricingTable, TokenUsage, UsageLedger, UNKNOWN_MODEL};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Spending caps in USD. A cap of None is not enforced.
+///
+/// Set per project in `.elves/config.json`, and app-wide in `~/.elves/settings.json`,
+/// where the daily and monthly caps cover all projects together.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BudgetLimits {
+    pub enabled: bool,
+    /// Most a single session may spend.
+    pub session_usd: Option<f64>,
+    /// Most that may be spent per UTC day.
+    pub daily_usd: Option<f64>,
+    /// Most that may be spent per UTC calendar month.
+    pub monthly_usd: Option<f64>,
+    /// Fractions of a cap (e.g. 0.8) at which a warning is emitted, once per period.
+    pub warn_at: Vec<f64>,
+}
+
+impl Default for BudgetLimits {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            session_usd: None,
+            daily_usd: None,
+            monthly_usd: None,
+            warn_at: vec![0.8],
+        }
+    }
+}
+
+/// Which cap a verdict is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Session,
+    ProjectDaily,
+    ProjectMonthly,
+    GlobalDaily,
+    GlobalMonthly,
+}
+
+impl BudgetScope {
+    /// Which cap this is, for messages and session summaries.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Session => "session budget",
+            Self::ProjectDaily => "project daily budget",
+            Self::ProjectMonthly => "project monthly budget",
+            Self::GlobalDaily => "daily budget",
+            Self::GlobalMonthly => "monthly budget",
+        }
+    }
+}
+
+/// Spend in USD counted against each cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Spend {
+    pub session: f64,
+    pub project_day: f64,
+    pub project_month: f64,
+    pub global_day: f64,
+    pub global_month: f64,
+}
+
+/// A cap reached or approached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetVerdict {
+    /// Spend passed `threshold` (a fraction) of the cap.
+    Warning { scope: BudgetScope, threshold: f64, spent: f64, limit: f64 },
+    /// Spend reached the cap; the session should be stopped.
+    Exceeded { scope: BudgetScope, spent: f64, limit: f64 },
+}
+
+/// Every enabled cap with the spend counted against it. The session cap comes from the
+/// project, falling back to the app-wide one.
+fn caps(project: &BudgetLimits, global: &BudgetLimits, spend: &Spend) -> Vec<(BudgetScope, f64, f64, Vec<f64>)> {
+    let mut caps = Vec::new();
+    let mut push = |limits: &BudgetLimits, scope, limit: Option<f64>, spent| {
+        if let (true, Some(limit)) = (limits.enabled, limit) {
+            caps.push((scope, spent, limit, limits.warn_at.clone()));
+        }
+    };
+    let session_limits = if project.enabled && project.session_usd.is_some() { project } else { global };
+    push(session_limits, BudgetScope::Session, session_limits.session_usd, spend.session);
+    push(project, BudgetScope::ProjectDaily, project.daily_usd, spend.project_day);
+    push(project, BudgetScope::ProjectMonthly, project.monthly_usd, spend.project_month);
+    push(global, BudgetScope::GlobalDaily, global.daily_usd, spend.global_day);
+    push(global, BudgetScope::GlobalMonthly, global.monthly_usd, spend.global_month);
+    caps
+}
+
+/// Check spend against every enabled cap. Each reached cap is reported as exceeded;
+/// for the others, the highest warning threshold passed is reported.
+pub fn check(project: &BudgetLimits, global: &BudgetLimits, spend: &Spend) -> Vec<BudgetVerdict> {
+    caps(project, global, spend)
+        .into_iter()
+        .filter_map(|(scope, spent, limit, warn_at)| {
+            if spent >= limit {
+                return Some(BudgetVerdict::Exceeded { scope, spent, limit });
+            }
+            warn_at
+                .into_iter()
+                .filter(|threshold| spent >= limit * threshold)
+                .max_by(f64::total_cmp)
+                .map(|threshold| BudgetVerdict::Warning { scope, threshold, spent, limit })
+        })
+        .collect()
+}
+
+/// How much a new session may spend before reaching a cap, or None if no cap is
+/// enabled. `spend.session` is ignored. Zero means a cap is already reached.
+pub fn remaining(project: &BudgetLimits, global: &BudgetLimits, spend: &Spend) -> Option<f64> {
+    let spend = Spend { session: 0.0, ..*spend };
+    caps(project, global, &spend)
+        .into_iter()
+        .map(|(_, spent, limit, _)| (limit - spent).max(0.0))
+        .min_by(f64::total_cmp)
+}
+
+struct Running {
+    project_id: String,
+    /// Recorded cost of the session's finished attempts and runs, already counted in the
+    /// database.
+    prior_usd: f64,
+    runs: Vec<Run>,
+}
+
+impl Running {
+    /// Live spend of the session's runs.
+    fn spent(&self) -> f64 {
+        self.runs.iter().map(Run::spent).sum()
+    }
+
+    fn run(&mut self, pty_id: Option<&str>) -> Option<&mut Run> {
+        self.runs.iter_mut().find(|run| run.pty_id.as_deref() == pty_id)
+    }
+}
+
+/// One agent run of a session: its print-mode process, or one of its PTYs.
+struct Run {
+    pty_id: Option<String>,
+    usage: UsageLedger,
+    pricing: PricingTable,
+    /// Estimated cost of each Claude message so far, by message ID, until the run's
+    /// result reports its usage.
+    messages: HashMap<String, f64>,
+}
+
+impl Run {
+    fn new(pty_id: Option<String>, pricing: PricingTable, model: Option<String>) -> Self {
+        Self {
+            pty_id,
+            usage: UsageLedger::new(pricing.clone(), model),
+            pricing,
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Spend of this run: its reported usage, or the per-message estimate while that is
+    /// higher.
+    fn spent(&self) -> f64 {
+        self.usage.total_cost().max(self.messages.values().sum())
+    }
+}
+
+/// Live spend of running sessions, print-mode and PTY, and the warnings already given.
+/// Managed as Tauri app state.
+pub struct BudgetTracker {
+    sessions: Mutex<HashMap<String, Running>>,
+    warned: Mutex<HashSet<String>>,
+}
+
+impl BudgetTracker {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            warned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Start tracking a print-mode run of a session, pricing usage of `model` (unless the
+    /// runtime reports another) with `pricing`. `prior_usd` is what the session's earlier
+    /// attempts already spent, so a retried session keeps counting against its cap.
+    pub fn track(
+        &self,
+        session_id: &str,
+        project_id: &str,
+        prior_usd: f64,
+        pricing: PricingTable,
+        model: Option<String>,
+    ) {
+        let mut sessions = self.sessions.lock().expect("Budget lock poisoned");
+        sessions.insert(
+            session_id.to_string(),
+            Running {
+                project_id: project_id.to_string(),
+                prior_usd,
+                runs: vec![Run::new(None, pricing, model)],
+            },
+        );
+    }
+
+    /// Start tracking one PTY of a session. Team sessions add a PTY per role.
+    pub fn track_pty(
+        &self,
+        session_id: &str,
+        project_id: &str,
+        prior_usd: f64,
+        pty_id: &str,
+        pricing: PricingTable,
+        model: Option<String>,
+    ) {
+        let mut sessions = self.sessions.lock().expect("Budget lock poisoned");
+        let running = sessions.entry(session_id.to_string()).or_insert_with(|| Running {
+            project_id: project_id.to_string(),
+            prior_usd,
+            runs: Vec::new(),
+        });
+        running.runs.push(Run::new(Some(pty_id.to_string()), pricing, model));
+    }
+
+    /// Stop tracking a session, once its spend is recorded in the database.
+    pub fn untrack(&self, session_id: &str) {
+        self.sessions.lock().expect("Budget lock poisoned").remove(session_id);
+    }
+
+    /// Stop tracking a PTY of a session, once its spend is recorded in the database. The
+    /// session is dropped with its last PTY; until then the PTY's spend still counts
+    /// against the session's cap.
+    pub fn untrack_pty(&self, session_id: &str, pty_id: &str) {
+        let mut sessions = self.sessions.lock().expect("Budget lock poisoned");
+        let Some(running) = sessions.get_mut(session_id) else {
+            return;
+        };
+        if let Some(index) = running.runs.iter().position(|run| run.pty_id.as_deref() == Some(pty_id)) {
+            let run = running.runs.remove(index);
+            running.prior_usd += run.spent();
+        }
+        if running.runs.is_empty() {
+            sessions.remove(session_id);
+        }
+    }
+
+    /// PTYs of a session being tracked.
+    pub fn pty_ids(&self, session_id: &str) -> Vec<String> {
+        let sessions = self.sessions.lock().expect("Budget lock poisoned");
+        sessions
+            .get(session_id)
+            .map(|running| running.runs.iter().filter_map(|run| run.pty_id.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Add the spend reported by the events of a session's run: its print-mode process,
+    /// or the PTY `pty_id`. Token usage is priced per model; a reported cost only counts
+    /// for runs that report no token counts.
+    /// Returns the session's project and spend so far, earlier attempts included, if it
+    /// changed.
+    pub fn record(&self, session_id: &str, pty_id: Option<&str>, events: &[ElfEvent]) -> Option<(String, f64)> {
+        let mut sessions = self.sessions.lock().expect("Budget lock poisoned");
+        let running = sessions.get_mut(session_id)?;
+        let run = running.run(pty_id)?;
+        let before = run.spent();
+        run.usage.record(None, events);
+        let changed = run.spent() != before;
+        changed.then(|| (running.project_id.clone(), running.prior_usd + running.spent()))
+    }
+
+    /// Add the usage of a Claude message as a print-mode run streams it, before the run's
+    /// result reports it. A message's usage is repeated on each of its lines, so the
+    /// latest one per `message_id` counts. Returns the session's project and spend so far
+    /// if it changed.
+    pub fn record_message(&self, session_id: &str, message_id: &str, usage: &ElfEvent) -> Option<(String, f64)> {
+        let ElfEvent::Usage { input_tokens, output_tokens, cache_read_tokens, cache_write_tokens, model } = usage
+        else {
+            return None;
+        };
+        let tokens = TokenUsage {
+            input_tokens: *input_tokens,
+            output_tokens: *output_tokens,
+            cache_read_tokens: *cache_read_tokens,
+            cache_write_tokens: *cache_write_tokens,
+        };
+        let mut sessions = self.sessions.lock().expect("Budget lock poisoned");
+        let running = sessions.get_mut(session_id)?;
+        let run = running.run(None)?;
+        let before = run.spent();
+        let cost = run.pricing.cost(model.as_deref().unwrap_or(UNKNOWN_MODEL), &tokens);
+        run.messages.insert(message_id.to_string(), cost);
+        let changed = run.spent() != before;
+        changed.then(|| (running.project_id.clone(), running.prior_usd + running.spent()))
+    }
+
+    /// Live spend of running sessions, all or those of one project. Finished attempts and
+    /// runs are left out, as their cost is already recorded.
+    pub fn live_spend(&self, project_id: Option<&str>) -> f64 {
+        let sessions = self.sessions.lock().expect("Budget lock poisoned");
+        sessions
+            .values()
+            .filter(|running| project_id.is_none_or(|id| running.project_id == id))
+            .map(Running::spent)
+            .sum()
+    }
+
+    /// Whether a warning with this key is new. Keys name the cap and its period, so each
+    /// warning is given once.
+    pub fn first_warning(&self, key: String) -> bool {
+        self.warned.lock().expect("Budget lock poisoned").insert(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(session: Option<f64>, daily: Option<f64>, monthly: Option<f64>) -> BudgetLimits {
+        BudgetLimits {
+            enabled: true,
+            session_usd: session,
+            daily_usd: daily,
+            monthly_usd: monthly,
+            warn_at: vec![0.5, 0.8],
+        }
+    }
+
+    #[test]
+    fn disabled_limits_are_never_checked() {
+        let spend = Spend { session: 100.0, project_day: 100.0, ..Spend::default() };
+        let off = BudgetLimits { enabled: false, ..limits(Some(1.0), Some(1.0), None) };
+        assert!(check(&off, &BudgetLimits::default(), &spend).is_empty());
+        assert_eq!(remaining(&off, &BudgetLimits::default(), &spend), None);
+    }
+
+    #[test]
+    fn caps_warn_at_the_highest_threshold_then_exceed() {
+        let project = limits(Some(10.0), Some(20.0), None);
+        let global = limits(None, None, Some(100.0));
+        let spend = Spend { session: 8.5, project_day: 12.0, global_month: 100.0, ..Spend::default() };
+        assert_eq!(
+            check(&project, &global, &spend),
+            vec![
+                BudgetVerdict::Warning { scope: BudgetScope::Session, threshold: 0.8, spent: 8.5, limit: 10.0 },
+                BudgetVerdict::Warning { scope: BudgetScope::ProjectDaily, threshold: 0.5, spent: 12.0, limit: 20.0 },
+                BudgetVerdict::Exceeded { scope: BudgetScope::GlobalMonthly, spent: 100.0, limit: 100.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn session_cap_falls_back_to_the_global_one() {
+        let spend = Spend { session: 5.0, ..Spend::default() };
+        let verdicts = check(&BudgetLimits::default(), &limits(Some(5.0), None, None), &spend);
+        assert_eq!(
+            verdicts,
+            vec![BudgetVerdict::Exceeded { scope: BudgetScope::Session, spent: 5.0, limit: 5.0 }]
+        );
+    }
+
+    #[test]
+    fn remaining_is_the_tightest_cap() {
+        let project = limits(Some(10.0), Some(20.0), Some(200.0));
+        let spend = Spend { session: 9.0, project_day: 15.0, project_month: 150.0, ..Spend::default() };
+        assert_eq!(remaining(&project, &BudgetLimits::default(), &spend), Some(5.0));
+
+        let spent = Spend { project_day: 25.0, ..spend };
+        assert_eq!(remaining(&project, &BudgetLimits::default(), &spent), Some(0.0));
+    }
+
+    #[test]
+    fn tracker_prices_usage_and_takes_reported_costs() {
+        let tracker = BudgetTracker::new();
+        tracker.track("s1", "p1", 0.0, PricingTable::default(), Some("claude-sonnet-4-5".into()));
+        tracker.track("s2", "p2", 0.0, PricingTable::default(), None);
+        let usage = ElfEvent::Usage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            model: None,
+        };
+
+        assert_eq!(tracker.record("s1", None, std::slice::from_ref(&usage)), Some(("p1".to_string(), 4.5)));
+        assert_eq!(tracker.record("s1", None, &[ElfEvent::Text { text: "hi".into() }]), None);
+        let result = ElfEvent::Result { text: None, is_error: false, cost_usd: Some(2.0), duration_ms: None, session_id: None };
+        assert_eq!(tracker.record("s2", None, &[result]), Some(("p2".to_string(), 2.0)));
+
+        assert_eq!(tracker.live_spend(None), 6.5);
+        assert_eq!(tracker.live_spend(Some("p2")), 2.0);
+        tracker.untrack("s1");
+        assert_eq!(tracker.live_spend(None), 2.0);
+        assert_eq!(tracker.record("s1", None, &[]), None);
+
+        assert!(tracker.first_warning("session:s2:0.8".to_string()));
+        assert!(!tracker.first_warning("session:s2:0.8".to_string()));
+    }
+
+    #[test]
+    fn retried_sessions_keep_their_earlier_spend() {
+        let tracker = BudgetTracker::new();
+        tracker.track("s1", "p1", 3.0, PricingTable::default(), None);
+        let result = ElfEvent::Result { text: None, is_error: false, cost_usd: Some(2.0), duration_ms: None, session_id: None };
+
+        assert_eq!(tracker.record("s1", None, &[result]), Some(("p1".to_string(), 5.0)));
+        assert_eq!(tracker.live_spend(Some("p1")), 2.0);
+    }
+
+    #[test]
+    fn claude_messages_count_once_until_the_result_reports_usage() {
+        let tracker = BudgetTracker::new();
+        tracker.track("s1", "p1", 0.0, PricingTable::default(), None);
+        let message = |output| ElfEvent::Usage {
+            input_tokens: 0,
+            output_tokens: output,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            model: Some("claude-sonnet-4-5".into()),
+        };
+
+        assert_eq!(tracker.record_message("s1", "m1", &message(100_000)), Some(("p1".to_string(), 1.5)));
+        assert_eq!(tracker.record_message("s1", "m1", &message(100_000)), None);
+        assert_eq!(tracker.record_message("s1", "m2", &message(100_000)), Some(("p1".to_string(), 3.0)));
+        assert_eq!(tracker.live_spend(None), 3.0);
+
+        let result = ElfEvent::Result { text: None, is_error: false, cost_usd: Some(3.5), duration_ms: None, session_id: None };
+        assert_eq!(tracker.record("s1", None, &[result]), Some(("p1".to_string(), 3.5)));
+    }
+
+    #[test]
+    fn team_ptys_add_up_until_the_last_one_exits() {
+        let tracker = BudgetTracker::new();
+        tracker.track_pty("s1", "p1", 1.0, "pty-1", PricingTable::default(), None);
+        tracker.track_pty("s1", "p1", 1.0, "pty-2", PricingTable::default(), None);
+        assert_eq!(tracker.pty_ids("s1"), vec!["pty-1".to_string(), "pty-2".to_string()]);
+        let cost = |usd| ElfEvent::Result { text: None, is_error: false, cost_usd: Some(usd), duration_ms: None, session_id: None };
+
+        assert_eq!(tracker.record("s1", Some("pty-1"), &[cost(2.0)]), Some(("p1".to_string(), 3.0)));
+        assert_eq!(tracker.record("s1", Some("pty-2"), &[cost(0.5)]), Some(("p1".to_string(), 3.5)));
+        assert_eq!(tracker.record("s1", Some("pty-3"), &[cost(9.0)]), None);
+        assert_eq!(tracker.live_spend(None), 2.5);
+
+        tracker.untrack_pty("s1", "pty-1");
+        assert_eq!(tracker.live_spend(None), 0.5);
+        assert_eq!(tracker.record("s1", Some("pty-2"), &[cost(1.0)]), Some(("p1".to_string(), 4.0)));
+        tracker.untrack_pty("s1", "pty-2");
+        assert!(tracker.pty_ids("s1").is_empty());
+        assert_eq!(tracker.record("s1", Some("pty-2"), &[cost(2.0)]), None);
+    }
+}
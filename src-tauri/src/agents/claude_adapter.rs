@@ -1,7 +1,10 @@
 // Claude Code adapter — spawns Claude CLI as a subprocess and parses its output.
 
 use crate::agents::analyzer::TaskPlan;
+use crate::agents::events::{self, ElfEvent};
+use crate::agents::run_state;
 use crate::agents::runtime;
+use crate::secrets::redact;
 use serde::{Deserialize, Serialize};
 
 /// Options for customizing a Claude Code CLI invocation.
@@ -27,10 +30,14 @@ pub struct ClaudeSpawnOptions {
     pub resume_session_id: Option<String>,
     /// Continue the most recent session (--continue)
     pub continue_session: Option<bool>,
+    /// MCP flags (--mcp-config, --strict-mcp-config) registering the ELVES MCP server and
+    /// gateway-wrapped servers. Set by the backend, never taken from the frontend.
+    #[serde(skip)]
+    pub mcp_args: Vec<String>,
 }
 
 /// A parsed event from Claude Code's output stream.
-/// These are normalized into the ElfEvent format for the frontend.
+/// `events` holds the typed ElfEvents translated from the raw line.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClaudeEvent {
@@ -40,6 +47,9 @@ pub struct ClaudeEvent {
     pub payload: serde_json::Value,
     /// Unix timestamp (seconds since epoch) when the event was received.
     pub timestamp: i64,
+    /// Typed events for this line — empty for lines that carry nothing to show or store.
+    #[serde(default)]
+    pub events: Vec<ElfEvent>,
 }
 
 /// Spawn a Claude Code CLI process in non-interactive (print) mode.
@@ -80,6 +90,8 @@ pub fn spawn_claude(
         .env_remove("CLAUDECODE")
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped());
+    redact::strip_inherited_secrets(&mut cmd);
+    run_state::isolate_process_group(&mut cmd);
 
     cmd.spawn()
 }
@@ -90,7 +102,7 @@ pub fn spawn_claude(
 /// object per line. Lines with a "type" field use that as the event_type.
 /// Lines without a "type" field default to "output".
 /// Non-JSON lines are wrapped as plain text output events.
-/// Empty lines return None.
+/// Empty lines return None. `events` is filled by `events::from_claude`.
 pub fn parse_claude_output(line: &str) -> Option<ClaudeEvent> {
     let trimmed = line.trim();
     if trimmed.is_empty() {
@@ -109,6 +121,7 @@ pub fn parse_claude_output(line: &str) -> Option<ClaudeEvent> {
 
             Some(ClaudeEvent {
                 event_type,
+                events: events::from_claude(&value),
                 payload: value,
                 timestamp: now,
             })
@@ -119,6 +132,7 @@ pub fn parse_claude_output(line: &str) -> Option<ClaudeEvent> {
                 event_type: "output".to_string(),
                 payload: serde_json::json!({ "text": trimmed }),
                 timestamp: now,
+                events: vec![ElfEvent::Text { text: trimmed.to_string() }],
             })
         }
     }
@@ -163,6 +177,8 @@ pub fn spawn_claude_team(
         .env_remove("CLAUDECODE")
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped());
+    redact::strip_inherited_secrets(&mut cmd);
+    run_state::isolate_process_group(&mut cmd);
 
     cmd.spawn()
 }
@@ -196,6 +212,7 @@ fn apply_spawn_options(cmd: &mut std::process::Command, options: &ClaudeSpawnOpt
     if options.continue_session == Some(true) {
         cmd.arg("--continue");
     }
+    cmd.args(&options.mcp_args);
 }
 
 /// Build a structured team prompt from a TaskPlan.
@@ -316,12 +333,24 @@ mod tests {
         assert_eq!(event.event_type, "thinking");
     }
 
+    #[test]
+    fn parse_fills_typed_events() {
+        let line = r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]}}"#;
+        let event = parse_claude_output(line).expect("Should parse assistant event");
+        assert_eq!(event.events.len(), 1);
+        assert_eq!(event.events[0].kind(), "tool_call");
+
+        let plain = parse_claude_output("plain text").expect("Should wrap plain text");
+        assert_eq!(plain.events, vec![ElfEvent::Text { text: "plain text".to_string() }]);
+    }
+
     #[test]
     fn claude_event_serializes_to_camel_case() {
         let event = ClaudeEvent {
             event_type: "test".to_string(),
             payload: serde_json::json!({"key": "value"}),
             timestamp: 1700000000,
+            events: Vec::new(),
         };
         let json = serde_json::to_string(&event).expect("Should serialize");
         assert!(json.contains("eventType"));
@@ -376,6 +405,7 @@ mod tests {
             ],
             runtime_recommendation: "claude-code".to_string(),
             estimated_duration: "~6 minutes".to_string(),
+            template_id: None,
         }
     }
 
@@ -437,6 +467,7 @@ mod tests {
             task_graph: vec![],
             runtime_recommendation: "claude-code".to_string(),
             estimated_duration: "~1 minute".to_string(),
+            template_id: None,
         };
         let prompt = build_team_prompt("Simple task", &plan);
         assert!(prompt.contains("Simple task"));
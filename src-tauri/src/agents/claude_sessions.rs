@@ -0,0 +1,286 @@
+// External Claude sessions — Claude Code sessions started outside ELVES, read from their
+// transcripts under `~/.claude/projects/<encoded-cwd>/<session-id>.jsonl`.
+//
+// A transcript is parsed with the same parser that tails PTY runs (see transcript.rs), so
+// imported sessions get the typed events, usage and session ID of a run ELVES started.
+// commands/external_sessions.rs lists them per project and imports them as sessions.
+
+use crate::agents::events::{self, ElfEvent};
+use crate::agents::pricing::{PricingTable, TokenUsage, UsageLedger};
+use crate::agents::transcript::{claude_project_dirs, TranscriptKind, TranscriptParser};
+use chrono::DateTime;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A parsed Claude Code transcript.
+#[derive(Debug, Clone)]
+pub struct ClaudeTranscript {
+    /// Claude's session ID, as used with `claude --resume`.
+    pub session_id: String,
+    pub first_prompt: Option<String>,
+    /// Unix seconds of the first and last timestamped lines.
+    pub started_at: Option<i64>,
+    pub ended_at: Option<i64>,
+    /// Model of the last assistant message.
+    pub model: Option<String>,
+    /// User prompts and assistant messages. Claude writes a line per content block of a
+    /// message, so assistant messages are counted by ID.
+    pub message_count: u32,
+    /// Typed events with the Unix seconds of the line they came from. Empty for
+    /// transcripts read with `summarize_transcript`.
+    pub events: Vec<(i64, ElfEvent)>,
+    pub usage: UsageLedger,
+}
+
+impl ClaudeTranscript {
+    /// Token counts summed over all models.
+    pub fn tokens(&self) -> TokenUsage {
+        self.usage.usage().iter().fold(TokenUsage::default(), |mut total, entry| {
+            total.input_tokens += entry.tokens.input_tokens;
+            total.output_tokens += entry.tokens.output_tokens;
+            total.cache_read_tokens += entry.tokens.cache_read_tokens;
+            total.cache_write_tokens += entry.tokens.cache_write_tokens;
+            total
+        })
+    }
+
+    /// The last assistant text, used as the session summary.
+    pub fn last_text(&self) -> Option<&str> {
+        self.events.iter().rev().find_map(|(_, event)| match event {
+            ElfEvent::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Add a line's events, timestamped with the line or else the latest time seen.
+    fn record(&mut self, timestamp: Option<i64>, events: Vec<ElfEvent>) {
+        self.usage.record(None, &events);
+        let at = timestamp.or(self.ended_at).unwrap_or_default();
+        for event in events {
+            if let ElfEvent::Init { session_id: Some(id), .. } = &event {
+                if self.session_id.is_empty() {
+                    self.session_id = id.clone();
+                }
+            }
+            self.events.push((at, event));
+        }
+    }
+}
+
+/// Whether `id` can name a transcript file: Claude's session IDs are UUIDs.
+pub fn is_valid_session_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// The transcripts of sessions run in `project_path`.
+pub fn transcript_files(home: &Path, project_path: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = claude_project_dirs(home, project_path)
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// The transcript of one session run in `project_path`, if it exists.
+pub fn find_transcript(home: &Path, project_path: &str, session_id: &str) -> Option<PathBuf> {
+    if !is_valid_session_id(session_id) {
+        return None;
+    }
+    claude_project_dirs(home, project_path)
+        .into_iter()
+        .map(|dir| dir.join(format!("{session_id}.jsonl")))
+        .find(|path| path.is_file())
+}
+
+/// Parse a transcript. `fallback_id` (the file name) is used when no line names the
+/// session. None if the transcript has no messages.
+pub fn parse_transcript(content: &str, fallback_id: &str, pricing: PricingTable) -> Option<ClaudeTranscript> {
+    read_transcript(content, fallback_id, pricing, true)
+}
+
+/// Read what a session list shows of a transcript — its ID, prompt, times, model,
+/// message count and usage — without building its events.
+pub fn summarize_transcript(content: &str, fallback_id: &str, pricing: PricingTable) -> Option<ClaudeTranscript> {
+    read_transcript(content, fallback_id, pricing, false)
+}
+
+fn read_transcript(content: &str, fallback_id: &str, pricing: PricingTable, with_events: bool) -> Option<ClaudeTranscript> {
+    let mut parser = TranscriptParser::new(TranscriptKind::Claude);
+    let mut transcript = ClaudeTranscript {
+        session_id: String::new(),
+        first_prompt: None,
+        started_at: None,
+        ended_at: None,
+        model: None,
+        message_count: 0,
+        events: Vec::new(),
+        usage: UsageLedger::new(pricing, None),
+    };
+    let mut message_ids = HashSet::new();
+    // Without events, usage is the last one reported for each message
+    let mut message_usage = HashMap::new();
+
+    for line in content.lines() {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let timestamp = value
+            .get("timestamp")
+            .and_then(Value::as_str)
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|at| at.timestamp());
+        if let Some(at) = timestamp {
+            transcript.started_at = Some(transcript.started_at.map_or(at, |start| start.min(at)));
+            transcript.ended_at = Some(transcript.ended_at.map_or(at, |end| end.max(at)));
+        }
+
+        match value.get("type").and_then(Value::as_str) {
+            Some("user") => {
+                if let Some(prompt) = user_prompt(&value) {
+                    transcript.message_count += 1;
+                    transcript.first_prompt.get_or_insert(prompt);
+                }
+            }
+            Some("assistant") => {
+                let message_id = value.pointer("/message/id").and_then(Value::as_str);
+                if message_id.is_none_or(|id| message_ids.insert(id.to_string())) {
+                    transcript.message_count += 1;
+                }
+                if let Some(model) = value.pointer("/message/model").and_then(Value::as_str).filter(|m| !m.starts_with('<')) {
+                    transcript.model = Some(model.to_string());
+                }
+                if !with_events {
+                    if let Some((id, usage)) = events::claude_message_usage(&value) {
+                        message_usage.insert(id, usage);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if with_events {
+            let events = parser.parse_line(&value);
+            transcript.record(timestamp, events);
+        } else if transcript.session_id.is_empty() {
+            if let Some(id) = value.get("sessionId").and_then(Value::as_str) {
+                transcript.session_id = id.to_string();
+            }
+        }
+    }
+    if with_events {
+        let remaining = parser.finish();
+        transcript.record(transcript.ended_at, remaining);
+    } else {
+        let usage: Vec<ElfEvent> = message_usage.into_values().collect();
+        transcript.usage.record(None, &usage);
+    }
+
+    if transcript.message_count == 0 {
+        return None;
+    }
+    if transcript.session_id.is_empty() {
+        transcript.session_id = fallback_id.to_string();
+    }
+    Some(transcript)
+}
+
+/// The text a user typed, for `user` lines that are prompts rather than tool results,
+/// command output or other injected context.
+fn user_prompt(value: &Value) -> Option<String> {
+    if value.get("isMeta").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    let text = match value.pointer("/message/content")? {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    let text = text.trim();
+    (!text.is_empty() && !text.starts_with('<')).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transcript() -> String {
+        [
+            json!({ "type": "summary", "summary": "Fix login" }),
+            json!({ "type": "user", "sessionId": "abc-123", "timestamp": "2025-06-01T10:00:00Z",
+                    "message": { "role": "user", "content": "<command-name>/clear</command-name>" } }),
+            json!({ "type": "user", "sessionId": "abc-123", "timestamp": "2025-06-01T10:00:05Z",
+                    "message": { "role": "user", "content": "Fix the login bug" } }),
+            json!({ "type": "assistant", "sessionId": "abc-123", "timestamp": "2025-06-01T10:00:10Z",
+                    "message": { "id": "m1", "model": "claude-sonnet-4-5", "content": [
+                        { "type": "tool_use", "id": "t1", "name": "Read", "input": { "file_path": "a.rs" } }
+                    ], "usage": { "input_tokens": 100, "output_tokens": 20, "cache_read_input_tokens": 50 } } }),
+            json!({ "type": "user", "sessionId": "abc-123", "timestamp": "2025-06-01T10:00:11Z",
+                    "message": { "role": "user", "content": [{ "type": "tool_result", "tool_use_id": "t1", "content": "ok" }] } }),
+            json!({ "type": "assistant", "sessionId": "abc-123", "timestamp": "2025-06-01T10:02:00Z",
+                    "message": { "id": "m2", "model": "claude-sonnet-4-5", "content": [{ "type": "text", "text": "Fixed it." }],
+                    "usage": { "input_tokens": 10, "output_tokens": 5 } } }),
+            // A second content block of the same message repeats its usage
+            json!({ "type": "assistant", "sessionId": "abc-123", "timestamp": "2025-06-01T10:02:00Z",
+                    "message": { "id": "m2", "model": "claude-sonnet-4-5", "content": [{ "type": "text", "text": "Done." }],
+                    "usage": { "input_tokens": 10, "output_tokens": 5 } } }),
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    #[test]
+    fn transcripts_summarize_prompt_times_model_and_usage() {
+        let parsed = parse_transcript(&transcript(), "file-id", PricingTable::default()).unwrap();
+        assert_eq!(parsed.session_id, "abc-123");
+        assert_eq!(parsed.first_prompt.as_deref(), Some("Fix the login bug"));
+        assert_eq!(parsed.started_at, Some(1_748_772_000));
+        assert_eq!(parsed.ended_at, Some(1_748_772_120));
+        assert_eq!(parsed.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(parsed.message_count, 3);
+        assert_eq!(parsed.last_text(), Some("Done."));
+
+        let tokens = parsed.tokens();
+        assert_eq!(tokens.input_tokens, 110);
+        assert_eq!(tokens.output_tokens, 25);
+        assert_eq!(tokens.cache_read_tokens, 50);
+
+        let (at, first) = &parsed.events[0];
+        assert_eq!(*at, 1_748_772_000);
+        assert!(matches!(first, ElfEvent::Init { session_id: Some(id), .. } if id == "abc-123"));
+        assert!(parsed.events.iter().any(|(_, e)| matches!(e, ElfEvent::ToolCall { name, .. } if name == "Read")));
+    }
+
+    #[test]
+    fn summaries_match_the_full_parse_without_events() {
+        let parsed = parse_transcript(&transcript(), "file-id", PricingTable::default()).unwrap();
+        let summary = summarize_transcript(&transcript(), "file-id", PricingTable::default()).unwrap();
+        assert_eq!(summary.session_id, "abc-123");
+        assert_eq!(summary.first_prompt, parsed.first_prompt);
+        assert_eq!((summary.started_at, summary.ended_at), (parsed.started_at, parsed.ended_at));
+        assert_eq!(summary.model, parsed.model);
+        assert_eq!(summary.message_count, 3);
+        assert_eq!(summary.tokens(), parsed.tokens());
+        assert!(summary.events.is_empty());
+    }
+
+    #[test]
+    fn empty_transcripts_and_unsafe_ids_are_rejected() {
+        assert!(parse_transcript("{\"type\":\"summary\"}", "x", PricingTable::default()).is_none());
+        assert!(is_valid_session_id("0b6e1f2a-1234"));
+        assert!(!is_valid_session_id("../secrets"));
+        assert!(!is_valid_session_id(""));
+    }
+}
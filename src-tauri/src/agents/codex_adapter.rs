@@ -5,9 +5,16 @@
 // so the frontend never knows which runtime is underneath.
 
 use crate::agents::analyzer::TaskPlan;
+use crate::agents::events::{self, ElfEvent};
+use crate::agents::run_state;
 use crate::agents::runtime;
+use crate::secrets::redact;
 use serde::{Deserialize, Serialize};
 
+/// Model `codex exec` runs unless configured otherwise. Codex doesn't report its model
+/// in the JSONL stream, so usage is priced as this model.
+pub const DEFAULT_MODEL: &str = "gpt-5-codex";
+
 /// A parsed event from the Codex CLI's JSONL output stream.
 /// These are normalized into the ElfEvent format for the frontend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +41,9 @@ pub struct NormalizedEvent {
     pub timestamp: i64,
     /// The originating runtime identifier.
     pub runtime: String,
+    /// Typed events translated from the raw line by `events::from_codex`.
+    #[serde(default)]
+    pub events: Vec<ElfEvent>,
 }
 
 /// Tracks which phase of a Codex team run is active and maps phases to elf IDs.
@@ -97,27 +107,32 @@ impl CodexTeamParser {
 
 /// Spawn a Codex CLI process for a single-agent task.
 ///
-/// Runs: `codex exec --full-auto --json "<task>"`
+/// Runs: `codex [-c overrides...] exec --full-auto --json "<task>"`
 /// in the given working directory. Uses `exec` subcommand for non-interactive
 /// (piped stdout) mode, and `--json` for machine-readable JSONL output.
+/// `config_overrides` are `-c key=value` pairs, used to register the ELVES MCP server.
 ///
 /// Returns the child process handle. The caller reads stdout line-by-line
 /// and passes each line to `parse_codex_output` for event extraction.
 pub fn spawn_codex(
     task: &str,
     working_dir: &str,
+    config_overrides: &[String],
 ) -> Result<std::process::Child, std::io::Error> {
     let codex_bin = runtime::resolve_binary("codex")
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e))?;
-    std::process::Command::new(&codex_bin)
+    let mut cmd = std::process::Command::new(&codex_bin);
+    cmd.args(config_overrides)
         .arg("exec")
         .arg("--full-auto")
         .arg("--json")
         .arg(task)
         .current_dir(working_dir)
         .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
+        .stderr(std::process::Stdio::piped());
+    redact::strip_inherited_secrets(&mut cmd);
+    run_state::isolate_process_group(&mut cmd);
+    cmd.spawn()
 }
 
 /// Parse a single line of JSONL output from the Codex CLI into a CodexEvent.
@@ -168,6 +183,9 @@ pub fn parse_codex_output(line: &str) -> Option<CodexEvent> {
 /// - "patch" / "apply"    → "file_change" (file modifications)
 /// - "error"              → "error" (runtime error)
 /// - everything else      → "output" (generic content)
+///
+/// The typed `events` are derived from the raw Codex type, which also covers the
+/// `codex exec --json` item stream (`item.completed`, `turn.completed`, ...).
 pub fn normalize_codex_event(event: CodexEvent) -> NormalizedEvent {
     let unified_type = match event.event_type.as_str() {
         "plan" | "thinking" => "thinking",
@@ -180,6 +198,7 @@ pub fn normalize_codex_event(event: CodexEvent) -> NormalizedEvent {
 
     NormalizedEvent {
         event_type: unified_type.to_string(),
+        events: events::from_codex(&event.event_type, &event.payload),
         payload: event.payload,
         timestamp: event.timestamp,
         runtime: "codex".to_string(),
@@ -190,26 +209,31 @@ pub fn normalize_codex_event(event: CodexEvent) -> NormalizedEvent {
 ///
 /// Constructs a team prompt from the TaskPlan describing each role and its focus,
 /// then spawns Codex with `exec --full-auto --json` for non-interactive output.
+/// `config_overrides` are passed through as for `spawn_codex`.
 ///
 /// Returns the child process handle. The caller manages stdout/stderr.
 pub fn spawn_codex_team(
     task: &str,
     working_dir: &str,
     plan: &TaskPlan,
+    config_overrides: &[String],
 ) -> Result<std::process::Child, std::io::Error> {
     let team_prompt = build_codex_team_prompt(task, plan);
 
     let codex_bin = runtime::resolve_binary("codex")
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e))?;
-    std::process::Command::new(&codex_bin)
+    let mut cmd = std::process::Command::new(&codex_bin);
+    cmd.args(config_overrides)
         .arg("exec")
         .arg("--full-auto")
         .arg("--json")
         .arg(&team_prompt)
         .current_dir(working_dir)
         .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
+        .stderr(std::process::Stdio::piped());
+    redact::strip_inherited_secrets(&mut cmd);
+    run_state::isolate_process_group(&mut cmd);
+    cmd.spawn()
 }
 
 /// Build a structured team prompt from a TaskPlan, formatted for Codex.
@@ -451,6 +475,13 @@ mod tests {
         assert_eq!(normalized.timestamp, 1700000000);
     }
 
+    #[test]
+    fn normalize_fills_typed_events() {
+        let line = r#"{"type":"item.completed","item":{"id":"i1","type":"agent_message","text":"All done"}}"#;
+        let normalized = normalize_codex_event(parse_codex_output(line).expect("Should parse item"));
+        assert_eq!(normalized.events, vec![ElfEvent::Text { text: "All done".to_string() }]);
+    }
+
     #[test]
     fn normalized_event_serializes_to_camel_case() {
         let normalized = NormalizedEvent {
@@ -458,6 +489,7 @@ mod tests {
             payload: serde_json::json!({}),
             timestamp: 1700000000,
             runtime: "codex".to_string(),
+            events: Vec::new(),
         };
         let json = serde_json::to_string(&normalized).expect("Should serialize");
         assert!(json.contains("eventType"));
@@ -512,6 +544,7 @@ mod tests {
             ],
             runtime_recommendation: "codex".to_string(),
             estimated_duration: "~6 minutes".to_string(),
+            template_id: None,
         }
     }
 
@@ -573,6 +606,7 @@ mod tests {
             task_graph: vec![],
             runtime_recommendation: "codex".to_string(),
             estimated_duration: "~1 minute".to_string(),
+            template_id: None,
         };
         let prompt = build_codex_team_prompt("Simple task", &plan);
         assert!(prompt.contains("Simple task"));
@@ -675,6 +709,7 @@ mod tests {
             payload: serde_json::json!({"path": "/src/lib.rs"}),
             timestamp: 1700000000,
             runtime: "codex".to_string(),
+            events: Vec::new(),
         };
         let json = serde_json::to_string(&event).expect("Should serialize");
         let deserialized: NormalizedEvent = serde_json::from_str(&json).expect("Should deserialize");
@@ -0,0 +1,750 @@
+// Typed agent events — the unified ElfEvent model every runtime adapter produces.
+//
+// Claude's stream-json and Codex's JSONL use different shapes for the same things
+// (a tool call, a tool result, a file edit, token usage). Adapters translate each raw
+// line into zero or more `ElfEvent`s, so storage, memory extraction, export, and the
+// frontend read typed fields instead of digging through runtime-specific JSON.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Claude tools whose `file_path` (or `notebook_path`) input is a file being written.
+const CLAUDE_EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// One typed event from an agent's output stream.
+///
+/// Serialized with a `kind` tag and camelCase fields, e.g.
+/// `{"kind":"tool_call","id":"toolu_1","name":"Read","input":{...}}`. The same JSON is
+/// stored as the event payload and emitted to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum ElfEvent {
+    /// The runtime started and reported its own session (thread) ID and model.
+    Init {
+        session_id: Option<String>,
+        model: Option<String>,
+    },
+    /// Agent reasoning.
+    Thinking { text: String },
+    /// Assistant text shown to the user.
+    Text { text: String },
+    /// A tool invocation. `id` pairs it with its `ToolResult`.
+    ToolCall {
+        id: Option<String>,
+        name: String,
+        input: Value,
+    },
+    /// The response to a tool invocation.
+    ToolResult {
+        id: Option<String>,
+        is_error: bool,
+        content: String,
+    },
+    /// A file written or modified by the agent.
+    FileEdit { path: String },
+    /// Token usage reported by the runtime, for one model when the runtime breaks
+    /// usage down by model.
+    Usage {
+        input_tokens: i64,
+        output_tokens: i64,
+        cache_read_tokens: i64,
+        cache_write_tokens: i64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+    },
+    /// The final outcome of a run.
+    Result {
+        text: Option<String>,
+        is_error: bool,
+        cost_usd: Option<f64>,
+        duration_ms: Option<i64>,
+        session_id: Option<String>,
+    },
+    /// A runtime error.
+    Error { message: String },
+}
+
+impl ElfEvent {
+    /// The `kind` tag, also stored in the events table's indexed `kind` column.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Init { .. } => "init",
+            Self::Thinking { .. } => "thinking",
+            Self::Text { .. } => "text",
+            Self::ToolCall { .. } => "tool_call",
+            Self::ToolResult { .. } => "tool_result",
+            Self::FileEdit { .. } => "file_edit",
+            Self::Usage { .. } => "usage",
+            Self::Result { .. } => "result",
+            Self::Error { .. } => "error",
+        }
+    }
+}
+
+/// Translate one Claude stream-json line into typed events.
+///
+/// - `system` → `Init`
+/// - `assistant` → `Thinking` / `Text` / `ToolCall` per content block, plus `FileEdit`
+///   for edit tools
+/// - `user` → `ToolResult` per tool_result block
+/// - `result` → `Usage` per model of its `modelUsage` (or one from its `usage` totals),
+///   followed by `Result`
+/// - legacy flat `tool_use` / `tool_result` / `thinking` / `error` lines and plain text
+///   are mapped to their typed equivalents
+pub fn from_claude(value: &Value) -> Vec<ElfEvent> {
+    let event_type = value.get("type").and_then(Value::as_str).unwrap_or("output");
+    match event_type {
+        "system" => vec![ElfEvent::Init {
+            session_id: str_field(value, &["session_id"]),
+            model: str_field(value, &["model"]),
+        }],
+        "assistant" => match value.pointer("/message/content").and_then(Value::as_array) {
+            Some(blocks) => blocks.iter().flat_map(claude_assistant_block).collect(),
+            None => text_event(value).into_iter().collect(),
+        },
+        "user" => value
+            .pointer("/message/content")
+            .and_then(Value::as_array)
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter(|b| b.get("type").and_then(Value::as_str) == Some("tool_result"))
+                    .map(|b| ElfEvent::ToolResult {
+                        id: str_field(b, &["tool_use_id"]),
+                        is_error: b.get("is_error").and_then(Value::as_bool).unwrap_or(false),
+                        content: content_text(b.get("content")),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        "result" => {
+            let mut events = claude_model_usage(value);
+            if events.is_empty() {
+                events.extend(usage_event(value.get("usage").unwrap_or(value)));
+            }
+            let is_error = value.get("is_error").and_then(Value::as_bool).unwrap_or(false)
+                || value
+                    .get("subtype")
+                    .and_then(Value::as_str)
+                    .is_some_and(|s| s.starts_with("error"));
+            events.push(ElfEvent::Result {
+                text: str_field(value, &["result", "text", "content"]),
+                is_error,
+                cost_usd: ["total_cost_usd", "cost_usd", "cost"]
+                    .iter()
+                    .find_map(|k| value.get(*k).and_then(Value::as_f64)),
+                duration_ms: value.get("duration_ms").and_then(Value::as_i64),
+                session_id: str_field(value, &["session_id"]),
+            });
+            events
+        }
+        "tool_use" | "tool_call" => vec![legacy_tool_call(value)],
+        "tool_result" => vec![ElfEvent::ToolResult {
+            id: str_field(value, &["tool_use_id", "id"]),
+            is_error: value.get("is_error").and_then(Value::as_bool).unwrap_or(false),
+            content: content_text(value.get("content").or_else(|| value.get("output"))),
+        }],
+        "thinking" => str_field(value, &["thinking", "text", "content"])
+            .map(|text| ElfEvent::Thinking { text })
+            .into_iter()
+            .collect(),
+        "error" => vec![error_event(value)],
+        _ => text_event(value).into_iter().collect(),
+    }
+}
+
+/// Translate one Codex JSONL line (by its raw `type`) into typed events.
+///
+/// Handles both the `codex exec --json` item stream (`thread.started`, `item.*`,
+/// `turn.completed`, `turn.failed`) and the older flat event types (`plan`, `exec`,
+/// `function_call`, `patch`, ...).
+pub fn from_codex(event_type: &str, value: &Value) -> Vec<ElfEvent> {
+    match event_type {
+        "thread.started" => vec![ElfEvent::Init {
+            session_id: str_field(value, &["thread_id"]),
+            model: None,
+        }],
+        "item.started" | "item.completed" => value
+            .get("item")
+            .map(|item| codex_item(item, event_type == "item.completed"))
+            .unwrap_or_default(),
+        "turn.completed" => value
+            .get("usage")
+            .and_then(usage_event)
+            .into_iter()
+            .collect(),
+        "turn.failed" => vec![error_event(value)],
+        "item.updated" | "turn.started" => Vec::new(),
+        "plan" | "thinking" | "reasoning" => str_field(value, &["text", "content", "plan", "message"])
+            .map(|text| ElfEvent::Thinking { text })
+            .into_iter()
+            .collect(),
+        "tool_call" | "exec" | "function_call" => vec![legacy_tool_call(value)],
+        "tool_result" | "function_result" => vec![ElfEvent::ToolResult {
+            id: str_field(value, &["call_id", "id"]),
+            is_error: value.get("is_error").and_then(Value::as_bool).unwrap_or(false),
+            content: content_text(
+                value
+                    .get("output")
+                    .or_else(|| value.get("result"))
+                    .or_else(|| value.get("content")),
+            ),
+        }],
+        "patch" | "apply" | "file_edit" => file_paths(value)
+            .into_iter()
+            .map(|path| ElfEvent::FileEdit { path })
+            .collect(),
+        "error" => vec![error_event(value)],
+        _ => text_event(value).into_iter().collect(),
+    }
+}
+
+/// One block of a Claude assistant message.
+fn claude_assistant_block(block: &Value) -> Vec<ElfEvent> {
+    match block.get("type").and_then(Value::as_str) {
+        Some("thinking") => non_blank(str_field(block, &["thinking", "text"]))
+            .map(|text| vec![ElfEvent::Thinking { text }])
+            .unwrap_or_default(),
+        Some("text") => non_blank(str_field(block, &["text"]))
+            .map(|text| vec![ElfEvent::Text { text }])
+            .unwrap_or_default(),
+        Some("tool_use") => {
+            let name = str_field(block, &["name"]).unwrap_or_default();
+            let input = block.get("input").cloned().unwrap_or(Value::Null);
+            let edited = CLAUDE_EDIT_TOOLS
+                .contains(&name.as_str())
+                .then(|| str_field(&input, &["file_path", "notebook_path"]))
+                .flatten();
+            let mut events = vec![ElfEvent::ToolCall {
+                id: str_field(block, &["id"]),
+                name,
+                input,
+            }];
+            if let Some(path) = edited {
+                events.push(ElfEvent::FileEdit { path });
+            }
+            events
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Translate one line of a Codex rollout log (`~/.codex/sessions/.../rollout-*.jsonl`),
+/// which interactive Codex runs write, into typed events.
+///
+/// - `session_meta` → `Init` with the session ID; `turn_context` → `Init` with the model
+/// - `response_item` messages, reasoning, tool calls and their output → `Text`,
+///   `Thinking`, `ToolCall` and `ToolResult`
+/// - `event_msg` token counts → `Usage` of the last turn
+pub fn from_codex_rollout(value: &Value) -> Vec<ElfEvent> {
+    let payload = value.get("payload").unwrap_or(&Value::Null);
+    match value.get("type").and_then(Value::as_str).unwrap_or_default() {
+        "session_meta" => vec![ElfEvent::Init {
+            session_id: str_field(payload, &["id"]),
+            model: None,
+        }],
+        "turn_context" => str_field(payload, &["model"])
+            .map(|model| ElfEvent::Init { session_id: None, model: Some(model) })
+            .into_iter()
+            .collect(),
+        "response_item" => codex_response_item(payload),
+        "event_msg" if payload.get("type").and_then(Value::as_str) == Some("token_count") => payload
+            .pointer("/info/last_token_usage")
+            .and_then(usage_event)
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// One `response_item` payload of a Codex rollout log.
+fn codex_response_item(item: &Value) -> Vec<ElfEvent> {
+    let id = str_field(item, &["call_id", "id"]);
+    match item.get("type").and_then(Value::as_str).unwrap_or_default() {
+        "message" if item.get("role").and_then(Value::as_str) == Some("assistant") => {
+            non_blank(Some(content_text(item.get("content"))))
+                .map(|text| vec![ElfEvent::Text { text }])
+                .unwrap_or_default()
+        }
+        "reasoning" => non_blank(Some(content_text(item.get("summary"))))
+            .map(|text| vec![ElfEvent::Thinking { text }])
+            .unwrap_or_default(),
+        "function_call" => {
+            let arguments = str_field(item, &["arguments"]).unwrap_or_default();
+            vec![ElfEvent::ToolCall {
+                id,
+                name: str_field(item, &["name"]).unwrap_or_else(|| "unknown".to_string()),
+                input: serde_json::from_str(&arguments).unwrap_or(Value::String(arguments)),
+            }]
+        }
+        "custom_tool_call" => vec![ElfEvent::ToolCall {
+            id,
+            name: str_field(item, &["name"]).unwrap_or_else(|| "unknown".to_string()),
+            input: serde_json::json!({ "input": item.get("input").cloned().unwrap_or(Value::Null) }),
+        }],
+        "function_call_output" | "custom_tool_call_output" => vec![ElfEvent::ToolResult {
+            id,
+            is_error: false,
+            content: content_text(item.get("output")),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// One Codex `item.*` payload. Tool-like items produce a `ToolCall` when they start
+/// and a `ToolResult` when they complete; message-like items only on completion.
+fn codex_item(item: &Value, completed: bool) -> Vec<ElfEvent> {
+    let id = str_field(item, &["id"]);
+    let item_type = str_field(item, &["type", "item_type"]).unwrap_or_default();
+    match (item_type.as_str(), completed) {
+        ("agent_message" | "assistant_message", true) => non_blank(str_field(item, &["text"]))
+            .map(|text| vec![ElfEvent::Text { text }])
+            .unwrap_or_default(),
+        ("reasoning", true) => non_blank(str_field(item, &["text"]))
+            .map(|text| vec![ElfEvent::Thinking { text }])
+            .unwrap_or_default(),
+        ("command_execution", false) => vec![ElfEvent::ToolCall {
+            id,
+            name: "shell".to_string(),
+            input: serde_json::json!({ "command": item.get("command").cloned().unwrap_or(Value::Null) }),
+        }],
+        ("command_execution", true) => vec![ElfEvent::ToolResult {
+            id,
+            is_error: item.get("exit_code").and_then(Value::as_i64).is_some_and(|c| c != 0)
+                || item.get("status").and_then(Value::as_str) == Some("failed"),
+            content: content_text(item.get("aggregated_output")),
+        }],
+        ("mcp_tool_call", false) => vec![ElfEvent::ToolCall {
+            id,
+            name: match (str_field(item, &["server"]), str_field(item, &["tool"])) {
+                (Some(server), Some(tool)) => format!("{server}/{tool}"),
+                (_, tool) => tool.unwrap_or_default(),
+            },
+            input: item.get("arguments").cloned().unwrap_or(Value::Null),
+        }],
+        ("mcp_tool_call", true) => vec![ElfEvent::ToolResult {
+            id,
+            is_error: item.get("status").and_then(Value::as_str) == Some("failed"),
+            content: content_text(item.get("result").or_else(|| item.get("error"))),
+        }],
+        ("web_search", false) => vec![ElfEvent::ToolCall {
+            id,
+            name: "web_search".to_string(),
+            input: serde_json::json!({ "query": item.get("query").cloned().unwrap_or(Value::Null) }),
+        }],
+        ("file_change", true) => file_paths(item)
+            .into_iter()
+            .map(|path| ElfEvent::FileEdit { path })
+            .collect(),
+        ("error", true) => vec![error_event(item)],
+        _ => Vec::new(),
+    }
+}
+
+/// A flat tool call line: `{"type":"tool_use","tool":"read_file","input":{...}}`.
+/// Without an explicit input object the whole line is the input.
+fn legacy_tool_call(value: &Value) -> ElfEvent {
+    let input = ["input", "arguments", "args"]
+        .iter()
+        .find_map(|k| value.get(*k))
+        .cloned()
+        .unwrap_or_else(|| value.clone());
+    ElfEvent::ToolCall {
+        id: str_field(value, &["id", "call_id", "tool_use_id"]),
+        name: str_field(value, &["name", "tool", "command"]).unwrap_or_else(|| "unknown".to_string()),
+        input,
+    }
+}
+
+/// One `Usage` event per model from a Claude result's `modelUsage` breakdown, e.g.
+/// `{"claude-sonnet-4-5": {"inputTokens": 10, "outputTokens": 3, ...}}`. Empty when the
+/// result has none.
+fn claude_model_usage(value: &Value) -> Vec<ElfEvent> {
+    let Some(models) = value.get("modelUsage").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    models
+        .iter()
+        .map(|(model, usage)| {
+            let count = |key: &str| usage.get(key).and_then(Value::as_i64).unwrap_or(0);
+            ElfEvent::Usage {
+                input_tokens: count("inputTokens"),
+                output_tokens: count("outputTokens"),
+                cache_read_tokens: count("cacheReadInputTokens"),
+                cache_write_tokens: count("cacheCreationInputTokens"),
+                model: Some(model.clone()),
+            }
+        })
+        .collect()
+}
+
+/// The usage of the message a Claude `assistant` line belongs to, with the message's ID.
+/// Claude repeats a message's usage on every content block of it, so callers count it
+/// once per ID. None for lines without usage or a real model (synthetic messages).
+pub fn claude_message_usage(value: &Value) -> Option<(String, ElfEvent)> {
+    let message = value.get("message")?;
+    let model = message.get("model").and_then(Value::as_str).filter(|m| !m.starts_with('<'))?;
+    let Some(ElfEvent::Usage { input_tokens, output_tokens, cache_read_tokens, cache_write_tokens, .. }) =
+        usage_event(message.get("usage")?)
+    else {
+        return None;
+    };
+    let message_id = message.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+    let usage = ElfEvent::Usage {
+        input_tokens,
+        output_tokens,
+        cache_read_tokens,
+        cache_write_tokens,
+        model: Some(model.to_string()),
+    };
+    Some((message_id, usage))
+}
+
+/// A `Usage` event from a usage object, or None if it reports no tokens at all.
+///
+/// Codex counts its `cached_input_tokens` in `input_tokens` too, so they are taken out of
+/// the input to price each token once; Claude reports cache reads separately.
+pub fn usage_event(usage: &Value) -> Option<ElfEvent> {
+    let count = |keys: &[&str]| keys.iter().find_map(|k| usage.get(*k).and_then(Value::as_i64));
+    let codex_cached = count(&["cached_input_tokens"]);
+    let input_tokens = count(&["input_tokens"]).map(|input| (input - codex_cached.unwrap_or(0)).max(0));
+    let output_tokens = count(&["output_tokens"]);
+    let cache_read_tokens = count(&["cache_read_input_tokens"]).or(codex_cached);
+    let cache_write_tokens = count(&["cache_creation_input_tokens"]);
+    if [input_tokens, output_tokens, cache_read_tokens, cache_write_tokens]
+        .iter()
+        .all(Option::is_none)
+    {
+        // Older result lines only report a total
+        return count(&["total_tokens"]).map(|total| ElfEvent::Usage {
+            input_tokens: 0,
+            output_tokens: total,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            model: None,
+        });
+    }
+    Some(ElfEvent::Usage {
+        input_tokens: input_tokens.unwrap_or(0),
+        output_tokens: output_tokens.unwrap_or(0),
+        cache_read_tokens: cache_read_tokens.unwrap_or(0),
+        cache_write_tokens: cache_write_tokens.unwrap_or(0),
+        model: None,
+    })
+}
+
+/// An `Error` from `message`, `error` as a string, or `error.message`.
+fn error_event(value: &Value) -> ElfEvent {
+    let message = str_field(value, &["message", "error"])
+        .or_else(|| value.pointer("/error/message").and_then(Value::as_str).map(String::from))
+        .unwrap_or_else(|| value.to_string());
+    ElfEvent::Error { message }
+}
+
+/// A `Text` event from a line's `text`, `message`, `content`, or `result` string.
+fn text_event(value: &Value) -> Option<ElfEvent> {
+    non_blank(str_field(value, &["text", "message", "content", "result"])).map(|text| ElfEvent::Text { text })
+}
+
+/// Paths touched by a patch: `path` / `file`, or each entry of a `changes` / `files` list.
+fn file_paths(value: &Value) -> Vec<String> {
+    if let Some(path) = str_field(value, &["path", "file", "file_path"]) {
+        return vec![path];
+    }
+    ["changes", "files"]
+        .iter()
+        .filter_map(|k| value.get(*k).and_then(Value::as_array))
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .as_str()
+                .map(String::from)
+                .or_else(|| str_field(entry, &["path", "file"]))
+        })
+        .collect()
+}
+
+/// Tool result content as text: a string as-is, text blocks joined by newlines,
+/// anything else as compact JSON.
+fn content_text(content: Option<&Value>) -> String {
+    match content {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .map(|b| match b {
+                Value::String(s) => s.clone(),
+                _ => str_field(b, &["text"]).unwrap_or_else(|| b.to_string()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// The first of `keys` whose value is a string.
+fn str_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|k| value.get(*k).and_then(Value::as_str))
+        .map(String::from)
+}
+
+fn non_blank(text: Option<String>) -> Option<String> {
+    text.filter(|t| !t.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn claude_assistant_blocks_become_typed_events() {
+        let line = json!({
+            "type": "assistant",
+            "message": { "content": [
+                { "type": "thinking", "thinking": "Let me look" },
+                { "type": "text", "text": "Editing now" },
+                { "type": "text", "text": "  " },
+                { "type": "tool_use", "id": "toolu_1", "name": "Edit",
+                  "input": { "file_path": "src/main.rs", "old_string": "a", "new_string": "b" } }
+            ]}
+        });
+        let events = from_claude(&line);
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], ElfEvent::Thinking { text: "Let me look".into() });
+        assert_eq!(events[1], ElfEvent::Text { text: "Editing now".into() });
+        match &events[2] {
+            ElfEvent::ToolCall { id, name, input } => {
+                assert_eq!(id.as_deref(), Some("toolu_1"));
+                assert_eq!(name, "Edit");
+                assert_eq!(input["file_path"], "src/main.rs");
+            }
+            other => panic!("Expected tool call, got {other:?}"),
+        }
+        assert_eq!(events[3], ElfEvent::FileEdit { path: "src/main.rs".into() });
+    }
+
+    #[test]
+    fn claude_tool_results_join_text_blocks() {
+        let line = json!({
+            "type": "user",
+            "message": { "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_1", "is_error": true,
+                  "content": [{ "type": "text", "text": "line 1" }, { "type": "text", "text": "line 2" }] },
+                { "type": "text", "text": "ignored" }
+            ]}
+        });
+        assert_eq!(
+            from_claude(&line),
+            vec![ElfEvent::ToolResult {
+                id: Some("toolu_1".into()),
+                is_error: true,
+                content: "line 1\nline 2".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn claude_result_breaks_usage_down_by_model() {
+        let line = json!({
+            "type": "result", "subtype": "success", "result": "Done",
+            "usage": { "input_tokens": 110, "output_tokens": 55 },
+            "modelUsage": {
+                "claude-opus-4-5": { "inputTokens": 100, "outputTokens": 50,
+                                     "cacheReadInputTokens": 7, "cacheCreationInputTokens": 3, "costUSD": 0.1 },
+                "claude-haiku-4-5": { "inputTokens": 10, "outputTokens": 5 }
+            }
+        });
+        let events = from_claude(&line);
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            ElfEvent::Usage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                model: Some("claude-haiku-4-5".into()),
+            }
+        );
+        assert_eq!(
+            events[1],
+            ElfEvent::Usage {
+                input_tokens: 100,
+                output_tokens: 50,
+                cache_read_tokens: 7,
+                cache_write_tokens: 3,
+                model: Some("claude-opus-4-5".into()),
+            }
+        );
+        assert_eq!(events[2].kind(), "result");
+    }
+
+    #[test]
+    fn claude_result_reports_usage_and_outcome() {
+        let line = json!({
+            "type": "result", "subtype": "success", "is_error": false,
+            "result": "Done", "total_cost_usd": 0.12, "duration_ms": 4200, "session_id": "abc",
+            "usage": { "input_tokens": 100, "output_tokens": 50,
+                       "cache_read_input_tokens": 1000, "cache_creation_input_tokens": 20 }
+        });
+        let events = from_claude(&line);
+        assert_eq!(
+            events[0],
+            ElfEvent::Usage { input_tokens: 100, output_tokens: 50, cache_read_tokens: 1000, cache_write_tokens: 20, model: None }
+        );
+        assert_eq!(
+            events[1],
+            ElfEvent::Result {
+                text: Some("Done".into()),
+                is_error: false,
+                cost_usd: Some(0.12),
+                duration_ms: Some(4200),
+                session_id: Some("abc".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn claude_legacy_and_plain_lines() {
+        let tool = from_claude(&json!({ "type": "tool_use", "tool": "read_file", "path": "src/main.rs" }));
+        match &tool[0] {
+            ElfEvent::ToolCall { name, input, .. } => {
+                assert_eq!(name, "read_file");
+                assert_eq!(input["path"], "src/main.rs");
+            }
+            other => panic!("Expected tool call, got {other:?}"),
+        }
+        assert_eq!(
+            from_claude(&json!({ "type": "result", "result": "ok", "cost": 0.05 }))[0],
+            ElfEvent::Result { text: Some("ok".into()), is_error: false, cost_usd: Some(0.05), duration_ms: None, session_id: None }
+        );
+        assert_eq!(from_claude(&json!({ "text": "hello" })), vec![ElfEvent::Text { text: "hello".into() }]);
+        assert!(from_claude(&json!({ "tokens": 42 })).is_empty());
+        assert_eq!(
+            from_claude(&json!({ "type": "system", "subtype": "init", "session_id": "s1", "model": "opus" })),
+            vec![ElfEvent::Init { session_id: Some("s1".into()), model: Some("opus".into()) }]
+        );
+    }
+
+    #[test]
+    fn codex_item_stream_becomes_typed_events() {
+        let started = json!({ "type": "item.started",
+            "item": { "id": "item_1", "type": "command_execution", "command": "ls", "status": "in_progress" } });
+        let completed = json!({ "type": "item.completed",
+            "item": { "id": "item_1", "type": "command_execution", "command": "ls",
+                      "aggregated_output": "src\n", "exit_code": 1, "status": "failed" } });
+        match &from_codex("item.started", &started)[0] {
+            ElfEvent::ToolCall { id, name, input } => {
+                assert_eq!(id.as_deref(), Some("item_1"));
+                assert_eq!(name, "shell");
+                assert_eq!(input["command"], "ls");
+            }
+            other => panic!("Expected tool call, got {other:?}"),
+        }
+        assert_eq!(
+            from_codex("item.completed", &completed),
+            vec![ElfEvent::ToolResult { id: Some("item_1".into()), is_error: true, content: "src\n".into() }]
+        );
+
+        let changes = json!({ "type": "item.completed", "item": { "id": "item_2", "type": "file_change",
+            "changes": [{ "path": "a.rs", "kind": "update" }, { "path": "b.rs", "kind": "add" }] } });
+        assert_eq!(
+            from_codex("item.completed", &changes),
+            vec![ElfEvent::FileEdit { path: "a.rs".into() }, ElfEvent::FileEdit { path: "b.rs".into() }]
+        );
+
+        let usage = json!({ "type": "turn.completed",
+            "usage": { "input_tokens": 10, "cached_input_tokens": 4, "output_tokens": 3 } });
+        assert_eq!(
+            from_codex("turn.completed", &usage),
+            vec![ElfEvent::Usage { input_tokens: 6, output_tokens: 3, cache_read_tokens: 4, cache_write_tokens: 0, model: None }]
+        );
+        assert_eq!(
+            from_codex("turn.failed", &json!({ "type": "turn.failed", "error": { "message": "boom" } })),
+            vec![ElfEvent::Error { message: "boom".into() }]
+        );
+    }
+
+    #[test]
+    fn codex_rollout_lines_map_to_typed_events() {
+        let meta = json!({ "type": "session_meta", "payload": { "id": "rollout-1", "cwd": "/repo" } });
+        assert_eq!(from_codex_rollout(&meta), vec![ElfEvent::Init { session_id: Some("rollout-1".into()), model: None }]);
+        let context = json!({ "type": "turn_context", "payload": { "cwd": "/repo", "model": "gpt-5-codex" } });
+        assert_eq!(from_codex_rollout(&context), vec![ElfEvent::Init { session_id: None, model: Some("gpt-5-codex".into()) }]);
+
+        let message = json!({ "type": "response_item", "payload": { "type": "message", "role": "assistant",
+            "content": [{ "type": "output_text", "text": "All done" }] } });
+        assert_eq!(from_codex_rollout(&message), vec![ElfEvent::Text { text: "All done".into() }]);
+        let prompt = json!({ "type": "response_item", "payload": { "type": "message", "role": "user",
+            "content": [{ "type": "input_text", "text": "Fix it" }] } });
+        assert!(from_codex_rollout(&prompt).is_empty());
+
+        let call = json!({ "type": "response_item", "payload": { "type": "function_call", "name": "shell",
+            "arguments": "{\"command\":[\"ls\"]}", "call_id": "call_1" } });
+        match &from_codex_rollout(&call)[0] {
+            ElfEvent::ToolCall { id, name, input } => {
+                assert_eq!(id.as_deref(), Some("call_1"));
+                assert_eq!(name, "shell");
+                assert_eq!(input["command"][0], "ls");
+            }
+            other => panic!("Expected tool call, got {other:?}"),
+        }
+        let output = json!({ "type": "response_item", "payload": { "type": "function_call_output",
+            "call_id": "call_1", "output": "a.rs" } });
+        assert_eq!(
+            from_codex_rollout(&output),
+            vec![ElfEvent::ToolResult { id: Some("call_1".into()), is_error: false, content: "a.rs".into() }]
+        );
+
+        let tokens = json!({ "type": "event_msg", "payload": { "type": "token_count", "info": {
+            "total_token_usage": { "input_tokens": 50, "cached_input_tokens": 20, "output_tokens": 9 },
+            "last_token_usage": { "input_tokens": 30, "cached_input_tokens": 10, "output_tokens": 5 } } } });
+        assert_eq!(
+            from_codex_rollout(&tokens),
+            vec![ElfEvent::Usage { input_tokens: 20, output_tokens: 5, cache_read_tokens: 10, cache_write_tokens: 0, model: None }]
+        );
+        let rate_limits = json!({ "type": "event_msg", "payload": { "type": "token_count", "info": null } });
+        assert!(from_codex_rollout(&rate_limits).is_empty());
+    }
+
+    #[test]
+    fn codex_legacy_types_map_to_typed_events() {
+        assert_eq!(
+            from_codex("plan", &json!({ "type": "plan", "text": "step 1" })),
+            vec![ElfEvent::Thinking { text: "step 1".into() }]
+        );
+        assert_eq!(
+            from_codex("patch", &json!({ "type": "patch", "files": ["x.rs"] })),
+            vec![ElfEvent::FileEdit { path: "x.rs".into() }]
+        );
+        match &from_codex("function_call", &json!({ "type": "function_call", "name": "grep", "arguments": { "q": "x" } }))[0] {
+            ElfEvent::ToolCall { name, input, .. } => {
+                assert_eq!(name, "grep");
+                assert_eq!(input["q"], "x");
+            }
+            other => panic!("Expected tool call, got {other:?}"),
+        }
+        assert_eq!(
+            from_codex("message", &json!({ "text": "plain output" })),
+            vec![ElfEvent::Text { text: "plain output".into() }]
+        );
+    }
+
+    #[test]
+    fn serializes_with_kind_tag_and_camel_case_fields() {
+        let event = ElfEvent::ToolResult { id: Some("t".into()), is_error: false, content: "ok".into() };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["kind"], "tool_result");
+        assert_eq!(json["isError"], false);
+        assert_eq!(event.kind(), "tool_result");
+
+        let usage = ElfEvent::Usage { input_tokens: 1, output_tokens: 2, cache_read_tokens: 3, cache_write_tokens: 4, model: None };
+        let json = serde_json::to_string(&usage).unwrap();
+        assert!(json.contains("\"cacheReadTokens\":3"));
+        assert!(!json.contains("model"));
+        let back: ElfEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, usage);
+    }
+}
@@ -0,0 +1,199 @@
+// Failure classification — decides whether an agent run failed, and why, from its exit
+// status, stderr, and reported errors.
+
+use serde::{Deserialize, Serialize};
+use std::process::ExitStatus;
+
+/// Why an agent run failed. Stored in `sessions.failure_reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureReason {
+    /// Missing or rejected credentials.
+    Auth,
+    /// The provider throttled the run.
+    RateLimit,
+    /// A spending cap, quota, or credit balance ran out.
+    BudgetExceeded,
+    /// The process exited abnormally for any other reason.
+    Crash,
+    /// The process was terminated by a signal it did not raise itself.
+    Killed,
+}
+
+impl FailureReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::RateLimit => "rate_limit",
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::Crash => "crash",
+            Self::Killed => "killed",
+        }
+    }
+}
+
+/// How an agent process ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessExit {
+    /// Exit code, when the process exited normally.
+    pub code: Option<i32>,
+    /// Terminating signal, when the process was killed by one (Unix only).
+    pub signal: Option<i32>,
+}
+
+impl From<ExitStatus> for ProcessExit {
+    fn from(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+        Self {
+            code: status.code(),
+            signal,
+        }
+    }
+}
+
+/// Phrases that identify a failure class, checked in order against lowercased output.
+/// Budget comes first since quota errors are often reported with rate-limit status codes.
+/// Phrases are taken from the runtimes' and providers' actual error messages rather than
+/// bare words, since agent output routinely mentions budgets, billing, and API keys.
+const PATTERNS: &[(FailureReason, &[&str])] = &[
+    (
+        FailureReason::BudgetExceeded,
+        &[
+            "error_max_budget_usd",
+            "exceeded usd budget",
+            "credit balance is too low",
+            "insufficient_quota",
+            "quota exceeded",
+            "exceeded your current quota",
+            "spending limit reached",
+            "check your plan and billing details",
+        ],
+    ),
+    (
+        FailureReason::RateLimit,
+        &[
+            "rate limit",
+            "rate_limit",
+            "ratelimit",
+            "too many requests",
+            "usage limit",
+            "overloaded",
+        ],
+    ),
+    (
+        FailureReason::Auth,
+        &[
+            "invalid api key",
+            "invalid x-api-key",
+            "authentication_error",
+            "authentication failed",
+            "unauthorized",
+            "not logged in",
+            "please run /login",
+            "oauth token has expired",
+            "incorrect api key provided",
+            "missing api key",
+        ],
+    ),
+];
+
+/// Signals that mean something outside the process ended it, rather than a crash.
+const KILL_SIGNALS: &[i32] = &[1, 2, 9, 15]; // SIGHUP, SIGINT, SIGKILL, SIGTERM
+
+/// Classify how a run ended. Returns None when it succeeded.
+///
+/// A run failed when it was killed by a signal, exited non-zero, or reported an error
+/// (`reported_error`, e.g. an error `result`) whose text matches a known failure class.
+/// `messages` are the stderr lines and error messages the run produced; they pick the
+/// class of a non-zero exit, which is a `Crash` when none match.
+pub fn classify(exit: ProcessExit, reported_error: bool, messages: &[String]) -> Option<FailureReason> {
+    if let Some(signal) = exit.signal {
+        return Some(if KILL_SIGNALS.contains(&signal) {
+            FailureReason::Killed
+        } else {
+            FailureReason::Crash
+        });
+    }
+
+    let matched = match_messages(messages);
+    match exit.code {
+        Some(code) if code != 0 => Some(matched.unwrap_or(FailureReason::Crash)),
+        _ if reported_error => matched,
+        _ => None,
+    }
+}
+
+/// The first failure class whose phrases appear in any message.
+fn match_messages(messages: &[String]) -> Option<FailureReason> {
+    let text = messages.join("\n").to_lowercase();
+    PATTERNS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().any(|phrase| text.contains(phrase)))
+        .map(|(reason, _)| *reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exited(code: i32) -> ProcessExit {
+        ProcessExit { code: Some(code), signal: None }
+    }
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn clean_exit_is_not_a_failure() {
+        assert_eq!(classify(exited(0), false, &lines(&["rate limit warning"])), None);
+    }
+
+    #[test]
+    fn nonzero_exit_is_classified_from_messages() {
+        assert_eq!(
+            classify(exited(1), false, &lines(&["Error: Invalid API key · Please run /login"])),
+            Some(FailureReason::Auth)
+        );
+        assert_eq!(
+            classify(exited(1), false, &lines(&["API Error: 429 Too Many Requests"])),
+            Some(FailureReason::RateLimit)
+        );
+        assert_eq!(
+            classify(exited(1), false, &lines(&["429: You exceeded your current quota (insufficient_quota)"])),
+            Some(FailureReason::BudgetExceeded)
+        );
+        assert_eq!(
+            classify(exited(2), false, &lines(&["error: unexpected argument '--bogus'"])),
+            Some(FailureReason::Crash)
+        );
+    }
+
+    #[test]
+    fn reported_error_with_clean_exit_needs_a_match() {
+        assert_eq!(
+            classify(exited(0), true, &lines(&["Credit balance is too low"])),
+            Some(FailureReason::BudgetExceeded)
+        );
+        assert_eq!(classify(exited(0), true, &lines(&["max turns reached"])), None);
+    }
+
+    #[test]
+    fn incidental_mentions_do_not_pick_a_class() {
+        assert_eq!(
+            classify(exited(1), false, &lines(&["Error: cannot read budget.yaml", "set the API key in billing.ts"])),
+            Some(FailureReason::Crash)
+        );
+    }
+
+    #[test]
+    fn signals_are_killed_or_crash() {
+        let signaled = |signal| ProcessExit { code: None, signal: Some(signal) };
+        assert_eq!(classify(signaled(9), false, &[]), Some(FailureReason::Killed));
+        assert_eq!(classify(signaled(15), false, &[]), Some(FailureReason::Killed));
+        assert_eq!(classify(signaled(11), false, &[]), Some(FailureReason::Crash));
+    }
+}
@@ -3,6 +3,7 @@
 use rusqlite::Connection;
 use serde::Serialize;
 
+use crate::agents::events::ElfEvent;
 use crate::db::events::{self, EventRow};
 use crate::db::memory::{self, MemoryRow};
 use crate::db::DbError;
@@ -128,60 +129,83 @@ struct ExtractedEntry {
 }
 
 /// Extract memory candidates from a single event based on type and content patterns.
+///
+/// Typed events (rows with a `kind`) contribute their text, tool name, or error message;
+/// raw rows from before typed events fall back to the whole payload string.
 fn extract_from_event(event: &EventRow, entries: &mut Vec<ExtractedEntry>) {
-    let payload_lower = event.payload.to_lowercase();
-
-    match event.event_type.as_str() {
-        // Output events contain agent reasoning — extract as context
-        "output" | "assistant" | "text" => {
-            // Check for decision patterns first (higher priority)
-            if contains_any(&payload_lower, DECISION_KEYWORDS) {
-                entries.push(ExtractedEntry {
-                    category: "decision".to_string(),
-                    content: truncate_content(&event.payload, 500),
-                    tags: vec!["auto-extracted".to_string()],
-                });
-            }
-            // Check for learning patterns
-            else if contains_any(&payload_lower, LEARNING_KEYWORDS) {
-                entries.push(ExtractedEntry {
-                    category: "learning".to_string(),
-                    content: truncate_content(&event.payload, 500),
-                    tags: vec!["auto-extracted".to_string()],
-                });
-            }
-            // Long output events are likely substantive context
-            else if event.payload.len() > 100 {
-                entries.push(ExtractedEntry {
-                    category: "context".to_string(),
-                    content: truncate_content(&event.payload, 300),
-                    tags: vec!["auto-extracted".to_string()],
-                });
+    let typed = event
+        .kind
+        .as_ref()
+        .and_then(|_| serde_json::from_str::<ElfEvent>(&event.payload).ok());
+    if let Some(typed) = typed {
+        match typed {
+            ElfEvent::Text { text } => extract_from_text(&text, entries),
+            ElfEvent::ToolCall { name, input, .. } => {
+                extract_tool_use(&format!("{name} {input}"), entries)
             }
+            ElfEvent::Error { message } => extract_error(&message, entries),
+            _ => {}
         }
+        return;
+    }
 
+    match event.event_type.as_str() {
+        // Output events contain agent reasoning — extract as context
+        "output" | "assistant" | "text" => extract_from_text(&event.payload, entries),
         // Tool calls reveal what the agent did — useful context
-        "tool_use" | "tool_call" => {
-            entries.push(ExtractedEntry {
-                category: "context".to_string(),
-                content: format!("Tool used: {}", truncate_content(&event.payload, 200)),
-                tags: vec!["auto-extracted".to_string(), "tool-usage".to_string()],
-            });
-        }
-
+        "tool_use" | "tool_call" => extract_tool_use(&event.payload, entries),
         // Error events paired with subsequent resolution → learning
-        "error" => {
-            entries.push(ExtractedEntry {
-                category: "learning".to_string(),
-                content: format!("Error encountered: {}", truncate_content(&event.payload, 300)),
-                tags: vec!["auto-extracted".to_string(), "error".to_string()],
-            });
-        }
-
+        "error" => extract_error(&event.payload, entries),
         _ => {}
     }
 }
 
+/// Agent output: a decision, a learning, or (when long enough) general context.
+fn extract_from_text(text: &str, entries: &mut Vec<ExtractedEntry>) {
+    let text_lower = text.to_lowercase();
+
+    // Check for decision patterns first (higher priority)
+    if contains_any(&text_lower, DECISION_KEYWORDS) {
+        entries.push(ExtractedEntry {
+            category: "decision".to_string(),
+            content: truncate_content(text, 500),
+            tags: vec!["auto-extracted".to_string()],
+        });
+    }
+    // Check for learning patterns
+    else if contains_any(&text_lower, LEARNING_KEYWORDS) {
+        entries.push(ExtractedEntry {
+            category: "learning".to_string(),
+            content: truncate_content(text, 500),
+            tags: vec!["auto-extracted".to_string()],
+        });
+    }
+    // Long output events are likely substantive context
+    else if text.len() > 100 {
+        entries.push(ExtractedEntry {
+            category: "context".to_string(),
+            content: truncate_content(text, 300),
+            tags: vec!["auto-extracted".to_string()],
+        });
+    }
+}
+
+fn extract_tool_use(tool: &str, entries: &mut Vec<ExtractedEntry>) {
+    entries.push(ExtractedEntry {
+        category: "context".to_string(),
+        content: format!("Tool used: {}", truncate_content(tool, 200)),
+        tags: vec!["auto-extracted".to_string(), "tool-usage".to_string()],
+    });
+}
+
+fn extract_error(message: &str, entries: &mut Vec<ExtractedEntry>) {
+    entries.push(ExtractedEntry {
+        category: "learning".to_string(),
+        content: format!("Error encountered: {}", truncate_content(message, 300)),
+        tags: vec!["auto-extracted".to_string(), "error".to_string()],
+    });
+}
+
 /// Check if text contains any of the given keyword patterns.
 fn contains_any(text: &str, keywords: &[&str]) -> bool {
     keywords.iter().any(|kw| text.contains(kw))
@@ -401,6 +425,33 @@ mod tests {
         assert!(result.memories[0].content.contains("Error encountered:"));
     }
 
+    #[test]
+    fn extract_uses_typed_event_fields() {
+        let conn = test_conn();
+        seed_session(&conn, "proj-1", "sess-1");
+
+        let text = ElfEvent::Text {
+            text: "We decided to keep SQLite for the event log".to_string(),
+        };
+        let tool = ElfEvent::ToolCall {
+            id: Some("t1".to_string()),
+            name: "Read".to_string(),
+            input: serde_json::json!({ "file_path": "src/main.rs" }),
+        };
+        for event in [&text, &tool] {
+            let payload = serde_json::to_string(event).unwrap();
+            events::insert_typed_event(&conn, "sess-1", None, event.kind(), &payload).unwrap();
+        }
+
+        let result = extract_memories(&conn, "sess-1").expect("Should extract");
+        assert_eq!(result.memories.len(), 2);
+        // Only the text itself is stored, not the surrounding event JSON
+        assert_eq!(result.memories[0].category, "decision");
+        assert_eq!(result.memories[0].content, "We decided to keep SQLite for the event log");
+        assert!(result.memories[1].content.starts_with("Tool used: Read "));
+        assert!(result.session_summary.contains("1 tool calls"));
+    }
+
     #[test]
     fn extract_deduplicates_similar_content() {
         let conn = test_conn();
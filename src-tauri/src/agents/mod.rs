@@ -1,11 +1,21 @@
 // Agent subsystem — runtime detection, process management, protocol adapters, and task analysis.
 
 pub mod analyzer;
+pub mod budget;
 pub mod claude_adapter;
 pub mod claude_discovery;
+pub mod claude_sessions;
 pub mod codex_adapter;
 pub mod context_builder;
+pub mod events;
+pub mod failure;
 pub mod interop;
 pub mod memory_extractor;
+pub mod pricing;
 pub mod process;
+pub mod recording;
+pub mod run_state;
 pub mod runtime;
+pub mod scrollback;
+pub mod transcript;
+pub mod watchdog;
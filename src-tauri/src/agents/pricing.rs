@@ -0,0 +1,286 @@
+// Model pricing — USD per million tokens for each model, and the cost of a run's usage.
+//
+// Every runtime's cost is computed from its token counts with the same table, so Claude
+// and Codex sessions are priced alike. Built-in prices cover current Claude and OpenAI
+// models; `pricing.models` in `~/.elves/settings.json` overrides or extends them. A
+// model is priced by the longest key its name starts with, so `claude-sonnet-4-5-20250929`
+// uses the `claude-sonnet-4-5` price.
+
+use crate::agents::events::ElfEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Model name recorded for usage of a model the runtime never reported.
+pub const UNKNOWN_MODEL: &str = "unknown";
+
+/// USD per million tokens of each kind.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    pub cache_read: f64,
+    pub cache_write: f64,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self { input, output, cache_read, cache_write }
+    }
+
+    /// Cost in USD of the given token counts.
+    pub fn cost(&self, tokens: &TokenUsage) -> f64 {
+        (tokens.input_tokens as f64 * self.input
+            + tokens.output_tokens as f64 * self.output
+            + tokens.cache_read_tokens as f64 * self.cache_read
+            + tokens.cache_write_tokens as f64 * self.cache_write)
+            / 1_000_000.0
+    }
+}
+
+/// List prices by model name prefix. Aliases (`opus`, `sonnet`, `haiku`) cover runs
+/// started with `--model <alias>` whose runtime never reports the full name.
+const BUILT_IN_PRICES: &[(&str, ModelPrice)] = &[
+    ("claude-opus-4-5", ModelPrice::new(5.0, 25.0, 0.5, 6.25)),
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0, 1.5, 18.75)),
+    ("claude-3-opus", ModelPrice::new(15.0, 75.0, 1.5, 18.75)),
+    ("claude-sonnet-4", ModelPrice::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-haiku-4-5", ModelPrice::new(1.0, 5.0, 0.1, 1.25)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0, 0.08, 1.0)),
+    ("opus", ModelPrice::new(5.0, 25.0, 0.5, 6.25)),
+    ("sonnet", ModelPrice::new(3.0, 15.0, 0.3, 3.75)),
+    ("haiku", ModelPrice::new(1.0, 5.0, 0.1, 1.25)),
+    ("gpt-5", ModelPrice::new(1.25, 10.0, 0.125, 0.0)),
+    ("gpt-5-mini", ModelPrice::new(0.25, 2.0, 0.025, 0.0)),
+    ("gpt-5-nano", ModelPrice::new(0.05, 0.4, 0.005, 0.0)),
+    ("gpt-4.1", ModelPrice::new(2.0, 8.0, 0.5, 0.0)),
+    ("o3", ModelPrice::new(2.0, 8.0, 0.5, 0.0)),
+    ("o4-mini", ModelPrice::new(1.1, 4.4, 0.275, 0.0)),
+    ("codex-mini", ModelPrice::new(1.5, 6.0, 0.375, 0.0)),
+];
+
+/// The prices used to cost token usage, stored under `pricing` in the app settings.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PricingTable {
+    /// Prices by model name prefix, taking precedence over the built-in prices.
+    pub models: BTreeMap<String, ModelPrice>,
+    /// Price of models matching no entry.
+    pub fallback: ModelPrice,
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self {
+            models: BTreeMap::new(),
+            fallback: ModelPrice::new(3.0, 15.0, 0.3, 3.75),
+        }
+    }
+}
+
+impl PricingTable {
+    /// The price of a model: the configured entry with the longest matching prefix, else
+    /// the built-in one, else the fallback. Matching ignores case.
+    pub fn price(&self, model: &str) -> ModelPrice {
+        let model = model.to_ascii_lowercase();
+        let longest = |entries: &mut dyn Iterator<Item = (&str, ModelPrice)>| {
+            entries
+                .filter(|(prefix, _)| model.starts_with(&prefix.to_ascii_lowercase()))
+                .max_by_key(|(prefix, _)| prefix.len())
+                .map(|(_, price)| price)
+        };
+        longest(&mut self.models.iter().map(|(prefix, price)| (prefix.as_str(), *price)))
+            .or_else(|| longest(&mut BUILT_IN_PRICES.iter().copied()))
+            .unwrap_or(self.fallback)
+    }
+
+    /// Cost in USD of a model's token usage.
+    pub fn cost(&self, model: &str, tokens: &TokenUsage) -> f64 {
+        self.price(model).cost(tokens)
+    }
+}
+
+/// Token counts by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+}
+
+impl TokenUsage {
+    fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+    }
+}
+
+/// Usage and cost of one model, for one elf in team runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelUsage {
+    pub elf_id: Option<String>,
+    pub model: String,
+    pub tokens: TokenUsage,
+    pub cost_usd: f64,
+}
+
+/// A run's usage per elf and model, priced as it is reported.
+///
+/// Usage events without a model are attributed to the model from the runtime's init
+/// event, or the model the run was started with.
+#[derive(Debug, Clone)]
+pub struct UsageLedger {
+    pricing: PricingTable,
+    model: Option<String>,
+    entries: Vec<ModelUsage>,
+    reported_cost: Option<f64>,
+}
+
+impl UsageLedger {
+    pub fn new(pricing: PricingTable, model: Option<String>) -> Self {
+        Self {
+            pricing,
+            model,
+            entries: Vec::new(),
+            reported_cost: None,
+        }
+    }
+
+    /// Add the usage reported by a line's events, attributed to `elf_id`. Returns
+    /// whether the run's cost or usage changed.
+    pub fn record(&mut self, elf_id: Option<&str>, events: &[ElfEvent]) -> bool {
+        let mut changed = false;
+        for event in events {
+            match event {
+                ElfEvent::Init { model: Some(model), .. } => self.model = Some(model.clone()),
+                ElfEvent::Usage { input_tokens, output_tokens, cache_read_tokens, cache_write_tokens, model } => {
+                    let tokens = TokenUsage {
+                        input_tokens: *input_tokens,
+                        output_tokens: *output_tokens,
+                        cache_read_tokens: *cache_read_tokens,
+                        cache_write_tokens: *cache_write_tokens,
+                    };
+                    let model = model
+                        .clone()
+                        .or_else(|| self.model.clone())
+                        .unwrap_or_else(|| UNKNOWN_MODEL.to_string());
+                    self.add(elf_id, &model, &tokens);
+                    changed = true;
+                }
+                ElfEvent::Result { cost_usd: Some(cost), .. } => {
+                    self.reported_cost = Some(*cost);
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+        changed
+    }
+
+    fn add(&mut self, elf_id: Option<&str>, model: &str, tokens: &TokenUsage) {
+        let cost = self.pricing.cost(model, tokens);
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.elf_id.as_deref() == elf_id && entry.model == model)
+        {
+            Some(entry) => {
+                entry.tokens.add(tokens);
+                entry.cost_usd += cost;
+            }
+            None => self.entries.push(ModelUsage {
+                elf_id: elf_id.map(String::from),
+                model: model.to_string(),
+                tokens: *tokens,
+                cost_usd: cost,
+            }),
+        }
+    }
+
+    /// The run's usage per elf and model. A run that reported a cost but no token counts
+    /// has a single entry with that cost.
+    pub fn usage(&self) -> Vec<ModelUsage> {
+        match (self.entries.is_empty(), self.reported_cost) {
+            (true, Some(cost)) => vec![ModelUsage {
+                elf_id: None,
+                model: self.model.clone().unwrap_or_else(|| UNKNOWN_MODEL.to_string()),
+                tokens: TokenUsage::default(),
+                cost_usd: cost,
+            }],
+            _ => self.entries.clone(),
+        }
+    }
+
+    /// Total cost of the run so far.
+    pub fn total_cost(&self) -> f64 {
+        self.usage().iter().map(|entry| entry.cost_usd).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input: i64, output: i64, model: Option<&str>) -> ElfEvent {
+        ElfEvent::Usage {
+            input_tokens: input,
+            output_tokens: output,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            model: model.map(String::from),
+        }
+    }
+
+    #[test]
+    fn models_are_priced_by_their_longest_prefix() {
+        let mut table = PricingTable::default();
+        assert_eq!(table.price("claude-opus-4-5-20251101").input, 5.0);
+        assert_eq!(table.price("claude-opus-4-1-20250805").input, 15.0);
+        assert_eq!(table.price("GPT-5-mini").output, 2.0);
+        assert_eq!(table.price("mystery-model"), table.fallback);
+
+        table.models.insert("claude-opus".into(), ModelPrice::new(1.0, 2.0, 0.0, 0.0));
+        assert_eq!(table.price("claude-opus-4-5").input, 1.0);
+
+        let tokens = TokenUsage { input_tokens: 1_000_000, output_tokens: 500_000, cache_read_tokens: 2_000_000, cache_write_tokens: 0 };
+        assert_eq!(PricingTable::default().cost("gpt-5-codex", &tokens), 1.25 + 5.0 + 0.25);
+    }
+
+    #[test]
+    fn ledger_prices_usage_per_elf_and_model() {
+        let mut ledger = UsageLedger::new(PricingTable::default(), Some("sonnet".into()));
+        assert!(!ledger.record(None, &[ElfEvent::Text { text: "hi".into() }]));
+
+        let init = ElfEvent::Init { session_id: None, model: Some("claude-opus-4-5".into()) };
+        assert!(ledger.record(Some("elf-1"), &[init, usage(1_000_000, 0, None)]));
+        ledger.record(Some("elf-1"), &[usage(1_000_000, 0, None)]);
+        ledger.record(Some("elf-2"), &[usage(0, 1_000_000, Some("claude-haiku-4-5"))]);
+
+        let entries = ledger.usage();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].elf_id.as_deref(), Some("elf-1"));
+        assert_eq!(entries[0].model, "claude-opus-4-5");
+        assert_eq!(entries[0].tokens.input_tokens, 2_000_000);
+        assert_eq!(entries[0].cost_usd, 10.0);
+        assert_eq!(entries[1].cost_usd, 5.0);
+        assert_eq!(ledger.total_cost(), 15.0);
+    }
+
+    #[test]
+    fn ledger_falls_back_to_a_reported_cost_without_token_counts() {
+        let mut ledger = UsageLedger::new(PricingTable::default(), None);
+        let result = ElfEvent::Result { text: None, is_error: false, cost_usd: Some(0.5), duration_ms: None, session_id: None };
+        assert!(ledger.record(None, std::slice::from_ref(&result)));
+        assert_eq!(ledger.usage()[0].model, UNKNOWN_MODEL);
+        assert_eq!(ledger.total_cost(), 0.5);
+
+        // Once tokens are reported, the table prices the run
+        ledger.record(None, &[usage(0, 1_000_000, Some("gpt-5")), result]);
+        assert_eq!(ledger.total_cost(), 10.0);
+    }
+}
@@ -2,10 +2,58 @@
 //
 // Supports both single-agent sessions (one process per session) and team sessions
 // (multiple processes per session). The `teams` map handles multi-process tracking.
+// Agents lead their own process groups, which are killed as a whole; when a run
+// directory is configured, each tracked agent also has a PID file there (see run_state).
 
+use crate::agents::run_state::{self, RunDir};
 use std::collections::{HashMap, HashSet};
-use std::process::Child;
+use std::process::{Child, ExitStatus};
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often `wait_for_exit` and graceful stops poll a tracked child.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a graceful stop waits after each signal before escalating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopTimeouts {
+    /// After SIGINT, which lets an agent finish writing its final result.
+    pub interrupt: Duration,
+    /// After SIGTERM, before SIGKILL.
+    pub terminate: Duration,
+}
+
+/// Stop processes gracefully: SIGINT to each one's process group (or the process itself
+/// when it leads none), then SIGTERM once `timeouts.interrupt` has passed. `running` is
+/// polled in between and reports whether a process is still alive. Returns the processes
+/// still running after `timeouts.terminate`, for the caller to kill.
+pub fn interrupt_then_terminate<P>(
+    mut processes: Vec<P>,
+    timeouts: StopTimeouts,
+    pid: impl Fn(&P) -> Option<u32>,
+    mut running: impl FnMut(&mut P) -> bool,
+) -> Vec<P> {
+    for (signal, timeout) in [("INT", timeouts.interrupt), ("TERM", timeouts.terminate)] {
+        processes.retain_mut(&mut running);
+        if processes.is_empty() {
+            break;
+        }
+        for pid in processes.iter().filter_map(&pid) {
+            if !run_state::signal_process_group(pid, signal) {
+                run_state::signal_process(pid, signal);
+            }
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            processes.retain_mut(&mut running);
+            if processes.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+            std::thread::sleep(EXIT_POLL_INTERVAL);
+        }
+    }
+    processes
+}
 
 /// Tracks active agent child processes keyed by session ID.
 ///
@@ -22,6 +70,8 @@ pub struct ProcessManager {
     /// When a session is in this set, the stdout stream reader should NOT
     /// emit `session:completed` on process exit — the PTY terminal takes over.
     interactive: Mutex<HashSet<String>>,
+    /// Where PID files of tracked agents are kept, if anywhere.
+    run_dir: Option<RunDir>,
 }
 
 impl ProcessManager {
@@ -31,18 +81,49 @@ impl ProcessManager {
             processes: Mutex::new(HashMap::new()),
             teams: Mutex::new(HashMap::new()),
             interactive: Mutex::new(HashSet::new()),
+            run_dir: None,
+        }
+    }
+
+    /// Create a process manager that records a PID file in `run_dir` for every tracked
+    /// agent, so agents that outlive the app can be cleaned up on the next start.
+    pub fn with_run_dir(run_dir: RunDir) -> Self {
+        Self {
+            run_dir: Some(run_dir),
+            ..Self::new()
+        }
+    }
+
+    fn track(&self, session_id: &str, child: &Child) {
+        if let Some(run_dir) = &self.run_dir {
+            run_dir.record(&run_state::track(session_id, child.id()));
         }
     }
 
+    fn untrack(&self, child: &Child) {
+        if let Some(run_dir) = &self.run_dir {
+            run_dir.forget(child.id());
+        }
+    }
+
+    /// Kill a child together with its process group (tool subprocesses included), reap it,
+    /// and remove its PID file.
+    fn terminate(&self, mut child: Child) {
+        run_state::signal_process_group(child.id(), "KILL");
+        let _ = child.kill();
+        let _ = child.wait();
+        self.untrack(&child);
+    }
+
     /// Register a spawned child process for the given session (single-agent mode).
     ///
     /// If a process already exists for this session, the old process is killed
     /// and reaped before inserting the new one to prevent zombie process leaks.
     pub fn register(&self, session_id: &str, child: Child) {
+        self.track(session_id, &child);
         let mut processes = self.processes.lock().expect("ProcessManager lock poisoned");
-        if let Some(mut old) = processes.insert(session_id.to_string(), child) {
-            let _ = old.kill();
-            let _ = old.wait();
+        if let Some(old) = processes.insert(session_id.to_string(), child) {
+            self.terminate(old);
         }
     }
 
@@ -52,37 +133,115 @@ impl ProcessManager {
     /// (but not killed — the caller should kill them first if needed).
     #[allow(dead_code)]
     pub fn register_team(&self, session_id: &str, children: Vec<Child>) {
+        for child in &children {
+            self.track(session_id, child);
+        }
         let mut teams = self.teams.lock().expect("ProcessManager teams lock poisoned");
         teams.insert(session_id.to_string(), children);
     }
 
     /// Kill the process for a specific session and remove it from tracking.
     ///
-    /// Sends SIGKILL to the child and reaps the zombie via `wait()`.
+    /// Sends SIGKILL to the child's process group and reaps the zombie via `wait()`.
     /// Returns true if a process was found and killed, false if no process
     /// existed for the given session_id.
     pub fn kill(&self, session_id: &str) -> bool {
         let mut processes = self.processes.lock().expect("ProcessManager lock poisoned");
-        if let Some(mut child) = processes.remove(session_id) {
-            let _ = child.kill();
-            let _ = child.wait();
+        if let Some(child) = processes.remove(session_id) {
+            self.terminate(child);
             true
         } else {
             false
         }
     }
 
+    /// Stop the process for a specific session gracefully and remove it from tracking.
+    ///
+    /// Escalates SIGINT → SIGTERM → SIGKILL on its process group (see
+    /// `interrupt_then_terminate`), blocking until it is gone. The process stops being
+    /// tracked first, so `wait_for_exit` returns None; its stdout stays open until it
+    /// exits, so the final result is still read. Returns false if no process existed.
+    pub fn stop(&self, session_id: &str, timeouts: StopTimeouts) -> bool {
+        let child = self.processes.lock().expect("ProcessManager lock poisoned").remove(session_id);
+        match child {
+            Some(child) => {
+                self.stop_children(vec![child], timeouts);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop all processes of a team session gracefully, like `stop`. Returns the number
+    /// of processes stopped.
+    pub fn stop_team(&self, session_id: &str, timeouts: StopTimeouts) -> usize {
+        let children = self.teams.lock().expect("ProcessManager teams lock poisoned").remove(session_id);
+        let children = children.unwrap_or_default();
+        let count = children.len();
+        self.stop_children(children, timeouts);
+        count
+    }
+
+    fn stop_children(&self, children: Vec<Child>, timeouts: StopTimeouts) {
+        let survivors = interrupt_then_terminate(
+            children,
+            timeouts,
+            |child| Some(child.id()),
+            |child| match child.try_wait() {
+                Ok(None) => true,
+                Ok(Some(_)) | Err(_) => {
+                    self.untrack(child);
+                    false
+                }
+            },
+        );
+        for child in survivors {
+            self.terminate(child);
+        }
+    }
+
+    /// Wait for the single-agent process of a session to exit, then stop tracking it.
+    ///
+    /// Polls instead of blocking in `wait()` so the child stays registered — and
+    /// killable by `kill()` — while it shuts down. Returns None if no process is tracked,
+    /// or if it was removed (e.g. killed by `stop_task`) before its exit was observed.
+    pub fn wait_for_exit(&self, session_id: &str) -> Option<ExitStatus> {
+        loop {
+            {
+                let mut processes = self.processes.lock().expect("ProcessManager lock poisoned");
+                let child = processes.get_mut(session_id)?;
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        if let Some(child) = processes.remove(session_id) {
+                            self.untrack(&child);
+                        }
+                        return Some(status);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::warn!("[session {session_id}] Failed to poll process exit: {e}");
+                        if let Some(child) = processes.remove(session_id) {
+                            self.untrack(&child);
+                        }
+                        return None;
+                    }
+                }
+            }
+            std::thread::sleep(EXIT_POLL_INTERVAL);
+        }
+    }
+
     /// Kill all processes for a team session and remove them from tracking.
     ///
     /// Returns the number of child processes that were killed. Returns 0 if
     /// no team was registered for the given session_id.
+    #[allow(dead_code)]
     pub fn kill_team(&self, session_id: &str) -> usize {
         let mut teams = self.teams.lock().expect("ProcessManager teams lock poisoned");
         if let Some(children) = teams.remove(session_id) {
             let count = children.len();
-            for mut child in children {
-                let _ = child.kill();
-                let _ = child.wait();
+            for child in children {
+                self.terminate(child);
             }
             count
         } else {
@@ -93,16 +252,14 @@ impl ProcessManager {
     /// Kill all active processes (both single and team). Called on app shutdown.
     ///
     /// Returns the total number of processes that were killed.
-    #[allow(dead_code)]
     pub fn kill_all(&self) -> usize {
         let mut count = 0;
 
         {
             let mut processes = self.processes.lock().expect("ProcessManager lock poisoned");
             count += processes.len();
-            for (_, mut child) in processes.drain() {
-                let _ = child.kill();
-                let _ = child.wait();
+            for (_, child) in processes.drain() {
+                self.terminate(child);
             }
         }
 
@@ -110,9 +267,8 @@ impl ProcessManager {
             let mut teams = self.teams.lock().expect("ProcessManager teams lock poisoned");
             for (_, children) in teams.drain() {
                 count += children.len();
-                for mut child in children {
-                    let _ = child.kill();
-                    let _ = child.wait();
+                for child in children {
+                    self.terminate(child);
                 }
             }
         }
@@ -279,6 +435,21 @@ mod tests {
         pm.kill("sess-1");
     }
 
+    #[test]
+    fn wait_for_exit_returns_status_and_untracks() {
+        let pm = ProcessManager::new();
+        let child = Command::new("sh")
+            .args(["-c", "exit 3"])
+            .spawn()
+            .expect("Failed to spawn sh");
+
+        pm.register("sess-1", child);
+        let status = pm.wait_for_exit("sess-1").expect("Should observe exit");
+        assert_eq!(status.code(), Some(3));
+        assert!(!pm.is_running("sess-1"));
+        assert!(pm.wait_for_exit("sess-1").is_none());
+    }
+
     // --- Team session tests ---
 
     fn spawn_sleep() -> Child {
@@ -368,4 +539,84 @@ mod tests {
         pm.kill_team("team-x");
         assert!(!pm.is_running("team-x"));
     }
+
+    // --- Run directory tests ---
+
+    #[test]
+    fn run_dir_tracks_registered_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let run_dir = RunDir::new(dir.path());
+        let pm = ProcessManager::with_run_dir(run_dir.clone());
+
+        let child = Command::new("sh")
+            .args(["-c", "exit 0"])
+            .spawn()
+            .expect("Failed to spawn sh");
+        let pid = child.id();
+        pm.register("sess-1", child);
+        pm.register_team("team-1", vec![spawn_sleep()]);
+        assert_eq!(run_dir.list().len(), 2);
+        assert!(run_dir.list().iter().any(|p| p.pid == pid && p.session_id == "sess-1"));
+
+        pm.wait_for_exit("sess-1");
+        assert_eq!(run_dir.list().len(), 1);
+        pm.kill_all();
+        assert!(run_dir.list().is_empty());
+    }
+
+    // --- Graceful stop tests ---
+
+    const QUICK: StopTimeouts = StopTimeouts {
+        interrupt: Duration::from_millis(500),
+        terminate: Duration::from_millis(500),
+    };
+
+    #[test]
+    fn stop_lets_the_process_handle_sigint() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("interrupted");
+        let pm = ProcessManager::new();
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            &format!("trap 'echo done > {}; exit 0' INT; while true; do sleep 0.05; done", marker.display()),
+        ]);
+        run_state::isolate_process_group(&mut cmd);
+        pm.register("sess-1", cmd.spawn().expect("Failed to spawn sh"));
+        std::thread::sleep(Duration::from_millis(200));
+
+        assert!(pm.stop("sess-1", QUICK));
+        assert!(!pm.is_running("sess-1"));
+        assert!(marker.exists());
+    }
+
+    #[test]
+    fn stop_escalates_when_signals_are_ignored() {
+        let pm = ProcessManager::new();
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "trap '' INT TERM; while true; do sleep 0.05; done"]);
+        run_state::isolate_process_group(&mut cmd);
+        pm.register("sess-1", cmd.spawn().expect("Failed to spawn sh"));
+        std::thread::sleep(Duration::from_millis(200));
+
+        let started = Instant::now();
+        assert!(pm.stop("sess-1", QUICK));
+        assert!(started.elapsed() >= Duration::from_millis(1000));
+        assert_eq!(pm.active_count(), 0);
+    }
+
+    #[test]
+    fn stop_nonexistent_returns_false() {
+        let pm = ProcessManager::new();
+        assert!(!pm.stop("nope", QUICK));
+        assert_eq!(pm.stop_team("nope", QUICK), 0);
+    }
+
+    #[test]
+    fn stop_team_stops_every_process() {
+        let pm = ProcessManager::new();
+        pm.register_team("team-1", vec![spawn_sleep(), spawn_sleep()]);
+        assert_eq!(pm.stop_team("team-1", QUICK), 2);
+        assert!(!pm.is_running("team-1"));
+    }
 }
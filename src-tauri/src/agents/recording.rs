@@ -0,0 +1,125 @@
+// Terminal recording — writes a PTY's output and resizes as an asciicast v2 file
+// (https://docs.asciinema.org/manual/asciicast/v2/), playable with `asciinema play` and
+// embedded in session replays.
+//
+// A cast is a JSON header line followed by one `[seconds, code, data]` line per event:
+// code "o" for output and "r" for a resize to "COLSxROWS". Recordings of ELVES sessions
+// are stored under `~/.elves/recordings/` and linked to their session in the database.
+
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Default recordings directory: ~/.elves/recordings
+pub fn default_recordings_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".elves")
+        .join("recordings")
+}
+
+/// The cast file of a session's PTY run started at `started_ms` (Unix milliseconds):
+/// `<session>-<started>.cast`, or `<session>-<elf>-<started>.cast` for a team role.
+/// Resumed sessions run again under the same ID, so each run gets its own file.
+pub fn recording_path(dir: &Path, session_id: &str, elf_id: Option<&str>, started_ms: i64) -> PathBuf {
+    match elf_id {
+        Some(elf_id) => dir.join(format!("{session_id}-{elf_id}-{started_ms}.cast")),
+        None => dir.join(format!("{session_id}-{started_ms}.cast")),
+    }
+}
+
+/// Writes asciicast v2 events, timed from when the recording started.
+pub struct CastRecorder<W: Write = BufWriter<File>> {
+    out: W,
+    started: Instant,
+}
+
+impl CastRecorder {
+    /// Create the cast file at `path`, creating its directory if needed. Fails rather
+    /// than overwrite an earlier recording.
+    pub fn create(path: &Path, cols: u16, rows: u16) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::options().write(true).create_new(true).open(path)?;
+        Self::new(BufWriter::new(file), cols, rows)
+    }
+}
+
+impl<W: Write> CastRecorder<W> {
+    /// Start a recording of a `cols` x `rows` terminal by writing the header.
+    pub fn new(mut out: W, cols: u16, rows: u16) -> std::io::Result<Self> {
+        let header = json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": chrono::Utc::now().timestamp(),
+            "env": { "TERM": std::env::var("TERM").unwrap_or_else(|_| "xterm-256color".to_string()) },
+        });
+        writeln!(out, "{header}")?;
+        Ok(Self { out, started: Instant::now() })
+    }
+
+    /// Record terminal output.
+    pub fn output(&mut self, data: &str) -> std::io::Result<()> {
+        self.event("o", data)
+    }
+
+    /// Record a terminal resize.
+    pub fn resize(&mut self, cols: u16, rows: u16) -> std::io::Result<()> {
+        self.event("r", &format!("{cols}x{rows}"))
+    }
+
+    /// Write buffered events to the file.
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> std::io::Result<()> {
+        let elapsed = (self.started.elapsed().as_secs_f64() * 1e6).round() / 1e6;
+        writeln!(self.out, "{}", json!([elapsed, code, data]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn casts_have_a_header_then_timed_output_and_resizes() {
+        let mut recorder = CastRecorder::new(Vec::new(), 80, 24).unwrap();
+        recorder.output("hello \u{1b}[1mworld\u{1b}[0m\r\n").unwrap();
+        recorder.resize(120, 40).unwrap();
+        let cast = String::from_utf8(recorder.out).unwrap();
+
+        let lines: Vec<Value> = cast.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["height"], 24);
+        assert!(lines[1][0].as_f64().unwrap() >= 0.0);
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "hello \u{1b}[1mworld\u{1b}[0m\r\n");
+        assert_eq!(lines[2][1], "r");
+        assert_eq!(lines[2][2], "120x40");
+    }
+
+    #[test]
+    fn recordings_are_named_after_their_session_elf_and_run() {
+        let dir = Path::new("/home/u/.elves/recordings");
+        assert_eq!(recording_path(dir, "s1", None, 1000), dir.join("s1-1000.cast"));
+        assert_eq!(recording_path(dir, "s1", Some("e1"), 1000), dir.join("s1-e1-1000.cast"));
+        assert_ne!(recording_path(dir, "s1", None, 1000), recording_path(dir, "s1", None, 2000));
+    }
+
+    #[test]
+    fn existing_recordings_are_never_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s1-1000.cast");
+        CastRecorder::create(&path, 80, 24).unwrap();
+        assert!(CastRecorder::create(&path, 80, 24).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// Run state — PID files for the agent processes this app has running, so processes that
+// outlive the app (crash, force quit) can be found and cleaned up on the next start.
+//
+// Each print-mode agent is spawned as the leader of its own process group, so signalling
+// the group also reaches the tool subprocesses it started. One file per agent lives under
+// `~/.elves/run/<pid>.json`; it is removed when the agent exits or is killed. Start times
+// are recorded so a recycled PID is never mistaken for a surviving agent.
+//
+// PTY sessions are not tracked: closing the PTY master hangs up their whole session.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// An agent process recorded in the run directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedProcess {
+    pub pid: u32,
+    /// Process group the agent leads; signalled as a whole.
+    pub pgid: u32,
+    /// Start time as reported by `ps`, used to tell the agent apart from a later process
+    /// that reused its PID. None if it could not be read.
+    pub start_time: Option<String>,
+    pub session_id: String,
+}
+
+/// Default run directory: ~/.elves/run
+pub fn default_run_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".elves")
+        .join("run")
+}
+
+/// The directory of PID files for running agents.
+#[derive(Debug, Clone)]
+pub struct RunDir {
+    dir: PathBuf,
+}
+
+impl RunDir {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn file_for(&self, pid: u32) -> PathBuf {
+        self.dir.join(format!("{pid}.json"))
+    }
+
+    /// Record a running agent. Failures are logged, not returned: tracking is best effort
+    /// and must not stop the agent from running.
+    pub fn record(&self, process: &TrackedProcess) {
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|()| serde_json::to_vec(process).map_err(std::io::Error::other))
+            .and_then(|json| std::fs::write(self.file_for(process.pid), json));
+        if let Err(e) = result {
+            log::warn!("Failed to record agent process {}: {e}", process.pid);
+        }
+    }
+
+    /// Stop tracking an agent that has exited or been killed.
+    pub fn forget(&self, pid: u32) {
+        let path = self.file_for(pid);
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to remove {}: {e}", path.display());
+            }
+        }
+    }
+
+    /// All recorded agents. Unreadable files are skipped.
+    pub fn list(&self) -> Vec<TrackedProcess> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| read_tracked(&entry.path()))
+            .collect()
+    }
+}
+
+fn read_tracked(path: &Path) -> Option<TrackedProcess> {
+    let json = std::fs::read(path).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Describe a just-spawned agent for recording. Agents lead their own process group.
+pub fn track(session_id: &str, pid: u32) -> TrackedProcess {
+    TrackedProcess {
+        pid,
+        pgid: pid,
+        start_time: process_start_time(pid),
+        session_id: session_id.to_string(),
+    }
+}
+
+/// Make a command's child the leader of a new process group (Unix only).
+pub fn isolate_process_group(cmd: &mut Command) {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(cmd, 0);
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// Send `signal` (e.g. "TERM", "KILL") to every process in a group. Returns true if the
+/// group existed and was signalled. Always false off Unix.
+pub fn signal_process_group(pgid: u32, signal: &str) -> bool {
+    if !cfg!(unix) || pgid <= 1 {
+        return false;
+    }
+    Command::new("kill")
+        .args([format!("-{signal}"), "--".to_string(), format!("-{pgid}")])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Send `signal` to a single process. Returns true if it was signalled. Always false off Unix.
+pub fn signal_process(pid: u32, signal: &str) -> bool {
+    if !cfg!(unix) || pid <= 1 {
+        return false;
+    }
+    Command::new("kill")
+        .args([format!("-{signal}"), pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// When a process started, as reported by `ps`. None if it is not running.
+pub fn process_start_time(pid: u32) -> Option<String> {
+    if !cfg!(unix) {
+        return None;
+    }
+    let output = Command::new("ps")
+        .args(["-o", "lstart=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let start = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !start.is_empty()).then_some(start)
+}
+
+/// Kill the process groups of recorded agents that survived the previous app run, then
+/// clear the run directory. Returns the agents that were killed.
+///
+/// An agent is only killed if its PID still belongs to a process with the recorded start
+/// time; otherwise the PID has been reused and the file is simply removed.
+pub fn clean_up_survivors(run_dir: &RunDir) -> Vec<TrackedProcess> {
+    let mut killed = Vec::new();
+    for process in run_dir.list() {
+        let alive = process.start_time.is_some()
+            && process_start_time(process.pid) == process.start_time;
+        if alive && signal_process_group(process.pgid, "KILL") {
+            killed.push(process.clone());
+        }
+        run_dir.forget(process.pid);
+    }
+    killed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracked(pid: u32, start_time: Option<&str>) -> TrackedProcess {
+        TrackedProcess {
+            pid,
+            pgid: pid,
+            start_time: start_time.map(str::to_string),
+            session_id: "sess-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn record_list_and_forget() {
+        let dir = tempfile::tempdir().unwrap();
+        let run_dir = RunDir::new(dir.path().join("run"));
+        assert!(run_dir.list().is_empty());
+
+        run_dir.record(&tracked(100, Some("Sun Oct 18 10:00:00 2026")));
+        run_dir.record(&tracked(200, None));
+        let mut listed = run_dir.list();
+        listed.sort_by_key(|p| p.pid);
+        assert_eq!(listed, vec![tracked(100, Some("Sun Oct 18 10:00:00 2026")), tracked(200, None)]);
+
+        run_dir.forget(100);
+        run_dir.forget(100);
+        assert_eq!(run_dir.list(), vec![tracked(200, None)]);
+    }
+
+    #[test]
+    fn start_time_of_running_process() {
+        assert!(process_start_time(std::process::id()).is_some());
+    }
+
+    #[test]
+    fn survivors_with_reused_pids_are_not_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let run_dir = RunDir::new(dir.path());
+        // Our own PID, but with a start time that does not match
+        run_dir.record(&tracked(std::process::id(), Some("Thu Jan  1 00:00:00 1970")));
+        run_dir.record(&tracked(std::process::id() + 1, None));
+
+        assert!(clean_up_survivors(&run_dir).is_empty());
+        assert!(run_dir.list().is_empty());
+    }
+
+    #[test]
+    fn survivors_are_killed_with_their_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let run_dir = RunDir::new(dir.path());
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 30 & wait"]);
+        isolate_process_group(&mut cmd);
+        let mut child = cmd.spawn().expect("Failed to spawn sh");
+        run_dir.record(&track("sess-1", child.id()));
+
+        let killed = clean_up_survivors(&run_dir);
+        assert_eq!(killed.len(), 1);
+        assert_eq!(killed[0].pid, child.id());
+        assert!(child.wait().unwrap().code().is_none());
+        assert!(run_dir.list().is_empty());
+    }
+}
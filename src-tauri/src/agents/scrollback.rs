@@ -0,0 +1,223 @@
+// PTY scrollback — a bounded buffer of a terminal's recent output, kept by the backend so
+// a reloaded or reopened terminal view can be rehydrated, and searched.
+//
+// The buffer holds raw output, escape sequences included, so xterm redraws it as it was.
+// Once full, the oldest output is dropped up to the next line break, so replayed history
+// never starts partway through a line or an escape sequence.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Default scrollback size per PTY: 1 MiB.
+pub const DEFAULT_SCROLLBACK_BYTES: usize = 1024 * 1024;
+
+/// Longest line text returned by a search.
+const MAX_MATCH_CHARS: usize = 300;
+
+/// A line of scrollback containing the search query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollbackMatch {
+    /// Index of the line in the scrollback, oldest first.
+    pub line: usize,
+    /// The line as displayed, without escape sequences.
+    pub text: String,
+}
+
+/// Recent output of one PTY, at most `capacity` bytes.
+#[derive(Debug)]
+pub struct Scrollback {
+    chunks: VecDeque<String>,
+    len: usize,
+    capacity: usize,
+}
+
+impl Scrollback {
+    /// An empty scrollback of `capacity` bytes. A capacity of 0 keeps nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            len: 0,
+            capacity,
+        }
+    }
+
+    /// Append output, dropping the oldest output beyond the capacity.
+    pub fn push(&mut self, data: &str) {
+        if data.is_empty() || self.capacity == 0 {
+            return;
+        }
+        self.chunks.push_back(data.to_string());
+        self.len += data.len();
+
+        let mut trimmed = false;
+        while self.len > self.capacity {
+            let excess = self.len - self.capacity;
+            let Some(front) = self.chunks.front_mut() else {
+                break;
+            };
+            if front.len() <= excess {
+                self.len -= front.len();
+                self.chunks.pop_front();
+            } else {
+                let mut cut = excess;
+                while !front.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                front.drain(..cut);
+                self.len -= cut;
+            }
+            trimmed = true;
+        }
+
+        // Start at a line break, unless that would drop everything kept
+        if trimmed && self.chunks.len() > 1 {
+            if let Some(front) = self.chunks.front_mut() {
+                let cut = front.find('\n').map_or(front.len(), |newline| newline + 1);
+                front.drain(..cut);
+                self.len -= cut;
+                if front.is_empty() {
+                    self.chunks.pop_front();
+                }
+            }
+        }
+    }
+
+    /// All kept output, oldest first.
+    pub fn contents(&self) -> String {
+        let mut contents = String::with_capacity(self.len);
+        for chunk in &self.chunks {
+            contents.push_str(chunk);
+        }
+        contents
+    }
+
+    /// Lines containing `query`, ignoring case and escape sequences, oldest first.
+    /// Returns at most `limit` matches, the most recent ones when there are more.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<ScrollbackMatch> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let text = strip_ansi(&self.contents());
+        let mut matches: Vec<ScrollbackMatch> = text
+            .split('\n')
+            .map(displayed_line)
+            .enumerate()
+            .filter(|(_, line)| line.to_lowercase().contains(&query))
+            .map(|(line, text)| ScrollbackMatch {
+                line,
+                text: text.chars().take(MAX_MATCH_CHARS).collect(),
+            })
+            .collect();
+        if matches.len() > limit {
+            matches.drain(..matches.len() - limit);
+        }
+        matches
+    }
+}
+
+/// What a line shows once carriage returns have moved the cursor back to its start:
+/// the text written last (e.g. the final state of a progress bar).
+fn displayed_line(line: &str) -> &str {
+    line.trim_end_matches('\r')
+        .rsplit('\r')
+        .next()
+        .unwrap_or_default()
+}
+
+/// Remove terminal escape sequences (CSI, OSC and other ESC sequences) and control
+/// characters other than line breaks, carriage returns and tabs.
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // CSI: parameters, then a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, DCS and similar strings: until BEL or ESC \
+                Some(']' | 'P' | '_' | '^') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more character
+                Some('(' | ')' | '*' | '+' | '#') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\n' | '\r' | '\t' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrollback_keeps_the_most_recent_output_from_a_line_start() {
+        let mut scrollback = Scrollback::new(16);
+        scrollback.push("first line\r\n");
+        scrollback.push("second\r\n");
+        scrollback.push("third\r\n");
+        let contents = scrollback.contents();
+        assert!(contents.len() <= 16);
+        assert_eq!(contents, "second\r\nthird\r\n");
+
+        let mut unbounded = Scrollback::new(DEFAULT_SCROLLBACK_BYTES);
+        unbounded.push("a");
+        unbounded.push("b\r\n");
+        assert_eq!(unbounded.contents(), "ab\r\n");
+
+        let mut disabled = Scrollback::new(0);
+        disabled.push("ignored");
+        assert_eq!(disabled.contents(), "");
+    }
+
+    #[test]
+    fn trimming_never_splits_a_character() {
+        let mut scrollback = Scrollback::new(5);
+        scrollback.push("ééé");
+        scrollback.push("ü");
+        let contents = scrollback.contents();
+        assert!(contents.len() <= 5);
+        assert!(contents.ends_with('ü'));
+    }
+
+    #[test]
+    fn search_ignores_escape_sequences_case_and_overwritten_text() {
+        let mut scrollback = Scrollback::new(DEFAULT_SCROLLBACK_BYTES);
+        scrollback.push("\u{1b}]0;title\u{7}$ cargo test\r\n");
+        scrollback.push("\u{1b}[32mtest result: \u{1b}[1mOK\u{1b}[0m\r\n");
+        scrollback.push("Progress 10%\rProgress 100%\r\n");
+
+        let matches = scrollback.search("RESULT: ok", 10);
+        assert_eq!(matches, vec![ScrollbackMatch { line: 1, text: "test result: OK".into() }]);
+        assert_eq!(scrollback.search("progress", 10)[0].text, "Progress 100%");
+        assert!(scrollback.search("10%", 10).is_empty());
+        assert!(scrollback.search("title", 10).is_empty());
+        assert!(scrollback.search("  ", 10).is_empty());
+
+        let limited = scrollback.search("t", 1);
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].line, 1);
+    }
+}
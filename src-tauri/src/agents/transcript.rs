@@ -0,0 +1,451 @@
+// Agent transcripts — find, tail and parse the session logs Claude Code and Codex write
+// during interactive (PTY) runs.
+//
+// PTY output goes straight to the terminal, so the session ID, tool calls and usage of an
+// interactive run are read from the runtime's own transcript instead: Claude's
+// `~/.claude/projects/<encoded-cwd>/<session-id>.jsonl`, or Codex's rollout log under
+// `~/.codex/sessions/YYYY/MM/DD/`. commands/transcripts.rs tails them while the PTY runs.
+
+use crate::agents::events::{self, ElfEvent};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// How many lines at the start of a transcript are searched for a run's prompt.
+const HINT_SEARCH_LINES: usize = 64;
+
+/// Which runtime wrote a transcript, and so its format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptKind {
+    Claude,
+    Codex,
+}
+
+impl TranscriptKind {
+    pub fn for_runtime(runtime: &str) -> Self {
+        if runtime == "codex" {
+            Self::Codex
+        } else {
+            Self::Claude
+        }
+    }
+}
+
+/// Claude's directory name for a project: its path with every character other than
+/// ASCII letters and digits replaced by `-`.
+pub fn encode_project_path(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Finds the transcript of one PTY run.
+///
+/// Created before the agent starts, it snapshots the transcripts already on disk, so
+/// only a transcript the run creates (or the one it resumes) is picked up.
+#[derive(Debug, Clone)]
+pub struct TranscriptLocator {
+    kind: TranscriptKind,
+    dirs: Vec<PathBuf>,
+    /// Transcripts present before the run, with their length at the time.
+    existing: HashMap<PathBuf, u64>,
+    /// Claude transcript the run resumes, which it appends to.
+    resumed: Option<PathBuf>,
+    working_dir: String,
+    /// Text the run's prompt contains, to tell apart runs started together in one
+    /// directory (team roles).
+    hint: Option<String>,
+}
+
+impl TranscriptLocator {
+    /// Snapshot where a run of `kind` in `working_dir` will write its transcript, under
+    /// the user's `home` directory.
+    pub fn new(kind: TranscriptKind, home: &Path, working_dir: &str, resume_id: Option<&str>, hint: Option<String>) -> Self {
+        let dirs = match kind {
+            TranscriptKind::Claude => claude_project_dirs(home, working_dir),
+            TranscriptKind::Codex => codex_session_dirs(home),
+        };
+        let existing = dirs
+            .iter()
+            .flat_map(|dir| jsonl_files(dir))
+            .map(|(path, len, _)| (path, len))
+            .collect();
+        let resumed = match (kind, resume_id, dirs.first()) {
+            (TranscriptKind::Claude, Some(id), Some(dir)) => Some(dir.join(format!("{id}.jsonl"))),
+            _ => None,
+        };
+        Self {
+            kind,
+            dirs,
+            existing,
+            resumed,
+            working_dir: working_dir.to_string(),
+            hint,
+        }
+    }
+
+    pub fn kind(&self) -> TranscriptKind {
+        self.kind
+    }
+
+    /// The run's transcript and the offset its lines start at, once the agent has
+    /// written to it. Transcripts in `claimed` belong to other runs. New transcripts are
+    /// checked oldest first.
+    pub fn find(&self, claimed: &HashSet<PathBuf>) -> Option<(PathBuf, u64)> {
+        let mut new: Vec<(PathBuf, SystemTime)> = self
+            .dirs
+            .iter()
+            .flat_map(|dir| jsonl_files(dir))
+            .filter(|(path, _, _)| !self.existing.contains_key(path) && !claimed.contains(path))
+            .map(|(path, _, modified)| (path, modified))
+            .collect();
+        new.sort_by_key(|(_, modified)| *modified);
+        if let Some(path) = new.into_iter().map(|(path, _)| path).find(|path| self.is_this_run(path)) {
+            return Some((path, 0));
+        }
+
+        // A resumed Claude session appends to its existing transcript
+        let resumed = self.resumed.as_ref().filter(|path| !claimed.contains(*path))?;
+        let before = self.existing.get(resumed).copied().unwrap_or(0);
+        let len = std::fs::metadata(resumed).ok()?.len();
+        (len > before).then(|| (resumed.clone(), before))
+    }
+
+    /// Whether a new transcript belongs to this run: Codex logs must be for the run's
+    /// directory, and the first lines must contain the hint, if any.
+    fn is_this_run(&self, path: &Path) -> bool {
+        let Ok(file) = std::fs::File::open(path) else {
+            return false;
+        };
+        let head: Vec<String> = std::io::BufReader::new(file)
+            .lines()
+            .take(HINT_SEARCH_LINES)
+            .map_while(Result::ok)
+            .collect();
+        if self.kind == TranscriptKind::Codex {
+            let cwd = head
+                .first()
+                .and_then(|line| serde_json::from_str::<Value>(line).ok())
+                .and_then(|meta| meta.pointer("/payload/cwd").and_then(Value::as_str).map(String::from));
+            if cwd.as_deref() != Some(self.working_dir.as_str()) {
+                return false;
+            }
+        }
+        self.hint
+            .as_ref()
+            .is_none_or(|hint| head.iter().any(|line| line.contains(hint.as_str())))
+    }
+}
+
+/// Claude's project directories for `working_dir`: one for the path as given, and one
+/// for its canonical form when that differs (e.g. symlinked temp directories).
+pub fn claude_project_dirs(home: &Path, working_dir: &str) -> Vec<PathBuf> {
+    let projects = home.join(".claude").join("projects");
+    let mut dirs = vec![projects.join(encode_project_path(working_dir))];
+    if let Ok(canonical) = std::fs::canonicalize(working_dir) {
+        let dir = projects.join(encode_project_path(&canonical.to_string_lossy()));
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Codex's rollout directories for today and yesterday (local time), so a run started
+/// just before midnight is still found.
+fn codex_session_dirs(home: &Path) -> Vec<PathBuf> {
+    let sessions = home.join(".codex").join("sessions");
+    let today = chrono::Local::now().date_naive();
+    [Some(today), today.pred_opt()]
+        .into_iter()
+        .flatten()
+        .map(|day| sessions.join(day.format("%Y/%m/%d").to_string()))
+        .collect()
+}
+
+/// The `.jsonl` files in a directory, with their length and modification time.
+fn jsonl_files(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .filter_map(|path| {
+            let meta = std::fs::metadata(&path).ok()?;
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((path, meta.len(), modified))
+        })
+        .collect()
+}
+
+/// Reads the complete lines appended to a transcript since the last read. A line still
+/// being written is kept until its newline arrives.
+#[derive(Debug)]
+pub struct TranscriptTail {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl TranscriptTail {
+    pub fn new(path: PathBuf, offset: u64) -> Self {
+        Self { path, offset, partial: Vec::new() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Lines appended since the last call, without their newlines.
+    pub fn read_lines(&mut self) -> std::io::Result<Vec<String>> {
+        let mut file = std::fs::File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut appended = Vec::new();
+        self.offset += file.read_to_end(&mut appended)? as u64;
+        self.partial.extend_from_slice(&appended);
+
+        let Some(last_newline) = self.partial.iter().rposition(|b| *b == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let rest = self.partial.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        Ok(String::from_utf8_lossy(&complete)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
+
+/// Turns transcript lines into typed events.
+///
+/// Claude writes a line per content block of an assistant message, each repeating the
+/// message's usage, so usage is held until the next message starts (or `finish`) and
+/// reported once. Codex repeats its token counts when nothing changed; repeats are dropped.
+#[derive(Debug)]
+pub struct TranscriptParser {
+    kind: TranscriptKind,
+    session_id: Option<String>,
+    pending_usage: Option<(String, ElfEvent)>,
+    last_codex_total: Option<Value>,
+}
+
+impl TranscriptParser {
+    pub fn new(kind: TranscriptKind) -> Self {
+        Self {
+            kind,
+            session_id: None,
+            pending_usage: None,
+            last_codex_total: None,
+        }
+    }
+
+    /// The events of one transcript line.
+    pub fn parse_line(&mut self, value: &Value) -> Vec<ElfEvent> {
+        match self.kind {
+            TranscriptKind::Claude => self.parse_claude(value),
+            TranscriptKind::Codex => self.parse_codex(value),
+        }
+    }
+
+    /// Events held back until the transcript ends: the last message's usage.
+    pub fn finish(&mut self) -> Vec<ElfEvent> {
+        self.pending_usage.take().map(|(_, usage)| usage).into_iter().collect()
+    }
+
+    fn parse_claude(&mut self, value: &Value) -> Vec<ElfEvent> {
+        let mut parsed = Vec::new();
+        if let Some(session_id) = value.get("sessionId").and_then(Value::as_str) {
+            if self.session_id.as_deref() != Some(session_id) {
+                self.session_id = Some(session_id.to_string());
+                parsed.push(ElfEvent::Init {
+                    session_id: Some(session_id.to_string()),
+                    model: None,
+                });
+            }
+        }
+        match value.get("type").and_then(Value::as_str) {
+            Some("assistant") => {
+                parsed.extend(self.claude_usage(value));
+                parsed.extend(events::from_claude(value));
+            }
+            Some("user") => parsed.extend(events::from_claude(value)),
+            _ => {}
+        }
+        parsed
+    }
+
+    /// The previous message's usage once a new message starts.
+    fn claude_usage(&mut self, value: &Value) -> Option<ElfEvent> {
+        let (message_id, usage) = events::claude_message_usage(value)?;
+        match self.pending_usage.replace((message_id.clone(), usage)) {
+            Some((previous_id, previous)) if previous_id != message_id => Some(previous),
+            _ => None,
+        }
+    }
+
+    fn parse_codex(&mut self, value: &Value) -> Vec<ElfEvent> {
+        if let Some(total) = value.pointer("/payload/info/total_token_usage") {
+            if self.last_codex_total.as_ref() == Some(total) {
+                return Vec::new();
+            }
+            self.last_codex_total = Some(total.clone());
+        }
+        events::from_codex_rollout(value)
+    }
+}
+
+/// Transcripts being tailed, so runs started together never pick the same one.
+/// Managed as Tauri app state.
+pub struct TranscriptClaims(Mutex<HashSet<PathBuf>>);
+
+impl TranscriptClaims {
+    pub fn new() -> Self {
+        Self(Mutex::new(HashSet::new()))
+    }
+
+    /// Claim the transcript `locator` finds among those not yet claimed.
+    pub fn claim(&self, locator: &TranscriptLocator) -> Option<(PathBuf, u64)> {
+        let mut claimed = self.0.lock().expect("Transcript claims lock poisoned");
+        let found = locator.find(&claimed)?;
+        claimed.insert(found.0.clone());
+        Some(found)
+    }
+
+    /// Release a transcript once its run has ended.
+    pub fn release(&self, path: &Path) {
+        self.0.lock().expect("Transcript claims lock poisoned").remove(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn write_lines(path: &Path, lines: &[Value]) {
+        let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+        std::fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn project_paths_are_encoded_like_claude() {
+        assert_eq!(encode_project_path("/Users/me/my.app"), "-Users-me-my-app");
+        assert_eq!(encode_project_path("/tmp/work_tree-2"), "-tmp-work-tree-2");
+    }
+
+    #[test]
+    fn locator_finds_only_new_transcripts_of_its_run() {
+        let home = tempdir().unwrap();
+        let dir = home.path().join(".claude/projects").join(encode_project_path("/repo"));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_lines(&dir.join("old.jsonl"), &[json!({ "type": "user", "message": { "content": "You are the Tester." } })]);
+
+        let tester = TranscriptLocator::new(TranscriptKind::Claude, home.path(), "/repo", None, Some("You are the Tester.".into()));
+        let solo = TranscriptLocator::new(TranscriptKind::Claude, home.path(), "/repo", None, None);
+        assert_eq!(tester.find(&HashSet::new()), None);
+
+        let new = dir.join("new.jsonl");
+        write_lines(&new, &[json!({ "type": "user", "message": { "content": "You are the Reviewer." } })]);
+        assert_eq!(tester.find(&HashSet::new()), None);
+        assert_eq!(solo.find(&HashSet::new()), Some((new.clone(), 0)));
+
+        let claims = TranscriptClaims::new();
+        assert_eq!(claims.claim(&solo), Some((new.clone(), 0)));
+        assert_eq!(claims.claim(&solo), None);
+        claims.release(&new);
+        assert!(claims.claim(&solo).is_some());
+    }
+
+    #[test]
+    fn resumed_claude_transcripts_are_read_from_where_they_ended() {
+        let home = tempdir().unwrap();
+        let dir = home.path().join(".claude/projects").join(encode_project_path("/repo"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("abc.jsonl");
+        write_lines(&path, &[json!({ "type": "user" })]);
+        let before = std::fs::metadata(&path).unwrap().len();
+
+        let locator = TranscriptLocator::new(TranscriptKind::Claude, home.path(), "/repo", Some("abc"), None);
+        assert_eq!(locator.find(&HashSet::new()), None);
+        write_lines(&path, &[json!({ "type": "user" }), json!({ "type": "assistant" })]);
+        assert_eq!(locator.find(&HashSet::new()), Some((path, before)));
+    }
+
+    #[test]
+    fn codex_rollouts_must_be_for_the_working_dir() {
+        let home = tempdir().unwrap();
+        let day = chrono::Local::now().date_naive().format("%Y/%m/%d").to_string();
+        let dir = home.path().join(".codex/sessions").join(day);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let locator = TranscriptLocator::new(TranscriptKind::Codex, home.path(), "/repo", None, None);
+        write_lines(&dir.join("rollout-a.jsonl"), &[json!({ "type": "session_meta", "payload": { "cwd": "/other" } })]);
+        assert_eq!(locator.find(&HashSet::new()), None);
+        let ours = dir.join("rollout-b.jsonl");
+        write_lines(&ours, &[json!({ "type": "session_meta", "payload": { "cwd": "/repo" } })]);
+        assert_eq!(locator.find(&HashSet::new()), Some((ours, 0)));
+    }
+
+    #[test]
+    fn tail_returns_complete_lines_only() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("t.jsonl");
+        std::fs::write(&path, "{\"a\":1}\n{\"b\":").unwrap();
+        let mut tail = TranscriptTail::new(path.clone(), 0);
+        assert_eq!(tail.read_lines().unwrap(), vec!["{\"a\":1}"]);
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, b"2}\n").unwrap();
+        assert_eq!(tail.read_lines().unwrap(), vec!["{\"b\":2}"]);
+        assert!(tail.read_lines().unwrap().is_empty());
+    }
+
+    #[test]
+    fn claude_usage_is_reported_once_per_message() {
+        let mut parser = TranscriptParser::new(TranscriptKind::Claude);
+        let block = |id: &str, output: i64, content: Value| {
+            json!({ "type": "assistant", "sessionId": "sid-1", "message": {
+                "id": id, "model": "claude-sonnet-4-5", "content": [content],
+                "usage": { "input_tokens": 10, "output_tokens": output } } })
+        };
+
+        let first = parser.parse_line(&block("m1", 1, json!({ "type": "text", "text": "Looking" })));
+        assert_eq!(first[0], ElfEvent::Init { session_id: Some("sid-1".into()), model: None });
+        assert_eq!(first[1], ElfEvent::Text { text: "Looking".into() });
+
+        let second = parser.parse_line(&block("m1", 4, json!({ "type": "tool_use", "id": "t1", "name": "Read", "input": {} })));
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].kind(), "tool_call");
+
+        let third = parser.parse_line(&block("m2", 2, json!({ "type": "text", "text": "Done" })));
+        assert_eq!(
+            third[0],
+            ElfEvent::Usage {
+                input_tokens: 10,
+                output_tokens: 4,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                model: Some("claude-sonnet-4-5".into()),
+            }
+        );
+        assert_eq!(parser.finish().len(), 1);
+        assert!(parser.finish().is_empty());
+    }
+
+    #[test]
+    fn repeated_codex_token_counts_are_dropped() {
+        let mut parser = TranscriptParser::new(TranscriptKind::Codex);
+        let tokens = json!({ "type": "event_msg", "payload": { "type": "token_count", "info": {
+            "total_token_usage": { "input_tokens": 5, "output_tokens": 1 },
+            "last_token_usage": { "input_tokens": 5, "output_tokens": 1 } } } });
+        assert_eq!(parser.parse_line(&tokens).len(), 1);
+        assert!(parser.parse_line(&tokens).is_empty());
+    }
+}
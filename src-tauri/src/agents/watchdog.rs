@@ -0,0 +1,397 @@
+// Session watchdog — tracks agent activity per session and decides when a session has
+// stalled or run past its limits.
+//
+// Stream readers report each parsed event and PTY readers each chunk of output. A
+// background thread (commands/watchdog.rs) calls `scan` periodically and acts on the
+// verdicts: a stall warning first, then a graceful stop once a limit is exceeded.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Limits a session may run under. Set per project in `.elves/config.json`, and
+/// overridable per session. A limit of None is not enforced.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchdogLimits {
+    pub enabled: bool,
+    /// Longest a session may run, from when it started. Retries count toward it.
+    pub max_wall_clock_secs: Option<u64>,
+    /// Longest a session may go without new events or terminal output.
+    pub max_idle_secs: Option<u64>,
+    /// How long without activity before a stall warning, ahead of `max_idle_secs`.
+    pub stall_warning_secs: Option<u64>,
+    /// Most events a print-mode session may produce.
+    pub max_events: Option<u64>,
+    /// Most tool calls a print-mode session may make.
+    pub max_tool_calls: Option<u64>,
+}
+
+impl Default for WatchdogLimits {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_wall_clock_secs: Some(2 * 60 * 60),
+            max_idle_secs: Some(15 * 60),
+            stall_warning_secs: Some(5 * 60),
+            max_events: None,
+            max_tool_calls: None,
+        }
+    }
+}
+
+/// Which limit a session ran past.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    WallClock,
+    Idle,
+    Events,
+    ToolCalls,
+}
+
+impl LimitKind {
+    /// Summary stored on a session stopped for exceeding this limit.
+    pub fn summary(self) -> &'static str {
+        match self {
+            Self::WallClock => "Stopped by watchdog: maximum run time reached",
+            Self::Idle => "Stopped by watchdog: no activity",
+            Self::Events => "Stopped by watchdog: too many events",
+            Self::ToolCalls => "Stopped by watchdog: too many tool calls",
+        }
+    }
+}
+
+/// What the watchdog decided about a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// No activity for `idle`; warned once until activity resumes.
+    Stalled { idle: Duration },
+    /// A limit was exceeded; the session should be stopped.
+    Exceeded(LimitKind),
+}
+
+/// Activity counters of one session.
+#[derive(Debug, Clone)]
+pub struct Activity {
+    pub started_at: Instant,
+    pub last_activity_at: Instant,
+    pub events: u64,
+    pub tool_calls: u64,
+}
+
+impl Activity {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            started_at: now,
+            last_activity_at: now,
+            events: 0,
+            tool_calls: 0,
+        }
+    }
+}
+
+/// Check a session's activity against its limits at `now`. Exceeded limits win over
+/// stalls; `warned` suppresses repeated stall warnings for the same quiet spell.
+pub fn check(limits: &WatchdogLimits, activity: &Activity, warned: bool, now: Instant) -> Option<Verdict> {
+    if !limits.enabled {
+        return None;
+    }
+    let over = |limit: Option<u64>, value: u64| limit.is_some_and(|max| value >= max);
+    let running = now.saturating_duration_since(activity.started_at);
+    let idle = now.saturating_duration_since(activity.last_activity_at);
+
+    if over(limits.max_wall_clock_secs, running.as_secs()) {
+        return Some(Verdict::Exceeded(LimitKind::WallClock));
+    }
+    if over(limits.max_idle_secs, idle.as_secs()) {
+        return Some(Verdict::Exceeded(LimitKind::Idle));
+    }
+    if over(limits.max_events, activity.events) {
+        return Some(Verdict::Exceeded(LimitKind::Events));
+    }
+    if over(limits.max_tool_calls, activity.tool_calls) {
+        return Some(Verdict::Exceeded(LimitKind::ToolCalls));
+    }
+    if !warned && over(limits.stall_warning_secs, idle.as_secs()) {
+        return Some(Verdict::Stalled { idle });
+    }
+    None
+}
+
+struct Watched {
+    limits: WatchdogLimits,
+    activity: Activity,
+    /// PTYs the session runs in, for sessions whose activity is terminal output. Team
+    /// sessions have one per role.
+    pty_ids: Vec<String>,
+    warned: bool,
+}
+
+/// Activity and limits of every running session. Managed as Tauri app state.
+pub struct Watchdog {
+    sessions: Mutex<HashMap<String, Watched>>,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start watching a session, resetting its activity. `pty_id` is set for sessions
+    /// running in a terminal.
+    pub fn watch(&self, session_id: &str, limits: WatchdogLimits, pty_id: Option<String>) {
+        self.watch_since(session_id, limits, pty_id, Instant::now());
+    }
+
+    /// Start watching an attempt of a session that started at `started_at`, resetting
+    /// its activity but not its run time, so retries share one wall-clock limit.
+    pub fn watch_attempt(&self, session_id: &str, limits: WatchdogLimits, started_at: i64) {
+        let running = chrono::Utc::now().timestamp().saturating_sub(started_at).max(0) as u64;
+        let now = Instant::now();
+        let started = now.checked_sub(Duration::from_secs(running)).unwrap_or(now);
+        self.watch_since(session_id, limits, None, started);
+    }
+
+    fn watch_since(&self, session_id: &str, limits: WatchdogLimits, pty_id: Option<String>, started: Instant) {
+        let mut activity = Activity::new(Instant::now());
+        activity.started_at = started;
+        let mut sessions = self.sessions.lock().expect("Watchdog lock poisoned");
+        sessions.insert(
+            session_id.to_string(),
+            Watched {
+                limits,
+                activity,
+                pty_ids: pty_id.into_iter().collect(),
+                warned: false,
+            },
+        );
+    }
+
+    /// Add a PTY to a session's watch, starting to watch the session if needed. Used for
+    /// team sessions, whose roles each run in their own PTY but share the session's limits.
+    pub fn watch_pty(&self, session_id: &str, limits: WatchdogLimits, pty_id: String) {
+        let mut sessions = self.sessions.lock().expect("Watchdog lock poisoned");
+        match sessions.get_mut(session_id) {
+            Some(watched) => watched.pty_ids.push(pty_id),
+            None => {
+                sessions.insert(
+                    session_id.to_string(),
+                    Watched {
+                        limits,
+                        activity: Activity::new(Instant::now()),
+                        pty_ids: vec![pty_id],
+                        warned: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Stop watching a session.
+    pub fn unwatch(&self, session_id: &str) {
+        self.sessions.lock().expect("Watchdog lock poisoned").remove(session_id);
+    }
+
+    /// Stop watching a PTY, e.g. when it exits. The session stops being watched once
+    /// none of its PTYs are left.
+    pub fn unwatch_pty(&self, pty_id: &str) {
+        let mut sessions = self.sessions.lock().expect("Watchdog lock poisoned");
+        sessions.retain(|_, watched| {
+            let had_ptys = !watched.pty_ids.is_empty();
+            watched.pty_ids.retain(|id| id != pty_id);
+            !had_ptys || !watched.pty_ids.is_empty()
+        });
+    }
+
+    /// Replace a watched session's limits. Returns false if the session is not watched.
+    pub fn set_limits(&self, session_id: &str, limits: WatchdogLimits) -> bool {
+        let mut sessions = self.sessions.lock().expect("Watchdog lock poisoned");
+        match sessions.get_mut(session_id) {
+            Some(watched) => {
+                watched.limits = limits;
+                watched.warned = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record an event from a session's agent.
+    pub fn record_event(&self, session_id: &str, is_tool_call: bool) {
+        let mut sessions = self.sessions.lock().expect("Watchdog lock poisoned");
+        if let Some(watched) = sessions.get_mut(session_id) {
+            watched.activity.last_activity_at = Instant::now();
+            watched.activity.events += 1;
+            if is_tool_call {
+                watched.activity.tool_calls += 1;
+            }
+            watched.warned = false;
+        }
+    }
+
+    /// Record output from a PTY. Counts as activity, not as events.
+    pub fn record_pty_output(&self, pty_id: &str) {
+        let mut sessions = self.sessions.lock().expect("Watchdog lock poisoned");
+        if let Some(watched) = sessions.values_mut().find(|w| w.pty_ids.iter().any(|id| id == pty_id)) {
+            watched.activity.last_activity_at = Instant::now();
+            watched.warned = false;
+        }
+    }
+
+    /// Check every watched session at `now`. Sessions that exceeded a limit stop being
+    /// watched; stalled ones are only reported once per quiet spell.
+    /// Returns (session ID, PTY IDs, verdict) for each session that needs attention.
+    pub fn scan(&self, now: Instant) -> Vec<(String, Vec<String>, Verdict)> {
+        let mut sessions = self.sessions.lock().expect("Watchdog lock poisoned");
+        let mut verdicts = Vec::new();
+        for (session_id, watched) in sessions.iter_mut() {
+            if let Some(verdict) = check(&watched.limits, &watched.activity, watched.warned, now) {
+                if let Verdict::Stalled { .. } = verdict {
+                    watched.warned = true;
+                }
+                verdicts.push((session_id.clone(), watched.pty_ids.clone(), verdict));
+            }
+        }
+        sessions.retain(|session_id, _| {
+            !verdicts
+                .iter()
+                .any(|(id, _, verdict)| id == session_id && matches!(verdict, Verdict::Exceeded(_)))
+        });
+        verdicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> WatchdogLimits {
+        WatchdogLimits {
+            enabled: true,
+            max_wall_clock_secs: Some(3600),
+            max_idle_secs: Some(600),
+            stall_warning_secs: Some(120),
+            max_events: Some(1000),
+            max_tool_calls: Some(50),
+        }
+    }
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn disabled_limits_are_never_exceeded() {
+        let start = Instant::now();
+        let activity = Activity::new(start);
+        let disabled = WatchdogLimits { enabled: false, ..limits() };
+        assert_eq!(check(&disabled, &activity, false, start + secs(100_000)), None);
+    }
+
+    #[test]
+    fn idle_sessions_warn_then_exceed() {
+        let start = Instant::now();
+        let activity = Activity::new(start);
+        assert_eq!(check(&limits(), &activity, false, start + secs(60)), None);
+        assert_eq!(
+            check(&limits(), &activity, false, start + secs(130)),
+            Some(Verdict::Stalled { idle: secs(130) })
+        );
+        assert_eq!(check(&limits(), &activity, true, start + secs(130)), None);
+        assert_eq!(
+            check(&limits(), &activity, true, start + secs(600)),
+            Some(Verdict::Exceeded(LimitKind::Idle))
+        );
+    }
+
+    #[test]
+    fn wall_clock_and_counts_are_enforced() {
+        let start = Instant::now();
+        let mut activity = Activity::new(start);
+        activity.last_activity_at = start + secs(3599);
+        assert_eq!(
+            check(&limits(), &activity, false, start + secs(3600)),
+            Some(Verdict::Exceeded(LimitKind::WallClock))
+        );
+
+        let mut activity = Activity::new(start);
+        activity.tool_calls = 50;
+        assert_eq!(
+            check(&limits(), &activity, false, start),
+            Some(Verdict::Exceeded(LimitKind::ToolCalls))
+        );
+        activity.events = 1000;
+        assert_eq!(
+            check(&limits(), &activity, false, start),
+            Some(Verdict::Exceeded(LimitKind::Events))
+        );
+    }
+
+    #[test]
+    fn scan_warns_once_and_drops_exceeded_sessions() {
+        let watchdog = Watchdog::new();
+        watchdog.watch("s1", limits(), None);
+        watchdog.watch("s2", limits(), Some("pty-2".to_string()));
+        watchdog.set_limits("s2", WatchdogLimits { max_tool_calls: Some(1), ..limits() });
+        watchdog.record_event("s2", true);
+
+        let now = Instant::now() + secs(130);
+        let verdicts = watchdog.scan(now);
+        assert_eq!(verdicts.len(), 2);
+        assert!(verdicts.iter().any(|(id, _, v)| id == "s1" && matches!(v, Verdict::Stalled { .. })));
+        assert!(verdicts
+            .iter()
+            .any(|(id, ptys, v)| id == "s2" && *ptys == ["pty-2"] && *v == Verdict::Exceeded(LimitKind::ToolCalls)));
+
+        // s1 was already warned; s2 is no longer watched
+        assert!(watchdog.scan(now).is_empty());
+        assert!(!watchdog.set_limits("s2", limits()));
+
+        // Activity clears the warning
+        watchdog.record_event("s1", false);
+        assert_eq!(watchdog.scan(Instant::now() + secs(130)).len(), 1);
+    }
+
+    #[test]
+    fn retried_attempts_keep_the_sessions_run_time() {
+        let watchdog = Watchdog::new();
+        let started_at = chrono::Utc::now().timestamp() - 3000;
+        watchdog.watch_attempt("s1", limits(), started_at);
+        watchdog.record_event("s1", false);
+
+        // Fresh activity, but 3000s of the 3600s wall clock were spent by earlier attempts
+        let verdicts = watchdog.scan(Instant::now() + secs(700));
+        assert_eq!(verdicts.len(), 1);
+        assert_eq!(verdicts[0].2, Verdict::Exceeded(LimitKind::WallClock));
+    }
+
+    #[test]
+    fn pty_output_counts_as_activity() {
+        let watchdog = Watchdog::new();
+        watchdog.watch("s1", limits(), Some("pty-1".to_string()));
+        watchdog.record_pty_output("pty-1");
+        watchdog.unwatch_pty("pty-1");
+        assert!(watchdog.scan(Instant::now() + secs(10_000)).is_empty());
+    }
+
+    #[test]
+    fn team_sessions_are_watched_until_their_last_pty_exits() {
+        let watchdog = Watchdog::new();
+        watchdog.watch_pty("team", limits(), "pty-a".to_string());
+        watchdog.watch_pty("team", limits(), "pty-b".to_string());
+        watchdog.unwatch_pty("pty-a");
+
+        let verdicts = watchdog.scan(Instant::now() + secs(10_000));
+        assert_eq!(verdicts.len(), 1);
+        assert_eq!(verdicts[0].1, vec!["pty-b".to_string()]);
+        assert_eq!(verdicts[0].2, Verdict::Exceeded(LimitKind::WallClock));
+
+        watchdog.watch_pty("team", limits(), "pty-c".to_string());
+        watchdog.unwatch_pty("pty-c");
+        assert!(watchdog.scan(Instant::now() + secs(10_000)).is_empty());
+    }
+}
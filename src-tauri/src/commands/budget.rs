@@ -0,0 +1,243 @@
+// Budget commands — spend so far against each cap, and the enforcement used when runs
+// start and as running sessions report usage.
+//
+// Runs are refused while any cap is reached, and Claude runs get the tightest remaining
+// budget as `--max-budget-usd`. Sessions that reach a cap, print-mode or PTY, are failed
+// with reason `budget_exceeded` and stopped. Events:
+// - `budget:warning` — spend passed a warning threshold of a cap, once per cap and period
+// - `budget:exceeded` — a running session reached a cap and is being stopped
+
+use crate::agents::budget::{self, BudgetLimits, BudgetScope, BudgetTracker, BudgetVerdict, Spend};
+use crate::agents::events::{self, ElfEvent};
+use crate::agents::failure::FailureReason;
+use crate::agents::process::ProcessManager;
+use crate::commands::projects::DbState;
+use crate::commands::pty::PtyManager;
+use crate::commands::settings;
+use crate::commands::tasks;
+use crate::db;
+use chrono::{Datelike, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// A project's caps and what has been spent against them.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub project: BudgetLimits,
+    pub global: BudgetLimits,
+    pub project_day_usd: f64,
+    pub project_month_usd: f64,
+    pub global_day_usd: f64,
+    pub global_month_usd: f64,
+    /// How much a new session may spend, or None if no cap applies.
+    pub remaining_usd: Option<f64>,
+}
+
+/// Spend so far today and this month, for a project and across all projects, including
+/// running sessions.
+#[tauri::command]
+pub fn get_budget_status(app: AppHandle, project_id: String) -> Result<BudgetStatus, String> {
+    let spend = spend(&app, &project_id, None)?;
+    let project = project_budget(&app, &project_id);
+    let global = settings::global_budget();
+    Ok(BudgetStatus {
+        remaining_usd: budget::remaining(&project, &global, &spend),
+        project,
+        global,
+        project_day_usd: spend.project_day,
+        project_month_usd: spend.project_month,
+        global_day_usd: spend.global_day,
+        global_month_usd: spend.global_month,
+    })
+}
+
+/// Check that a project may start a run. Returns how much the run may spend (None if
+/// uncapped), or an error naming the cap that is already reached.
+pub(crate) fn check_can_start(app: &AppHandle, project_id: &str) -> Result<Option<f64>, String> {
+    let spend = spend(app, project_id, None)?;
+    let project = project_budget(app, project_id);
+    let global = settings::global_budget();
+    let remaining = budget::remaining(&project, &global, &spend);
+    if remaining.is_some_and(|usd| usd <= 0.0) {
+        let scope = budget::check(&project, &global, &spend)
+            .into_iter()
+            .find_map(|verdict| match verdict {
+                BudgetVerdict::Exceeded { scope, .. } => Some(scope),
+                BudgetVerdict::Warning { .. } => None,
+            })
+            .unwrap_or(BudgetScope::Session);
+        return Err(format!("Budget exceeded: {} reached", scope.label()));
+    }
+    Ok(remaining)
+}
+
+/// The smaller of a requested `--max-budget-usd` and the remaining budget.
+pub(crate) fn capped(requested: Option<f64>, remaining: Option<f64>) -> Option<f64> {
+    match (requested, remaining) {
+        (Some(requested), Some(remaining)) => Some(requested.min(remaining)),
+        (requested, remaining) => requested.or(remaining),
+    }
+}
+
+/// Record spend from the events of a running session's print-mode process, or of its PTY
+/// `pty_id`, warn about caps the session approaches, and stop it once it reaches one.
+pub(crate) fn record_spend(app: &AppHandle, session_id: &str, pty_id: Option<&str>, events: &[ElfEvent]) {
+    if let Some((project_id, session_spend)) = app.state::<BudgetTracker>().record(session_id, pty_id, events) {
+        enforce(app, session_id, &project_id, session_spend);
+    }
+}
+
+/// Record the usage of a streamed Claude `assistant` line, so a print run can be stopped
+/// before its final result reports what it spent.
+pub(crate) fn record_message_spend(app: &AppHandle, session_id: &str, line: &Value) {
+    let Some((message_id, usage)) = events::claude_message_usage(line) else {
+        return;
+    };
+    let tracker = app.state::<BudgetTracker>();
+    if let Some((project_id, session_spend)) = tracker.record_message(session_id, &message_id, &usage) {
+        enforce(app, session_id, &project_id, session_spend);
+    }
+}
+
+/// Warn about caps a running session approaches and stop it once it reaches one.
+fn enforce(app: &AppHandle, session_id: &str, project_id: &str, session_spend: f64) {
+    let tracker = app.state::<BudgetTracker>();
+    let Ok(spend) = spend(app, project_id, Some(session_spend)) else {
+        return;
+    };
+    let project = project_budget(app, project_id);
+    let global = settings::global_budget();
+
+    for verdict in budget::check(&project, &global, &spend) {
+        match verdict {
+            BudgetVerdict::Warning { scope, threshold, spent, limit } => {
+                let key = format!("{}:{threshold}", period_key(scope, session_id, project_id));
+                if tracker.first_warning(key) {
+                    log::info!("[session {session_id}] Spent ${spent:.2} of {} ${limit:.2}", scope.label());
+                    let _ = app.emit(
+                        "budget:warning",
+                        serde_json::json!({
+                            "sessionId": session_id,
+                            "projectId": project_id,
+                            "scope": scope,
+                            "threshold": threshold,
+                            "spentUsd": spent,
+                            "limitUsd": limit,
+                        }),
+                    );
+                }
+            }
+            BudgetVerdict::Exceeded { scope, spent, limit } => {
+                if tracker.first_warning(format!("exceeded:{session_id}")) {
+                    let app = app.clone();
+                    let session_id = session_id.to_string();
+                    std::thread::spawn(move || stop_for_budget(&app, &session_id, scope, spent, limit));
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// Fail a session that reached a cap and stop its agents gracefully.
+///
+/// The status is written before stopping so the output reader records the final usage
+/// without completing, failing, or retrying the session again.
+fn stop_for_budget(app: &AppHandle, session_id: &str, scope: BudgetScope, spent: f64, limit: f64) {
+    let message = format!("Stopped: {} of ${limit:.2} reached (${spent:.2} spent)", scope.label());
+    log::info!("[session {session_id}] {message}");
+    {
+        let db_state = app.state::<DbState>();
+        let Ok(conn) = db_state.0.lock() else {
+            return;
+        };
+        let active = matches!(
+            db::sessions::get_session(&conn, session_id),
+            Ok(Some(session)) if session.status == "active"
+        );
+        if !active {
+            return;
+        }
+        let reason = FailureReason::BudgetExceeded.as_str();
+        if let Err(e) = db::sessions::fail_session(&conn, session_id, reason, Some(&message)) {
+            log::warn!("[session {session_id}] Failed to mark session over budget: {e}");
+        }
+    }
+
+    let _ = app.emit(
+        "budget:exceeded",
+        serde_json::json!({
+            "sessionId": session_id,
+            "scope": scope,
+            "spentUsd": spent,
+            "limitUsd": limit,
+        }),
+    );
+
+    let timeouts = settings::stop_timeouts();
+    let pty_ids = app.state::<BudgetTracker>().pty_ids(session_id);
+    if pty_ids.is_empty() {
+        let process_mgr = app.state::<ProcessManager>();
+        process_mgr.stop(session_id, timeouts);
+        process_mgr.stop_team(session_id, timeouts);
+    }
+    for pty_id in &pty_ids {
+        if let Err(e) = app.state::<PtyManager>().stop(pty_id, timeouts) {
+            log::warn!("[session {session_id}] Failed to stop PTY {pty_id}: {e}");
+        }
+    }
+
+    tasks::emit_session_failed(app, session_id, FailureReason::BudgetExceeded, None, &message);
+}
+
+/// Spend counted against each cap: recorded session costs since the start of the UTC day
+/// and month, plus live spend of running sessions. `session` is the running session's
+/// spend, when checking one.
+fn spend(app: &AppHandle, project_id: &str, session: Option<f64>) -> Result<Spend, String> {
+    let now = Utc::now();
+    let day_start = Utc
+        .with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+        .single()
+        .map_or(0, |start| start.timestamp());
+    let month_start = Utc
+        .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .map_or(0, |start| start.timestamp());
+
+    let tracker = app.state::<BudgetTracker>();
+    let project_live = tracker.live_spend(Some(project_id));
+    let global_live = tracker.live_spend(None);
+
+    let db_state = app.state::<DbState>();
+    let conn = db_state.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let total = |project: Option<&str>, since| {
+        db::sessions::total_cost_since(&conn, project, since).map_err(|e| format!("Database error: {e}"))
+    };
+    Ok(Spend {
+        session: session.unwrap_or(0.0),
+        project_day: total(Some(project_id), day_start)? + project_live,
+        project_month: total(Some(project_id), month_start)? + project_live,
+        global_day: total(None, day_start)? + global_live,
+        global_month: total(None, month_start)? + global_live,
+    })
+}
+
+/// Identifies a cap and its current period, so each warning is given once per period.
+fn period_key(scope: BudgetScope, session_id: &str, project_id: &str) -> String {
+    let now = Utc::now();
+    match scope {
+        BudgetScope::Session => format!("session:{session_id}"),
+        BudgetScope::ProjectDaily => format!("project:{project_id}:{}", now.format("%Y-%m-%d")),
+        BudgetScope::ProjectMonthly => format!("project:{project_id}:{}", now.format("%Y-%m")),
+        BudgetScope::GlobalDaily => format!("global:{}", now.format("%Y-%m-%d")),
+        BudgetScope::GlobalMonthly => format!("global:{}", now.format("%Y-%m")),
+    }
+}
+
+/// The spending caps of a project; the default (disabled) caps when the project or its
+/// config can't be read.
+fn project_budget(app: &AppHandle, project_id: &str) -> BudgetLimits {
+    tasks::project_config_of(app, project_id).map(|config| config.budget).unwrap_or_default()
+}
@@ -0,0 +1,465 @@
+// Codex insights — usage of Codex sessions read from its rollout logs under
+// `~/.codex/sessions/YYYY/MM/DD/*.jsonl`, merged into the Insights dashboard next to Claude
+// Code's usage data.
+//
+// Each rollout is one session. It is parsed with the transcript parser used to tail live
+// Codex runs (see agents/transcript.rs): token usage per model, tool calls, messages, its
+// working directory and how long it ran, from the first to the last line's timestamp.
+// Parsed logs are cached until the file changes.
+
+use crate::agents::events::ElfEvent;
+use crate::agents::pricing::{PricingTable, UsageLedger};
+use crate::agents::transcript::{TranscriptKind, TranscriptParser};
+use crate::commands::insights::{
+    project_name_from_path, truncate, DailyActivityEntry, InsightsData, ModelUsageEntry, ProjectSummary, RuntimeSummary,
+    SessionSummary,
+};
+use chrono::{DateTime, Local, Timelike, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Runtime name Codex sessions are reported under.
+pub(crate) const CODEX_RUNTIME: &str = "codex";
+
+/// Depth of rollout files below `~/.codex/sessions` (year, month, day).
+const ROLLOUT_DEPTH: usize = 3;
+
+/// Token counts of one model in a Codex session. Codex reports cached tokens as part
+/// of its input tokens; `input_tokens` here excludes them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CodexModelTokens {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Usage of one Codex session, from its rollout log.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CodexSession {
+    pub session_id: String,
+    /// Working directory the session ran in.
+    pub cwd: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub models: BTreeMap<String, CodexModelTokens>,
+    pub tool_counts: HashMap<String, u32>,
+    pub user_messages: u32,
+    pub assistant_messages: u32,
+    pub first_prompt: Option<String>,
+}
+
+impl CodexSession {
+    fn duration_minutes(&self) -> u64 {
+        match (self.started_at, self.ended_at) {
+            (Some(start), Some(end)) => (end - start).num_minutes().max(0) as u64,
+            _ => 0,
+        }
+    }
+
+    fn tokens(&self) -> u64 {
+        self.models
+            .values()
+            .map(|tokens| tokens.input_tokens + tokens.cached_input_tokens + tokens.output_tokens)
+            .sum()
+    }
+
+    fn tool_calls(&self) -> u32 {
+        self.tool_counts.values().sum()
+    }
+}
+
+/// Parsed rollout logs by path, with the modification time and length they were parsed
+/// at, so each `load_insights` call only re-reads logs that changed. Managed as Tauri
+/// app state.
+pub struct CodexSessionCache {
+    rollouts: Mutex<HashMap<PathBuf, CachedRollout>>,
+}
+
+struct CachedRollout {
+    modified: SystemTime,
+    len: u64,
+    session: Option<CodexSession>,
+}
+
+impl CodexSessionCache {
+    pub fn new() -> Self {
+        Self {
+            rollouts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Parse every rollout log under `~/.codex/sessions`, reusing the cached result of logs
+/// unchanged since the last call. Unreadable files and logs without a session ID are
+/// skipped.
+pub(crate) fn read_codex_sessions(home: &Path, cache: &CodexSessionCache) -> Vec<CodexSession> {
+    let mut files = Vec::new();
+    collect_rollouts(&home.join(".codex").join("sessions"), ROLLOUT_DEPTH, &mut files);
+    let Ok(mut rollouts) = cache.rollouts.lock() else {
+        return Vec::new();
+    };
+    // Forget logs that were deleted
+    rollouts.retain(|path, _| files.contains(path));
+
+    let mut sessions = Vec::new();
+    for path in files {
+        let Ok((modified, len)) = std::fs::metadata(&path).and_then(|meta| Ok((meta.modified()?, meta.len()))) else {
+            continue;
+        };
+        let fresh = rollouts
+            .get(&path)
+            .is_some_and(|cached| cached.modified == modified && cached.len == len);
+        if !fresh {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let session = parse_rollout(&content);
+            rollouts.insert(path.clone(), CachedRollout { modified, len, session });
+        }
+        if let Some(session) = rollouts.get(&path).and_then(|cached| cached.session.clone()) {
+            sessions.push(session);
+        }
+    }
+    sessions
+}
+
+/// The `.jsonl` files `depth` directories below `dir`.
+fn collect_rollouts(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if depth > 0 && path.is_dir() {
+            collect_rollouts(&path, depth - 1, files);
+        } else if depth == 0 && path.extension().is_some_and(|ext| ext == "jsonl") {
+            files.push(path);
+        }
+    }
+}
+
+/// Summarize one rollout log. None if it names no session.
+pub(crate) fn parse_rollout(content: &str) -> Option<CodexSession> {
+    let mut session = CodexSession::default();
+    let mut parser = TranscriptParser::new(TranscriptKind::Codex);
+    let mut usage = UsageLedger::new(PricingTable::default(), None);
+
+    for line in content.lines() {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if let Some(at) = value
+            .get("timestamp")
+            .and_then(Value::as_str)
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        {
+            let at = at.with_timezone(&Utc);
+            session.started_at = Some(session.started_at.map_or(at, |start| start.min(at)));
+            session.ended_at = Some(session.ended_at.map_or(at, |end| end.max(at)));
+        }
+        match value.get("type").and_then(Value::as_str) {
+            Some("session_meta") | Some("turn_context") => {
+                if let Some(cwd) = value.pointer("/payload/cwd").and_then(Value::as_str) {
+                    session.cwd = Some(cwd.to_string());
+                }
+            }
+            Some("event_msg") if value.pointer("/payload/type").and_then(Value::as_str) == Some("user_message") => {
+                session.user_messages += 1;
+                if session.first_prompt.is_none() {
+                    session.first_prompt = value.pointer("/payload/message").and_then(Value::as_str).map(String::from);
+                }
+            }
+            _ => {}
+        }
+
+        let events = parser.parse_line(&value);
+        usage.record(None, &events);
+        for event in &events {
+            match event {
+                ElfEvent::Init { session_id: Some(id), .. } => session.session_id = id.clone(),
+                ElfEvent::Text { .. } => session.assistant_messages += 1,
+                ElfEvent::ToolCall { name, .. } => *session.tool_counts.entry(name.clone()).or_insert(0) += 1,
+                _ => {}
+            }
+        }
+    }
+    usage.record(None, &parser.finish());
+
+    for entry in usage.usage() {
+        let tokens = session.models.entry(entry.model).or_default();
+        tokens.cached_input_tokens += entry.tokens.cache_read_tokens.max(0) as u64;
+        tokens.input_tokens += entry.tokens.input_tokens.max(0) as u64;
+        tokens.output_tokens += entry.tokens.output_tokens.max(0) as u64;
+    }
+    (!session.session_id.is_empty()).then_some(session)
+}
+
+/// Add Codex sessions to insights: totals, daily activity, hours, models, projects,
+/// tools, recent sessions and a Codex runtime summary.
+pub(crate) fn merge_codex_sessions(data: &mut InsightsData, sessions: &[CodexSession]) {
+    if sessions.is_empty() {
+        return;
+    }
+    let mut summary = RuntimeSummary::new(CODEX_RUNTIME);
+    let mut daily: BTreeMap<String, DailyActivityEntry> = data
+        .daily_activity
+        .drain(..)
+        .map(|entry| (entry.date.clone(), entry))
+        .collect();
+    let mut models: BTreeMap<String, CodexModelTokens> = BTreeMap::new();
+    let mut tool_counts: HashMap<String, u32> = data.top_tools.drain(..).map(|tool| (tool.name, tool.count)).collect();
+
+    for session in sessions {
+        let messages = (session.user_messages + session.assistant_messages) as u64;
+        let duration = session.duration_minutes();
+        let tokens = session.tokens();
+        summary.sessions += 1;
+        summary.messages += messages;
+        summary.duration_minutes += duration;
+        summary.tool_calls += session.tool_calls();
+
+        for (model, counts) in &session.models {
+            let total = models.entry(model.clone()).or_default();
+            total.input_tokens += counts.input_tokens;
+            total.cached_input_tokens += counts.cached_input_tokens;
+            total.output_tokens += counts.output_tokens;
+            summary.input_tokens += counts.input_tokens + counts.cached_input_tokens;
+            summary.output_tokens += counts.output_tokens;
+        }
+        for (tool, count) in &session.tool_counts {
+            *tool_counts.entry(tool.clone()).or_insert(0) += count;
+        }
+
+        if let Some(started) = session.started_at {
+            let local = started.with_timezone(&Local);
+            let date = local.format("%Y-%m-%d").to_string();
+            let day = daily.entry(date.clone()).or_insert_with(|| DailyActivityEntry {
+                date,
+                session_count: 0,
+                message_count: 0,
+                tool_call_count: 0,
+            });
+            day.session_count += 1;
+            day.message_count += messages as u32;
+            day.tool_call_count += session.tool_calls();
+            if let Some(hour) = data.hourly_distribution.get_mut(local.hour() as usize) {
+                *hour += 1;
+            }
+        }
+
+        let project = session
+            .cwd
+            .as_deref()
+            .map(project_name_from_path)
+            .unwrap_or_else(|| "unknown".to_string());
+        match data
+            .projects
+            .iter_mut()
+            .find(|entry| entry.runtime == CODEX_RUNTIME && entry.name == project)
+        {
+            Some(entry) => {
+                entry.sessions += 1;
+                entry.duration_minutes += duration;
+                entry.tokens += tokens;
+            }
+            None => data.projects.push(ProjectSummary {
+                name: project.clone(),
+                runtime: CODEX_RUNTIME.to_string(),
+                sessions: 1,
+                lines_added: 0,
+                commits: 0,
+                duration_minutes: duration,
+                tokens,
+            }),
+        }
+
+        data.recent_sessions.push(SessionSummary {
+            session_id: session.session_id.clone(),
+            project,
+            runtime: CODEX_RUNTIME.to_string(),
+            start_time: session.started_at.map(|start| start.to_rfc3339()).unwrap_or_default(),
+            duration_minutes: duration,
+            first_prompt: session.first_prompt.as_deref().map(|p| truncate(p, 120)).unwrap_or_default(),
+            outcome: "unknown".to_string(),
+            brief_summary: String::new(),
+            lines_added: 0,
+            tokens,
+            commits: 0,
+        });
+    }
+
+    data.total_sessions += summary.sessions;
+    data.total_messages += summary.messages;
+    data.total_input_tokens += summary.input_tokens;
+    data.total_output_tokens += summary.output_tokens;
+    data.total_duration_minutes += summary.duration_minutes;
+    let first_day = daily.keys().next().cloned();
+    data.first_session_date = match (data.first_session_date.take(), first_day) {
+        (Some(first), Some(day)) => Some(first.min(day)),
+        (first, day) => first.or(day),
+    };
+    data.daily_activity = daily.into_values().collect();
+
+    data.model_usage.extend(models.into_iter().map(|(model, tokens)| {
+        let total_input = tokens.input_tokens + tokens.cached_input_tokens;
+        ModelUsageEntry {
+            model,
+            runtime: CODEX_RUNTIME.to_string(),
+            input_tokens: tokens.input_tokens,
+            output_tokens: tokens.output_tokens,
+            cache_read_tokens: tokens.cached_input_tokens,
+            cache_creation_tokens: 0,
+            cache_hit_rate: if total_input > 0 {
+                (tokens.cached_input_tokens as f64 / total_input as f64) * 100.0
+            } else {
+                0.0
+            },
+        }
+    }));
+    data.model_usage.sort_by_key(|m| std::cmp::Reverse(m.input_tokens + m.output_tokens + m.cache_read_tokens));
+    data.projects.sort_by(|a, b| b.sessions.cmp(&a.sessions));
+    data.top_tools = crate::commands::insights::top_n(&tool_counts, 10);
+    data.recent_sessions.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+    data.recent_sessions.truncate(20);
+    data.runtimes.push(summary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rollout() -> String {
+        [
+            json!({ "timestamp": "2025-06-01T10:00:00.000Z", "type": "session_meta",
+                    "payload": { "id": "codex-1", "cwd": "/work/alpha" } }),
+            json!({ "timestamp": "2025-06-01T10:00:01.000Z", "type": "turn_context",
+                    "payload": { "cwd": "/work/alpha", "model": "gpt-5-codex" } }),
+            json!({ "timestamp": "2025-06-01T10:00:02.000Z", "type": "event_msg",
+                    "payload": { "type": "user_message", "message": "Fix the build" } }),
+            json!({ "timestamp": "2025-06-01T10:01:00.000Z", "type": "response_item",
+                    "payload": { "type": "function_call", "name": "shell", "arguments": "{}", "call_id": "c1" } }),
+            json!({ "timestamp": "2025-06-01T10:02:00.000Z", "type": "event_msg", "payload": { "type": "token_count", "info": {
+                    "total_token_usage": { "input_tokens": 100, "cached_input_tokens": 40, "output_tokens": 10 },
+                    "last_token_usage": { "input_tokens": 100, "cached_input_tokens": 40, "output_tokens": 10 } } } }),
+            // Repeated totals are not counted twice
+            json!({ "timestamp": "2025-06-01T10:02:01.000Z", "type": "event_msg", "payload": { "type": "token_count", "info": {
+                    "total_token_usage": { "input_tokens": 100, "cached_input_tokens": 40, "output_tokens": 10 },
+                    "last_token_usage": { "input_tokens": 100, "cached_input_tokens": 40, "output_tokens": 10 } } } }),
+            json!({ "timestamp": "2025-06-01T10:05:00.000Z", "type": "response_item",
+                    "payload": { "type": "message", "role": "assistant", "content": [{ "type": "output_text", "text": "Fixed." }] } }),
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    fn empty_insights() -> InsightsData {
+        InsightsData {
+            total_sessions: 2,
+            total_messages: 0,
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_duration_minutes: 0,
+            total_commits: 0,
+            lines_added: 0,
+            lines_removed: 0,
+            files_changed: 0,
+            first_session_date: Some("2025-07-01".into()),
+            daily_activity: Vec::new(),
+            hourly_distribution: vec![0; 24],
+            model_usage: Vec::new(),
+            projects: Vec::new(),
+            outcomes: Vec::new(),
+            top_helpfulness: Vec::new(),
+            top_satisfaction: Vec::new(),
+            top_friction: Vec::new(),
+            top_goals: Vec::new(),
+            top_session_types: Vec::new(),
+            top_tools: Vec::new(),
+            top_languages: Vec::new(),
+            feature_adoption: crate::commands::insights::FeatureAdoption {
+                task_agent: 0,
+                mcp: 0,
+                web_search: 0,
+                web_fetch: 0,
+                total: 0,
+            },
+            recent_sessions: Vec::new(),
+            runtimes: Vec::new(),
+            report_html: None,
+        }
+    }
+
+    #[test]
+    fn rollouts_summarize_usage_tools_and_duration() {
+        let session = parse_rollout(&rollout()).unwrap();
+        assert_eq!(session.session_id, "codex-1");
+        assert_eq!(session.cwd.as_deref(), Some("/work/alpha"));
+        assert_eq!(session.duration_minutes(), 5);
+        assert_eq!(session.user_messages, 1);
+        assert_eq!(session.assistant_messages, 1);
+        assert_eq!(session.first_prompt.as_deref(), Some("Fix the build"));
+        assert_eq!(session.tool_counts.get("shell"), Some(&1));
+        assert_eq!(
+            session.models.get("gpt-5-codex"),
+            Some(&CodexModelTokens { input_tokens: 60, cached_input_tokens: 40, output_tokens: 10 })
+        );
+
+        assert!(parse_rollout("not json\n{}").is_none());
+    }
+
+    #[test]
+    fn rollouts_are_parsed_again_only_when_they_change() {
+        let home = tempfile::tempdir().unwrap();
+        let day = home.path().join(".codex/sessions/2025/06/01");
+        std::fs::create_dir_all(&day).unwrap();
+        let path = day.join("rollout-1.jsonl");
+        std::fs::write(&path, rollout()).unwrap();
+        let cache = CodexSessionCache::new();
+
+        let sessions = read_codex_sessions(home.path(), &cache);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, "codex-1");
+
+        // An unchanged log is served from the cache
+        if let Some(session) = cache.rollouts.lock().unwrap().get_mut(&path).unwrap().session.as_mut() {
+            session.session_id = "cached".to_string();
+        }
+        assert_eq!(read_codex_sessions(home.path(), &cache)[0].session_id, "cached");
+
+        // A changed log is parsed again
+        std::fs::write(&path, format!("{}\n", rollout())).unwrap();
+        assert_eq!(read_codex_sessions(home.path(), &cache)[0].session_id, "codex-1");
+
+        // A deleted log is forgotten
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_codex_sessions(home.path(), &cache).is_empty());
+        assert!(cache.rollouts.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn codex_sessions_merge_under_their_own_runtime() {
+        let mut data = empty_insights();
+        let session = parse_rollout(&rollout()).unwrap();
+        merge_codex_sessions(&mut data, &[session]);
+
+        assert_eq!(data.total_sessions, 3);
+        assert_eq!(data.total_input_tokens, 100);
+        assert_eq!(data.total_output_tokens, 10);
+        assert_eq!(data.first_session_date.as_deref(), Some("2025-06-01"));
+        assert_eq!(data.daily_activity.len(), 1);
+        assert_eq!(data.daily_activity[0].tool_call_count, 1);
+        assert_eq!(data.model_usage[0].runtime, CODEX_RUNTIME);
+        assert_eq!(data.model_usage[0].cache_hit_rate, 40.0);
+        assert_eq!(data.projects[0].name, "alpha");
+        assert_eq!(data.projects[0].runtime, CODEX_RUNTIME);
+        assert_eq!(data.top_tools[0].name, "shell");
+        assert_eq!(data.recent_sessions[0].session_id, "codex-1");
+        assert_eq!(data.runtimes[0].runtime, CODEX_RUNTIME);
+        assert_eq!(data.runtimes[0].tool_calls, 1);
+    }
+}
@@ -1,15 +1,27 @@
 // Session export commands — generate self-contained HTML replay files from session data.
+//
+// Replays embed the session's terminal recordings (see agents/recording.rs), if any, with
+// a small player that redraws the terminal screen.
 
 use crate::db;
+use crate::secrets::redact;
 use super::projects::DbState;
+use rusqlite::Connection;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::Deref;
+use std::path::Path;
 use tauri::State;
 use tauri_plugin_dialog::DialogExt;
 
+/// Events fetched per page while streaming a replay.
+const EXPORT_PAGE_SIZE: i64 = 500;
+
 /// Generate a self-contained HTML replay file for a session.
 ///
 /// Reads the session, its elves, and all events from SQLite, then builds an HTML string
 /// with inline CSS (neo-brutalist styling) and inline JS (replay engine with play/pause/speed).
-/// All session data is embedded as JSON in a script tag. The frontend handles the file save dialog.
+/// All session data, and any terminal recordings, are embedded as JSON in a script tag.
+/// The frontend handles the file save dialog.
 ///
 /// Returns the complete HTML string on success, or an error message if the session is not found.
 #[tauri::command]
@@ -18,62 +30,29 @@ pub fn export_session_html(
     session_id: String,
 ) -> Result<String, String> {
     let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
-
-    let session = db::sessions::get_session(&conn, &session_id)
-        .map_err(|e| format!("Database error: {e}"))?
-        .ok_or_else(|| format!("Session not found: {session_id}"))?;
-
-    let elves = db::elves::list_elves(&conn, &session_id)
-        .map_err(|e| format!("Database error: {e}"))?;
-
-    let events = db::events::list_events(&conn, &session_id)
-        .map_err(|e| format!("Database error: {e}"))?;
-
-    let session_json = serde_json::to_string(&session)
-        .map_err(|e| format!("Serialization error: {e}"))?;
-    let elves_json = serde_json::to_string(&elves)
-        .map_err(|e| format!("Serialization error: {e}"))?;
-    let events_json = serde_json::to_string(&events)
-        .map_err(|e| format!("Serialization error: {e}"))?;
-
-    let html = build_replay_html(&session_json, &elves_json, &events_json);
-    Ok(html)
+    let mut html = Vec::new();
+    write_session_replay(|| Ok(&*conn), &session_id, &mut html)?;
+    String::from_utf8(html).map_err(|e| format!("Serialization error: {e}"))
 }
 
 /// Save a session replay as an HTML file using the native save dialog.
 ///
-/// Generates the HTML replay (reusing `export_session_html` logic), shows a native save dialog
-/// for the user to choose a file path, and writes the HTML to disk. Returns `true` if the file
-/// was saved, `false` if the user cancelled the dialog.
+/// Shows a native save dialog for the user to choose a file path, then streams the replay
+/// into the file page by page, so long sessions are never held in memory as a whole. The
+/// database is locked only while each page is read, not while it is written.
+/// Returns `true` if the file was saved, `false` if the user cancelled the dialog.
 #[tauri::command]
 pub async fn save_session_replay(
     app: tauri::AppHandle,
     db: State<'_, DbState>,
     session_id: String,
 ) -> Result<bool, String> {
-    /* Generate the HTML string */
-    let html = {
+    {
         let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
-
-        let session = db::sessions::get_session(&conn, &session_id)
+        db::sessions::get_session(&conn, &session_id)
             .map_err(|e| format!("Database error: {e}"))?
             .ok_or_else(|| format!("Session not found: {session_id}"))?;
-
-        let elves = db::elves::list_elves(&conn, &session_id)
-            .map_err(|e| format!("Database error: {e}"))?;
-
-        let events = db::events::list_events(&conn, &session_id)
-            .map_err(|e| format!("Database error: {e}"))?;
-
-        let session_json = serde_json::to_string(&session)
-            .map_err(|e| format!("Serialization error: {e}"))?;
-        let elves_json = serde_json::to_string(&elves)
-            .map_err(|e| format!("Serialization error: {e}"))?;
-        let events_json = serde_json::to_string(&events)
-            .map_err(|e| format!("Serialization error: {e}"))?;
-
-        build_replay_html(&session_json, &elves_json, &events_json)
-    };
+    }
 
     /* Show native save dialog */
     let file_path = app
@@ -84,23 +63,166 @@ pub async fn save_session_replay(
         .add_filter("HTML", &["html"])
         .blocking_save_file();
 
-    match file_path {
-        Some(path) => {
-            std::fs::write(path.as_path().expect("Invalid file path"), html.as_bytes())
-                .map_err(|e| format!("Failed to write file: {e}"))?;
-            Ok(true)
+    let Some(file_path) = file_path else {
+        return Ok(false);
+    };
+    let path = file_path.as_path().ok_or("Invalid file path")?;
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to write file: {e}"))?;
+    let mut out = std::io::BufWriter::new(file);
+
+    let lock_db = || db.0.lock().map_err(|e| format!("Lock error: {e}"));
+    let written = write_session_replay(lock_db, &session_id, &mut out)
+        .and_then(|()| out.flush().map_err(|e| format!("Failed to write file: {e}")));
+    if let Err(e) = written {
+        // Don't leave a truncated replay behind
+        let _ = std::fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(true)
+}
+
+/// Write the complete replay HTML for a session to `out`, streaming its events and
+/// terminal recordings. `lock_db` gives access to the database; it is called per read and
+/// the connection released before anything is written.
+fn write_session_replay<C: Deref<Target = Connection>>(
+    lock_db: impl Fn() -> Result<C, String>,
+    session_id: &str,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    let conn = lock_db()?;
+    let session = db::sessions::get_session(&conn, session_id)
+        .map_err(|e| format!("Database error: {e}"))?
+        .ok_or_else(|| format!("Session not found: {session_id}"))?;
+
+    let elves = db::elves::list_elves(&conn, session_id)
+        .map_err(|e| format!("Database error: {e}"))?;
+
+    let session_json = serde_json::to_string(&session)
+        .map_err(|e| format!("Serialization error: {e}"))?;
+    let elves_json = serde_json::to_string(&elves)
+        .map_err(|e| format!("Serialization error: {e}"))?;
+    let recordings = db::recordings::list_recordings(&conn, session_id)
+        .map_err(|e| format!("Database error: {e}"))?;
+    drop(conn);
+
+    write_replay_html(
+        out,
+        &session_json,
+        &elves_json,
+        |out| write_events_json(&lock_db, session_id, out),
+        |out| write_recordings_json(&recordings, out),
+    )
+}
+
+/// Write a session's terminal recordings as a JSON array of `{ elfId, lines }`, where
+/// `lines` are the lines of the cast file. Recordings whose file is gone are left out.
+fn write_recordings_json(
+    recordings: &[db::recordings::RecordingRow],
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    let io_err = |e: std::io::Error| format!("Failed to write file: {e}");
+    out.write_all(b"[").map_err(io_err)?;
+    let mut first = true;
+    for recording in recordings {
+        let Ok(file) = std::fs::File::open(Path::new(&recording.path)) else {
+            log::debug!("Recording {} is missing, leaving it out of the replay", recording.path);
+            continue;
+        };
+        if !first {
+            out.write_all(b",").map_err(io_err)?;
+        }
+        first = false;
+        let elf_id = serde_json::to_string(&recording.elf_id)
+            .map_err(|e| format!("Serialization error: {e}"))?;
+        write!(out, "{{\"elfId\":{elf_id},\"lines\":[").map_err(io_err)?;
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read recording: {e}"))?;
+            let json = serde_json::to_string(&redact::redact(&line))
+                .map_err(|e| format!("Serialization error: {e}"))?;
+            if i > 0 {
+                out.write_all(b",").map_err(io_err)?;
+            }
+            out.write_all(script_safe(&json).as_bytes()).map_err(io_err)?;
+        }
+        out.write_all(b"]}").map_err(io_err)?;
+    }
+    out.write_all(b"]").map_err(io_err)
+}
+
+/// Write a session's events as a JSON array, one page at a time.
+fn write_events_json<C: Deref<Target = Connection>>(
+    lock_db: &impl Fn() -> Result<C, String>,
+    session_id: &str,
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    let io_err = |e: std::io::Error| format!("Failed to write file: {e}");
+    out.write_all(b"[").map_err(io_err)?;
+    let mut after_id = None;
+    let mut first = true;
+    loop {
+        let page = db::events::list_events_page(&*lock_db()?, session_id, after_id, EXPORT_PAGE_SIZE, &[])
+            .map_err(|e| format!("Database error: {e}"))?;
+        for event in &page.events {
+            let json = serde_json::to_string(event)
+                .map_err(|e| format!("Serialization error: {e}"))?;
+            if !first {
+                out.write_all(b",").map_err(io_err)?;
+            }
+            first = false;
+            out.write_all(script_safe(&redact::redact(&json)).as_bytes()).map_err(io_err)?;
+        }
+        match page.next_after_id {
+            Some(next) => after_id = Some(next),
+            None => break,
         }
-        None => Ok(false),
     }
+    out.write_all(b"]").map_err(io_err)
+}
+
+/// Escape JSON for embedding in a script tag. Session data can contain anything,
+/// including a closing script tag.
+fn script_safe(json: &str) -> String {
+    json.replace("</", "<\\/")
 }
 
-/// Build the complete self-contained HTML string for the session replay.
+/// Build the complete self-contained HTML string for the session replay from pre-serialized
+/// events JSON.
+#[cfg(test)]
+fn build_replay_html(session_json: &str, elves_json: &str, events_json: &str) -> String {
+    let mut html = Vec::new();
+    write_replay_html(
+        &mut html,
+        session_json,
+        elves_json,
+        |out| {
+            out.write_all(redact::redact(events_json).as_bytes())
+                .map_err(|e| e.to_string())
+        },
+        |out| out.write_all(b"[]").map_err(|e| e.to_string()),
+    )
+    .expect("Writing to memory cannot fail");
+    String::from_utf8(html).expect("Replay HTML is UTF-8")
+}
+
+/// Write the self-contained HTML replay to `out`.
 ///
 /// Embeds session data as JSON, includes inline neo-brutalist CSS and a JavaScript replay engine
 /// with play/pause, speed control, and event stepping. Works in any modern browser with zero
-/// external dependencies.
-fn build_replay_html(session_json: &str, elves_json: &str, events_json: &str) -> String {
-    format!(
+/// external dependencies. `write_events` and `write_recordings` write the events and
+/// terminal recordings JSON arrays in place. Stored secret values are masked before they
+/// are embedded.
+fn write_replay_html<W: Write>(
+    out: &mut W,
+    session_json: &str,
+    elves_json: &str,
+    write_events: impl FnOnce(&mut dyn Write) -> Result<(), String>,
+    write_recordings: impl FnOnce(&mut dyn Write) -> Result<(), String>,
+) -> Result<(), String> {
+    let io_err = |e: std::io::Error| format!("Failed to write file: {e}");
+    let session_json = script_safe(&redact::redact(session_json));
+    let elves_json = script_safe(&redact::redact(elves_json));
+    write!(
+        out,
         r##"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -115,7 +237,18 @@ fn build_replay_html(session_json: &str, elves_json: &str, events_json: &str) ->
 <script>
 window.__ELVES_SESSION__ = {session_json};
 window.__ELVES_ELVES__ = {elves_json};
-window.__ELVES_EVENTS__ = {events_json};
+window.__ELVES_EVENTS__ = "##,
+        css = REPLAY_CSS,
+        session_json = session_json,
+        elves_json = elves_json,
+    )
+    .map_err(io_err)?;
+    write_events(out)?;
+    out.write_all(b";\nwindow.__ELVES_RECORDINGS__ = ").map_err(io_err)?;
+    write_recordings(out)?;
+    write!(
+        out,
+        r##";
 </script>
 
 <div id="app">
@@ -156,6 +289,18 @@ window.__ELVES_EVENTS__ = {events_json};
     </section>
   </main>
 
+  <section class="terminal-panel" id="terminal-panel" hidden>
+    <div class="terminal-bar">
+      <h3 class="panel-title">TERMINAL</h3>
+      <select class="terminal-select" id="terminal-select"></select>
+      <button class="ctrl-btn" id="terminal-restart" title="Replay from the start">&#9198;</button>
+      <button class="ctrl-btn ctrl-btn-play" id="terminal-play" title="Play / Pause">&#9654;</button>
+      <span class="meta-item" id="terminal-time"></span>
+      <a class="speed-btn" id="terminal-download" title="Download for asciinema play">.CAST</a>
+    </div>
+    <pre class="terminal-screen" id="terminal-screen"></pre>
+  </section>
+
   <footer id="controls">
     <div class="controls-bar">
       <button class="ctrl-btn" id="btn-start" title="Jump to start">&#9198;</button>
@@ -184,14 +329,15 @@ window.__ELVES_EVENTS__ = {events_json};
 <script>
 {js}
 </script>
+<script>
+{terminal_js}
+</script>
 </body>
 </html>"##,
-        css = REPLAY_CSS,
         js = REPLAY_JS,
-        session_json = session_json,
-        elves_json = elves_json,
-        events_json = events_json,
+        terminal_js = TERMINAL_JS,
     )
+    .map_err(io_err)
 }
 
 /// Inline CSS for the self-contained HTML replay — neo-brutalist styling.
@@ -465,11 +611,14 @@ body {
 .event-type-tool_call   { background: var(--orange); color: #000; }
 .event-type-tool_result { background: var(--green); color: #000; }
 .event-type-output      { background: var(--gold); color: #000; }
+.event-type-text        { background: var(--gold); color: #000; }
+.event-type-result      { background: var(--gold); color: #000; }
 .event-type-spawn       { background: #E0C3FC; color: #000; }
 .event-type-chat        { background: #FFF; color: #000; }
 .event-type-error       { background: var(--red); color: #FFF; }
 .event-type-task_update { background: #B8E6D0; color: #000; }
 .event-type-file_change { background: #FFE4B5; color: #000; }
+.event-type-file_edit   { background: #FFE4B5; color: #000; }
 .event-type-permission_request { background: #FFB4B4; color: #000; }
 
 .event-body {
@@ -602,6 +751,57 @@ body {
   transition: width 0.15s;
 }
 
+/* ── Terminal recording ──────────────────────────────── */
+.terminal-panel {
+  border-top: var(--border);
+  padding: 16px;
+  background: var(--bg);
+}
+
+.terminal-panel[hidden] { display: none; }
+
+.terminal-bar {
+  display: flex;
+  align-items: center;
+  gap: 8px;
+  margin-bottom: 12px;
+}
+
+.terminal-bar .panel-title {
+  margin-bottom: 0;
+  padding-bottom: 0;
+  border-bottom: none;
+  margin-right: 8px;
+}
+
+.terminal-select {
+  border: 2px solid var(--black);
+  background: #FFF;
+  font-family: var(--font-mono);
+  font-size: 12px;
+  font-weight: 700;
+  padding: 6px 8px;
+}
+
+#terminal-download {
+  margin-left: auto;
+  color: var(--black);
+  text-decoration: none;
+}
+
+.terminal-screen {
+  border: var(--border);
+  box-shadow: var(--shadow);
+  background: var(--bg-dark);
+  color: #E8E8E8;
+  font-family: var(--font-mono);
+  font-size: 12px;
+  line-height: 1.35;
+  padding: 12px;
+  overflow: auto;
+  max-height: 70vh;
+}
+
 /* ── Branding ────────────────────────────────────────── */
 .branding {
   text-align: center;
@@ -698,11 +898,16 @@ const REPLAY_JS: &str = r#"
         if (typeof args === 'object') args = JSON.stringify(args);
         return tool + '(' + truncate(String(args), 100) + ')';
       case 'tool_result':
-        var out = payload.output || payload.result || '';
+        var out = payload.content || payload.output || payload.result || '';
         if (typeof out === 'object') out = JSON.stringify(out);
         return truncate(String(out), 200);
       case 'output':
+      case 'text':
         return payload.text || payload.content || JSON.stringify(payload);
+      case 'result':
+        return payload.text || (payload.isError ? 'Run failed' : 'Run finished');
+      case 'usage':
+        return (payload.inputTokens || 0) + ' in / ' + (payload.outputTokens || 0) + ' out tokens';
       case 'spawn':
         return 'Spawned: ' + (payload.name || payload.elfName || 'elf');
       case 'chat':
@@ -712,6 +917,7 @@ const REPLAY_JS: &str = r#"
       case 'task_update':
         return (payload.status || '') + ': ' + (payload.label || payload.task || '');
       case 'file_change':
+      case 'file_edit':
         return (payload.action || 'changed') + ' ' + (payload.path || payload.file || '');
       case 'permission_request':
         return 'Permission: ' + (payload.tool || payload.action || 'unknown');
@@ -974,6 +1180,388 @@ const REPLAY_JS: &str = r#"
 })();
 "#;
 
+/// Inline JavaScript for the terminal recording player. Parses the embedded asciicast
+/// files and redraws their output on a character grid, handling the cursor movement and
+/// erase sequences TUIs use. Colors and styles are dropped.
+const TERMINAL_JS: &str = r#"
+(function() {
+  'use strict';
+
+  var recordings = window.__ELVES_RECORDINGS__ || [];
+  if (recordings.length === 0) return;
+  var elves = window.__ELVES_ELVES__ || [];
+
+  /** Longest pause played back, in seconds, so idle stretches don't stall the replay. */
+  var IDLE_LIMIT = 2;
+
+  function parseCast(lines) {
+    var header = {};
+    try { header = JSON.parse(lines[0]); } catch (e) {}
+    var events = [];
+    var clock = 0;
+    var last = 0;
+    for (var i = 1; i < lines.length; i++) {
+      var evt;
+      try { evt = JSON.parse(lines[i]); } catch (e) { continue; }
+      if (!Array.isArray(evt) || evt.length < 3) continue;
+      clock += Math.min(Math.max(evt[0] - last, 0), IDLE_LIMIT);
+      last = evt[0];
+      events.push({ time: clock, code: evt[1], data: String(evt[2]) });
+    }
+    return { width: header.width || 80, height: header.height || 24, events: events };
+  }
+
+  /* ── Screen emulation ──────────────────────────── */
+
+  function Screen(cols, rows) {
+    this.cols = cols;
+    this.rows = rows;
+    this.state = 'normal';
+    this.params = '';
+    this.saved = { x: 0, y: 0 };
+    this.main = null;
+    this.reset();
+  }
+
+  Screen.prototype.blankLine = function() {
+    var line = [];
+    for (var i = 0; i < this.cols; i++) line.push(' ');
+    return line;
+  };
+
+  Screen.prototype.reset = function() {
+    this.lines = [];
+    for (var i = 0; i < this.rows; i++) this.lines.push(this.blankLine());
+    this.x = 0;
+    this.y = 0;
+    this.top = 0;
+    this.bottom = this.rows - 1;
+  };
+
+  Screen.prototype.resize = function(cols, rows) {
+    var old = this.lines;
+    // Drop lines from the top only as far as needed to keep the cursor on screen
+    var shift = Math.max(this.y - rows + 1, 0);
+    this.cols = cols;
+    this.rows = rows;
+    this.y -= shift;
+    this.lines = [];
+    for (var i = 0; i < rows; i++) {
+      var line = this.blankLine();
+      var src = old[i + shift];
+      if (src) for (var j = 0; j < Math.min(cols, src.length); j++) line[j] = src[j];
+      this.lines.push(line);
+    }
+    this.top = 0;
+    this.bottom = rows - 1;
+    this.clampCursor();
+  };
+
+  Screen.prototype.clampCursor = function() {
+    this.x = Math.max(0, Math.min(this.x, this.cols - 1));
+    this.y = Math.max(0, Math.min(this.y, this.rows - 1));
+  };
+
+  Screen.prototype.scrollUp = function(count) {
+    for (var i = 0; i < count; i++) {
+      this.lines.splice(this.top, 1);
+      this.lines.splice(this.bottom, 0, this.blankLine());
+    }
+  };
+
+  Screen.prototype.scrollDown = function(count) {
+    for (var i = 0; i < count; i++) {
+      this.lines.splice(this.bottom, 1);
+      this.lines.splice(this.top, 0, this.blankLine());
+    }
+  };
+
+  Screen.prototype.lineFeed = function() {
+    if (this.y === this.bottom) this.scrollUp(1);
+    else if (this.y < this.rows - 1) this.y++;
+  };
+
+  Screen.prototype.put = function(ch) {
+    if (this.x >= this.cols) {
+      this.x = 0;
+      this.lineFeed();
+    }
+    this.lines[this.y][this.x] = ch;
+    this.x++;
+  };
+
+  Screen.prototype.erase = function(row, from, to) {
+    for (var i = Math.max(from, 0); i < Math.min(to, this.cols); i++) this.lines[row][i] = ' ';
+  };
+
+  Screen.prototype.write = function(data) {
+    for (var i = 0; i < data.length; i++) {
+      var ch = data[i];
+      switch (this.state) {
+        case 'normal':
+          if (ch === '\x1b') this.state = 'esc';
+          else if (ch === '\r') this.x = 0;
+          else if (ch === '\n' || ch === '\x0b' || ch === '\x0c') this.lineFeed();
+          else if (ch === '\b') this.x = Math.max(0, Math.min(this.x, this.cols) - 1);
+          else if (ch === '\t') this.x = Math.min(this.cols - 1, (Math.floor(this.x / 8) + 1) * 8);
+          else if (ch >= ' ' && ch !== '\x7f') this.put(ch);
+          break;
+        case 'esc':
+          this.state = 'normal';
+          if (ch === '[') { this.state = 'csi'; this.params = ''; }
+          else if (ch === ']' || ch === 'P' || ch === '_' || ch === '^') this.state = 'string';
+          else if (ch === '(' || ch === ')' || ch === '*' || ch === '+' || ch === '#') this.state = 'skip';
+          else if (ch === 'c') this.reset();
+          else if (ch === 'D') this.lineFeed();
+          else if (ch === 'E') { this.x = 0; this.lineFeed(); }
+          else if (ch === 'M') { if (this.y === this.top) this.scrollDown(1); else if (this.y > 0) this.y--; }
+          else if (ch === '7') this.saved = { x: this.x, y: this.y };
+          else if (ch === '8') { this.x = this.saved.x; this.y = this.saved.y; this.clampCursor(); }
+          break;
+        case 'csi':
+          if (ch >= '@' && ch <= '~') {
+            this.state = 'normal';
+            this.csi(ch, this.params);
+          } else {
+            this.params += ch;
+          }
+          break;
+        case 'string':
+          // OSC/DCS strings end with BEL or ESC \
+          if (ch === '\x07') this.state = 'normal';
+          else if (ch === '\x1b') this.state = 'esc';
+          break;
+        case 'skip':
+          this.state = 'normal';
+          break;
+      }
+    }
+  };
+
+  Screen.prototype.csi = function(command, params) {
+    var isPrivate = params.charAt(0) === '?';
+    var args = params.replace(/^[?>=!]/, '').split(';').map(function(p) { return parseInt(p, 10); });
+    function arg(i, fallback) {
+      var value = args[i];
+      return isNaN(value) || value === 0 ? fallback : value;
+    }
+    var n = arg(0, 1);
+    var row, i;
+    switch (command) {
+      case 'A': this.y -= n; break;
+      case 'B': case 'e': this.y += n; break;
+      case 'C': case 'a': this.x += n; break;
+      case 'D': this.x -= n; break;
+      case 'E': this.y += n; this.x = 0; break;
+      case 'F': this.y -= n; this.x = 0; break;
+      case 'G': case '`': this.x = n - 1; break;
+      case 'd': this.y = n - 1; break;
+      case 'H': case 'f': this.y = arg(0, 1) - 1; this.x = arg(1, 1) - 1; break;
+      case 'J':
+        this.clampCursor();
+        var mode = isNaN(args[0]) ? 0 : args[0];
+        if (mode === 0) {
+          this.erase(this.y, this.x, this.cols);
+          for (row = this.y + 1; row < this.rows; row++) this.erase(row, 0, this.cols);
+        } else if (mode === 1) {
+          this.erase(this.y, 0, this.x + 1);
+          for (row = 0; row < this.y; row++) this.erase(row, 0, this.cols);
+        } else {
+          for (row = 0; row < this.rows; row++) this.erase(row, 0, this.cols);
+        }
+        break;
+      case 'K':
+        this.clampCursor();
+        var lineMode = isNaN(args[0]) ? 0 : args[0];
+        if (lineMode === 0) this.erase(this.y, this.x, this.cols);
+        else if (lineMode === 1) this.erase(this.y, 0, this.x + 1);
+        else this.erase(this.y, 0, this.cols);
+        break;
+      case 'X': this.erase(this.y, this.x, this.x + n); break;
+      case 'P':
+        this.lines[this.y].splice(this.x, n);
+        while (this.lines[this.y].length < this.cols) this.lines[this.y].push(' ');
+        break;
+      case '@':
+        for (i = 0; i < n; i++) this.lines[this.y].splice(this.x, 0, ' ');
+        this.lines[this.y].length = this.cols;
+        break;
+      case 'L':
+      case 'M':
+        if (this.y < this.top || this.y > this.bottom) break;
+        var top = this.top;
+        this.top = this.y;
+        if (command === 'L') this.scrollDown(n); else this.scrollUp(n);
+        this.top = top;
+        break;
+      case 'S': this.scrollUp(n); break;
+      case 'T': this.scrollDown(n); break;
+      case 'r':
+        this.top = arg(0, 1) - 1;
+        this.bottom = Math.min(arg(1, this.rows), this.rows) - 1;
+        if (this.top >= this.bottom) { this.top = 0; this.bottom = this.rows - 1; }
+        this.x = 0;
+        this.y = 0;
+        break;
+      case 's': this.saved = { x: this.x, y: this.y }; break;
+      case 'u': this.x = this.saved.x; this.y = this.saved.y; break;
+      case 'h':
+      case 'l':
+        // Alternate screen: full-screen TUIs draw there and restore the main screen on exit
+        if (isPrivate && (args.indexOf(1049) >= 0 || args.indexOf(1047) >= 0 || args.indexOf(47) >= 0)) {
+          if (command === 'h' && !this.main) {
+            this.main = { lines: this.lines, x: this.x, y: this.y };
+            this.lines = [];
+            for (row = 0; row < this.rows; row++) this.lines.push(this.blankLine());
+          } else if (command === 'l' && this.main) {
+            this.lines = this.main.lines;
+            this.x = this.main.x;
+            this.y = this.main.y;
+            this.main = null;
+            this.resize(this.cols, this.rows);
+          }
+        }
+        break;
+    }
+    this.clampCursor();
+  };
+
+  Screen.prototype.text = function() {
+    return this.lines.map(function(line) { return line.join('').replace(/\s+$/, ''); }).join('\n');
+  };
+
+  /* ── Player ────────────────────────────────────── */
+
+  var panel = document.getElementById('terminal-panel');
+  var select = document.getElementById('terminal-select');
+  var screenEl = document.getElementById('terminal-screen');
+  var playBtn = document.getElementById('terminal-play');
+  var timeEl = document.getElementById('terminal-time');
+  var downloadEl = document.getElementById('terminal-download');
+  panel.hidden = false;
+
+  var elfNames = {};
+  elves.forEach(function(elf) { elfNames[elf.id] = elf.name; });
+  recordings.forEach(function(recording, i) {
+    var option = document.createElement('option');
+    option.value = String(i);
+    option.textContent = recording.elfId ? (elfNames[recording.elfId] || recording.elfId) : 'Session';
+    select.appendChild(option);
+  });
+  if (recordings.length < 2) select.hidden = true;
+
+  var cast = null;
+  var screen = null;
+  var position = 0;
+  var clock = 0;
+  var playing = false;
+  var timer = null;
+  var lastTick = 0;
+
+  function formatClock(secs) {
+    var s = Math.floor(secs);
+    return Math.floor(s / 60) + ':' + (s % 60).toString().padStart(2, '0');
+  }
+
+  function render() {
+    screenEl.textContent = screen.text();
+    timeEl.textContent = formatClock(clock) + ' / ' + formatClock(duration());
+  }
+
+  function apply(evt) {
+    if (evt.code === 'o') {
+      screen.write(evt.data);
+    } else if (evt.code === 'r') {
+      var size = evt.data.split('x');
+      var cols = parseInt(size[0], 10);
+      var rows = parseInt(size[1], 10);
+      if (cols > 0 && rows > 0) screen.resize(cols, rows);
+    }
+  }
+
+  function rewind() {
+    screen = new Screen(cast.width, cast.height);
+    position = 0;
+    clock = 0;
+  }
+
+  function duration() {
+    return cast.events.length > 0 ? cast.events[cast.events.length - 1].time : 0;
+  }
+
+  function seekTo(time) {
+    while (position < cast.events.length && cast.events[position].time <= time) {
+      apply(cast.events[position]);
+      position++;
+    }
+    clock = time;
+    render();
+  }
+
+  function pause() {
+    playing = false;
+    playBtn.innerHTML = '&#9654;';
+    if (timer) {
+      clearTimeout(timer);
+      timer = null;
+    }
+  }
+
+  function tick() {
+    if (!playing) return;
+    var now = Date.now();
+    seekTo(clock + (now - lastTick) / 1000);
+    lastTick = now;
+    if (position >= cast.events.length) {
+      pause();
+      return;
+    }
+    timer = setTimeout(tick, 33);
+  }
+
+  function play() {
+    if (playing) return;
+    if (position >= cast.events.length) rewind();
+    playing = true;
+    playBtn.innerHTML = '&#9646;&#9646;';
+    lastTick = Date.now();
+    tick();
+  }
+
+  function load(index) {
+    pause();
+    var recording = recordings[index];
+    cast = parseCast(recording.lines);
+    rewind();
+    // Show how the terminal ended; play replays it from the start
+    seekTo(duration());
+
+    if (downloadEl.href) URL.revokeObjectURL(downloadEl.href);
+    var blob = new Blob([recording.lines.join('\n') + '\n'], { type: 'application/x-asciicast' });
+    downloadEl.href = URL.createObjectURL(blob);
+    var sessionId = (window.__ELVES_SESSION__ || {}).id || 'session';
+    downloadEl.download = sessionId + (recording.elfId ? '-' + recording.elfId : '') + '.cast';
+  }
+
+  playBtn.addEventListener('click', function() {
+    if (playing) pause(); else play();
+  });
+
+  document.getElementById('terminal-restart').addEventListener('click', function() {
+    pause();
+    rewind();
+    play();
+  });
+
+  select.addEventListener('change', function() {
+    load(parseInt(select.value, 10));
+  });
+
+  load(0);
+
+})();
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -997,6 +1585,82 @@ mod tests {
         assert!(html.contains("mvmcode.github.io/elves"));
     }
 
+    #[test]
+    fn write_session_replay_streams_all_events() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::schema::run_migrations(&conn).unwrap();
+        db::projects::create_project(&conn, "p1", "Test", "/tmp/test").unwrap();
+        db::sessions::create_session(&conn, "s1", "p1", "Long task", "claude-code", None).unwrap();
+        for i in 0..3 {
+            db::events::insert_event(&conn, "s1", None, "output", &format!("{{\"n\":{i}}}"), None).unwrap();
+        }
+
+        let mut out = Vec::new();
+        write_session_replay(|| Ok(&conn), "s1", &mut out).expect("Should write replay");
+        let html = String::from_utf8(out).unwrap();
+        assert!(html.contains("Long task"));
+        assert!(html.ends_with("</html>"));
+
+        let start = html.find("window.__ELVES_EVENTS__ = ").unwrap() + "window.__ELVES_EVENTS__ = ".len();
+        let end = start + html[start..].find(";\nwindow.__ELVES_RECORDINGS__").unwrap();
+        let events: serde_json::Value = serde_json::from_str(&html[start..end]).expect("Events should be a JSON array");
+        assert_eq!(events.as_array().unwrap().len(), 3);
+
+        assert!(write_session_replay(|| Ok(&conn), "missing", &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn write_session_replay_escapes_closing_script_tags() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::schema::run_migrations(&conn).unwrap();
+        db::projects::create_project(&conn, "p1", "Test", "/tmp/test").unwrap();
+        db::sessions::create_session(&conn, "s1", "p1", "Task </script>", "claude-code", None).unwrap();
+        db::events::insert_event(&conn, "s1", None, "output", "</script><script>alert(1)", None).unwrap();
+
+        let mut out = Vec::new();
+        write_session_replay(|| Ok(&conn), "s1", &mut out).expect("Should write replay");
+        let html = String::from_utf8(out).unwrap();
+        let data_end = html.find("window.__ELVES_RECORDINGS__").unwrap();
+        assert!(!html[..data_end].contains("</script>"));
+        assert!(html.contains("<\\/script><script>alert(1)"));
+    }
+
+    #[test]
+    fn write_session_replay_embeds_terminal_recordings() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::schema::run_migrations(&conn).unwrap();
+        db::projects::create_project(&conn, "p1", "Test", "/tmp/test").unwrap();
+        db::sessions::create_session(&conn, "s1", "p1", "Recorded task", "claude-code", None).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let cast = dir.path().join("s1.cast");
+        std::fs::write(
+            &cast,
+            "{\"version\":2,\"width\":80,\"height\":24}\n[0.5,\"o\",\"cat page.html\\r\\n</script>\"]\n",
+        )
+        .unwrap();
+        db::recordings::insert_recording(&conn, "s1", None, &cast.to_string_lossy()).unwrap();
+        let missing = dir.path().join("gone.cast");
+        db::recordings::insert_recording(&conn, "s1", Some("e1"), &missing.to_string_lossy()).unwrap();
+
+        let mut out = Vec::new();
+        write_session_replay(|| Ok(&conn), "s1", &mut out).expect("Should write replay");
+        let html = String::from_utf8(out).unwrap();
+        assert!(html.contains("terminal-panel"));
+
+        let marker = "window.__ELVES_RECORDINGS__ = ";
+        let start = html.find(marker).unwrap() + marker.len();
+        let end = start + html[start..].find(";\n</script>").unwrap();
+        assert!(!html[start..end].contains("</script>"), "Output must not close the script tag");
+        let recordings: serde_json::Value = serde_json::from_str(&html[start..end]).expect("Recordings should be JSON");
+        let recordings = recordings.as_array().unwrap();
+        assert_eq!(recordings.len(), 1, "Missing files are left out");
+        assert!(recordings[0]["elfId"].is_null());
+        let lines = recordings[0]["lines"].as_array().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].as_str().unwrap().contains("</script>"));
+    }
+
     #[test]
     fn build_replay_html_is_self_contained() {
         let html = build_replay_html("{}", "[]", "[]");
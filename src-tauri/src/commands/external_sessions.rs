@@ -0,0 +1,160 @@
+// External session commands — browse Claude Code sessions started outside ELVES (e.g. from a
+// plain terminal) and import them as ELVES sessions.
+//
+// An imported session is "completed" with the run's own times, events and per-model usage,
+// so it can be searched, replayed and memory-extracted like any other. Its Claude session
+// ID is recorded, so it can be resumed with `--resume`.
+
+use crate::agents::claude_sessions::{self, ClaudeTranscript};
+use crate::commands::projects::DbState;
+use crate::commands::settings;
+use crate::commands::tasks;
+use crate::db;
+use crate::db::sessions::SessionRow;
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
+
+/// A Claude Code session found in a project's transcripts.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalClaudeSession {
+    /// Claude's session ID, as used with `claude --resume`.
+    pub session_id: String,
+    pub first_prompt: Option<String>,
+    pub started_at: Option<i64>,
+    pub ended_at: Option<i64>,
+    pub model: Option<String>,
+    pub message_count: u32,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cost_usd: f64,
+    /// The ELVES session that ran or imported it, if any.
+    pub elves_session_id: Option<String>,
+}
+
+fn read_transcript(path: &Path) -> Option<ClaudeTranscript> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let fallback_id = path.file_stem()?.to_string_lossy().to_string();
+    claude_sessions::parse_transcript(&content, &fallback_id, settings::pricing_table())
+}
+
+fn summarize_transcript(path: &Path) -> Option<ClaudeTranscript> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let fallback_id = path.file_stem()?.to_string_lossy().to_string();
+    claude_sessions::summarize_transcript(&content, &fallback_id, settings::pricing_table())
+}
+
+/// List the Claude Code sessions run in a project's directory, newest first, including
+/// those ELVES started or already imported (see `elvesSessionId`). Transcripts are read
+/// on a blocking worker thread.
+#[tauri::command]
+pub async fn list_external_claude_sessions(
+    app: AppHandle,
+    project_path: String,
+) -> Result<Vec<ExternalClaudeSession>, String> {
+    tokio::task::spawn_blocking(move || list_sessions(&app, &project_path))
+        .await
+        .map_err(|e| format!("Failed to list Claude sessions: {e}"))?
+}
+
+fn list_sessions(app: &AppHandle, project_path: &str) -> Result<Vec<ExternalClaudeSession>, String> {
+    let home = dirs::home_dir().ok_or("Could not resolve home directory")?;
+    let mut sessions: Vec<ExternalClaudeSession> = claude_sessions::transcript_files(&home, project_path)
+        .iter()
+        .filter_map(|path| summarize_transcript(path))
+        .map(|transcript| {
+            let tokens = transcript.tokens();
+            ExternalClaudeSession {
+                session_id: transcript.session_id.clone(),
+                first_prompt: transcript.first_prompt.clone(),
+                started_at: transcript.started_at,
+                ended_at: transcript.ended_at,
+                model: transcript.model.clone(),
+                message_count: transcript.message_count,
+                input_tokens: tokens.input_tokens,
+                output_tokens: tokens.output_tokens,
+                cache_read_tokens: tokens.cache_read_tokens,
+                cache_write_tokens: tokens.cache_write_tokens,
+                cost_usd: transcript.usage.total_cost(),
+                elves_session_id: None,
+            }
+        })
+        .collect();
+
+    let db_state = app.state::<DbState>();
+    let conn = db_state.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    if let Some(project) = db::projects::get_project_by_path(&conn, project_path).map_err(|e| format!("Database error: {e}"))? {
+        for session in &mut sessions {
+            session.elves_session_id = db::sessions::find_by_claude_session_id(&conn, &project.id, &session.session_id)
+                .map_err(|e| format!("Database error: {e}"))?
+                .map(|row| row.id);
+        }
+    }
+    sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(sessions)
+}
+
+/// Import a Claude Code session run in a project's directory as a completed ELVES session,
+/// with its events, usage and Claude session ID. Returns the existing session if it was
+/// already imported or started by ELVES.
+#[tauri::command]
+pub fn import_claude_session(
+    db: State<'_, DbState>,
+    project_id: String,
+    claude_session_id: String,
+) -> Result<SessionRow, String> {
+    let home = dirs::home_dir().ok_or("Could not resolve home directory")?;
+    let project_path = {
+        let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+        if let Some(existing) = db::sessions::find_by_claude_session_id(&conn, &project_id, &claude_session_id)
+            .map_err(|e| format!("Database error: {e}"))?
+        {
+            return Ok(existing);
+        }
+        db::projects::get_project(&conn, &project_id)
+            .map_err(|e| format!("Database error: {e}"))?
+            .ok_or("Project not found")?
+            .path
+    };
+
+    let path = claude_sessions::find_transcript(&home, &project_path, &claude_session_id)
+        .ok_or_else(|| format!("No Claude session {claude_session_id} found for {project_path}"))?;
+    let transcript = read_transcript(&path).ok_or("The Claude session has no messages")?;
+
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let tx = conn.unchecked_transaction().map_err(|e| format!("Database error: {e}"))?;
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let task = transcript.first_prompt.clone().unwrap_or_else(|| "Imported Claude session".to_string());
+    db::sessions::create_session(&tx, &session_id, &project_id, &task, "claude-code", None)
+        .map_err(|e| format!("Database error: {e}"))?;
+    db::sessions::update_claude_session_id(&tx, &session_id, &claude_session_id)
+        .map_err(|e| format!("Database error: {e}"))?;
+
+    for (timestamp, event) in &transcript.events {
+        let payload = serde_json::to_string(event).unwrap_or_default();
+        db::events::insert_typed_event_at(&tx, &session_id, None, event.kind(), &payload, *timestamp)
+            .map_err(|e| format!("Database error: {e}"))?;
+    }
+    tasks::store_usage(&tx, &session_id, &transcript.usage);
+    if let Some(summary) = transcript.last_text() {
+        db::sessions::set_summary_if_missing(&tx, &session_id, &tasks::truncate_summary(summary.to_string()))
+            .map_err(|e| format!("Database error: {e}"))?;
+    }
+    let now = chrono::Utc::now().timestamp();
+    db::sessions::finish_imported_session(
+        &tx,
+        &session_id,
+        transcript.started_at.unwrap_or(now),
+        transcript.ended_at.unwrap_or(now),
+    )
+    .map_err(|e| format!("Database error: {e}"))?;
+    tx.commit().map_err(|e| format!("Database error: {e}"))?;
+
+    log::info!("[session {session_id}] Imported Claude session {claude_session_id}");
+    db::sessions::get_session(&conn, &session_id)
+        .map_err(|e| format!("Database error: {e}"))?
+        .ok_or_else(|| "Imported session not found".to_string())
+}
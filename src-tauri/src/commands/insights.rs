@@ -1,10 +1,17 @@
 // Insights command — reads Claude Code usage JSON files (session-meta, facets, stats-cache, report.html),
 // returning a single aggregated InsightsData struct for the dashboard.
+// Codex sessions from `~/.codex` are merged in under their own runtime (see codex_insights.rs).
+// `load_db_insights` instead computes insights from the ELVES database (see db/insights.rs).
 
+use crate::commands::codex_insights;
+use crate::commands::projects::DbState;
+use crate::db;
+use crate::db::insights::{DbInsights, InsightsFilter};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use tauri::State;
 
 // ── Output structs (sent to frontend, camelCase) ────────────────────────────
 
@@ -38,6 +45,8 @@ pub struct OutcomeEntry {
 #[serde(rename_all = "camelCase")]
 pub struct ModelUsageEntry {
     pub model: String,
+    /// Runtime the model was used with: "claude-code" or "codex".
+    pub runtime: String,
     pub input_tokens: u64,
     pub output_tokens: u64,
     pub cache_read_tokens: u64,
@@ -50,6 +59,7 @@ pub struct ModelUsageEntry {
 #[serde(rename_all = "camelCase")]
 pub struct ProjectSummary {
     pub name: String,
+    pub runtime: String,
     pub sessions: u32,
     pub lines_added: u64,
     pub commits: u32,
@@ -63,6 +73,7 @@ pub struct ProjectSummary {
 pub struct SessionSummary {
     pub session_id: String,
     pub project: String,
+    pub runtime: String,
     pub start_time: String,
     pub duration_minutes: u64,
     pub first_prompt: String,
@@ -84,6 +95,33 @@ pub struct FeatureAdoption {
     pub total: u32,
 }
 
+/// Totals of one runtime, for comparing Claude Code with Codex.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeSummary {
+    pub runtime: String,
+    pub sessions: u32,
+    pub messages: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub duration_minutes: u64,
+    pub tool_calls: u32,
+}
+
+impl RuntimeSummary {
+    pub fn new(runtime: &str) -> Self {
+        Self {
+            runtime: runtime.to_string(),
+            sessions: 0,
+            messages: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            duration_minutes: 0,
+            tool_calls: 0,
+        }
+    }
+}
+
 /// Full aggregated insights data returned to the frontend.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -128,6 +166,9 @@ pub struct InsightsData {
     // Sessions
     pub recent_sessions: Vec<SessionSummary>,
 
+    // Runtimes
+    pub runtimes: Vec<RuntimeSummary>,
+
     // Report
     pub report_html: Option<String>,
 }
@@ -248,7 +289,7 @@ fn read_json_dir<T: serde::de::DeserializeOwned>(dir: &PathBuf) -> Vec<T> {
 }
 
 /// Extract the project name from a path — last path component.
-fn project_name_from_path(path: &str) -> String {
+pub(crate) fn project_name_from_path(path: &str) -> String {
     std::path::Path::new(path)
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
@@ -256,7 +297,7 @@ fn project_name_from_path(path: &str) -> String {
 }
 
 /// Aggregate top-N items from a frequency map, sorted by count descending.
-fn top_n(map: &HashMap<String, u32>, n: usize) -> Vec<NamedCount> {
+pub(crate) fn top_n(map: &HashMap<String, u32>, n: usize) -> Vec<NamedCount> {
     let mut items: Vec<_> = map
         .iter()
         .map(|(name, &count)| NamedCount {
@@ -269,22 +310,28 @@ fn top_n(map: &HashMap<String, u32>, n: usize) -> Vec<NamedCount> {
     items
 }
 
-/// Truncate a string to at most `max_len` characters, appending "…" if truncated.
-fn truncate(s: &str, max_len: usize) -> String {
+/// Truncate a string to at most `max_len` bytes, cut on a char boundary, appending "…"
+/// if truncated.
+pub(crate) fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
-        s.to_string()
-    } else {
-        let mut truncated = s[..max_len].to_string();
-        truncated.push('…');
-        truncated
+        return s.to_string();
+    }
+    let mut end = max_len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
     }
+    format!("{}…", &s[..end])
 }
 
 // ── Main command ────────────────────────────────────────────────────────────
 
-/// Load insights data from Claude Code usage files: session-meta, facets, stats-cache, report.html.
+/// Runtime name Claude Code usage is reported under.
+const CLAUDE_RUNTIME: &str = "claude-code";
+
+/// Load insights data from Claude Code usage files (session-meta, facets, stats-cache,
+/// report.html) and Codex rollout logs.
 #[tauri::command]
-pub fn load_insights() -> Result<InsightsData, String> {
+pub fn load_insights(codex_cache: State<'_, codex_insights::CodexSessionCache>) -> Result<InsightsData, String> {
     let home = dirs::home_dir().ok_or("Could not resolve home directory")?;
     let usage_dir = home.join(".claude").join("usage-data");
     let meta_dir = usage_dir.join("session-meta");
@@ -352,6 +399,7 @@ pub fn load_insights() -> Result<InsightsData, String> {
 
                     ModelUsageEntry {
                         model: model.clone(),
+                        runtime: CLAUDE_RUNTIME.to_string(),
                         input_tokens: input,
                         output_tokens: output,
                         cache_read_tokens: cache_read,
@@ -442,6 +490,7 @@ pub fn load_insights() -> Result<InsightsData, String> {
 
         let entry = project_map.entry(project_name.clone()).or_insert_with(|| ProjectSummary {
             name: project_name,
+            runtime: CLAUDE_RUNTIME.to_string(),
             sessions: 0,
             lines_added: 0,
             commits: 0,
@@ -580,6 +629,7 @@ pub fn load_insights() -> Result<InsightsData, String> {
             Some(SessionSummary {
                 session_id,
                 project,
+                runtime: CLAUDE_RUNTIME.to_string(),
                 start_time,
                 duration_minutes: meta.duration_minutes.unwrap_or(0),
                 first_prompt,
@@ -614,7 +664,17 @@ pub fn load_insights() -> Result<InsightsData, String> {
             .sum()
     };
 
-    Ok(InsightsData {
+    let claude_runtime = RuntimeSummary {
+        sessions: final_total_sessions,
+        messages: final_total_messages,
+        input_tokens: total_input_tokens,
+        output_tokens: total_output_tokens,
+        duration_minutes: total_duration_minutes,
+        tool_calls: tool_counts.values().sum(),
+        ..RuntimeSummary::new(CLAUDE_RUNTIME)
+    };
+
+    let mut data = InsightsData {
         total_sessions: final_total_sessions,
         total_messages: final_total_messages,
         total_input_tokens,
@@ -639,6 +699,34 @@ pub fn load_insights() -> Result<InsightsData, String> {
         top_languages: top_n(&lang_counts, 10),
         feature_adoption,
         recent_sessions,
+        runtimes: if final_total_sessions > 0 { vec![claude_runtime] } else { Vec::new() },
         report_html,
-    })
+    };
+
+    // 6. Merge Codex sessions from ~/.codex/sessions
+    codex_insights::merge_codex_sessions(&mut data, &codex_insights::read_codex_sessions(&home, &codex_cache));
+
+    Ok(data)
+}
+
+/// Compute insights from the ELVES database: sessions, outcomes, spend, templates, roles,
+/// memory and workspaces, filtered by date range and project.
+#[tauri::command]
+pub fn load_db_insights(db: tauri::State<'_, DbState>, filter: Option<InsightsFilter>) -> Result<DbInsights, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::insights::load_insights(&conn, &filter.unwrap_or_default()).map_err(|e| format!("Database error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_cuts_on_a_char_boundary() {
+        assert_eq!(truncate("short", 120), "short");
+        assert_eq!(truncate("abcdef", 3), "abc…");
+        // "é" is two bytes, straddling the limit
+        assert_eq!(truncate("aé", 2), "a…");
+        assert_eq!(truncate(&"日本".repeat(50), 121), format!("{}…", "日本".repeat(20)));
+    }
 }
@@ -0,0 +1,73 @@
+// Database maintenance commands — retention compaction, size statistics, VACUUM and ANALYZE.
+//
+// Each can take a while on a large database, so they run on a blocking worker thread
+// rather than the main thread.
+
+use crate::db;
+use crate::db::maintenance::{CompactionReport, DatabaseStats, VacuumReport};
+use crate::settings::config;
+use super::projects::DbState;
+use tauri::{AppHandle, Manager};
+
+/// Seconds in a day, for converting retention days to a cutoff timestamp.
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Report the database size per table and per project.
+#[tauri::command]
+pub async fn database_stats(app: AppHandle) -> Result<DatabaseStats, String> {
+    with_database(app, db::maintenance::database_stats).await
+}
+
+/// Checkpoint the WAL and VACUUM the database to reclaim free space.
+///
+/// Holds the database lock for the duration, so running sessions pause their event
+/// writes until it finishes.
+#[tauri::command]
+pub async fn vacuum_database(app: AppHandle) -> Result<VacuumReport, String> {
+    with_database(app, db::maintenance::vacuum_database).await
+}
+
+/// Refresh the query planner statistics.
+#[tauri::command]
+pub async fn analyze_database(app: AppHandle) -> Result<(), String> {
+    with_database(app, db::maintenance::analyze_database).await
+}
+
+/// Compact events older than `retention_days` in finished, unpinned sessions.
+///
+/// Uses the configured retention period when `retention_days` is None. Runs even if
+/// automatic retention is disabled, so the user can compact on demand.
+#[tauri::command]
+pub async fn compact_session_events(
+    app: AppHandle,
+    retention_days: Option<u32>,
+) -> Result<CompactionReport, String> {
+    let days = match retention_days {
+        Some(days) => days,
+        None => config::read_app_settings(&config::default_settings_path())?
+            .retention
+            .full_event_days,
+    };
+    let cutoff = retention_cutoff(days);
+    with_database(app, move |conn| db::maintenance::compact_events(conn, cutoff)).await
+}
+
+/// Run `work` with the database locked, on a blocking worker thread.
+async fn with_database<T, F>(app: AppHandle, work: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&rusqlite::Connection) -> Result<T, db::DbError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+        work(&conn).map_err(|e| format!("Database error: {e}"))
+    })
+    .await
+    .map_err(|e| format!("Maintenance task failed: {e}"))?
+}
+
+/// Unix timestamp before which events are older than `days`.
+pub fn retention_cutoff(days: u32) -> i64 {
+    chrono::Utc::now().timestamp() - i64::from(days) * SECONDS_PER_DAY
+}
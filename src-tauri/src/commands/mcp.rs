@@ -2,29 +2,26 @@
 
 use crate::db;
 use crate::db::mcp::McpRow;
+use crate::mcp::client::McpClient;
+use crate::mcp::sync::{self, ServerSpec, SyncEntry, SyncStatus, SyncTarget};
+use crate::mcp::transport::{ServerEndpoint, TransportKind};
+use crate::mcp::types::{
+    McpPromptResult, McpResourceContent, McpServerInventory, McpTool, McpToolCallResult,
+};
+use crate::secrets::store::SecretStore;
 use super::projects::DbState;
+use super::secrets::{stash_credentials, SecretsState};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::PathBuf;
 use tauri::State;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-
-/// Split a command string that may contain embedded arguments (e.g., "npx -y @pkg")
-/// into (binary, extra_args). If the command has no spaces, returns (command, []).
-fn split_command(command: &str) -> (String, Vec<String>) {
-    let parts: Vec<&str> = command.split_whitespace().collect();
-    match parts.as_slice() {
-        [] => (command.to_string(), vec![]),
-        [binary] => (binary.to_string(), vec![]),
-        [binary, rest @ ..] => (binary.to_string(), rest.iter().map(|s| s.to_string()).collect()),
-    }
-}
 
-/// A tool exposed by an MCP server, returned from the tools/list JSON-RPC call.
-#[derive(Debug, Clone, Serialize)]
-pub struct McpTool {
-    pub name: String,
-    pub description: Option<String>,
-}
+/// How long an MCP server gets to connect, handshake, and answer a discovery call.
+const MCP_QUERY_TIMEOUT_SECS: u64 = 10;
+
+/// Tool calls do real work (searches, API requests), so they get a longer budget.
+const MCP_TOOL_CALL_TIMEOUT_SECS: u64 = 60;
 
 /// List all MCP servers.
 #[tauri::command]
@@ -37,7 +34,12 @@ pub fn list_mcp_servers(
 }
 
 /// Add a new MCP server. Returns the created server row.
+///
+/// `transport` defaults to "stdio", which uses `command`/`args`/`env`. For "http" and
+/// "sse" the server is reached at `url` with optional `headers` (a JSON object), and
+/// `command` is ignored.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn add_mcp_server(
     db: State<'_, DbState>,
     id: String,
@@ -46,12 +48,29 @@ pub fn add_mcp_server(
     args: Option<String>,
     env: Option<String>,
     scope: Option<String>,
+    transport: Option<String>,
+    url: Option<String>,
+    headers: Option<String>,
 ) -> Result<McpRow, String> {
     let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
-    let args_str = args.as_deref().unwrap_or("[]");
-    let env_str = env.as_deref().unwrap_or("{}");
     let scope_str = scope.as_deref().unwrap_or("global");
-    db::mcp::insert_mcp_server(&conn, &id, &name, &command, args_str, env_str, scope_str)
+    let kind = TransportKind::parse(transport.as_deref().unwrap_or("stdio"))?;
+
+    if kind == TransportKind::Stdio {
+        let args_str = args.as_deref().unwrap_or("[]");
+        let env_str = env.as_deref().unwrap_or("{}");
+        return db::mcp::insert_mcp_server(&conn, &id, &name, &command, args_str, env_str, scope_str)
+            .map_err(|e| format!("Database error: {e}"));
+    }
+
+    let url = url
+        .map(|u| u.trim().to_string())
+        .filter(|u| u.starts_with("http://") || u.starts_with("https://"))
+        .ok_or("Remote MCP servers need an http:// or https:// URL")?;
+    let headers_str = headers.as_deref().unwrap_or("{}");
+    serde_json::from_str::<HashMap<String, String>>(headers_str)
+        .map_err(|e| format!("Headers must be a JSON object of strings: {e}"))?;
+    db::mcp::insert_remote_mcp_server(&conn, &id, &name, kind.as_str(), &url, headers_str, scope_str)
         .map_err(|e| format!("Database error: {e}"))
 }
 
@@ -158,6 +177,22 @@ pub fn import_mcp_from_claude(
             }
             let command = config.get("command").and_then(|v| v.as_str()).unwrap_or("").to_string();
             if command.is_empty() {
+                // Remote servers have a url (and a type of "http" or "sse") instead of a command
+                if let Some(url) = config.get("url").and_then(|v| v.as_str()) {
+                    let kind = config.get("type").and_then(|v| v.as_str()).unwrap_or("http");
+                    let Ok(kind @ (TransportKind::Http | TransportKind::Sse)) = TransportKind::parse(kind) else { continue };
+                    let headers_json = config.get("headers")
+                        .map(|v| serde_json::to_string(v).unwrap_or_else(|_| "{}".to_string()))
+                        .unwrap_or_else(|| "{}".to_string());
+                    let id = uuid::Uuid::new_v4().to_string();
+                    match db::mcp::insert_remote_mcp_server(&conn, &id, name, kind.as_str(), url, &headers_json, "global") {
+                        Ok(_) => {
+                            imported += 1;
+                            existing_names.insert(name.clone());
+                        }
+                        Err(e) => log::warn!("Failed to import MCP server '{name}': {e}"),
+                    }
+                }
                 continue;
             }
 
@@ -183,6 +218,223 @@ pub fn import_mcp_from_claude(
     Ok(ImportResult { imported, scanned })
 }
 
+/// How ELVES servers in one scope compare with an external config file.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpSyncPreview {
+    pub target: String,
+    /// Absolute path of the external file (which may not exist yet).
+    pub path: String,
+    /// ELVES scope the file maps to: "global" or a project ID.
+    pub scope: String,
+    pub entries: Vec<SyncEntry>,
+}
+
+/// Outcome of applying a sync in one direction.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpSyncResult {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    /// Requested names that were already in sync, missing from the source side,
+    /// or not representable in the target (SSE servers in Codex).
+    pub skipped: Vec<String>,
+}
+
+/// Resolve a sync target to its file, the project path (for project-scoped targets),
+/// and the ELVES scope whose servers it is compared against.
+fn resolve_sync_target(
+    conn: &rusqlite::Connection,
+    target: SyncTarget,
+    project_id: Option<&str>,
+) -> Result<(PathBuf, Option<String>, String), String> {
+    let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+    if !target.is_project_scoped() {
+        return Ok((target.file_path(&home, None)?, None, "global".to_string()));
+    }
+
+    let project_id = project_id.ok_or_else(|| format!("The {} target needs a project", target.as_str()))?;
+    let project = db::projects::get_project(conn, project_id)
+        .map_err(|e| format!("Database error: {e}"))?
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+    let path = target.file_path(&home, Some(&project.path))?;
+    Ok((path, Some(project.path), project_id.to_string()))
+}
+
+/// ELVES servers in one scope, keyed by name.
+struct ScopedServers {
+    specs: BTreeMap<String, ServerSpec>,
+    /// Row ID for each server name, used to update drifted servers in place.
+    ids: HashMap<String, String>,
+}
+
+/// Collect the ELVES servers configured in `scope`.
+fn elves_servers_in_scope(conn: &rusqlite::Connection, scope: &str) -> Result<ScopedServers, String> {
+    let rows = db::mcp::list_mcp_servers(conn).map_err(|e| format!("Database error: {e}"))?;
+    let mut specs = BTreeMap::new();
+    let mut ids = HashMap::new();
+    for row in rows.iter().filter(|r| r.scope == scope) {
+        // First row wins if a scope somehow holds two servers with the same name
+        if !specs.contains_key(&row.name) {
+            specs.insert(row.name.clone(), ServerSpec::from_row(row));
+            ids.insert(row.name.clone(), row.id.clone());
+        }
+    }
+    Ok(ScopedServers { specs, ids })
+}
+
+/// The specs with `secret://` references filled in, for comparing against external
+/// files. A spec whose references can't be resolved (store locked, secret missing)
+/// is compared as-is and shows up as drift.
+fn resolve_for_compare(specs: &BTreeMap<String, ServerSpec>, store: &SecretStore) -> BTreeMap<String, ServerSpec> {
+    specs
+        .iter()
+        .map(|(name, spec)| (name.clone(), spec.resolve_secrets(store).unwrap_or_else(|_| spec.clone())))
+        .collect()
+}
+
+/// Compare ELVES servers with an external config without changing anything.
+///
+/// `target` is "claude" (~/.claude.json user servers), "claude-local" (~/.claude.json
+/// servers for one project), "mcp-json" (the project's .mcp.json), or "codex"
+/// (~/.codex/config.toml). User-level files map to the "global" scope; project files
+/// need `project_id` and map to that project's scope.
+#[tauri::command]
+pub fn preview_mcp_sync(
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+    target: String,
+    project_id: Option<String>,
+) -> Result<McpSyncPreview, String> {
+    let target = SyncTarget::parse(&target)?;
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let (path, project_path, scope) = resolve_sync_target(&conn, target, project_id.as_deref())?;
+    let elves = elves_servers_in_scope(&conn, &scope)?;
+    let resolved = resolve_for_compare(&elves.specs, &store);
+    let external = sync::read_external(target, &path, project_path.as_deref())?;
+
+    Ok(McpSyncPreview {
+        target: target.as_str().to_string(),
+        path: path.to_string_lossy().to_string(),
+        scope,
+        entries: sync::diff_servers(&elves.specs, &resolved, &external),
+    })
+}
+
+/// Apply a previewed sync for the selected server names.
+///
+/// `direction` "import" copies external definitions into ELVES (creating missing
+/// servers, overwriting drifted ones). "export" writes ELVES definitions into the
+/// external file, leaving servers that weren't named untouched. The diff is
+/// recomputed here so a stale preview can't clobber newer changes unnoticed.
+///
+/// Exports write resolved secret values, since the external tools can't read the
+/// ELVES store; a server whose secrets can't be resolved is refused. Imports move
+/// literal credentials into the store and keep `secret://` references in ELVES.
+#[tauri::command]
+pub fn apply_mcp_sync(
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+    target: String,
+    project_id: Option<String>,
+    direction: String,
+    names: Vec<String>,
+) -> Result<McpSyncResult, String> {
+    let target = SyncTarget::parse(&target)?;
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let mut store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let (path, project_path, scope) = resolve_sync_target(&conn, target, project_id.as_deref())?;
+    let ScopedServers { specs: elves, ids } = elves_servers_in_scope(&conn, &scope)?;
+    let resolved = resolve_for_compare(&elves, &store);
+    let external = sync::read_external(target, &path, project_path.as_deref())?;
+    let entries: HashMap<String, SyncEntry> = sync::diff_servers(&elves, &resolved, &external)
+        .into_iter()
+        .map(|entry| (entry.name.clone(), entry))
+        .collect();
+
+    let mut result = McpSyncResult { created: Vec::new(), updated: Vec::new(), skipped: Vec::new() };
+
+    match direction.as_str() {
+        "import" => {
+            for name in names {
+                let Some(entry) = entries.get(&name) else {
+                    result.skipped.push(name);
+                    continue;
+                };
+                let Some(spec) = entry.external.as_ref() else {
+                    result.skipped.push(name);
+                    continue;
+                };
+                let is_stdio = spec.transport == TransportKind::Stdio.as_str();
+                let mut spec = spec.clone();
+                let credentials = if is_stdio { &mut spec.env } else { &mut spec.headers };
+                stash_credentials(&mut store, &name, credentials)
+                    .map_err(|e| format!("Can't import the credentials of '{name}': {e}"))?;
+
+                let args = serde_json::to_string(&spec.args).unwrap_or_else(|_| "[]".to_string());
+                let env = serde_json::to_string(&spec.env).unwrap_or_else(|_| "{}".to_string());
+                let headers = serde_json::to_string(&spec.headers).unwrap_or_else(|_| "{}".to_string());
+                let url = spec.url.as_deref().unwrap_or_default();
+
+                match entry.status {
+                    SyncStatus::ExternalOnly => {
+                        let id = uuid::Uuid::new_v4().to_string();
+                        if is_stdio {
+                            db::mcp::insert_mcp_server(&conn, &id, &name, &spec.command, &args, &env, &scope)
+                        } else {
+                            db::mcp::insert_remote_mcp_server(&conn, &id, &name, &spec.transport, url, &headers, &scope)
+                        }
+                        .map_err(|e| format!("Database error: {e}"))?;
+                        result.created.push(name);
+                    }
+                    SyncStatus::Drift => {
+                        let id = &ids[&name];
+                        if is_stdio {
+                            db::mcp::update_mcp_server_launch(&conn, id, &spec.command, &args, &env)
+                        } else {
+                            db::mcp::update_mcp_server_remote(&conn, id, &spec.transport, url, &headers)
+                        }
+                        .map_err(|e| format!("Database error: {e}"))?;
+                        result.updated.push(name);
+                    }
+                    SyncStatus::ElvesOnly | SyncStatus::InSync => result.skipped.push(name),
+                }
+            }
+        }
+        "export" => {
+            let mut to_write = BTreeMap::new();
+            for name in names {
+                match entries.get(&name) {
+                    Some(entry) if matches!(entry.status, SyncStatus::ElvesOnly | SyncStatus::Drift) => {
+                        if let Some(spec) = &entry.elves {
+                            let spec = spec
+                                .resolve_secrets(&store)
+                                .map_err(|e| format!("Can't export '{name}': {e}"))?;
+                            to_write.insert(name, (entry.status, spec));
+                        }
+                    }
+                    _ => result.skipped.push(name),
+                }
+            }
+            let specs = to_write.iter().map(|(name, (_, spec))| (name.clone(), spec.clone())).collect();
+            let unsupported = sync::write_external(target, &path, project_path.as_deref(), &specs)?;
+            for (name, (status, _)) in to_write {
+                if unsupported.contains(&name) {
+                    result.skipped.push(name);
+                } else if status == SyncStatus::Drift {
+                    result.updated.push(name);
+                } else {
+                    result.created.push(name);
+                }
+            }
+        }
+        other => return Err(format!("Unknown sync direction '{other}' (expected import or export)")),
+    }
+
+    Ok(result)
+}
+
 /// A curated MCP server entry for the built-in catalog.
 #[derive(Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -404,142 +656,140 @@ pub fn load_mcp_catalog() -> Vec<McpCatalogItem> {
     ]
 }
 
-/// Spawn an MCP server, perform the JSON-RPC initialize + tools/list handshake,
-/// and return the available tools. Times out after 5 seconds.
+/// Load a server's row from the DB and resolve it into a connectable endpoint,
+/// filling in any `secret://` references from the secrets store.
 ///
-/// The server process is killed after tools are retrieved (or on error/timeout).
-#[tauri::command]
-pub async fn list_mcp_tools(
-    id: String,
-    db: State<'_, DbState>,
-) -> Result<Vec<McpTool>, String> {
-    // Extract server config from DB quickly, then release the lock before async work
-    let (command, args_json, env_json) = {
+/// Both locks are released before returning so callers can do async work freely.
+fn load_endpoint(
+    db: &State<'_, DbState>,
+    secrets: &State<'_, SecretsState>,
+    id: &str,
+) -> Result<ServerEndpoint, String> {
+    let server = {
         let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
-        let server = db::mcp::get_mcp_server(&conn, &id)
+        db::mcp::get_mcp_server(&conn, id)
             .map_err(|e| format!("Database error: {e}"))?
-            .ok_or_else(|| format!("MCP server not found: {id}"))?;
-        (server.command, server.args, server.env)
+            .ok_or_else(|| format!("MCP server not found: {id}"))?
     };
-
-    let db_args: Vec<String> = serde_json::from_str(&args_json).unwrap_or_default();
-    let env_map: HashMap<String, String> = serde_json::from_str(&env_json).unwrap_or_default();
-
-    // Shell-split the command string: if it contains spaces (e.g., "npx -y @pkg"),
-    // treat the first token as the binary and prepend the rest to the args list.
-    let (binary, mut args) = split_command(&command);
-    args.extend(db_args);
-
-    tokio::time::timeout(
-        std::time::Duration::from_secs(10),
-        query_mcp_tools(binary, args, env_map),
-    )
-    .await
-    .map_err(|_| "MCP server timed out after 5 seconds".to_string())?
+    let store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    ServerEndpoint::from_row(&server, &store)
 }
 
-/// Spawn the MCP server process, run the JSON-RPC initialize + tools/list handshake,
-/// and parse the result into `Vec<McpTool>`. Kills the process when done.
-async fn query_mcp_tools(
-    command: String,
-    args: Vec<String>,
-    env: HashMap<String, String>,
-) -> Result<Vec<McpTool>, String> {
-    let mut child = tokio::process::Command::new(&command)
-        .args(&args)
-        .envs(&env)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::null())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn MCP server `{command}`: {e}"))?;
-
-    let stdin = child.stdin.take().ok_or("Failed to acquire MCP server stdin")?;
-    let stdout = child.stdout.take().ok_or("Failed to acquire MCP server stdout")?;
-
-    let mut writer = tokio::io::BufWriter::new(stdin);
-    let mut lines = BufReader::new(stdout).lines();
-
-    // 1. Send initialize request (JSON-RPC id=1)
-    let init_req = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"elves","version":"1.0.0"}}}"#;
-    writer
-        .write_all(format!("{init_req}\n").as_bytes())
-        .await
-        .map_err(|e| format!("Failed to send initialize: {e}"))?;
-    writer.flush().await.map_err(|e| format!("Failed to flush: {e}"))?;
-
-    // 2. Read response for id=1, skipping any notifications
-    let init_resp = read_response_for_id(&mut lines, 1).await?;
-    if init_resp.get("error").is_some() {
-        let _ = child.kill().await;
-        return Err(format!("MCP initialize error: {}", init_resp["error"]));
-    }
-
-    // 3. Send initialized notification (MCP protocol requires this before further requests)
-    let notif = r#"{"jsonrpc":"2.0","method":"notifications/initialized","params":{}}"#;
-    writer
-        .write_all(format!("{notif}\n").as_bytes())
-        .await
-        .map_err(|e| format!("Failed to send initialized notification: {e}"))?;
-    writer.flush().await.map_err(|e| format!("Failed to flush: {e}"))?;
+/// Connect to the server over its configured transport, run the initialize handshake,
+/// then run `op` against the live client. The connection is closed afterwards — stdio
+/// servers are killed, HTTP sessions are ended — and the whole exchange is bounded by
+/// `timeout_secs`.
+async fn with_mcp_client<T, F, Fut>(
+    db: &State<'_, DbState>,
+    secrets: &State<'_, SecretsState>,
+    id: &str,
+    timeout_secs: u64,
+    op: F,
+) -> Result<T, String>
+where
+    F: FnOnce(McpClient) -> Fut,
+    Fut: Future<Output = (McpClient, Result<T, String>)>,
+{
+    let endpoint = load_endpoint(db, secrets, id)?;
+
+    let session = async move {
+        let mut client = McpClient::connect(&endpoint).await?;
+        if let Err(e) = client.initialize().await {
+            client.close().await;
+            return Err(e);
+        }
+        let (client, result) = op(client).await;
+        client.close().await;
+        result
+    };
 
-    // 4. Send tools/list request (JSON-RPC id=2)
-    let tools_req = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list","params":{}}"#;
-    writer
-        .write_all(format!("{tools_req}\n").as_bytes())
+    // Stdio children are spawned with kill_on_drop, so a timeout also tears them down
+    tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), session)
         .await
-        .map_err(|e| format!("Failed to send tools/list: {e}"))?;
-    writer.flush().await.map_err(|e| format!("Failed to flush: {e}"))?;
-
-    // 5. Read response for id=2
-    let tools_resp = read_response_for_id(&mut lines, 2).await?;
-    let _ = child.kill().await;
+        .map_err(|_| format!("MCP server timed out after {timeout_secs} seconds"))?
+}
 
-    if tools_resp.get("error").is_some() {
-        return Err(format!("MCP tools/list error: {}", tools_resp["error"]));
-    }
+/// Connect to an MCP server, perform the JSON-RPC initialize + tools/list handshake,
+/// and return the available tools. Times out after 10 seconds.
+///
+/// The connection is closed after tools are retrieved (or on error/timeout).
+#[tauri::command]
+pub async fn list_mcp_tools(
+    id: String,
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+) -> Result<Vec<McpTool>, String> {
+    with_mcp_client(&db, &secrets, &id, MCP_QUERY_TIMEOUT_SECS, |mut client| async move {
+        let result = client.list_tools().await;
+        (client, result)
+    })
+    .await
+}
 
-    // 6. Parse result.tools array into Vec<McpTool>
-    let tools = tools_resp
-        .get("result")
-        .and_then(|r| r.get("tools"))
-        .and_then(|t| t.as_array())
-        .ok_or("tools/list response missing result.tools array")?
-        .iter()
-        .filter_map(|tool| {
-            let name = tool.get("name")?.as_str()?.to_string();
-            let description = tool
-                .get("description")
-                .and_then(|d| d.as_str())
-                .map(|s| s.to_string());
-            Some(McpTool { name, description })
-        })
-        .collect();
+/// Connect to an MCP server and collect everything it offers: capabilities from the
+/// `initialize` response, plus tools, resources, resource templates, and prompts
+/// for each capability the server advertised.
+#[tauri::command]
+pub async fn inspect_mcp_server(
+    id: String,
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+) -> Result<McpServerInventory, String> {
+    with_mcp_client(&db, &secrets, &id, MCP_QUERY_TIMEOUT_SECS, |mut client| async move {
+        let result = client.inventory().await;
+        (client, result)
+    })
+    .await
+}
 
-    Ok(tools)
+/// Read a single resource from an MCP server by URI (`resources/read`).
+#[tauri::command]
+pub async fn read_mcp_resource(
+    id: String,
+    uri: String,
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+) -> Result<Vec<McpResourceContent>, String> {
+    with_mcp_client(&db, &secrets, &id, MCP_QUERY_TIMEOUT_SECS, |mut client| async move {
+        let result = client.read_resource(&uri).await;
+        (client, result)
+    })
+    .await
 }
 
-/// Read JSON-RPC lines from the server stdout, discarding notifications, until we
-/// find a response matching `expected_id`. Returns an error if the stream closes.
-async fn read_response_for_id(
-    lines: &mut tokio::io::Lines<BufReader<tokio::process::ChildStdout>>,
-    expected_id: u64,
-) -> Result<serde_json::Value, String> {
-    loop {
-        let line = lines
-            .next_line()
-            .await
-            .map_err(|e| format!("Read error: {e}"))?
-            .ok_or("MCP server closed stdout unexpectedly")?;
-
-        let json: serde_json::Value = match serde_json::from_str(&line) {
-            Ok(v) => v,
-            Err(_) => continue, // Skip non-JSON lines (e.g., debug output)
-        };
+/// Render a prompt from an MCP server with the given arguments (`prompts/get`).
+#[tauri::command]
+pub async fn get_mcp_prompt(
+    id: String,
+    name: String,
+    arguments: Option<HashMap<String, String>>,
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+) -> Result<McpPromptResult, String> {
+    let arguments = arguments.unwrap_or_default();
+    with_mcp_client(&db, &secrets, &id, MCP_QUERY_TIMEOUT_SECS, |mut client| async move {
+        let result = client.get_prompt(&name, &arguments).await;
+        (client, result)
+    })
+    .await
+}
 
-        if json.get("id").and_then(|v| v.as_u64()) == Some(expected_id) {
-            return Ok(json);
-        }
-        // Otherwise it's a notification or a response for a different id — skip it
-    }
+/// Call a tool on an MCP server (`tools/call`) with JSON arguments.
+///
+/// Tool failures come back as a result with `isError` set so the UI can show the
+/// server's message; only connection and protocol failures are returned as errors.
+#[tauri::command]
+pub async fn call_mcp_tool(
+    id: String,
+    name: String,
+    arguments: Option<serde_json::Value>,
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+) -> Result<McpToolCallResult, String> {
+    let arguments = arguments.unwrap_or_else(|| serde_json::json!({}));
+    with_mcp_client(&db, &secrets, &id, MCP_TOOL_CALL_TIMEOUT_SECS, |mut client| async move {
+        let result = client.call_tool(&name, arguments).await;
+        (client, result)
+    })
+    .await
 }
@@ -3,7 +3,7 @@
 use crate::agents::context_builder;
 use crate::agents::memory_extractor::{self, ExtractionResult};
 use crate::db;
-use crate::db::memory::{MemoryQuery, MemoryRow};
+use crate::db::memory::{MemoryQuery, MemoryRow, PinRequestRow};
 use super::projects::DbState;
 use tauri::State;
 
@@ -99,6 +99,30 @@ pub fn unpin_memory(
         .map_err(|e| format!("Database error: {e}"))
 }
 
+/// List pin requests agents made through the ELVES MCP server that await a decision.
+#[tauri::command]
+pub fn list_memory_pin_requests(
+    db: State<'_, DbState>,
+    project_id: Option<String>,
+) -> Result<Vec<PinRequestRow>, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::memory::list_pending_pin_requests(&conn, project_id.as_deref())
+        .map_err(|e| format!("Database error: {e}"))
+}
+
+/// Approve (pins the memory) or dismiss an agent's pin request.
+/// Returns false if the request was not found or already resolved.
+#[tauri::command]
+pub fn resolve_memory_pin_request(
+    db: State<'_, DbState>,
+    id: i64,
+    approve: bool,
+) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::memory::resolve_pin_request(&conn, id, approve)
+        .map_err(|e| format!("Database error: {e}"))
+}
+
 /// Full-text search over memories using FTS5.
 ///
 /// Searches content, category, and tags. Results ranked by FTS5 bm25 relevance.
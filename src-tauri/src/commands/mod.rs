@@ -1,19 +1,29 @@
 // Tauri command handlers — expose Rust backend functionality to the frontend via IPC.
 
 pub mod agents;
+pub mod budget;
+pub mod codex_insights;
 pub mod export;
+pub mod external_sessions;
 pub mod filesystem;
 pub mod git;
 pub mod insights;
+pub mod maintenance;
 pub mod mcp;
 pub mod memory;
 pub mod projects;
+pub mod queue;
 pub mod pty;
+pub mod recovery;
 pub mod registry;
 pub mod search;
+pub mod secrets;
 pub mod sessions;
+pub mod settings;
 pub mod skills;
 pub mod tasks;
 pub mod templates;
+pub mod transcripts;
 pub mod updates;
+pub mod watchdog;
 pub mod workspace;
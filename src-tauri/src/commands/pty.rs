@@ -2,16 +2,24 @@
 //
 // Bridges xterm.js on the frontend to real PTY processes via Tauri IPC.
 // Each PTY instance gets a unique ID. Output is streamed via Tauri events
-// (`pty:data:{id}` for stdout, `pty:exit:{id}` for process exit).
+// (`pty:data:{id}` for stdout, `pty:exit:{id}` for process exit). Recent output is kept
+// in a scrollback buffer (see agents/scrollback.rs), so reattached views get their
+// history back. Agent PTYs can also be recorded as asciicast files (see agents/recording.rs).
 
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::sync::Mutex;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use tauri::ipc::Channel;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::agents::process::{interrupt_then_terminate, StopTimeouts};
+use crate::agents::recording::CastRecorder;
+use crate::agents::run_state;
 use crate::agents::runtime::{ensure_full_path, resolve_binary};
+use crate::agents::scrollback::{Scrollback, ScrollbackMatch};
+use crate::agents::watchdog::Watchdog;
 
 /// Resolve a command name to an absolute path, falling back to the bare name.
 /// Uses `runtime::resolve_binary` for consistent resolution across the codebase.
@@ -35,26 +43,41 @@ fn resolve_command(command: &str) -> String {
 /// Coalescing pause to batch rapid PTY writes. Half a 60fps frame.
 const COALESCE_DELAY_MS: u64 = 8;
 
+/// A PTY's recording, shared by its reader thread (output) and `resize_pty` (resizes).
+type SharedRecorder = Arc<Mutex<CastRecorder>>;
+
+/// A PTY's scrollback, filled by its reader thread and read by the scrollback commands.
+type SharedScrollback = Arc<Mutex<Scrollback>>;
+
+/// Most scrollback search matches returned when the caller sets no limit.
+const DEFAULT_SEARCH_LIMIT: usize = 200;
+
 /// Holds the writable master handle and child process for a single PTY session.
 struct PtyInstance {
     writer: Box<dyn Write + Send>,
     master: Box<dyn portable_pty::MasterPty + Send>,
     child: Box<dyn portable_pty::Child + Send + Sync>,
+    recorder: Option<SharedRecorder>,
+    scrollback: SharedScrollback,
+    /// Set once `stop` has begun signalling the process.
+    stopping: bool,
 }
 
-/// Shared state tracking all active PTY instances by their unique ID.
-pub struct PtyManager(Mutex<HashMap<String, PtyInstance>>);
+/// Shared state tracking all active PTY instances by their unique ID. Shared with the
+/// background threads that stop PTYs. A PTY is removed once its output ends or it is stopped.
+pub struct PtyManager(Arc<Mutex<HashMap<String, PtyInstance>>>);
 
 impl PtyManager {
     pub fn new() -> Self {
-        Self(Mutex::new(HashMap::new()))
+        Self(Arc::new(Mutex::new(HashMap::new())))
     }
 
     /// Spawn a new PTY process. Starts a background reader thread that emits
     /// `pty:data:{id}` and `pty:exit:{id}` events. Returns the unique pty_id.
     ///
     /// Callable both from Tauri commands and directly from other Rust code
-    /// via an AppHandle reference.
+    /// via an AppHandle reference. With `recording`, output and resizes are also written
+    /// to that asciicast file; the PTY still runs if the file can't be created.
     pub fn spawn_with_app(
         &self,
         command: &str,
@@ -62,6 +85,7 @@ impl PtyManager {
         cwd: &str,
         app: &AppHandle,
         output_channel: Option<Channel<String>>,
+        recording: Option<&Path>,
     ) -> Result<String, String> {
         // Ensure full PATH is available (macOS .app bundles get minimal PATH)
         ensure_full_path();
@@ -87,6 +111,10 @@ impl PtyManager {
         cmd.env_remove("CLAUDECODE");
         cmd.env_remove("CLAUDE_CODE_ENTRYPOINT");
         cmd.env_remove("CLAUDE_CODE_EXPERIMENTAL_AGENT_TEAMS");
+        // Keep values from the secrets store out of interactive agents' environment
+        for name in crate::secrets::redact::inherited_secret_vars(crate::secrets::redact::AGENT_CREDENTIAL_VARS) {
+            cmd.env_remove(name);
+        }
         // Ensure TERM is set for proper TUI rendering in the PTY
         if std::env::var("TERM").is_err() {
             cmd.env("TERM", "xterm-256color");
@@ -110,10 +138,23 @@ impl PtyManager {
             .try_clone_reader()
             .map_err(|e| format!("Failed to clone PTY reader: {e}"))?;
 
+        let recorder = recording.and_then(|path| match CastRecorder::create(path, 80, 24) {
+            Ok(recorder) => Some(Arc::new(Mutex::new(recorder))),
+            Err(e) => {
+                log::warn!("Could not record PTY to {}: {e}", path.display());
+                None
+            }
+        });
+
+        let scrollback = Arc::new(Mutex::new(Scrollback::new(super::settings::scrollback_bytes())));
+
         let instance = PtyInstance {
             writer,
             master: pair.master,
             child,
+            recorder: recorder.clone(),
+            scrollback: scrollback.clone(),
+            stopping: false,
         };
         self.0
             .lock()
@@ -123,12 +164,89 @@ impl PtyManager {
         let pty_id_clone = pty_id.clone();
         let app_clone = app.clone();
         std::thread::spawn(move || {
-            pty_reader_loop(reader, &app_clone, &pty_id_clone, output_channel);
+            pty_reader_loop(reader, &app_clone, &pty_id_clone, output_channel, recorder, scrollback);
         });
 
         log::info!("Spawned PTY {pty_id}: {command} {}", args.join(" "));
         Ok(pty_id)
     }
+
+    /// Whether a PTY's process is still running. False once it has exited or been stopped.
+    pub fn is_running(&self, pty_id: &str) -> bool {
+        let Ok(mut map) = self.0.lock() else {
+            return false;
+        };
+        map.get_mut(pty_id)
+            .is_some_and(|instance| matches!(instance.child.try_wait(), Ok(None)))
+    }
+
+    /// A PTY's scrollback. Errors if no PTY has this ID.
+    fn scrollback(&self, pty_id: &str) -> Result<SharedScrollback, String> {
+        self.0
+            .lock()
+            .map_err(|e| format!("Failed to lock PTY state: {e}"))?
+            .get(pty_id)
+            .map(|instance| instance.scrollback.clone())
+            .ok_or_else(|| format!("PTY {pty_id} not found"))
+    }
+
+    /// Forget a PTY whose output has ended and reap its process, releasing its scrollback.
+    /// PTYs being stopped are left to `stop`, which removes them once the process is gone.
+    fn remove_exited(&self, pty_id: &str) {
+        let instance = match self.0.lock() {
+            Ok(mut map) if map.get(pty_id).is_some_and(|instance| !instance.stopping) => map.remove(pty_id),
+            _ => None,
+        };
+        if let Some(mut instance) = instance {
+            let _ = instance.child.wait();
+            log::info!("PTY {pty_id} exited");
+        }
+    }
+
+    /// Stop a PTY process gracefully in the background and remove it from the manager:
+    /// SIGINT, SIGTERM, then SIGKILL on its process group (see `interrupt_then_terminate`).
+    /// The PTY stays registered, marked as stopping, until the process is gone, so its
+    /// output, scrollback, and recording keep up with the agent's final writes.
+    /// Returns false if no PTY has this ID.
+    pub fn stop(&self, pty_id: &str, timeouts: StopTimeouts) -> Result<bool, String> {
+        let pid = {
+            let mut map = self.0.lock().map_err(|e| format!("Failed to lock PTY state: {e}"))?;
+            let Some(instance) = map.get_mut(pty_id) else {
+                return Ok(false);
+            };
+            if instance.stopping {
+                return Ok(true);
+            }
+            instance.stopping = true;
+            instance.child.process_id()
+        };
+
+        let instances = Arc::clone(&self.0);
+        let pty_id = pty_id.to_string();
+        std::thread::spawn(move || {
+            let running = |_: &mut Option<u32>| {
+                instances.lock().is_ok_and(|mut map| {
+                    map.get_mut(&pty_id)
+                        .is_some_and(|instance| matches!(instance.child.try_wait(), Ok(None)))
+                })
+            };
+            let survivors = interrupt_then_terminate(vec![pid], timeouts, |pid| *pid, running);
+            let instance = instances.lock().ok().and_then(|mut map| map.remove(&pty_id));
+            if let Some(mut instance) = instance {
+                if !survivors.is_empty() {
+                    // Reach the agent's subprocesses and tool shells too, not just the child
+                    if let Some(pid) = pid {
+                        run_state::signal_process_group(pid, "KILL");
+                    }
+                    // Kill the child process; ignore errors if already exited
+                    let _ = instance.child.kill();
+                }
+                // Dropping the instance closes the PTY, so the reader thread sees EOF
+            }
+            log::info!("Killed PTY {pty_id}");
+        });
+        Ok(true)
+    }
 }
 
 /// Shared reader loop for PTY output with coalescing.
@@ -145,11 +263,17 @@ impl PtyManager {
 ///
 /// UTF-8 safety: tracks incomplete multi-byte sequences at chunk boundaries to
 /// prevent corruption from `from_utf8_lossy` replacing partial codepoints.
+///
+/// Everything sent to the frontend is also kept in the scrollback and, with a recorder,
+/// recorded. Recording stops at the first write error, so a full disk doesn't interrupt
+/// the terminal.
 fn pty_reader_loop(
     mut reader: Box<dyn Read + Send>,
     app: &AppHandle,
     pty_id: &str,
     channel: Option<Channel<String>>,
+    mut recorder: Option<SharedRecorder>,
+    scrollback: SharedScrollback,
 ) {
     let mut buf = [0u8; 8192];
     // Tracks incomplete UTF-8 trailing bytes across iterations
@@ -161,6 +285,8 @@ fn pty_reader_loop(
         match reader.read(&mut buf) {
             Ok(0) => break,
             Ok(n) => {
+                app.state::<Watchdog>().record_pty_output(pty_id);
+
                 // Prepend any pending incomplete UTF-8 bytes from the previous chunk
                 let chunk = if pending.is_empty() {
                     &buf[..n]
@@ -173,6 +299,10 @@ fn pty_reader_loop(
                 let valid_len = find_utf8_boundary(chunk);
                 if valid_len > 0 {
                     let data = String::from_utf8_lossy(&chunk[..valid_len]).to_string();
+                    record_output(&mut recorder, pty_id, &data);
+                    if let Ok(mut scrollback) = scrollback.lock() {
+                        scrollback.push(&data);
+                    }
                     if let Some(ref ch) = channel {
                         let _ = ch.send(data);
                     } else {
@@ -201,15 +331,41 @@ fn pty_reader_loop(
     // Flush any remaining pending bytes (lossy is fine at EOF)
     if !pending.is_empty() {
         let data = String::from_utf8_lossy(&pending).to_string();
+        record_output(&mut recorder, pty_id, &data);
+        if let Ok(mut scrollback) = scrollback.lock() {
+            scrollback.push(&data);
+        }
         if let Some(ref ch) = channel {
             let _ = ch.send(data);
         } else {
             let _ = app.emit(&event_name, data);
         }
     }
+    if let Some(Ok(mut recorder)) = recorder.as_ref().map(|r| r.lock()) {
+        if let Err(e) = recorder.flush() {
+            log::warn!("Could not save the recording of PTY {pty_id}: {e}");
+        }
+    }
+    app.state::<Watchdog>().unwatch_pty(pty_id);
+    app.state::<PtyManager>().remove_exited(pty_id);
     let _ = app.emit(&exit_event, 0i32);
 }
 
+/// Record PTY output, dropping the recorder if the write fails.
+fn record_output(recorder: &mut Option<SharedRecorder>, pty_id: &str, data: &str) {
+    let Some(shared) = recorder else {
+        return;
+    };
+    let written = match shared.lock() {
+        Ok(mut r) => r.output(data).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = written {
+        log::warn!("Stopped recording PTY {pty_id}: {e}");
+        *recorder = None;
+    }
+}
+
 /// Find the last valid UTF-8 boundary in a byte slice.
 /// Returns the number of bytes that form complete UTF-8 sequences.
 /// Any trailing incomplete multi-byte sequence is excluded.
@@ -274,7 +430,7 @@ pub fn spawn_pty(
     _app: AppHandle,
     state: State<'_, PtyManager>,
 ) -> Result<String, String> {
-    state.spawn_with_app(&command, &args, &cwd, &_app, Some(on_output))
+    state.spawn_with_app(&command, &args, &cwd, &_app, Some(on_output), None)
 }
 
 /// Write data to a PTY's stdin.
@@ -329,6 +485,12 @@ pub fn resize_pty(
         })
         .map_err(|e| format!("Failed to resize PTY: {e}"))?;
 
+    if let Some(recorder) = &instance.recorder {
+        if let Ok(mut recorder) = recorder.lock() {
+            let _ = recorder.resize(cols, rows);
+        }
+    }
+
     Ok(())
 }
 
@@ -343,21 +505,42 @@ pub fn check_pty_exists(pty_id: String, state: State<'_, PtyManager>) -> bool {
         .unwrap_or(false)
 }
 
-/// Kill a PTY process and remove it from the manager.
+/// Read a PTY's recent output, escape sequences included, so a reloaded or reopened
+/// terminal view can write it to xterm before streaming new output.
 #[tauri::command]
-pub fn kill_pty(pty_id: String, state: State<'_, PtyManager>) -> Result<(), String> {
-    let mut map = state
-        .0
+pub fn read_pty_scrollback(pty_id: String, state: State<'_, PtyManager>) -> Result<String, String> {
+    let scrollback = state.scrollback(&pty_id)?;
+    let contents = scrollback
         .lock()
-        .map_err(|e| format!("Failed to lock PTY state: {e}"))?;
+        .map_err(|e| format!("Failed to lock scrollback: {e}"))?
+        .contents();
+    Ok(contents)
+}
 
-    if let Some(mut instance) = map.remove(&pty_id) {
-        // Kill the child process; ignore errors if already exited
-        let _ = instance.child.kill();
-        // Drop the writer to close stdin, causing the reader thread to detect EOF
-        drop(instance.writer);
-        log::info!("Killed PTY {pty_id}");
-    }
+/// Search a PTY's recent output for lines containing `query`, ignoring case and escape
+/// sequences. Returns the most recent `limit` matches (default 200), oldest first.
+#[tauri::command]
+pub fn search_pty_scrollback(
+    pty_id: String,
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, PtyManager>,
+) -> Result<Vec<ScrollbackMatch>, String> {
+    let scrollback = state.scrollback(&pty_id)?;
+    let matches = scrollback
+        .lock()
+        .map_err(|e| format!("Failed to lock scrollback: {e}"))?
+        .search(&query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT));
+    Ok(matches)
+}
 
+/// Stop a PTY process and remove it from the manager.
+///
+/// Escalates SIGINT → SIGTERM → SIGKILL on the PTY's process group in the background,
+/// with the same timeouts as `stop_task`, so an agent in the terminal can save its
+/// transcript. The PTY stays open, and its output keeps streaming, until the process exits.
+#[tauri::command]
+pub fn kill_pty(pty_id: String, state: State<'_, PtyManager>) -> Result<(), String> {
+    state.stop(&pty_id, super::settings::stop_timeouts())?;
     Ok(())
 }
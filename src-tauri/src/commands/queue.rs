@@ -0,0 +1,343 @@
+// Task queue commands — enqueue, reorder, and cancel tasks, and the dispatcher that starts
+// queued tasks, in print mode or in a PTY, as the concurrency caps allow.
+//
+// The app-wide cap comes from `~/.elves/settings.json` and the per-project cap from each
+// project's `.elves/config.json`. Every active session counts toward both, including ones
+// started directly rather than from the queue; direct PTY starts are refused at the caps.
+// Tasks of a project over its spending cap stay queued until the budget allows them.
+//
+// Events:
+// - `queue:updated` — the waiting tasks changed
+// - `queue:task_started` — a queued task started as a session
+// - `queue:task_failed` — a queued task could not be started
+
+use crate::agents::analyzer::TaskPlan;
+use crate::commands::budget;
+use crate::commands::projects::DbState;
+use crate::commands::settings;
+use crate::commands::tasks;
+use crate::db;
+use crate::db::queue::{NewQueuedTask, QueuedTask};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// How often the dispatcher checks for freed-up capacity.
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Serializes dispatching, so concurrent calls can't start more tasks than the caps
+/// allow. Managed as Tauri app state.
+pub struct TaskQueue {
+    dispatching: Mutex<()>,
+}
+
+impl TaskQueue {
+    pub fn new() -> Self {
+        Self {
+            dispatching: Mutex::new(()),
+        }
+    }
+}
+
+/// Add a task to the queue. It starts as soon as the concurrency caps allow, possibly
+/// right away. Tasks with a `plan` start as team tasks; with `pty`, a solo task starts
+/// in an interactive PTY instead of print mode.
+///
+/// Higher `priority` tasks are queued ahead of lower ones (default 0).
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub fn enqueue_task(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    project_id: String,
+    task: String,
+    runtime: String,
+    working_dir: Option<String>,
+    options: Option<String>,
+    worktree_slug: Option<String>,
+    plan: Option<TaskPlan>,
+    priority: Option<i32>,
+    pty: Option<bool>,
+) -> Result<QueuedTask, String> {
+    let pty = pty.unwrap_or(false);
+    if pty && plan.is_some() {
+        return Err("Team tasks can only be queued in print mode".to_string());
+    }
+    let plan = plan
+        .map(|plan| serde_json::to_string(&plan))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize plan: {e}"))?;
+    let queued = {
+        let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+        let new = NewQueuedTask {
+            project_id: &project_id,
+            task: &task,
+            runtime: &runtime,
+            working_dir: working_dir.as_deref(),
+            worktree_slug: worktree_slug.as_deref(),
+            options: options.as_deref(),
+            plan: plan.as_deref(),
+            pty,
+            priority: priority.unwrap_or(0),
+        };
+        db::queue::enqueue(&conn, &uuid::Uuid::new_v4().to_string(), &new)
+            .map_err(|e| format!("Database error: {e}"))?
+    };
+    log::info!("[queue] Enqueued task {} for project {project_id}", queued.id);
+
+    emit_queue_updated(&app);
+    dispatch_in_background(&app);
+    Ok(queued)
+}
+
+/// List the tasks waiting to start, in start order. Optionally limited to one project.
+#[tauri::command]
+pub fn list_task_queue(
+    db: State<'_, DbState>,
+    project_id: Option<String>,
+) -> Result<Vec<QueuedTask>, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::queue::list_queue(&conn, project_id.as_deref()).map_err(|e| format!("Database error: {e}"))
+}
+
+/// Move the given waiting tasks to the front of the queue, in the given order.
+#[tauri::command]
+pub fn reorder_queue(app: AppHandle, db: State<'_, DbState>, ids: Vec<String>) -> Result<(), String> {
+    {
+        let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+        db::queue::reorder_queue(&conn, &ids).map_err(|e| format!("Database error: {e}"))?;
+    }
+    emit_queue_updated(&app);
+    dispatch_in_background(&app);
+    Ok(())
+}
+
+/// Remove a waiting task from the queue. Returns false if it already started or was cancelled.
+#[tauri::command]
+pub fn cancel_queued(app: AppHandle, db: State<'_, DbState>, id: String) -> Result<bool, String> {
+    let cancelled = {
+        let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+        db::queue::cancel_queued(&conn, &id).map_err(|e| format!("Database error: {e}"))?
+    };
+    if cancelled {
+        emit_queue_updated(&app);
+    }
+    Ok(cancelled)
+}
+
+/// Start the dispatcher thread. Runs for the lifetime of the app, starting queued tasks
+/// as running sessions finish.
+pub fn spawn_queue_dispatcher(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        dispatch(&app);
+        std::thread::sleep(DISPATCH_INTERVAL);
+    });
+}
+
+/// Dispatch on a background thread, so commands never spawn agents on the main thread
+/// or wait there for a dispatch already under way.
+fn dispatch_in_background(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || dispatch(&app));
+}
+
+/// Start every queued task the concurrency caps allow right now.
+///
+/// Tasks of a project over a spending cap stay queued, noting why, and don't take a
+/// slot from the tasks behind them.
+fn dispatch(app: &AppHandle) {
+    let task_queue = app.state::<TaskQueue>();
+    let Ok(_dispatching) = task_queue.dispatching.lock() else {
+        return;
+    };
+
+    let waiting = {
+        let db_state = app.state::<DbState>();
+        let Ok(conn) = db_state.0.lock() else {
+            return;
+        };
+        match db::queue::list_queue(&conn, None) {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                log::warn!("[queue] Failed to read the task queue: {e}");
+                return;
+            }
+        }
+    };
+    if waiting.is_empty() {
+        return;
+    }
+
+    // Budget checks read the database themselves, so run them before locking it
+    let mut over_budget: HashMap<String, String> = HashMap::new();
+    for queued in &waiting {
+        if !over_budget.contains_key(&queued.project_id) {
+            if let Err(reason) = budget::check_can_start(app, &queued.project_id) {
+                over_budget.insert(queued.project_id.clone(), reason);
+            }
+        }
+    }
+
+    let mut changed = false;
+    let startable = {
+        let db_state = app.state::<DbState>();
+        let Ok(conn) = db_state.0.lock() else {
+            return;
+        };
+        for queued in &waiting {
+            let reason = over_budget.get(&queued.project_id).map(String::as_str);
+            match db::queue::set_waiting_reason(&conn, &queued.id, reason) {
+                Ok(noted) => changed |= noted,
+                Err(e) => log::warn!("[queue] Failed to note why task {} waits: {e}", queued.id),
+            }
+        }
+
+        let global_cap = settings::max_concurrent_sessions();
+        let mut project_caps: HashMap<String, Option<u32>> = HashMap::new();
+        let result = db::queue::startable_tasks(&conn, global_cap, |project_id| {
+            if over_budget.contains_key(project_id) {
+                return Some(0);
+            }
+            *project_caps
+                .entry(project_id.to_string())
+                .or_insert_with(|| project_cap(&conn, project_id))
+        });
+        match result {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                log::warn!("[queue] Failed to read the task queue: {e}");
+                return;
+            }
+        }
+    };
+
+    for queued in startable {
+        changed = true;
+        let started = start_queued(app, &queued);
+        let db_state = app.state::<DbState>();
+        let Ok(conn) = db_state.0.lock() else {
+            return;
+        };
+        match started {
+            Ok((session_id, pty_id)) => {
+                log::info!("[queue] Started queued task {} as session {session_id}", queued.id);
+                if let Err(e) = db::queue::mark_started(&conn, &queued.id, &session_id) {
+                    log::warn!("[queue] Failed to mark task {} started: {e}", queued.id);
+                }
+                let _ = app.emit(
+                    "queue:task_started",
+                    serde_json::json!({
+                        "queueId": &queued.id,
+                        "sessionId": &session_id,
+                        "projectId": &queued.project_id,
+                        "task": &queued.task,
+                        "runtime": &queued.runtime,
+                        "plan": &queued.plan,
+                        "ptyId": &pty_id,
+                    }),
+                );
+            }
+            Err(error) => {
+                log::warn!("[queue] Failed to start queued task {}: {error}", queued.id);
+                if let Err(e) = db::queue::mark_failed(&conn, &queued.id, &error) {
+                    log::warn!("[queue] Failed to mark task {} failed: {e}", queued.id);
+                }
+                let _ = app.emit(
+                    "queue:task_failed",
+                    serde_json::json!({
+                        "queueId": &queued.id,
+                        "task": &queued.task,
+                        "error": &error,
+                    }),
+                );
+            }
+        }
+    }
+    if changed {
+        emit_queue_updated(app);
+    }
+}
+
+/// Launch a queued task. Returns the new session ID, and the PTY ID for PTY tasks.
+fn start_queued(app: &AppHandle, queued: &QueuedTask) -> Result<(String, Option<String>), String> {
+    let db_state = app.state::<DbState>();
+    let queued = queued.clone();
+    if queued.pty {
+        let started = tasks::launch_task_pty(
+            app,
+            &db_state,
+            queued.project_id,
+            queued.task,
+            queued.runtime,
+            queued.working_dir,
+            queued.options,
+            queued.worktree_slug,
+            None,
+        )?;
+        return Ok((started.session_id, Some(started.pty_id)));
+    }
+    let session_id = match queued.plan {
+        Some(ref plan_json) => {
+            let plan: TaskPlan = serde_json::from_str(plan_json).map_err(|e| format!("Invalid plan: {e}"))?;
+            tasks::launch_team_task(
+                app,
+                &db_state,
+                queued.project_id,
+                queued.task,
+                plan,
+                queued.options,
+                queued.working_dir,
+                queued.worktree_slug,
+            )
+        }
+        None => tasks::launch_task(
+            app,
+            &db_state,
+            queued.project_id,
+            queued.task,
+            queued.runtime,
+            queued.options,
+            queued.working_dir,
+            queued.worktree_slug,
+        ),
+    }?;
+    Ok((session_id, None))
+}
+
+/// Refuse to start a session directly once the concurrency caps are reached, so direct
+/// starts can't run past them. The error suggests queueing the task instead.
+pub(crate) fn check_capacity(app: &AppHandle, project_id: &str) -> Result<(), String> {
+    let db_state = app.state::<DbState>();
+    let conn = db_state.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let global_cap = settings::max_concurrent_sessions();
+    let reached = db::queue::cap_reached(&conn, project_id, global_cap, project_cap(&conn, project_id))
+        .map_err(|e| format!("Database error: {e}"))?;
+    match reached {
+        Some(reason) => Err(format!(
+            "Concurrency limit reached: {reason}. Queue the task to start it when a session finishes"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A project's own concurrency cap, from its `.elves/config.json`.
+fn project_cap(conn: &rusqlite::Connection, project_id: &str) -> Option<u32> {
+    let project = db::projects::get_project(conn, project_id).ok().flatten()?;
+    crate::project::config::read_project_config(&project.path)
+        .ok()?
+        .max_concurrent_sessions
+}
+
+fn emit_queue_updated(app: &AppHandle) {
+    let db_state = app.state::<DbState>();
+    let Ok(conn) = db_state.0.lock() else {
+        return;
+    };
+    match db::queue::list_queue(&conn, None) {
+        Ok(queue) => {
+            let _ = app.emit("queue:updated", queue);
+        }
+        Err(e) => log::warn!("[queue] Failed to list the task queue: {e}"),
+    }
+}
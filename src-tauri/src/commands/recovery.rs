@@ -0,0 +1,75 @@
+// Startup recovery commands — list sessions interrupted by an app restart so they can be
+// resumed in a terminal or dismissed.
+
+use crate::db;
+use crate::db::sessions::SessionRow;
+use super::projects::DbState;
+use super::workspace::worktree_dir;
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+/// Summary stored on interrupted sessions the user chose not to resume.
+const DISMISSED_SUMMARY: &str = "Session interrupted (app restarted)";
+
+/// A session left running when the app last exited, with what is needed to resume it.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InterruptedSession {
+    pub session: SessionRow,
+    pub project_name: String,
+    /// Directory the agent ran in: the session's worktree, or the project root.
+    pub working_dir: String,
+    /// Whether `working_dir` still exists on disk.
+    pub workspace_exists: bool,
+    /// Whether the session can be resumed with `claude --resume`: it has a Claude session
+    /// ID, ran on Claude Code, and its workspace still exists.
+    pub resumable: bool,
+}
+
+/// List sessions interrupted by the last app exit, most recent first.
+#[tauri::command]
+pub fn list_interrupted_sessions(
+    db: State<'_, DbState>,
+) -> Result<Vec<InterruptedSession>, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let sessions = db::sessions::list_interrupted_sessions(&conn)
+        .map_err(|e| format!("Database error: {e}"))?;
+
+    let mut interrupted = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        let Some(project) = db::projects::get_project(&conn, &session.project_id)
+            .map_err(|e| format!("Database error: {e}"))?
+        else {
+            continue;
+        };
+        let working_dir = match &session.worktree_slug {
+            Some(slug) => worktree_dir(&project.path, slug),
+            None => project.path.clone(),
+        };
+        let workspace_exists = Path::new(&working_dir).is_dir();
+        let resumable = workspace_exists
+            && session.runtime != "codex"
+            && session.claude_session_id.is_some();
+        interrupted.push(InterruptedSession {
+            session,
+            project_name: project.name,
+            working_dir,
+            workspace_exists,
+            resumable,
+        });
+    }
+    Ok(interrupted)
+}
+
+/// Give up on an interrupted session: it is recorded as failed (killed) and no longer
+/// offered for resume.
+#[tauri::command]
+pub fn dismiss_interrupted_session(
+    db: State<'_, DbState>,
+    session_id: String,
+) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::sessions::fail_session(&conn, &session_id, "killed", Some(DISMISSED_SUMMARY))
+        .map_err(|e| format!("Database error: {e}"))
+}
@@ -0,0 +1,166 @@
+// Secrets Tauri commands — manage the encrypted secrets store.
+//
+// Secret values go in and never come back out over IPC: the frontend only sees
+// names and lock state. Values are read back solely to resolve `secret://`
+// references when an MCP server is launched.
+
+use crate::db;
+use crate::secrets::store::{is_credential_key, KeySource, SecretStore, SECRET_SCHEME};
+use super::projects::DbState;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::State;
+
+/// The secrets store, shared across commands.
+pub struct SecretsState(pub Mutex<SecretStore>);
+
+/// Lock state and contents (names only) of the secrets store.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretsStatus {
+    pub unlocked: bool,
+    /// "keyring" when the OS keyring holds the key, "passphrase" otherwise.
+    pub key_source: KeySource,
+    /// False until the first secret (or passphrase) is saved.
+    pub initialized: bool,
+    pub names: Vec<String>,
+}
+
+fn status_of(store: &SecretStore) -> SecretsStatus {
+    SecretsStatus {
+        unlocked: store.is_unlocked(),
+        key_source: store.key_source(),
+        initialized: store.exists(),
+        names: store.names(),
+    }
+}
+
+/// Report whether the store is unlocked and which secrets it holds.
+#[tauri::command]
+pub fn get_secrets_status(secrets: State<'_, SecretsState>) -> Result<SecretsStatus, String> {
+    let store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    Ok(status_of(&store))
+}
+
+/// Unlock a passphrase-protected store. On first use this sets the passphrase.
+#[tauri::command]
+pub fn unlock_secrets(
+    secrets: State<'_, SecretsState>,
+    passphrase: String,
+) -> Result<SecretsStatus, String> {
+    let mut store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    store.unlock_with_passphrase(&passphrase)?;
+    Ok(status_of(&store))
+}
+
+/// Drop decrypted values from memory until the store is unlocked again.
+#[tauri::command]
+pub fn lock_secrets(secrets: State<'_, SecretsState>) -> Result<SecretsStatus, String> {
+    let mut store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    store.lock();
+    Ok(status_of(&store))
+}
+
+/// Add or replace a secret. Reference it from configs as `secret://<name>`.
+#[tauri::command]
+pub fn set_secret(
+    secrets: State<'_, SecretsState>,
+    name: String,
+    value: String,
+) -> Result<(), String> {
+    let mut store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    store.set(&name, &value)
+}
+
+/// Delete a secret. Returns true if it existed.
+#[tauri::command]
+pub fn delete_secret(secrets: State<'_, SecretsState>, name: String) -> Result<bool, String> {
+    let mut store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    store.remove(&name)
+}
+
+/// Move plaintext MCP credentials in env values and header values into the secrets store.
+///
+/// By default only keys that look like credentials (`*_KEY`, `*_TOKEN`, `Authorization`,
+/// ...) are moved, so plain settings like `LOG_LEVEL` stay readable. Pass `keys` to move
+/// exactly those keys instead. Returns how many values were moved.
+#[tauri::command]
+pub fn migrate_mcp_secrets(
+    db: State<'_, DbState>,
+    secrets: State<'_, SecretsState>,
+    keys: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let mut store = secrets.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    if !store.is_unlocked() {
+        return Err("Unlock the secrets store first".to_string());
+    }
+
+    let servers = db::mcp::list_mcp_servers(&conn).map_err(|e| format!("Database error: {e}"))?;
+    let mut moved = 0usize;
+
+    for server in servers {
+        let is_stdio = server.transport == "stdio";
+        let source = if is_stdio { &server.env } else { &server.headers };
+        let mut values: BTreeMap<String, String> = serde_json::from_str(source).unwrap_or_default();
+
+        let count = match &keys {
+            Some(keys) => stash_values(&mut store, &server.name, &mut values, |key| keys.iter().any(|k| k == key))?,
+            None => stash_credentials(&mut store, &server.name, &mut values)?,
+        };
+        if count == 0 {
+            continue;
+        }
+        moved += count;
+
+        let rewritten = serde_json::to_string(&values).map_err(|e| format!("Serialize error: {e}"))?;
+        if is_stdio {
+            db::mcp::update_mcp_server_launch(&conn, &server.id, &server.command, &server.args, &rewritten)
+        } else {
+            let url = server.url.as_deref().unwrap_or_default();
+            db::mcp::update_mcp_server_remote(&conn, &server.id, &server.transport, url, &rewritten)
+        }
+        .map_err(|e| format!("Database error: {e}"))?;
+    }
+
+    Ok(moved)
+}
+
+/// Move literal credential values in `values` into the store as `mcp.<server>.<KEY>`
+/// and leave `secret://` references in their place. Returns how many were moved.
+pub(crate) fn stash_credentials(
+    store: &mut SecretStore,
+    server: &str,
+    values: &mut BTreeMap<String, String>,
+) -> Result<usize, String> {
+    stash_values(store, server, values, is_credential_key)
+}
+
+/// Move the literal values whose key passes `select`. Values that are already
+/// references or `${VAR}` expansions are left alone.
+fn stash_values(
+    store: &mut SecretStore,
+    server: &str,
+    values: &mut BTreeMap<String, String>,
+    select: impl Fn(&str) -> bool,
+) -> Result<usize, String> {
+    let mut moved = 0usize;
+    for (key, value) in values.iter_mut() {
+        if !select(key) || value.is_empty() || value.contains(SECRET_SCHEME) || value.contains("${") {
+            continue;
+        }
+        let name = format!("mcp.{}.{}", secret_name_part(server), secret_name_part(key));
+        store.set(&name, value)?;
+        *value = format!("{SECRET_SCHEME}{name}");
+        moved += 1;
+    }
+    Ok(moved)
+}
+
+/// Reduce a server name or env key to characters allowed in secret names.
+fn secret_name_part(raw: &str) -> String {
+    raw.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
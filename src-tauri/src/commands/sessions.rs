@@ -1,8 +1,11 @@
 // Session-related Tauri commands — CRUD operations for task execution sessions.
 
 use crate::db;
-use crate::db::events::EventRow;
-use crate::db::sessions::SessionRow;
+use crate::db::events::{EventPage, EventRow, EventStats};
+use crate::db::recordings::RecordingRow;
+use crate::db::search::{SessionSearchHit, SessionSearchQuery};
+use crate::db::sessions::{SessionAttempt, SessionRow};
+use crate::db::usage::SessionUsage;
 use super::projects::DbState;
 use tauri::State;
 
@@ -59,7 +62,7 @@ pub fn get_last_workspace_session(
         .map_err(|e| format!("Database error: {e}"))
 }
 
-/// List all events for a session, ordered chronologically.
+/// List all events for a session, in the order they were recorded.
 ///
 /// Used by the History tab to display session output when a session card is expanded.
 #[tauri::command]
@@ -72,6 +75,81 @@ pub fn list_session_events(
         .map_err(|e| format!("Database error: {e}"))
 }
 
+/// A session's token usage and cost per model, and per elf in team runs. The session's
+/// `tokensUsed` and `costEstimate` are the totals of these rows.
+#[tauri::command]
+pub fn get_session_usage(
+    db: State<'_, DbState>,
+    session_id: String,
+) -> Result<Vec<SessionUsage>, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::usage::list_session_usage(&conn, &session_id)
+        .map_err(|e| format!("Database error: {e}"))
+}
+
+/// A session's terminal recordings (asciicast files), one per recorded PTY run.
+#[tauri::command]
+pub fn list_session_recordings(
+    db: State<'_, DbState>,
+    session_id: String,
+) -> Result<Vec<RecordingRow>, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::recordings::list_recordings(&conn, &session_id)
+        .map_err(|e| format!("Database error: {e}"))
+}
+
+/// Page size used when the caller does not pass a limit.
+const DEFAULT_EVENT_PAGE_SIZE: i64 = 200;
+
+/// List one page of a session's events after the `after_id` cursor.
+///
+/// Pass the returned `nextAfterId` back as `after_id` to fetch the next page; it is null
+/// on the last page. `event_types` restricts the page to those event types.
+#[tauri::command]
+pub fn list_session_events_page(
+    db: State<'_, DbState>,
+    session_id: String,
+    after_id: Option<i64>,
+    limit: Option<i64>,
+    event_types: Option<Vec<String>>,
+) -> Result<EventPage, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::events::list_events_page(
+        &conn,
+        &session_id,
+        after_id,
+        limit.unwrap_or(DEFAULT_EVENT_PAGE_SIZE),
+        event_types.as_deref().unwrap_or_default(),
+    )
+    .map_err(|e| format!("Database error: {e}"))
+}
+
+/// Count a session's events, in total and per event type.
+#[tauri::command]
+pub fn get_session_event_stats(
+    db: State<'_, DbState>,
+    session_id: String,
+) -> Result<EventStats, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::events::event_stats(&conn, &session_id)
+        .map_err(|e| format!("Database error: {e}"))
+}
+
+/// Full-text search across session tasks, summaries, and event content.
+///
+/// `search` carries the query text plus optional project, runtime, status, and start-date
+/// filters. Returns ranked sessions with HTML-escaped, `<mark>`-highlighted snippets and
+/// the ids of matching events.
+#[tauri::command]
+pub fn search_sessions(
+    db: State<'_, DbState>,
+    search: SessionSearchQuery,
+) -> Result<Vec<SessionSearchHit>, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::search::search_sessions(&conn, &search)
+        .map_err(|e| format!("Database error: {e}"))
+}
+
 /// Mark a session as completed in the database. Sets `ended_at` to now.
 ///
 /// Called from the frontend when a PTY process exits, so the session status
@@ -105,3 +183,27 @@ pub fn update_claude_session_id(
     db::sessions::update_claude_session_id(&conn, &session_id, &claude_session_id)
         .map_err(|e| format!("Database error: {e}"))
 }
+
+/// Pin or unpin a session. Pinned sessions are exempt from event retention.
+#[tauri::command]
+pub fn set_session_pinned(
+    db: State<'_, DbState>,
+    session_id: String,
+    pinned: bool,
+) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::sessions::set_session_pinned(&conn, &session_id, pinned)
+        .map_err(|e| format!("Database error: {e}"))
+}
+
+/// List a session's run attempts, first first. Sessions gain attempts when a failed
+/// run is retried under the project's retry policy.
+#[tauri::command]
+pub fn list_session_attempts(
+    db: State<'_, DbState>,
+    session_id: String,
+) -> Result<Vec<SessionAttempt>, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    db::sessions::list_session_attempts(&conn, &session_id)
+        .map_err(|e| format!("Database error: {e}"))
+}
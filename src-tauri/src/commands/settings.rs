@@ -0,0 +1,61 @@
+// App settings commands — read and update ~/.elves/settings.json.
+
+use crate::agents::budget::BudgetLimits;
+use crate::agents::pricing::PricingTable;
+use crate::agents::process::StopTimeouts;
+use crate::settings::config::{self, AppSettings};
+use std::time::Duration;
+
+/// Read the app settings, falling back to defaults when none are saved.
+#[tauri::command]
+pub fn get_app_settings() -> Result<AppSettings, String> {
+    config::read_app_settings(&config::default_settings_path())
+}
+
+/// Replace the app settings.
+#[tauri::command]
+pub fn update_app_settings(settings: AppSettings) -> Result<(), String> {
+    config::write_app_settings(&config::default_settings_path(), &settings)
+}
+
+/// The saved app settings, or the defaults if they can't be read.
+fn app_settings() -> AppSettings {
+    config::read_app_settings(&config::default_settings_path()).unwrap_or_else(|e| {
+        log::warn!("Could not read app settings: {e}");
+        AppSettings::default()
+    })
+}
+
+/// Graceful stop timeouts from the app settings, or their defaults if unreadable.
+pub fn stop_timeouts() -> StopTimeouts {
+    let stop = app_settings().stop;
+    StopTimeouts {
+        interrupt: Duration::from_secs(stop.grace_period_secs),
+        terminate: Duration::from_secs(stop.terminate_timeout_secs),
+    }
+}
+
+/// How many sessions the task queue lets run at once across all projects.
+pub fn max_concurrent_sessions() -> u32 {
+    app_settings().queue.max_concurrent_sessions
+}
+
+/// Spending caps across all projects.
+pub fn global_budget() -> BudgetLimits {
+    app_settings().budget
+}
+
+/// The prices sessions are costed with.
+pub fn pricing_table() -> PricingTable {
+    app_settings().pricing
+}
+
+/// Whether agent terminals are recorded as asciicast files.
+pub fn recording_enabled() -> bool {
+    app_settings().recording.enabled
+}
+
+/// How many bytes of output each terminal keeps.
+pub fn scrollback_bytes() -> usize {
+    app_settings().scrollback.max_bytes
+}
@@ -1,16 +1,85 @@
 // Task execution commands — start and stop agent tasks via Tauri IPC.
 
 use crate::agents::analyzer::{self, TaskPlan};
+use crate::agents::budget::BudgetTracker;
 use crate::agents::claude_adapter::{self, ClaudeSpawnOptions};
 use crate::agents::codex_adapter;
+use crate::agents::events::ElfEvent;
+use crate::agents::failure::{self, FailureReason, ProcessExit};
 use crate::agents::interop;
+use crate::agents::pricing::UsageLedger;
 use crate::agents::process::ProcessManager;
+use crate::agents::recording;
+use crate::agents::watchdog::{Watchdog, WatchdogLimits};
+use crate::commands::budget;
 use crate::commands::projects::DbState;
 use crate::commands::pty::PtyManager;
+use crate::commands::queue;
+use crate::commands::settings;
+use crate::commands::transcripts::{self, PtyTranscript};
 use crate::db;
+use crate::mcp::agent_config::AgentMcpConfig;
+use crate::project::config::{self as project_config, RetryPolicy};
+use crate::secrets::redact;
 use serde::Serialize;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
 use tauri::{AppHandle, Emitter, Manager, State};
 
+/// How many trailing stderr lines are kept in memory to classify a failed run.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Prompt sent when a retry resumes a solo Claude conversation with `--resume`.
+const RESUME_PROMPT: &str =
+    "The previous run was interrupted by an error. Continue the task from where you left off.";
+
+/// Everything needed to launch a print-mode run, kept so a failed attempt can be retried.
+#[derive(Debug, Clone)]
+struct PrintRun {
+    session_id: String,
+    project_id: String,
+    is_codex: bool,
+    /// Task passed to the CLI; for Codex, memory context is already prepended.
+    task: String,
+    working_dir: String,
+    /// Claude spawn options, with memory context and MCP flags applied.
+    claude_options: ClaudeSpawnOptions,
+    /// Codex config overrides registering MCP servers.
+    codex_args: Vec<String>,
+    /// Set for team runs.
+    plan: Option<TaskPlan>,
+    /// Team elf IDs, used to attribute Codex phases to elves.
+    elf_ids: Option<Vec<String>>,
+}
+
+impl PrintRun {
+    /// The model usage is priced as until the runtime reports its own.
+    fn model(&self) -> Option<String> {
+        if self.is_codex {
+            Some(codex_adapter::DEFAULT_MODEL.to_string())
+        } else {
+            self.claude_options.model.clone()
+        }
+    }
+
+    /// An empty usage ledger for one attempt of this run, priced with the current table.
+    fn usage_ledger(&self) -> UsageLedger {
+        UsageLedger::new(settings::pricing_table(), self.model())
+    }
+}
+
+/// MCP servers to register with an agent this session spawns: the ELVES memory server,
+/// plus gateway-wrapped project servers when the project enables the MCP gateway.
+fn agent_mcp_config(
+    db: &DbState,
+    project_id: &str,
+    session_id: &str,
+    elf_id: Option<&str>,
+) -> Result<AgentMcpConfig, String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    Ok(AgentMcpConfig::for_session(&conn, project_id, session_id, elf_id))
+}
+
 /// Start a task: creates a session, spawns an elf, starts the Claude process.
 ///
 /// Emits events to the frontend via Tauri's event system as the agent works:
@@ -18,13 +87,13 @@ use tauri::{AppHandle, Emitter, Manager, State};
 /// - `session:error` — if spawning fails after DB rows are created
 ///
 /// Returns the session ID. The frontend subscribes to Tauri events keyed by
-/// this session ID to receive real-time updates from the agent.
+/// this session ID to receive real-time updates from the agent. Refused when the
+/// concurrency caps are reached.
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn start_task(
     app: AppHandle,
     db: State<'_, DbState>,
-    process_mgr: State<'_, ProcessManager>,
     project_id: String,
     task: String,
     runtime: String,
@@ -32,6 +101,26 @@ pub async fn start_task(
     working_dir: Option<String>,
     worktree_slug: Option<String>,
 ) -> Result<String, String> {
+    queue::check_capacity(&app, &project_id)?;
+    launch_task(&app, &db, project_id, task, runtime, options, working_dir, worktree_slug)
+}
+
+/// Create a task's session and elf rows and launch it in print mode. Shared by
+/// `start_task` and the task queue's dispatcher. Returns the session ID.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn launch_task(
+    app: &AppHandle,
+    db: &DbState,
+    project_id: String,
+    task: String,
+    runtime: String,
+    options: Option<String>,
+    working_dir: Option<String>,
+    worktree_slug: Option<String>,
+) -> Result<String, String> {
+    // Refuse to start once a spending cap is reached
+    let remaining_budget = budget::check_can_start(app, &project_id)?;
+
     let session_id = uuid::Uuid::new_v4().to_string();
     let elf_id = uuid::Uuid::new_v4().to_string();
 
@@ -90,18 +179,27 @@ pub async fn start_task(
             })
     };
 
-    // 6. Spawn the agent process — branch on runtime
+    // 6. Build the run — branch on runtime
     let is_codex = runtime == "codex";
 
-    let mut child = if is_codex {
+    let run = if is_codex {
         // For Codex, prepend memory context to the task prompt
         let codex_task = if memory_context.is_empty() {
             task.clone()
         } else {
             format!("{memory_context}\n\n---\n\n{task}")
         };
-        codex_adapter::spawn_codex(&codex_task, &working_dir)
-            .map_err(|e| format!("Failed to spawn codex: {e}"))?
+        PrintRun {
+            session_id: session_id.clone(),
+            project_id: project_id.clone(),
+            is_codex,
+            task: codex_task,
+            working_dir,
+            claude_options: ClaudeSpawnOptions::default(),
+            codex_args: agent_mcp_config(db, &project_id, &session_id, Some(&elf_id))?.codex_args(),
+            plan: None,
+            elf_ids: None,
+        }
     } else {
         // For Claude Code, inject memory via append_system_prompt
         let mut spawn_options: ClaudeSpawnOptions = match options {
@@ -120,38 +218,23 @@ pub async fn start_task(
                 None => memory_context,
             });
         }
-        claude_adapter::spawn_claude(&task, &working_dir, &spawn_options)
-            .map_err(|e| format!("Failed to spawn claude: {e}"))?
+        spawn_options.max_budget_usd = budget::capped(spawn_options.max_budget_usd, remaining_budget);
+        spawn_options.mcp_args = agent_mcp_config(db, &project_id, &session_id, Some(&elf_id))?.claude_args();
+        PrintRun {
+            session_id: session_id.clone(),
+            project_id: project_id.clone(),
+            is_codex,
+            task: task.clone(),
+            working_dir,
+            claude_options: spawn_options,
+            codex_args: Vec::new(),
+            plan: None,
+            elf_ids: None,
+        }
     };
 
-    // Take stdout and stderr before registering — we read them in background threads
-    let stdout = child.stdout.take();
-    let stderr = child.stderr.take();
-    process_mgr.register(&session_id, child);
-
-    // 6. Drain stderr in a background thread to prevent pipe buffer deadlock.
-    // If stderr fills up (64KB), the child process blocks on writes and stdout stalls.
-    if let Some(stderr) = stderr {
-        let sid_err = session_id.clone();
-        std::thread::spawn(move || {
-            drain_stderr(stderr, &sid_err);
-        });
-    }
-
-    // 7. Stream stdout events to the frontend in a background thread
-    if let Some(stdout) = stdout {
-        let app_handle = app.clone();
-        let sid = session_id.clone();
-        if is_codex {
-            std::thread::spawn(move || {
-                stream_codex_output(stdout, &app_handle, &sid, None);
-            });
-        } else {
-            std::thread::spawn(move || {
-                stream_claude_output(stdout, &app_handle, &sid);
-            });
-        }
-    }
+    // 7. Spawn the process and stream its output in background threads
+    launch_print_run(app, run, 1, None)?;
 
     Ok(session_id)
 }
@@ -172,22 +255,47 @@ pub struct StartTaskPtyResult {
 /// via `pty:data:{ptyId}` events — no stdout streaming thread needed here.
 ///
 /// When `resume_session_id` is present in spawn options, launches `claude --resume <id>`
-/// instead of a new task. DB session/elf creation is skipped for resume — reuses existing rows.
+/// instead of a new task. DB session/elf creation is skipped for resume — reuses existing rows,
+/// and `elves_session_id` (when given) is reopened as the session the PTY runs under.
+///
+/// Like every session, it counts toward the concurrency caps: when they are reached the
+/// start is refused, and the task can be queued to start in a PTY instead.
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn start_task_pty(
     app: AppHandle,
     db: State<'_, DbState>,
-    pty_mgr: State<'_, PtyManager>,
     project_id: String,
     task: String,
     runtime: String,
     working_dir: Option<String>,
     options: Option<String>,
     worktree_slug: Option<String>,
+    elves_session_id: Option<String>,
 ) -> Result<StartTaskPtyResult, String> {
+    queue::check_capacity(&app, &project_id)?;
+    launch_task_pty(&app, &db, project_id, task, runtime, working_dir, options, worktree_slug, elves_session_id)
+}
+
+/// Create (or reopen) a task's session and launch it in an interactive PTY. Shared by
+/// `start_task_pty` and the task queue's dispatcher.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn launch_task_pty(
+    app: &AppHandle,
+    db: &DbState,
+    project_id: String,
+    task: String,
+    runtime: String,
+    working_dir: Option<String>,
+    options: Option<String>,
+    worktree_slug: Option<String>,
+    elves_session_id: Option<String>,
+) -> Result<StartTaskPtyResult, String> {
+    // Refuse to start once a spending cap is reached
+    let remaining_budget = budget::check_can_start(app, &project_id)?;
+
     // Parse spawn options early — we need to check for resume_session_id
-    let spawn_options: ClaudeSpawnOptions = match options {
+    let mut spawn_options: ClaudeSpawnOptions = match options {
         Some(ref json) => match serde_json::from_str(json) {
             Ok(opts) => opts,
             Err(e) => {
@@ -198,15 +306,21 @@ pub async fn start_task_pty(
         None => ClaudeSpawnOptions::default(),
     };
 
+    spawn_options.max_budget_usd = budget::capped(spawn_options.max_budget_usd, remaining_budget);
+
     let is_resume = spawn_options.resume_session_id.is_some();
 
     // For resume: reuse existing session. For new task: create DB rows.
-    let session_id = if is_resume {
-        // Use the task string as the ELVES session ID for resume
-        // (the frontend passes the existing ELVES session ID as the task param)
-        uuid::Uuid::new_v4().to_string()
-    } else {
-        uuid::Uuid::new_v4().to_string()
+    // When resuming a recorded session, `elves_session_id` names it so its row is reopened
+    // instead of the terminal running under an untracked ID.
+    let session_id = match elves_session_id.filter(|_| is_resume) {
+        Some(id) => {
+            let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+            db::sessions::reopen_session(&conn, &id)
+                .map_err(|e| format!("Database error: {e}"))?;
+            id
+        }
+        None => uuid::Uuid::new_v4().to_string(),
     };
 
     if !is_resume {
@@ -278,9 +392,12 @@ pub async fn start_task_pty(
     let binary = binary_path.to_string_lossy().to_string();
     let mut args: Vec<String> = Vec::new();
 
+    let mcp_config = agent_mcp_config(db, &project_id, &session_id, None)?;
+
     if is_codex {
         // Codex CLI: `codex --full-auto "<task>"` (interactive PTY mode)
         // Codex has no --append-system-prompt, so prepend memory to the task text
+        args.extend(mcp_config.codex_args());
         args.push("--full-auto".to_string());
         if memory_context.is_empty() {
             args.push(task.clone());
@@ -317,6 +434,7 @@ pub async fn start_task_pty(
             args.push("--effort".to_string());
             args.push(effort.clone());
         }
+        args.extend(mcp_config.claude_args());
 
         // Inject memory context via --append-system-prompt (skip for resume)
         if !is_resume {
@@ -333,9 +451,30 @@ pub async fn start_task_pty(
         }
     }
 
-    // 6. Spawn via PtyManager — reuses existing PTY infrastructure
-    let pty_id = pty_mgr.spawn_with_app(&binary, &args, &working_dir, &app, None)
+    // 6. Spawn via PtyManager — reuses existing PTY infrastructure. Transcripts on disk
+    // are snapshotted first, so the watcher picks up only the one this run writes.
+    let locator = transcripts::locate(&runtime, &working_dir, spawn_options.resume_session_id.as_deref(), None);
+    let recording = recording_path(&session_id, None);
+    let pty_id = app
+        .state::<PtyManager>()
+        .spawn_with_app(&binary, &args, &working_dir, app, None, recording.as_deref())
         .map_err(|e| format!("Failed to spawn PTY: {e}"))?;
+    link_recording(app, &session_id, None, recording);
+    app.state::<Watchdog>().watch(&session_id, watchdog_limits(app, &project_id), Some(pty_id.clone()));
+    if let Some(locator) = locator {
+        transcripts::watch_pty_transcript(
+            app,
+            PtyTranscript {
+                session_id: session_id.clone(),
+                project_id: project_id.clone(),
+                elf_id: None,
+                pty_id: pty_id.clone(),
+                runtime: runtime.clone(),
+                model: pty_model(is_codex, &spawn_options),
+                locator,
+            },
+        );
+    }
 
     log::info!(
         "[session {session_id}] Started PTY-first task (resume={}): pty_id={pty_id}, working_dir={working_dir}",
@@ -345,9 +484,43 @@ pub async fn start_task_pty(
     Ok(StartTaskPtyResult { session_id, pty_id })
 }
 
-/// Stop a running task. Kills the agent process and marks the session as cancelled.
+/// The model a PTY run's usage is priced as until its transcript reports one.
+fn pty_model(is_codex: bool, spawn_options: &ClaudeSpawnOptions) -> Option<String> {
+    if is_codex {
+        Some(codex_adapter::DEFAULT_MODEL.to_string())
+    } else {
+        spawn_options.model.clone()
+    }
+}
+
+/// Where to record a PTY run of the session starting now, if terminal recording is
+/// enabled.
+fn recording_path(session_id: &str, elf_id: Option<&str>) -> Option<PathBuf> {
+    let started_ms = chrono::Utc::now().timestamp_millis();
+    settings::recording_enabled()
+        .then(|| recording::recording_path(&recording::default_recordings_dir(), session_id, elf_id, started_ms))
+}
+
+/// Link a PTY run's recording to its session, so replays can embed it.
+fn link_recording(app: &AppHandle, session_id: &str, elf_id: Option<&str>, path: Option<PathBuf>) {
+    let Some(path) = path else {
+        return;
+    };
+    let db_state = app.state::<DbState>();
+    if let Ok(conn) = db_state.0.lock() {
+        if let Err(e) = db::recordings::insert_recording(&conn, session_id, elf_id, &path.to_string_lossy()) {
+            log::warn!("[session {session_id}] Failed to link recording: {e}");
+        }
+    };
+}
+
+/// Stop a running task gracefully and mark the session as cancelled.
+///
+/// The session is marked cancelled first, so the output reader records the agent's final
+/// result and usage without completing, failing, or retrying it. The agent's process group
+/// then gets SIGINT, SIGTERM after the configured grace period, and finally SIGKILL.
 ///
-/// Returns true if a process was found and killed, false if no process was
+/// Returns true if a process was found and stopped, false if no process was
 /// running for the given session (e.g., it already completed).
 #[tauri::command]
 pub async fn stop_task(
@@ -356,32 +529,23 @@ pub async fn stop_task(
     process_mgr: State<'_, ProcessManager>,
     session_id: String,
 ) -> Result<bool, String> {
-    let killed = process_mgr.kill(&session_id);
-    let is_interactive = process_mgr.is_interactive(&session_id);
-
-    if killed || is_interactive {
-        process_mgr.clear_interactive(&session_id);
-    }
-
     // Always update DB and emit event so the frontend syncs — even if the process
     // already exited (crash, race condition). Skip DB write only if already terminal.
-    {
-        let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
-        let already_terminal = db::sessions::get_session(&conn, &session_id)
-            .ok()
-            .flatten()
-            .map(|s| matches!(s.status.as_str(), "completed" | "cancelled" | "error"))
-            .unwrap_or(false);
+    mark_cancelled(&db, &session_id, "Task stopped by user")?;
+
+    let stopped = {
+        let app = app.clone();
+        let session_id = session_id.clone();
+        tokio::task::spawn_blocking(move || {
+            app.state::<ProcessManager>().stop(&session_id, settings::stop_timeouts())
+        })
+        .await
+        .map_err(|e| format!("Stop error: {e}"))?
+    };
+    let is_interactive = process_mgr.is_interactive(&session_id);
 
-        if !already_terminal {
-            db::sessions::update_session_status(
-                &conn,
-                &session_id,
-                "cancelled",
-                Some("Task stopped by user"),
-            )
-            .map_err(|e| format!("Database error: {e}"))?;
-        }
+    if stopped || is_interactive {
+        process_mgr.clear_interactive(&session_id);
     }
 
     // Always emit so the frontend transitions out of "active" state
@@ -392,7 +556,23 @@ pub async fn stop_task(
         }),
     );
 
-    Ok(killed || is_interactive)
+    Ok(stopped || is_interactive)
+}
+
+/// Mark a session cancelled with `summary`, unless it already finished.
+fn mark_cancelled(db: &DbState, session_id: &str, summary: &str) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let already_terminal = db::sessions::get_session(&conn, session_id)
+        .ok()
+        .flatten()
+        .map(|s| matches!(s.status.as_str(), "completed" | "failed" | "interrupted" | "cancelled" | "timed_out" | "error"))
+        .unwrap_or(false);
+
+    if !already_terminal {
+        db::sessions::update_session_status(&conn, session_id, "cancelled", Some(summary))
+            .map_err(|e| format!("Database error: {e}"))?;
+    }
+    Ok(())
 }
 
 /// Analyze a task to determine deployment strategy (solo vs team).
@@ -436,12 +616,12 @@ const ELF_COLORS: &[&str] = &["#FFD93D", "#FF6B6B", "#6BCB77", "#4D96FF", "#FF8B
 /// then spawns Claude Code in team mode with the full team prompt.
 ///
 /// Returns the session ID. The frontend subscribes to events keyed by this ID.
+/// Refused when the concurrency caps are reached.
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn start_team_task(
     app: AppHandle,
     db: State<'_, DbState>,
-    process_mgr: State<'_, ProcessManager>,
     project_id: String,
     task: String,
     plan: TaskPlan,
@@ -449,6 +629,26 @@ pub async fn start_team_task(
     working_dir: Option<String>,
     worktree_slug: Option<String>,
 ) -> Result<String, String> {
+    queue::check_capacity(&app, &project_id)?;
+    launch_team_task(&app, &db, project_id, task, plan, options, working_dir, worktree_slug)
+}
+
+/// Create a team task's session and per-role elf rows and launch it in print mode.
+/// Shared by `start_team_task` and the task queue's dispatcher. Returns the session ID.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn launch_team_task(
+    app: &AppHandle,
+    db: &DbState,
+    project_id: String,
+    task: String,
+    plan: TaskPlan,
+    options: Option<String>,
+    working_dir: Option<String>,
+    worktree_slug: Option<String>,
+) -> Result<String, String> {
+    // Refuse to start once a spending cap is reached
+    let remaining_budget = budget::check_can_start(app, &project_id)?;
+
     let session_id = uuid::Uuid::new_v4().to_string();
     let runtime = plan.runtime_recommendation.clone();
 
@@ -457,6 +657,10 @@ pub async fn start_team_task(
         let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
         db::sessions::create_session(&conn, &session_id, &project_id, &task, &runtime, worktree_slug.as_deref())
             .map_err(|e| format!("Database error: {e}"))?;
+        if let Some(template_id) = &plan.template_id {
+            db::sessions::set_session_template(&conn, &session_id, template_id)
+                .map_err(|e| format!("Database error: {e}"))?;
+        }
     }
 
     // 2. Get working directory — use override if provided, else fall back to project path
@@ -517,18 +721,27 @@ pub async fn start_team_task(
             })
     };
 
-    // 5. Spawn the agent process — branch on runtime
+    // 5. Build the run — branch on runtime
     let is_codex = runtime == "codex";
 
-    let mut child = if is_codex {
+    let run = if is_codex {
         // For Codex team, prepend memory context to the task prompt
         let codex_task = if memory_context.is_empty() {
             task.clone()
         } else {
             format!("{memory_context}\n\n---\n\n{task}")
         };
-        codex_adapter::spawn_codex_team(&codex_task, &working_dir, &plan)
-            .map_err(|e| format!("Failed to spawn codex team: {e}"))?
+        PrintRun {
+            session_id: session_id.clone(),
+            project_id: project_id.clone(),
+            is_codex,
+            task: codex_task,
+            working_dir,
+            claude_options: ClaudeSpawnOptions::default(),
+            codex_args: agent_mcp_config(db, &project_id, &session_id, None)?.codex_args(),
+            plan: Some(plan),
+            elf_ids: Some(elf_ids),
+        }
     } else {
         // For Claude Code team, inject memory via append_system_prompt
         let mut spawn_options: ClaudeSpawnOptions = match options {
@@ -547,37 +760,23 @@ pub async fn start_team_task(
                 None => memory_context,
             });
         }
-        claude_adapter::spawn_claude_team(&task, &working_dir, &plan, &spawn_options)
-            .map_err(|e| format!("Failed to spawn claude team: {e}"))?
+        spawn_options.max_budget_usd = budget::capped(spawn_options.max_budget_usd, remaining_budget);
+        spawn_options.mcp_args = agent_mcp_config(db, &project_id, &session_id, None)?.claude_args();
+        PrintRun {
+            session_id: session_id.clone(),
+            project_id: project_id.clone(),
+            is_codex,
+            task: task.clone(),
+            working_dir,
+            claude_options: spawn_options,
+            codex_args: Vec::new(),
+            plan: Some(plan),
+            elf_ids: Some(elf_ids),
+        }
     };
 
-    // Take stdout and stderr before registering
-    let stdout = child.stdout.take();
-    let stderr = child.stderr.take();
-    process_mgr.register(&session_id, child);
-
-    // 6. Drain stderr to prevent pipe buffer deadlock
-    if let Some(stderr) = stderr {
-        let sid_err = session_id.clone();
-        std::thread::spawn(move || {
-            drain_stderr(stderr, &sid_err);
-        });
-    }
-
-    // 7. Stream stdout events to the frontend in a background thread
-    if let Some(stdout) = stdout {
-        let app_handle = app.clone();
-        let sid = session_id.clone();
-        if is_codex {
-            std::thread::spawn(move || {
-                stream_codex_output(stdout, &app_handle, &sid, Some(elf_ids));
-            });
-        } else {
-            std::thread::spawn(move || {
-                stream_claude_output(stdout, &app_handle, &sid);
-            });
-        }
-    }
+    // 6. Spawn the process and stream its output in background threads
+    launch_print_run(app, run, 1, None)?;
 
     Ok(session_id)
 }
@@ -604,7 +803,7 @@ pub struct StartTeamTaskPtyResult {
 /// Creates a single session, one elf per role, and spawns separate Claude
 /// processes in interactive PTY mode. Each role gets a role-scoped prompt.
 /// Returns session ID + a list of PTY entries for the frontend to render
-/// in a split terminal grid.
+/// in a split terminal grid. Refused when the concurrency caps are reached.
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn start_team_task_pty(
@@ -618,6 +817,11 @@ pub async fn start_team_task_pty(
     working_dir: Option<String>,
     worktree_slug: Option<String>,
 ) -> Result<StartTeamTaskPtyResult, String> {
+    queue::check_capacity(&app, &project_id)?;
+
+    // Refuse to start once a spending cap is reached
+    let remaining_budget = budget::check_can_start(&app, &project_id)?;
+
     let session_id = uuid::Uuid::new_v4().to_string();
     let runtime = plan.runtime_recommendation.clone();
 
@@ -626,6 +830,10 @@ pub async fn start_team_task_pty(
         let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
         db::sessions::create_session(&conn, &session_id, &project_id, &task, &runtime, worktree_slug.as_deref())
             .map_err(|e| format!("Database error: {e}"))?;
+        if let Some(template_id) = &plan.template_id {
+            db::sessions::set_session_template(&conn, &session_id, template_id)
+                .map_err(|e| format!("Database error: {e}"))?;
+        }
     }
 
     // 2. Resolve working directory
@@ -651,7 +859,7 @@ pub async fn start_team_task_pty(
     };
 
     // 4. Parse spawn options
-    let spawn_options: ClaudeSpawnOptions = match options {
+    let mut spawn_options: ClaudeSpawnOptions = match options {
         Some(ref json) => match serde_json::from_str(json) {
             Ok(opts) => opts,
             Err(e) => {
@@ -662,6 +870,8 @@ pub async fn start_team_task_pty(
         None => ClaudeSpawnOptions::default(),
     };
 
+    spawn_options.max_budget_usd = budget::capped(spawn_options.max_budget_usd, remaining_budget);
+
     // Budget splitting: divide total budget by number of roles
     let per_role_budget = spawn_options.max_budget_usd.map(|b| b / plan.roles.len() as f64);
 
@@ -714,9 +924,12 @@ pub async fn start_team_task_pty(
         let binary = binary_path.to_string_lossy().to_string();
         let mut args: Vec<String> = Vec::new();
 
+        let mcp_config = agent_mcp_config(&db, &project_id, &session_id, Some(&elf_id))?;
+
         if is_codex {
             // Codex CLI: `codex --full-auto "<prompt>"` (interactive PTY mode)
             // Codex has no --append-system-prompt, so prepend memory to the prompt
+            args.extend(mcp_config.codex_args());
             args.push("--full-auto".to_string());
             if memory_context.is_empty() {
                 args.push(role_prompt);
@@ -746,6 +959,7 @@ pub async fn start_team_task_pty(
                 args.push("--effort".to_string());
                 args.push(effort.clone());
             }
+            args.extend(mcp_config.claude_args());
 
             // Inject memory context via --append-system-prompt
             let combined_system_prompt = match (&spawn_options.append_system_prompt, memory_context.is_empty()) {
@@ -760,9 +974,28 @@ pub async fn start_team_task_pty(
             }
         }
 
-        // Spawn PTY for this role
-        let pty_id = pty_mgr.spawn_with_app(&binary, &args, &working_dir, &app, None)
+        // Spawn PTY for this role. Roles share a directory, so each one's transcript is
+        // told apart by its role prompt.
+        let locator = transcripts::locate(&runtime, &working_dir, None, Some(format!("You are the {}.", role.name)));
+        let recording = recording_path(&session_id, Some(&elf_id));
+        let pty_id = pty_mgr.spawn_with_app(&binary, &args, &working_dir, &app, None, recording.as_deref())
             .map_err(|e| format!("Failed to spawn PTY for role {}: {e}", role.name))?;
+        link_recording(&app, &session_id, Some(&elf_id), recording);
+        app.state::<Watchdog>().watch_pty(&session_id, watchdog_limits(&app, &project_id), pty_id.clone());
+        if let Some(locator) = locator {
+            transcripts::watch_pty_transcript(
+                &app,
+                PtyTranscript {
+                    session_id: session_id.clone(),
+                    project_id: project_id.clone(),
+                    elf_id: Some(elf_id.clone()),
+                    pty_id: pty_id.clone(),
+                    runtime: runtime.clone(),
+                    model: pty_model(is_codex, &spawn_options),
+                    locator,
+                },
+            );
+        }
 
         log::info!(
             "[session {session_id}] Spawned team PTY for role '{}': pty_id={pty_id}",
@@ -784,10 +1017,10 @@ pub async fn start_team_task_pty(
     Ok(StartTeamTaskPtyResult { session_id, pty_entries })
 }
 
-/// Stop a team task. Kills all agent processes and marks the session as cancelled.
+/// Stop a team task gracefully, like `stop_task`, and mark the session as cancelled.
 ///
-/// Attempts to kill both single and team processes for the session.
-/// Returns true if any process was killed.
+/// Stops both single and team processes for the session.
+/// Returns true if any process was stopped.
 #[tauri::command]
 pub async fn stop_team_task(
     app: AppHandle,
@@ -795,35 +1028,27 @@ pub async fn stop_team_task(
     process_mgr: State<'_, ProcessManager>,
     session_id: String,
 ) -> Result<bool, String> {
-    // Try both single-process and team kill paths
-    let single_killed = process_mgr.kill(&session_id);
-    let team_killed = process_mgr.kill_team(&session_id);
-    let is_interactive = process_mgr.is_interactive(&session_id);
-    let any_killed = single_killed || team_killed > 0 || is_interactive;
-
-    if any_killed {
-        process_mgr.clear_interactive(&session_id);
-    }
-
     // Always update DB and emit event so the frontend syncs — even if all processes
     // already exited. Skip DB write only if session is already in a terminal state.
-    {
-        let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
-        let already_terminal = db::sessions::get_session(&conn, &session_id)
-            .ok()
-            .flatten()
-            .map(|s| matches!(s.status.as_str(), "completed" | "cancelled" | "error"))
-            .unwrap_or(false);
+    mark_cancelled(&db, &session_id, "Team task stopped by user")?;
+
+    // Try both single-process and team stop paths
+    let (single_stopped, team_stopped) = {
+        let app = app.clone();
+        let session_id = session_id.clone();
+        tokio::task::spawn_blocking(move || {
+            let process_mgr = app.state::<ProcessManager>();
+            let timeouts = settings::stop_timeouts();
+            (process_mgr.stop(&session_id, timeouts), process_mgr.stop_team(&session_id, timeouts))
+        })
+        .await
+        .map_err(|e| format!("Stop error: {e}"))?
+    };
+    let is_interactive = process_mgr.is_interactive(&session_id);
+    let any_stopped = single_stopped || team_stopped > 0 || is_interactive;
 
-        if !already_terminal {
-            db::sessions::update_session_status(
-                &conn,
-                &session_id,
-                "cancelled",
-                Some("Team task stopped by user"),
-            )
-            .map_err(|e| format!("Database error: {e}"))?;
-        }
+    if any_stopped {
+        process_mgr.clear_interactive(&session_id);
     }
 
     // Always emit so the frontend transitions out of "active" state
@@ -834,7 +1059,7 @@ pub async fn stop_team_task(
         }),
     );
 
-    Ok(any_killed)
+    Ok(any_stopped)
 }
 
 /// Transition a session from non-interactive `--print` mode to interactive terminal.
@@ -865,20 +1090,118 @@ pub async fn transition_to_interactive(
     Ok(killed)
 }
 
-/// Drain stderr from the Claude process to prevent pipe buffer deadlock.
+/// Spawn the agent process for a print-mode run.
+///
+/// With `resume_session_id`, Claude resumes that conversation: solo runs are told to
+/// continue, team runs get their team prompt again. Codex runs always restart.
+fn spawn_print_run(run: &PrintRun, resume_session_id: Option<&str>) -> Result<std::process::Child, String> {
+    if run.is_codex {
+        return match &run.plan {
+            Some(plan) => codex_adapter::spawn_codex_team(&run.task, &run.working_dir, plan, &run.codex_args)
+                .map_err(|e| format!("Failed to spawn codex team: {e}")),
+            None => codex_adapter::spawn_codex(&run.task, &run.working_dir, &run.codex_args)
+                .map_err(|e| format!("Failed to spawn codex: {e}")),
+        };
+    }
+
+    let mut options = run.claude_options.clone();
+    if let Some(resume) = resume_session_id {
+        options.resume_session_id = Some(resume.to_string());
+    }
+    match &run.plan {
+        Some(plan) => claude_adapter::spawn_claude_team(&run.task, &run.working_dir, plan, &options)
+            .map_err(|e| format!("Failed to spawn claude team: {e}")),
+        None => {
+            let task = if resume_session_id.is_some() { RESUME_PROMPT } else { run.task.as_str() };
+            claude_adapter::spawn_claude(task, &run.working_dir, &options)
+                .map_err(|e| format!("Failed to spawn claude: {e}"))
+        }
+    }
+}
+
+/// Spawn attempt `attempt` of a print-mode run, register it, and stream its output.
+///
+/// Records the attempt on the session, then reads stderr and stdout in background
+/// threads; the stdout reader finishes the session (and retries it) when the process exits.
+fn launch_print_run(
+    app: &AppHandle,
+    run: PrintRun,
+    attempt: i32,
+    resume_session_id: Option<String>,
+) -> Result<(), String> {
+    let mut child = spawn_print_run(&run, resume_session_id.as_deref())?;
+
+    // Earlier attempts' usage and run time are already stored, so a retry keeps counting
+    // against the cap and the wall-clock limit
+    let (prior_usd, started_at) = {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+        db::sessions::begin_attempt(&conn, &run.session_id, attempt, resume_session_id.as_deref())
+            .map_err(|e| format!("Database error: {e}"))?;
+        db::sessions::get_session(&conn, &run.session_id)
+            .map_err(|e| format!("Database error: {e}"))?
+            .map_or((0.0, chrono::Utc::now().timestamp()), |session| (session.cost_estimate, session.started_at))
+    };
+
+    // Take stdout and stderr before registering — we read them in background threads
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    app.state::<ProcessManager>().register(&run.session_id, child);
+    app.state::<Watchdog>().watch_attempt(&run.session_id, watchdog_limits(app, &run.project_id), started_at);
+    app.state::<BudgetTracker>()
+        .track(&run.session_id, &run.project_id, prior_usd, settings::pricing_table(), run.model());
+
+    // Drain stderr in a background thread to prevent pipe buffer deadlock.
+    // If stderr fills up (64KB), the child process blocks on writes and stdout stalls.
+    let stderr_tail = stderr.map(|stderr| {
+        let app_handle = app.clone();
+        let sid_err = run.session_id.clone();
+        std::thread::spawn(move || drain_stderr(stderr, &app_handle, &sid_err))
+    });
+
+    // Stream stdout events to the frontend in a background thread
+    if let Some(stdout) = stdout {
+        let app_handle = app.clone();
+        if run.is_codex {
+            std::thread::spawn(move || {
+                stream_codex_output(stdout, &app_handle, run, attempt, stderr_tail);
+            });
+        } else {
+            std::thread::spawn(move || {
+                stream_claude_output(stdout, &app_handle, run, attempt, stderr_tail);
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Drain stderr from the agent process to prevent pipe buffer deadlock.
 ///
-/// Reads stderr line-by-line and logs each line at warn level. Without this,
-/// if Claude writes enough to stderr to fill the OS pipe buffer (~64KB on macOS),
-/// the process blocks on stderr writes and stdout stalls — deadlocking the stream.
-fn drain_stderr(stderr: std::process::ChildStderr, session_id: &str) {
+/// Reads stderr line-by-line, logs each line at warn level, and stores it as a `stderr`
+/// event so auth errors, rate limits, and bad flags show up in the session history.
+/// Without draining, if the agent writes enough to stderr to fill the OS pipe buffer
+/// (~64KB on macOS), the process blocks on stderr writes and stdout stalls — deadlocking
+/// the stream. Returns the last `STDERR_TAIL_LINES` lines, for classifying a failed run.
+fn drain_stderr(stderr: std::process::ChildStderr, app: &AppHandle, session_id: &str) -> Vec<String> {
     use std::io::BufRead;
+    let db_state = app.state::<DbState>();
     let reader = std::io::BufReader::new(stderr);
+    let mut tail = std::collections::VecDeque::with_capacity(STDERR_TAIL_LINES);
     for line in reader.lines() {
         match line {
             Ok(line) if !line.trim().is_empty() => {
-                // Use both log and eprintln to ensure visibility
-                log::warn!("[session {session_id}] claude stderr: {line}");
-                eprintln!("[ELVES] claude stderr [{session_id}]: {line}");
+                log::warn!("[session {session_id}] agent stderr: {line}");
+                if let Ok(conn) = db_state.0.lock() {
+                    let payload = serde_json::json!({ "text": &line }).to_string();
+                    if let Err(e) = db::events::insert_event(&conn, session_id, None, "stderr", &payload, None) {
+                        log::warn!("Failed to store stderr for session {session_id}: {e}");
+                    }
+                }
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
             }
             Err(e) => {
                 log::warn!("[session {session_id}] stderr read error: {e}");
@@ -887,32 +1210,363 @@ fn drain_stderr(stderr: std::process::ChildStderr, session_id: &str) {
             _ => {}
         }
     }
+    tail.into()
+}
+
+/// Wait for a print-mode agent process to exit and record how it ended.
+///
+/// Stores the exit code, then classifies the run from its exit status, stderr tail,
+/// and reported `errors`. A failed run is retried when the project's retry policy
+/// covers its failure class; otherwise it is marked "failed" with its reason and
+/// `session:failed` is emitted. A successful run is marked "completed" with `summary`
+/// and `session:completed` is emitted. Returns without touching the session if it
+/// was cancelled meanwhile.
+fn finish_session(
+    app: &AppHandle,
+    run: PrintRun,
+    attempt: i32,
+    stderr_tail: Option<JoinHandle<Vec<String>>>,
+    reported_error: bool,
+    errors: Vec<String>,
+    summary: Option<String>,
+) {
+    let session_id = run.session_id.as_str();
+    let db_state = app.state::<DbState>();
+    let process_mgr = app.state::<ProcessManager>();
+
+    let exit = process_mgr.wait_for_exit(session_id).map(ProcessExit::from);
+    app.state::<Watchdog>().unwatch(session_id);
+    app.state::<BudgetTracker>().untrack(session_id);
+    // stderr closes when the process exits, so this join doesn't block for long
+    let stderr_lines = stderr_tail.and_then(|handle| handle.join().ok()).unwrap_or_default();
+
+    // Check if session was already stopped by stop_task or the watchdog (prevents
+    // double-event race). Without this guard, stop_task emits session:cancelled and then
+    // the reader sees EOF and emits session:completed — leaving the frontend in an
+    // inconsistent state.
+    if session_stopped(app, session_id) {
+        log::info!("[session {session_id}] Skipping completion — session already stopped");
+        return;
+    }
+
+    let mut messages = stderr_lines;
+    messages.extend(errors);
+    let failure = failure::classify(exit.unwrap_or_default(), reported_error, &messages);
+    let exit_code = exit.and_then(|e| e.code);
+    log::info!("[session {session_id}] Attempt {attempt} exit: {exit:?}, failure: {failure:?}");
+
+    if let Ok(conn) = db_state.0.lock() {
+        if let Some(code) = exit_code {
+            let _ = db::sessions::update_session_exit_code(&conn, session_id, code);
+        }
+        let _ = db::sessions::end_attempt(&conn, session_id, attempt, exit_code, failure.map(|r| r.as_str()));
+    }
+
+    let Some(reason) = failure else {
+        if let Ok(conn) = db_state.0.lock() {
+            let _ = db::sessions::update_session_status(
+                &conn,
+                session_id,
+                "completed",
+                summary.as_deref().or(Some("Task completed")),
+            );
+        }
+        let _ = app.emit(
+            "session:completed",
+            serde_json::json!({
+                "sessionId": session_id,
+            }),
+        );
+        return;
+    };
+
+    let message = failure_message(exit, &messages);
+    let policy = retry_policy(app, &run.project_id);
+    if let Some(delay) = policy.delay_before_retry(attempt as u32, reason) {
+        retry_print_run(app, run, attempt + 1, policy.max_attempts, reason, delay, &message);
+        return;
+    }
+
+    if let Ok(conn) = db_state.0.lock() {
+        let _ = db::sessions::fail_session(&conn, session_id, reason.as_str(), Some(&message));
+    }
+    emit_session_failed(app, session_id, reason, exit_code, &message);
+}
+
+/// Wait out the retry backoff, then relaunch the run as attempt `attempt`.
+///
+/// Resumes the Claude conversation when its session ID was captured, and otherwise
+/// restarts the task. Emits `session:retrying` before waiting. Gives up if the user
+/// stops the session during the wait; fails the session if the relaunch cannot spawn.
+fn retry_print_run(
+    app: &AppHandle,
+    run: PrintRun,
+    attempt: i32,
+    max_attempts: u32,
+    reason: FailureReason,
+    delay: std::time::Duration,
+    message: &str,
+) {
+    let session_id = run.session_id.clone();
+    let db_state = app.state::<DbState>();
+
+    let resume_session_id = if run.is_codex {
+        None
+    } else {
+        db_state
+            .0
+            .lock()
+            .ok()
+            .and_then(|conn| db::sessions::get_session(&conn, &session_id).ok().flatten())
+            .and_then(|session| session.claude_session_id)
+    };
+
+    log::info!(
+        "[session {session_id}] Retrying after {} in {}s (attempt {attempt} of {max_attempts}, resume: {})",
+        reason.as_str(),
+        delay.as_secs(),
+        resume_session_id.is_some(),
+    );
+
+    if let Ok(conn) = db_state.0.lock() {
+        let payload = serde_json::json!({
+            "text": format!("Retrying after {}: {message}", reason.as_str()),
+            "attempt": attempt,
+            "reason": reason,
+            "delaySecs": delay.as_secs(),
+            "resume": resume_session_id.is_some(),
+        });
+        let _ = db::events::insert_event(&conn, &session_id, None, "retry", &payload.to_string(), None);
+    }
+
+    let _ = app.emit(
+        "session:retrying",
+        serde_json::json!({
+            "sessionId": &session_id,
+            "attempt": attempt,
+            "maxAttempts": max_attempts,
+            "reason": reason,
+            "delaySecs": delay.as_secs(),
+            "resume": resume_session_id.is_some(),
+            "message": message,
+        }),
+    );
+
+    std::thread::sleep(delay);
+
+    // The user may have stopped the session during the backoff
+    if session_stopped(app, &session_id) {
+        log::info!("[session {session_id}] Retry abandoned — session cancelled during backoff");
+        return;
+    }
+
+    if let Err(e) = launch_print_run(app, run, attempt, resume_session_id) {
+        log::warn!("[session {session_id}] Retry failed to start: {e}");
+        let message = format!("Retry failed to start: {e}");
+        if let Ok(conn) = db_state.0.lock() {
+            let _ = db::sessions::fail_session(&conn, &session_id, reason.as_str(), Some(&message));
+        }
+        emit_session_failed(app, &session_id, reason, None, &message);
+    }
+}
+
+/// The retry policy of a session's project; the default (disabled) policy when the
+/// project or its config can't be read.
+fn retry_policy(app: &AppHandle, project_id: &str) -> RetryPolicy {
+    project_config_of(app, project_id).map(|config| config.retry).unwrap_or_default()
+}
+
+/// The watchdog limits of a session's project; the default (disabled) limits when the
+/// project or its config can't be read.
+pub(crate) fn watchdog_limits(app: &AppHandle, project_id: &str) -> WatchdogLimits {
+    project_config_of(app, project_id).map(|config| config.watchdog).unwrap_or_default()
+}
+
+pub(crate) fn project_config_of(app: &AppHandle, project_id: &str) -> Option<project_config::ProjectConfig> {
+    let db_state = app.state::<DbState>();
+    let project_path = db_state
+        .0
+        .lock()
+        .ok()
+        .and_then(|conn| db::projects::get_project(&conn, project_id).ok().flatten())
+        .map(|project| project.path);
+    project_path.and_then(|path| project_config::read_project_config(&path).ok())
+}
+
+/// Whether the session has been stopped — cancelled by `stop_task`, timed out by the
+/// watchdog, or failed for reaching a spending cap.
+fn session_stopped(app: &AppHandle, session_id: &str) -> bool {
+    let db_state = app.state::<DbState>();
+    let Ok(conn) = db_state.0.lock() else {
+        return false;
+    };
+    matches!(
+        db::sessions::get_session(&conn, session_id),
+        Ok(Some(session)) if matches!(session.status.as_str(), "cancelled" | "timed_out")
+            || (session.status == "failed"
+                && session.failure_reason.as_deref() == Some(FailureReason::BudgetExceeded.as_str()))
+    )
+}
+
+/// Report a parsed agent event to the watchdog.
+fn record_activity(app: &AppHandle, session_id: &str, events: &[ElfEvent]) {
+    let is_tool_call = events.iter().any(|event| matches!(event, ElfEvent::ToolCall { .. }));
+    app.state::<Watchdog>().record_event(session_id, is_tool_call);
+}
+
+/// Tell the frontend a session failed, with its classified reason.
+pub(crate) fn emit_session_failed(app: &AppHandle, session_id: &str, reason: FailureReason, exit_code: Option<i32>, message: &str) {
+    let _ = app.emit(
+        "session:failed",
+        serde_json::json!({
+            "sessionId": session_id,
+            "reason": reason,
+            "exitCode": exit_code,
+            "message": message,
+        }),
+    );
+}
+
+/// One-line description of a failed run: its last error or stderr line, else how it exited.
+fn failure_message(exit: Option<ProcessExit>, messages: &[String]) -> String {
+    let message = match (messages.last(), exit) {
+        (Some(last), _) => last.trim().to_string(),
+        (None, Some(ProcessExit { signal: Some(signal), .. })) => format!("Process killed by signal {signal}"),
+        (None, Some(ProcessExit { code: Some(code), .. })) => format!("Process exited with code {code}"),
+        (None, _) => "Process failed".to_string(),
+    };
+    redact::redact(&truncate_summary(message))
+}
+
+/// Cap a session summary at 500 bytes, on a character boundary.
+pub(crate) fn truncate_summary(text: String) -> String {
+    if text.len() <= 500 {
+        return text;
+    }
+    let mut end = 497;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &text[..end])
+}
+
+/// Emit one output line's typed events as `elf:event`, with stored secrets masked.
+/// Lines without typed events (e.g. empty user turns) are not emitted.
+pub(crate) fn emit_elf_events(
+    app: &AppHandle,
+    session_id: &str,
+    elf_id: Option<&str>,
+    runtime: Option<&str>,
+    events: &[ElfEvent],
+    timestamp: i64,
+) {
+    if events.is_empty() {
+        return;
+    }
+    let events = redact::redact_json(&serde_json::to_value(events).unwrap_or_default());
+    let _ = app.emit(
+        "elf:event",
+        serde_json::json!({
+            "sessionId": session_id,
+            "elfId": elf_id,
+            "events": events,
+            "timestamp": timestamp,
+            "runtime": runtime,
+        }),
+    );
+}
+
+/// Persist one output line: each typed event as its own row with its `kind`, or the raw
+/// line (kind NULL) when it produced none, so nothing the runtime said is lost.
+pub(crate) fn store_elf_events(
+    conn: &rusqlite::Connection,
+    session_id: &str,
+    elf_id: Option<&str>,
+    raw_type: &str,
+    raw_payload: &serde_json::Value,
+    events: &[ElfEvent],
+) -> Result<(), db::DbError> {
+    if events.is_empty() {
+        let payload = serde_json::to_string(raw_payload).unwrap_or_default();
+        db::events::insert_event(conn, session_id, elf_id, raw_type, &payload, None)?;
+        return Ok(());
+    }
+    for event in events {
+        let payload = serde_json::to_string(event).unwrap_or_default();
+        db::events::insert_typed_event(conn, session_id, elf_id, event.kind(), &payload)?;
+    }
+    Ok(())
+}
+
+/// Store Claude Code's own session ID (used for `--resume`) and tell the frontend.
+pub(crate) fn record_claude_session_id(app: &AppHandle, db_state: &DbState, session_id: &str, claude_sid: &str) {
+    if let Ok(conn) = db_state.0.lock() {
+        let _ = db::sessions::update_claude_session_id(&conn, session_id, claude_sid);
+    }
+    let _ = app.emit(
+        "session:claude_id",
+        serde_json::json!({
+            "sessionId": session_id,
+            "claudeSessionId": claude_sid,
+        }),
+    );
+}
+
+/// Store an attempt's usage per elf and model, and set the session's totals to the sum
+/// of its usage. Attempts add to the session's usage, so retried runs count every attempt.
+pub(crate) fn store_usage(conn: &rusqlite::Connection, session_id: &str, usage: &UsageLedger) {
+    for entry in usage.usage() {
+        let row = db::usage::SessionUsage {
+            session_id: session_id.to_string(),
+            elf_id: entry.elf_id,
+            model: entry.model,
+            input_tokens: entry.tokens.input_tokens,
+            output_tokens: entry.tokens.output_tokens,
+            cache_read_tokens: entry.tokens.cache_read_tokens,
+            cache_write_tokens: entry.tokens.cache_write_tokens,
+            cost_usd: entry.cost_usd,
+        };
+        if let Err(e) = db::usage::add_session_usage(conn, &row) {
+            log::warn!("[session {session_id}] Failed to store usage of {}: {e}", row.model);
+        }
+    }
+    match db::usage::sync_session_totals(conn, session_id) {
+        Ok((tokens, cost)) => log::info!("[session {session_id}] Usage: tokens={tokens}, cost={cost:.4}"),
+        Err(e) => log::warn!("[session {session_id}] Failed to update usage totals: {e}"),
+    }
 }
 
 /// Read Claude's stdout line-by-line, parse events, and emit them to the frontend.
 ///
 /// Runs in a background thread. For each parsed line:
-/// 1. Emits `elf:event` to the frontend for real-time display
-/// 2. Persists the event to SQLite for history and replay
+/// 1. Emits the line's typed ElfEvents as `elf:event` to the frontend for real-time display
+/// 2. Persists the typed events to SQLite for history and replay
 ///
 /// When stdout closes (process finished):
-/// 1. Extracts token/cost data from the last `result` event
-/// 2. Updates session usage stats in the database
-/// 3. Updates session status to "completed" with a summary from the result
-/// 4. Emits `session:completed` to the frontend
+/// 1. Prices the run's `Usage` events per model with the pricing table
+/// 2. Stores the usage per model and updates the session's totals (see `store_usage`)
+/// 3. Waits for the exit status and marks the session "completed" with a summary from
+///    the result, or "failed" with a classified reason (see `finish_session`)
 fn stream_claude_output(
     stdout: std::process::ChildStdout,
     app: &AppHandle,
-    session_id: &str,
+    run: PrintRun,
+    attempt: i32,
+    stderr_tail: Option<JoinHandle<Vec<String>>>,
 ) {
     use std::io::BufRead;
 
+    let session_id = run.session_id.as_str();
+
     eprintln!("[ELVES] Starting stdout stream for session {session_id}");
     log::info!("[session {session_id}] Starting stdout stream reader");
 
     let db_state = app.state::<DbState>();
     let reader = std::io::BufReader::new(stdout);
-    let mut last_result_payload: Option<serde_json::Value> = None;
+    let mut usage = run.usage_ledger();
+    let mut result_text: Option<String> = None;
+    let mut result_is_error = false;
+    let mut errors: Vec<String> = Vec::new();
     let mut event_count: u32 = 0;
 
     for line in reader.lines() {
@@ -920,6 +1574,12 @@ fn stream_claude_output(
             Ok(line) => {
                 if let Some(event) = claude_adapter::parse_claude_output(&line) {
                     event_count += 1;
+                    record_activity(app, session_id, &event.events);
+                    if event.event_type == "assistant" {
+                        budget::record_message_spend(app, session_id, &event.payload);
+                    }
+                    budget::record_spend(app, session_id, None, &event.events);
+                    usage.record(None, &event.events);
 
                     if event_count <= 3 || event.event_type == "result" {
                         log::info!(
@@ -929,69 +1589,41 @@ fn stream_claude_output(
                         );
                     }
 
-                    // Capture Claude Code's session ID from system events for resume support
-                    if event.event_type == "system" {
-                        if let Some(claude_sid) = event.payload.get("session_id").and_then(|v| v.as_str()) {
-                            if let Ok(conn) = db_state.0.lock() {
-                                let _ = db::sessions::update_claude_session_id(&conn, session_id, claude_sid);
+                    for typed in &event.events {
+                        match typed {
+                            // Capture Claude Code's session ID for resume support. Result
+                            // events reliably include it even if the init event was missed.
+                            ElfEvent::Init { session_id: Some(claude_sid), .. }
+                            | ElfEvent::Result { session_id: Some(claude_sid), .. } => {
+                                record_claude_session_id(app, &db_state, session_id, claude_sid);
                             }
-                            let _ = app.emit(
-                                "session:claude_id",
-                                serde_json::json!({
-                                    "sessionId": session_id,
-                                    "claudeSessionId": claude_sid,
-                                }),
-                            );
+                            _ => {}
                         }
-                    }
-
-                    // Also capture session_id from result events as fallback —
-                    // result events reliably include it even if the system event was missed
-                    if event.event_type == "result" {
-                        if let Some(claude_sid) = event.payload.get("session_id").and_then(|v| v.as_str()) {
-                            if let Ok(conn) = db_state.0.lock() {
-                                let _ = db::sessions::update_claude_session_id(&conn, session_id, claude_sid);
+                        // Track the latest result for the session summary
+                        match typed {
+                            ElfEvent::Result { text, is_error, .. } => {
+                                if text.is_some() {
+                                    result_text = text.clone();
+                                }
+                                result_is_error = *is_error;
+                                if let (true, Some(text)) = (*is_error, text) {
+                                    errors.push(text.clone());
+                                }
                             }
-                            let _ = app.emit(
-                                "session:claude_id",
-                                serde_json::json!({
-                                    "sessionId": session_id,
-                                    "claudeSessionId": claude_sid,
-                                }),
-                            );
+                            ElfEvent::Error { message } => errors.push(message.clone()),
+                            _ => {}
                         }
                     }
 
-                    // 1. Emit to frontend for real-time display
-                    let _ = app.emit(
-                        "elf:event",
-                        serde_json::json!({
-                            "sessionId": session_id,
-                            "eventType": &event.event_type,
-                            "payload": &event.payload,
-                            "timestamp": event.timestamp,
-                        }),
-                    );
+                    // 1. Emit to frontend for real-time display, with stored secrets masked
+                    emit_elf_events(app, session_id, None, None, &event.events, event.timestamp);
 
                     // 2. Persist to SQLite for history and replay
                     if let Ok(conn) = db_state.0.lock() {
-                        let payload_str = serde_json::to_string(&event.payload).unwrap_or_default();
-                        if let Err(e) = db::events::insert_event(
-                            &conn,
-                            session_id,
-                            None,
-                            &event.event_type,
-                            &payload_str,
-                            None,
-                        ) {
+                        if let Err(e) = store_elf_events(&conn, session_id, None, &event.event_type, &event.payload, &event.events) {
                             log::warn!("Failed to store event for session {session_id}: {e}");
                         }
                     }
-
-                    // 3. Track the last result event for usage extraction
-                    if event.event_type == "result" {
-                        last_result_payload = Some(event.payload.clone());
-                    }
                 }
             }
             Err(error) => {
@@ -1014,71 +1646,15 @@ fn stream_claude_output(
         return;
     }
 
-    // Check if session was already cancelled by stop_task (prevents double-event race).
-    // Without this guard, stop_task emits session:cancelled and then this function
-    // sees EOF and emits session:completed — leaving the frontend in an inconsistent state.
+    // stdout closed — the Claude process is finishing.
     if let Ok(conn) = db_state.0.lock() {
-        if let Ok(Some(session)) = db::sessions::get_session(&conn, session_id) {
-            if session.status == "cancelled" {
-                log::info!("[session {session_id}] Skipping completion — session already cancelled");
-                return;
-            }
-        }
+        store_usage(&conn, session_id, &usage);
     }
 
-    // stdout closed — the Claude process has finished.
-    if let Ok(conn) = db_state.0.lock() {
-        // Extract token/cost data from the result event if available
-        if let Some(ref result) = last_result_payload {
-            let cost = result.get("cost_usd")
-                .and_then(|v| v.as_f64())
-                .or_else(|| result.get("cost").and_then(|v| v.as_f64()))
-                .unwrap_or(0.0);
-
-            let tokens = result.get("total_tokens")
-                .and_then(|v| v.as_i64())
-                .or_else(|| {
-                    // Sum input + output tokens if total not provided
-                    let input = result.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let output = result.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    if input > 0 || output > 0 { Some(input + output) } else { None }
-                })
-                .unwrap_or(0);
-
-            log::info!("[session {session_id}] Result: tokens={tokens}, cost={cost}");
-
-            if tokens > 0 || cost > 0.0 {
-                let _ = db::sessions::update_session_usage(&conn, session_id, tokens, cost);
-            }
-        }
-
-        // Extract summary from the result event's text content
-        let summary = last_result_payload.as_ref()
-            .and_then(|r| {
-                r.get("result").and_then(|v| v.as_str())
-                    .or_else(|| r.get("text").and_then(|v| v.as_str()))
-                    .or_else(|| r.get("content").and_then(|v| v.as_str()))
-            })
-            .map(|text| {
-                if text.len() > 500 { format!("{}...", &text[..497]) } else { text.to_string() }
-            });
-
-        log::info!("[session {session_id}] Summary: {:?}", summary.as_deref().unwrap_or("(none)"));
-
-        let _ = db::sessions::update_session_status(
-            &conn,
-            session_id,
-            "completed",
-            summary.as_deref().or(Some("Task completed")),
-        );
-    }
+    let summary = result_text.map(truncate_summary);
+    log::info!("[session {session_id}] Summary: {:?}", summary.as_deref().unwrap_or("(none)"));
 
-    let _ = app.emit(
-        "session:completed",
-        serde_json::json!({
-            "sessionId": session_id,
-        }),
-    );
+    finish_session(app, run, attempt, stderr_tail, result_is_error, errors, summary);
 }
 
 /// Read Codex's stdout line-by-line, parse and normalize events, emit to frontend.
@@ -1087,31 +1663,38 @@ fn stream_claude_output(
 /// 1. Parses the JSONL output into a CodexEvent via `parse_codex_output`
 /// 2. Normalizes into the unified ElfEvent format via `normalize_codex_event`
 /// 3. Detects "Phase N" transitions (when `elf_ids` is Some) to attribute events to the correct elf
-/// 4. Emits `elf:event` to the frontend for real-time display (with elfId when in team mode)
-/// 5. Persists the event to SQLite for history and replay
+/// 4. Emits the typed ElfEvents as `elf:event` to the frontend (with elfId when in team mode)
+/// 5. Persists the typed events to SQLite for history and replay
 ///
-/// Pass `elf_ids = None` for solo Codex runs, `Some(elf_ids)` for team runs.
+/// Solo runs have `run.elf_ids = None`, team runs `Some(elf_ids)`.
 /// When `Some`, a `CodexTeamParser` tracks phase transitions and tags each event
 /// with the elf ID for the active phase.
 ///
-/// When stdout closes (process finished), marks the session as completed.
+/// When stdout closes (process finished), waits for the exit status and marks the
+/// session "completed" or "failed" (see `finish_session`).
 fn stream_codex_output(
     stdout: std::process::ChildStdout,
     app: &AppHandle,
-    session_id: &str,
-    elf_ids: Option<Vec<String>>,
+    run: PrintRun,
+    attempt: i32,
+    stderr_tail: Option<JoinHandle<Vec<String>>>,
 ) {
     use std::io::BufRead;
 
+    let session_id = run.session_id.as_str();
+
     eprintln!("[ELVES] Starting Codex stdout stream for session {session_id}");
     log::info!("[session {session_id}] Starting Codex stdout stream reader");
 
     let db_state = app.state::<DbState>();
     let reader = std::io::BufReader::new(stdout);
     let mut event_count: u32 = 0;
+    let mut errors: Vec<String> = Vec::new();
+    // Codex reports usage per turn and no cost; each turn is priced as it completes
+    let mut usage = run.usage_ledger();
 
     // Create a phase parser for team runs, None for solo runs
-    let mut team_parser = elf_ids.map(codex_adapter::CodexTeamParser::new);
+    let mut team_parser = run.elf_ids.clone().map(codex_adapter::CodexTeamParser::new);
 
     for line in reader.lines() {
         match line {
@@ -1119,6 +1702,8 @@ fn stream_codex_output(
                 if let Some(codex_event) = codex_adapter::parse_codex_output(&line) {
                     let normalized = codex_adapter::normalize_codex_event(codex_event);
                     event_count += 1;
+                    record_activity(app, session_id, &normalized.events);
+                    budget::record_spend(app, session_id, None, &normalized.events);
 
                     if event_count <= 3 || normalized.event_type == "error" {
                         log::info!(
@@ -1128,35 +1713,39 @@ fn stream_codex_output(
                         );
                     }
 
+                    for typed in &normalized.events {
+                        if let ElfEvent::Error { message } = typed {
+                            errors.push(message.clone());
+                        }
+                    }
+
                     // Detect phase transitions and resolve the current elf ID for attribution
                     let elf_id: Option<String> = team_parser.as_mut().and_then(|parser| {
                         parser.detect_phase_transition(&line);
                         parser.current_elf_id().map(|id| id.to_string())
                     });
-
-                    // 1. Emit to frontend for real-time display (with elfId when in team mode)
-                    let _ = app.emit(
-                        "elf:event",
-                        serde_json::json!({
-                            "sessionId": session_id,
-                            "elfId": elf_id.as_deref(),
-                            "eventType": &normalized.event_type,
-                            "payload": &normalized.payload,
-                            "timestamp": normalized.timestamp,
-                            "runtime": "codex",
-                        }),
+                    usage.record(elf_id.as_deref(), &normalized.events);
+
+                    // 1. Emit to frontend for real-time display (with elfId when in team mode),
+                    // with stored secrets masked
+                    emit_elf_events(
+                        app,
+                        session_id,
+                        elf_id.as_deref(),
+                        Some(&normalized.runtime),
+                        &normalized.events,
+                        normalized.timestamp,
                     );
 
                     // 2. Persist to SQLite for history and replay (with elf attribution)
                     if let Ok(conn) = db_state.0.lock() {
-                        let payload_str = serde_json::to_string(&normalized.payload).unwrap_or_default();
-                        if let Err(e) = db::events::insert_event(
+                        if let Err(e) = store_elf_events(
                             &conn,
                             session_id,
                             elf_id.as_deref(),
                             &normalized.event_type,
-                            &payload_str,
-                            None,
+                            &normalized.payload,
+                            &normalized.events,
                         ) {
                             log::warn!("Failed to store Codex event for session {session_id}: {e}");
                         }
@@ -1173,21 +1762,12 @@ fn stream_codex_output(
     eprintln!("[ELVES] Codex stdout closed for session {session_id} after {event_count} events");
     log::info!("[session {session_id}] Codex stdout closed after {event_count} events");
 
-    // stdout closed — the Codex process has finished.
     if let Ok(conn) = db_state.0.lock() {
-        let _ = db::sessions::update_session_status(
-            &conn,
-            session_id,
-            "completed",
-            Some("Task completed"),
-        );
+        store_usage(&conn, session_id, &usage);
     }
 
-    let _ = app.emit(
-        "session:completed",
-        serde_json::json!({
-            "sessionId": session_id,
-        }),
-    );
+    // stdout closed — the Codex process is finishing. Codex reports failed turns as
+    // error events, so any error counts as a reported one.
+    let reported_error = !errors.is_empty();
+    finish_session(app, run, attempt, stderr_tail, reported_error, errors, None);
 }
-
@@ -0,0 +1,174 @@
+// Transcript watcher — records what interactive (PTY) runs do by tailing the transcript
+// their runtime writes (see agents/transcript.rs).
+//
+// Started alongside each agent PTY. Transcript lines become typed events that are stored
+// and emitted as `elf:event` like print-mode output, and Claude's session ID is recorded
+// for resume. Usage counts against the budget caps as it is read (see commands/budget.rs).
+// When the PTY exits, the run's usage is stored per model and its last message becomes
+// the session summary, unless the session already has one.
+
+use crate::agents::budget::BudgetTracker;
+use crate::agents::events::ElfEvent;
+use crate::agents::pricing::UsageLedger;
+use crate::agents::transcript::{TranscriptClaims, TranscriptKind, TranscriptLocator, TranscriptParser, TranscriptTail};
+use crate::commands::budget;
+use crate::commands::projects::DbState;
+use crate::commands::pty::PtyManager;
+use crate::commands::settings;
+use crate::commands::tasks;
+use crate::db;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// How often the transcript is checked for new lines.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for the agent to create its transcript.
+const FIND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// One PTY run whose transcript is to be tailed.
+pub(crate) struct PtyTranscript {
+    pub session_id: String,
+    pub project_id: String,
+    /// Set for team roles, so their events and usage are attributed to the role's elf.
+    pub elf_id: Option<String>,
+    pub pty_id: String,
+    pub runtime: String,
+    /// Model the run was started with, until the transcript reports one.
+    pub model: Option<String>,
+    pub locator: TranscriptLocator,
+}
+
+/// Snapshot where a PTY run will write its transcript. Call before spawning the agent,
+/// so transcripts of earlier runs are ignored. None if the home directory is unknown.
+pub(crate) fn locate(runtime: &str, working_dir: &str, resume_id: Option<&str>, hint: Option<String>) -> Option<TranscriptLocator> {
+    let home = dirs::home_dir()?;
+    Some(TranscriptLocator::new(TranscriptKind::for_runtime(runtime), &home, working_dir, resume_id, hint))
+}
+
+/// Track a PTY run's spend and tail its transcript in a background thread until the PTY
+/// exits.
+pub(crate) fn watch_pty_transcript(app: &AppHandle, run: PtyTranscript) {
+    // Usage of the session's earlier runs is already stored and keeps counting
+    let prior_usd = {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.0.lock();
+        conn.ok()
+            .and_then(|conn| db::sessions::get_session(&conn, &run.session_id).ok().flatten())
+            .map_or(0.0, |session| session.cost_estimate)
+    };
+    app.state::<BudgetTracker>().track_pty(
+        &run.session_id,
+        &run.project_id,
+        prior_usd,
+        &run.pty_id,
+        settings::pricing_table(),
+        run.model.clone(),
+    );
+    let app = app.clone();
+    std::thread::spawn(move || {
+        tail_transcript(&app, &run);
+        app.state::<BudgetTracker>().untrack_pty(&run.session_id, &run.pty_id);
+    });
+}
+
+fn tail_transcript(app: &AppHandle, run: &PtyTranscript) {
+    let session_id = run.session_id.as_str();
+    let pty_mgr = app.state::<PtyManager>();
+    let claims = app.state::<TranscriptClaims>();
+
+    let started = Instant::now();
+    let (path, offset) = loop {
+        if let Some(found) = claims.claim(&run.locator) {
+            break found;
+        }
+        if !pty_mgr.is_running(&run.pty_id) || started.elapsed() > FIND_TIMEOUT {
+            log::info!("[session {session_id}] No transcript found for PTY {}", run.pty_id);
+            return;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    log::info!("[session {session_id}] Tailing transcript {}", path.display());
+
+    let kind = run.locator.kind();
+    let mut tail = TranscriptTail::new(path, offset);
+    let mut parser = TranscriptParser::new(kind);
+    let mut usage = UsageLedger::new(settings::pricing_table(), run.model.clone());
+    let mut last_text: Option<String> = None;
+
+    loop {
+        // Check before reading, so lines written just before the agent exits are read
+        let running = pty_mgr.is_running(&run.pty_id);
+        match tail.read_lines() {
+            Ok(lines) => {
+                for line in lines {
+                    let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+                        continue;
+                    };
+                    let events = parser.parse_line(&value);
+                    record_events(app, run, kind, &events, &mut usage, &mut last_text);
+                }
+            }
+            Err(e) => {
+                log::warn!("[session {session_id}] Failed to read transcript {}: {e}", tail.path().display());
+                break;
+            }
+        }
+        if !running {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    let remaining = parser.finish();
+    record_events(app, run, kind, &remaining, &mut usage, &mut last_text);
+    claims.release(tail.path());
+
+    let db_state = app.state::<DbState>();
+    let Ok(conn) = db_state.0.lock() else {
+        return;
+    };
+    tasks::store_usage(&conn, session_id, &usage);
+    if let Some(summary) = last_text.map(tasks::truncate_summary) {
+        if let Err(e) = db::sessions::set_summary_if_missing(&conn, session_id, &summary) {
+            log::warn!("[session {session_id}] Failed to store transcript summary: {e}");
+        }
+    }
+}
+
+/// Price, emit and store the events of one transcript line, count its usage against the
+/// budget caps, and record Claude's session ID for solo runs.
+fn record_events(
+    app: &AppHandle,
+    run: &PtyTranscript,
+    kind: TranscriptKind,
+    events: &[ElfEvent],
+    usage: &mut UsageLedger,
+    last_text: &mut Option<String>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    let session_id = run.session_id.as_str();
+    let db_state = app.state::<DbState>();
+    usage.record(run.elf_id.as_deref(), events);
+    budget::record_spend(app, session_id, Some(&run.pty_id), events);
+    for event in events {
+        match event {
+            // Team roles each have their own Claude session; none of them resumes the team
+            ElfEvent::Init { session_id: Some(claude_sid), .. } if kind == TranscriptKind::Claude && run.elf_id.is_none() => {
+                tasks::record_claude_session_id(app, &db_state, session_id, claude_sid);
+            }
+            ElfEvent::Text { text } => *last_text = Some(text.clone()),
+            _ => {}
+        }
+    }
+
+    let timestamp = chrono::Utc::now().timestamp();
+    tasks::emit_elf_events(app, session_id, run.elf_id.as_deref(), Some(&run.runtime), events, timestamp);
+    if let Ok(conn) = db_state.0.lock() {
+        let payload = serde_json::to_value(events).unwrap_or_default();
+        if let Err(e) = tasks::store_elf_events(&conn, session_id, run.elf_id.as_deref(), "transcript", &payload, events) {
+            log::warn!("[session {session_id}] Failed to store transcript events: {e}");
+        }
+    };
+}
@@ -0,0 +1,111 @@
+// Session watchdog commands — the background thread that enforces session limits, and
+// per-session overrides of the project's limits.
+//
+// Stalled sessions get a `session:stalled` warning. Sessions past a limit are marked
+// `timed_out`, get a final `session:stalled`, are stopped gracefully, and then
+// `session:timed_out` is emitted.
+
+use crate::agents::process::ProcessManager;
+use crate::agents::watchdog::{LimitKind, Verdict, Watchdog, WatchdogLimits};
+use crate::commands::projects::DbState;
+use crate::commands::pty::PtyManager;
+use crate::commands::settings;
+use crate::db;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// How often running sessions are checked against their limits.
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Replace the watchdog limits of a running session, overriding its project's limits.
+///
+/// Returns false if the session is not running.
+#[tauri::command]
+pub fn set_session_limits(
+    watchdog: State<'_, Watchdog>,
+    session_id: String,
+    limits: WatchdogLimits,
+) -> Result<bool, String> {
+    Ok(watchdog.set_limits(&session_id, limits))
+}
+
+/// Start the watchdog thread. Runs for the lifetime of the app.
+pub fn spawn_watchdog(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SCAN_INTERVAL);
+        for (session_id, pty_ids, verdict) in app.state::<Watchdog>().scan(Instant::now()) {
+            match verdict {
+                Verdict::Stalled { idle } => {
+                    log::info!("[session {session_id}] No activity for {}s", idle.as_secs());
+                    let _ = app.emit(
+                        "session:stalled",
+                        serde_json::json!({
+                            "sessionId": &session_id,
+                            "reason": "idle",
+                            "idleSecs": idle.as_secs(),
+                            "stopping": false,
+                        }),
+                    );
+                }
+                Verdict::Exceeded(limit) => {
+                    let app = app.clone();
+                    std::thread::spawn(move || time_out_session(&app, &session_id, &pty_ids, limit));
+                }
+            }
+        }
+    });
+}
+
+/// Stop a session that ran past `limit` and mark it `timed_out`.
+///
+/// The status is written before stopping so the output reader records the final result
+/// without completing, failing, or retrying the session.
+fn time_out_session(app: &AppHandle, session_id: &str, pty_ids: &[String], limit: LimitKind) {
+    log::info!("[session {session_id}] Exceeded watchdog limit {limit:?}, stopping");
+    {
+        let db_state = app.state::<DbState>();
+        let Ok(conn) = db_state.0.lock() else {
+            return;
+        };
+        let active = matches!(
+            db::sessions::get_session(&conn, session_id),
+            Ok(Some(session)) if session.status == "active"
+        );
+        if !active {
+            return;
+        }
+        if let Err(e) = db::sessions::update_session_status(&conn, session_id, "timed_out", Some(limit.summary())) {
+            log::warn!("[session {session_id}] Failed to mark session timed out: {e}");
+        }
+    }
+
+    let _ = app.emit(
+        "session:stalled",
+        serde_json::json!({
+            "sessionId": session_id,
+            "reason": limit,
+            "stopping": true,
+        }),
+    );
+
+    let timeouts = settings::stop_timeouts();
+    if pty_ids.is_empty() {
+        let process_mgr = app.state::<ProcessManager>();
+        process_mgr.stop(session_id, timeouts);
+        process_mgr.stop_team(session_id, timeouts);
+    }
+    for pty_id in pty_ids {
+        if let Err(e) = app.state::<PtyManager>().stop(pty_id, timeouts) {
+            log::warn!("[session {session_id}] Failed to stop PTY {pty_id}: {e}");
+        }
+    }
+
+    let _ = app.emit(
+        "session:timed_out",
+        serde_json::json!({
+            "sessionId": session_id,
+            "reason": limit,
+            "summary": limit.summary(),
+        }),
+    );
+}
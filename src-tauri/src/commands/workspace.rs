@@ -12,7 +12,10 @@ use std::fs;
 use std::path::Path;
 
 use super::git::run_git;
+use crate::commands::projects::DbState;
+use crate::db;
 use crate::project::config::{self, ProjectConfig};
+use tauri::State;
 
 // ---------------------------------------------------------------------------
 // Types — single-repo workspace
@@ -143,7 +146,7 @@ fn validate_slug(slug: &str) -> Result<(), String> {
 }
 
 /// Resolve the worktree directory path for a given slug.
-fn worktree_dir(project_path: &str, slug: &str) -> String {
+pub(crate) fn worktree_dir(project_path: &str, slug: &str) -> String {
     Path::new(project_path)
         .join(".claude")
         .join("worktrees")
@@ -522,6 +525,7 @@ pub fn push_workspace(project_path: String, slug: String) -> Result<String, Stri
 /// to be installed and authenticated. Returns the PR URL on success.
 #[tauri::command]
 pub fn create_pr_from_workspace(
+    db: State<'_, DbState>,
     project_path: String,
     slug: String,
     title: String,
@@ -554,6 +558,7 @@ pub fn create_pr_from_workspace(
     }
 
     let pr_url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    record_ship(&db, &project_path, &slug, "pr");
     Ok(pr_url)
 }
 
@@ -564,6 +569,7 @@ pub fn create_pr_from_workspace(
 /// Returns true on success.
 #[tauri::command]
 pub fn merge_workspace(
+    db: State<'_, DbState>,
     project_path: String,
     slug: String,
     target_branch: String,
@@ -597,6 +603,7 @@ pub fn merge_workspace(
         }
     }
 
+    record_ship(&db, &project_path, &slug, "merge");
     Ok(true)
 }
 
@@ -628,13 +635,35 @@ pub fn remove_workspace(
 /// extraction is handled separately by the frontend calling existing memory commands.
 #[tauri::command]
 pub fn complete_workspace(
+    db: State<'_, DbState>,
     project_path: String,
     slug: String,
     strategy: String,
     #[allow(unused_variables)] extract_memory: bool,
 ) -> Result<bool, String> {
     validate_slug(&slug)?;
-    complete_workspace_for_repo(&project_path, &slug, &strategy)
+    let completed = complete_workspace_for_repo(&project_path, &slug, &strategy)?;
+    record_ship(&db, &project_path, &slug, "complete");
+    Ok(completed)
+}
+
+/// Log that a workspace of the project at `project_path` was shipped, for insights.
+/// Failing to log never fails the git operation that already succeeded.
+fn record_ship(db: &DbState, project_path: &str, slug: &str, action: &str) {
+    let Ok(conn) = db.0.lock() else {
+        return;
+    };
+    let project = match db::projects::get_project_by_path(&conn, project_path) {
+        Ok(Some(project)) => project,
+        Ok(None) => return,
+        Err(e) => {
+            log::warn!("Failed to look up project at {project_path}: {e}");
+            return;
+        }
+    };
+    if let Err(e) = db::insights::record_workspace_ship(&conn, &project.id, slug, action) {
+        log::warn!("Failed to record shipped workspace {slug}: {e}");
+    }
 }
 
 // ---------------------------------------------------------------------------
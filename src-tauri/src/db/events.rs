@@ -19,8 +19,42 @@ pub struct EventRow {
     /// Optional personality-driven status message for the UI.
     pub funny_status: Option<String>,
     pub timestamp: i64,
+    /// Typed ElfEvent kind (thinking, text, tool_call, ...) when the payload is a typed
+    /// event; None for raw runtime lines and rows written before typed events.
+    pub kind: Option<String>,
 }
 
+/// One page of a session's events, in insertion (id) order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPage {
+    pub events: Vec<EventRow>,
+    /// Cursor for the next page: pass as `after_id`. None when this is the last page.
+    pub next_after_id: Option<i64>,
+}
+
+/// Number of events of one type in a session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTypeCount {
+    pub event_type: String,
+    pub count: i64,
+}
+
+/// Event count and type histogram for a session.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStats {
+    pub total: i64,
+    /// Per-type counts, most frequent first.
+    pub by_type: Vec<EventTypeCount>,
+}
+
+/// Largest page `list_events_page` returns, whatever the caller asks for.
+pub const MAX_PAGE_SIZE: i64 = 1000;
+
+const EVENT_COLUMNS: &str = "id, session_id, elf_id, event_type, payload, funny_status, timestamp, kind";
+
 /// Insert a new event into the session event log. Returns the created event row.
 ///
 /// The `timestamp` is set to the current UTC time. The `id` is auto-incremented by SQLite.
@@ -32,71 +66,151 @@ pub fn insert_event(
     payload: &str,
     funny_status: Option<&str>,
 ) -> Result<EventRow, DbError> {
-    let now = chrono::Utc::now().timestamp();
+    insert_row(conn, session_id, elf_id, event_type, payload, funny_status, None, chrono::Utc::now().timestamp())
+}
+
+/// Insert a typed ElfEvent. `payload` is the serialized event; `kind` is stored both as
+/// the event type and in the indexed `kind` column.
+pub fn insert_typed_event(
+    conn: &Connection,
+    session_id: &str,
+    elf_id: Option<&str>,
+    kind: &str,
+    payload: &str,
+) -> Result<EventRow, DbError> {
+    insert_row(conn, session_id, elf_id, kind, payload, None, Some(kind), chrono::Utc::now().timestamp())
+}
+
+/// Insert a typed ElfEvent that happened at `timestamp` (Unix seconds), e.g. from an
+/// imported transcript.
+pub fn insert_typed_event_at(
+    conn: &Connection,
+    session_id: &str,
+    elf_id: Option<&str>,
+    kind: &str,
+    payload: &str,
+    timestamp: i64,
+) -> Result<EventRow, DbError> {
+    insert_row(conn, session_id, elf_id, kind, payload, None, Some(kind), timestamp)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_row(
+    conn: &Connection,
+    session_id: &str,
+    elf_id: Option<&str>,
+    event_type: &str,
+    payload: &str,
+    funny_status: Option<&str>,
+    kind: Option<&str>,
+    timestamp: i64,
+) -> Result<EventRow, DbError> {
+    // Last line of defense: no stored secret value is ever persisted in an event
+    let payload = crate::secrets::redact::redact(payload);
     conn.execute(
-        "INSERT INTO events (session_id, elf_id, event_type, payload, funny_status, timestamp)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![session_id, elf_id, event_type, payload, funny_status, now],
+        "INSERT INTO events (session_id, elf_id, event_type, payload, funny_status, timestamp, kind)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![session_id, elf_id, event_type, payload, funny_status, timestamp, kind],
     )?;
 
     let row_id = conn.last_insert_rowid();
-    let mut stmt = conn.prepare(
-        "SELECT id, session_id, elf_id, event_type, payload, funny_status, timestamp
-         FROM events WHERE id = ?1",
+    let event = conn.query_row(
+        &format!("SELECT {EVENT_COLUMNS} FROM events WHERE id = ?1"),
+        params![row_id],
+        map_event_row,
     )?;
 
-    let event = stmt.query_row(params![row_id], |row| {
-        Ok(EventRow {
-            id: row.get(0)?,
-            session_id: row.get(1)?,
-            elf_id: row.get(2)?,
-            event_type: row.get(3)?,
-            payload: row.get(4)?,
-            funny_status: row.get(5)?,
-            timestamp: row.get(6)?,
-        })
-    })?;
-
     Ok(event)
 }
 
-/// List all events for a session, ordered chronologically (oldest first).
-/// Secondary sort by ID ensures stable ordering for events with the same timestamp.
+/// List all events for a session in the order they were recorded (by id), the same
+/// order as `list_events_page`. Imported and back-dated events keep their place.
 pub fn list_events(
     conn: &Connection,
     session_id: &str,
 ) -> Result<Vec<EventRow>, DbError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, session_id, elf_id, event_type, payload, funny_status, timestamp
-         FROM events WHERE session_id = ?1 ORDER BY timestamp ASC, id ASC",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {EVENT_COLUMNS} FROM events WHERE session_id = ?1 ORDER BY id ASC"
+    ))?;
 
     let rows = stmt
-        .query_map(params![session_id], |row| {
-            Ok(EventRow {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                elf_id: row.get(2)?,
-                event_type: row.get(3)?,
-                payload: row.get(4)?,
-                funny_status: row.get(5)?,
-                timestamp: row.get(6)?,
-            })
-        })?
+        .query_map(params![session_id], map_event_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(rows)
 }
 
-/// Count the total number of events in a session.
-#[allow(dead_code)]
-pub fn count_events(conn: &Connection, session_id: &str) -> Result<i64, DbError> {
-    let count: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM events WHERE session_id = ?1",
-        params![session_id],
-        |row| row.get(0),
+/// List one page of a session's events after the `after_id` cursor (exclusive), oldest
+/// first by id. `event_types` restricts the page to those types; empty means all types.
+/// `limit` is clamped to 1..=MAX_PAGE_SIZE.
+pub fn list_events_page(
+    conn: &Connection,
+    session_id: &str,
+    after_id: Option<i64>,
+    limit: i64,
+    event_types: &[String],
+) -> Result<EventPage, DbError> {
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+    let mut sql = format!("SELECT {EVENT_COLUMNS} FROM events WHERE session_id = ?1 AND id > ?2");
+    let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> =
+        vec![Box::new(session_id.to_string()), Box::new(after_id.unwrap_or(0))];
+    if !event_types.is_empty() {
+        let placeholders: Vec<String> = (0..event_types.len())
+            .map(|i| format!("?{}", i + 3))
+            .collect();
+        sql.push_str(&format!(" AND event_type IN ({})", placeholders.join(", ")));
+        for event_type in event_types {
+            param_values.push(Box::new(event_type.clone()));
+        }
+    }
+    // Fetch one extra row to learn whether another page follows
+    sql.push_str(&format!(" ORDER BY id ASC LIMIT ?{}", param_values.len() + 1));
+    param_values.push(Box::new(limit + 1));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
+    let mut events = stmt
+        .query_map(params_refs.as_slice(), map_event_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let next_after_id = if events.len() as i64 > limit {
+        events.truncate(limit as usize);
+        events.last().map(|e| e.id)
+    } else {
+        None
+    };
+    Ok(EventPage { events, next_after_id })
+}
+
+/// Count a session's events, in total and per event type.
+pub fn event_stats(conn: &Connection, session_id: &str) -> Result<EventStats, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT event_type, COUNT(*) AS n FROM events WHERE session_id = ?1
+         GROUP BY event_type ORDER BY n DESC, event_type ASC",
     )?;
-    Ok(count)
+    let by_type = stmt
+        .query_map(params![session_id], |row| {
+            Ok(EventTypeCount {
+                event_type: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let total = by_type.iter().map(|c| c.count).sum();
+    Ok(EventStats { total, by_type })
+}
+
+fn map_event_row(row: &rusqlite::Row) -> rusqlite::Result<EventRow> {
+    Ok(EventRow {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        elf_id: row.get(2)?,
+        event_type: row.get(3)?,
+        payload: row.get(4)?,
+        funny_status: row.get(5)?,
+        timestamp: row.get(6)?,
+        kind: row.get(7)?,
+    })
 }
 
 #[cfg(test)]
@@ -175,6 +289,27 @@ mod tests {
 
         assert!(event.elf_id.is_none());
         assert!(event.funny_status.is_none());
+        assert!(event.kind.is_none());
+    }
+
+    #[test]
+    fn insert_typed_event_sets_kind() {
+        let conn = test_conn();
+        seed_session(&conn, "proj-1", "sess-1");
+
+        let event = insert_typed_event(
+            &conn,
+            "sess-1",
+            Some("elf-1"),
+            "tool_call",
+            r#"{"kind":"tool_call","id":"t1","name":"Read","input":{}}"#,
+        )
+        .expect("Should insert typed event");
+
+        assert_eq!(event.event_type, "tool_call");
+        assert_eq!(event.kind.as_deref(), Some("tool_call"));
+        let events = list_events(&conn, "sess-1").unwrap();
+        assert_eq!(events[0].kind.as_deref(), Some("tool_call"));
     }
 
     #[test]
@@ -187,40 +322,42 @@ mod tests {
     }
 
     #[test]
-    fn list_events_ordered_chronologically() {
+    fn list_events_ordered_by_id() {
         let conn = test_conn();
         seed_session(&conn, "proj-1", "sess-1");
 
-        // Insert events with manually controlled timestamps for deterministic ordering
+        // A back-dated event stays after the events recorded before it
         conn.execute(
             "INSERT INTO events (session_id, elf_id, event_type, payload, funny_status, timestamp)
-             VALUES ('sess-1', NULL, 'second', '{}', NULL, 2000)",
+             VALUES ('sess-1', NULL, 'recorded', '{}', NULL, 2000)",
             [],
         ).unwrap();
         conn.execute(
             "INSERT INTO events (session_id, elf_id, event_type, payload, funny_status, timestamp)
-             VALUES ('sess-1', NULL, 'first', '{}', NULL, 1000)",
+             VALUES ('sess-1', NULL, 'back_dated', '{}', NULL, 1000)",
             [],
         ).unwrap();
 
         let events = list_events(&conn, "sess-1").unwrap();
         assert_eq!(events.len(), 2);
-        assert_eq!(events[0].event_type, "first");
-        assert_eq!(events[1].event_type, "second");
+        assert_eq!(events[0].event_type, "recorded");
+        assert_eq!(events[1].event_type, "back_dated");
+        let page = list_events_page(&conn, "sess-1", None, 10, &[]).unwrap();
+        assert_eq!(page.events.iter().map(|e| e.id).collect::<Vec<_>>(), events.iter().map(|e| e.id).collect::<Vec<_>>());
     }
 
     #[test]
-    fn count_events_returns_correct_count() {
+    fn event_stats_count_all_events() {
         let conn = test_conn();
         seed_session(&conn, "proj-1", "sess-1");
 
-        assert_eq!(count_events(&conn, "sess-1").unwrap(), 0);
+        assert_eq!(event_stats(&conn, "sess-1").unwrap().total, 0);
 
         insert_event(&conn, "sess-1", None, "type_a", "{}", None).unwrap();
         insert_event(&conn, "sess-1", None, "type_b", "{}", None).unwrap();
         insert_event(&conn, "sess-1", None, "type_c", "{}", None).unwrap();
 
-        assert_eq!(count_events(&conn, "sess-1").unwrap(), 3);
+        assert_eq!(event_stats(&conn, "sess-1").unwrap().total, 3);
     }
 
     #[test]
@@ -233,13 +370,69 @@ mod tests {
         insert_event(&conn, "sess-1", None, "type_b", "{}", None).unwrap();
         insert_event(&conn, "sess-2", None, "type_c", "{}", None).unwrap();
 
-        assert_eq!(count_events(&conn, "sess-1").unwrap(), 2);
-        assert_eq!(count_events(&conn, "sess-2").unwrap(), 1);
+        assert_eq!(event_stats(&conn, "sess-1").unwrap().total, 2);
+        assert_eq!(event_stats(&conn, "sess-2").unwrap().total, 1);
 
         let events = list_events(&conn, "sess-1").unwrap();
         assert_eq!(events.len(), 2);
     }
 
+    #[test]
+    fn list_events_page_walks_cursor() {
+        let conn = test_conn();
+        seed_session(&conn, "proj-1", "sess-1");
+        seed_session(&conn, "proj-1", "sess-2");
+        for i in 0..5 {
+            insert_event(&conn, "sess-1", None, "output", &format!("{{\"n\":{i}}}"), None).unwrap();
+        }
+        insert_event(&conn, "sess-2", None, "output", "{}", None).unwrap();
+
+        let first = list_events_page(&conn, "sess-1", None, 2, &[]).unwrap();
+        assert_eq!(first.events.len(), 2);
+        let cursor = first.next_after_id.expect("More pages should follow");
+        assert_eq!(cursor, first.events[1].id);
+
+        let second = list_events_page(&conn, "sess-1", Some(cursor), 2, &[]).unwrap();
+        assert!(second.events[0].id > cursor);
+        let third = list_events_page(&conn, "sess-1", second.next_after_id, 2, &[]).unwrap();
+        assert_eq!(third.events.len(), 1);
+        assert!(third.next_after_id.is_none(), "Last page has no cursor");
+        assert!(third.events.iter().all(|e| e.session_id == "sess-1"));
+    }
+
+    #[test]
+    fn list_events_page_filters_types() {
+        let conn = test_conn();
+        seed_session(&conn, "proj-1", "sess-1");
+        insert_event(&conn, "sess-1", None, "tool_call", "{}", None).unwrap();
+        insert_event(&conn, "sess-1", None, "text", "{}", None).unwrap();
+        insert_event(&conn, "sess-1", None, "error", "{}", None).unwrap();
+
+        let types = vec!["tool_call".to_string(), "error".to_string()];
+        let page = list_events_page(&conn, "sess-1", None, 10, &types).unwrap();
+        let found: Vec<&str> = page.events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(found, ["tool_call", "error"]);
+        assert!(page.next_after_id.is_none());
+    }
+
+    #[test]
+    fn event_stats_counts_by_type() {
+        let conn = test_conn();
+        seed_session(&conn, "proj-1", "sess-1");
+        for event_type in ["text", "tool_call", "tool_call", "tool_result", "tool_call"] {
+            insert_event(&conn, "sess-1", None, event_type, "{}", None).unwrap();
+        }
+
+        let stats = event_stats(&conn, "sess-1").unwrap();
+        assert_eq!(stats.total, 5);
+        assert_eq!(
+            stats.by_type[0],
+            EventTypeCount { event_type: "tool_call".to_string(), count: 3 }
+        );
+        assert_eq!(stats.by_type.len(), 3);
+        assert_eq!(event_stats(&conn, "missing").unwrap().total, 0);
+    }
+
     #[test]
     fn serializes_to_camel_case_json() {
         let conn = test_conn();
@@ -0,0 +1,404 @@
+// Insights queries — statistics computed from ELVES' own records: sessions per project,
+// runtime and status, outcomes, cost and tokens per day, durations by template, roles,
+// memory growth, and how many workspaces get shipped. Also the workspace ship log.
+//
+// Every query takes the same filter: a date range over when sessions started (and
+// memories were added), and optionally one project.
+
+use rusqlite::{params, Connection, Params};
+use serde::{Deserialize, Serialize};
+
+use super::DbError;
+
+/// Which records insights cover. Bounds are Unix seconds; `until` is exclusive.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InsightsFilter {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub project_id: Option<String>,
+}
+
+/// A name with how often it occurs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsightCount {
+    pub name: String,
+    pub count: i64,
+}
+
+/// How finished sessions ended.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutcomeRates {
+    pub completed: i64,
+    /// Sessions that failed, errored or timed out.
+    pub failed: i64,
+    /// Sessions cancelled or interrupted.
+    pub stopped: i64,
+    /// Completed sessions as a fraction of completed and failed ones; 0 when there are none.
+    pub success_rate: f64,
+}
+
+/// Sessions, tokens and cost of the sessions started on one UTC day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub sessions: i64,
+    pub tokens: i64,
+    pub cost_usd: f64,
+}
+
+/// How long finished sessions started from one template took on average.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateDuration {
+    pub template_id: String,
+    /// The template's name, or its ID once it has been deleted.
+    pub name: String,
+    pub sessions: i64,
+    pub avg_duration_secs: f64,
+}
+
+/// Memories added on one UTC day, and how many there were by its end.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryGrowth {
+    pub date: String,
+    pub added: i64,
+    pub total: i64,
+}
+
+/// Workspaces that sessions ran in, and how many of them were merged or opened as a PR.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceShipRate {
+    pub workspaces: i64,
+    pub shipped: i64,
+    /// Shipped workspaces as a fraction of all of them; 0 when there are none.
+    pub ship_rate: f64,
+}
+
+/// Everything the database insights view shows.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbInsights {
+    pub total_sessions: i64,
+    pub total_tokens: i64,
+    pub total_cost_usd: f64,
+    pub sessions_by_project: Vec<InsightCount>,
+    pub sessions_by_runtime: Vec<InsightCount>,
+    pub sessions_by_status: Vec<InsightCount>,
+    pub outcomes: OutcomeRates,
+    pub failure_reasons: Vec<InsightCount>,
+    pub daily_usage: Vec<DailyUsage>,
+    pub template_durations: Vec<TemplateDuration>,
+    pub top_roles: Vec<InsightCount>,
+    pub memory_growth: Vec<MemoryGrowth>,
+    pub workspaces: WorkspaceShipRate,
+}
+
+/// How many roles `top_roles` lists.
+const TOP_ROLES: i64 = 10;
+
+/// Conditions selecting the filtered sessions, with `?1` since, `?2` until and `?3` the
+/// project. Qualified with the `s` alias so they can be used in joins.
+const SESSION_FILTER: &str = "(?1 IS NULL OR s.started_at >= ?1) AND (?2 IS NULL OR s.started_at < ?2) \
+     AND (?3 IS NULL OR s.project_id = ?3)";
+
+/// Compute insights over the sessions, memories and workspaces matching the filter.
+pub fn load_insights(conn: &Connection, filter: &InsightsFilter) -> Result<DbInsights, DbError> {
+    let bounds = params![filter.since, filter.until, filter.project_id];
+
+    let (total_sessions, total_tokens, total_cost_usd) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*), COALESCE(SUM(s.tokens_used), 0), COALESCE(SUM(s.cost_estimate), 0.0)
+             FROM sessions s WHERE {SESSION_FILTER}"
+        ),
+        bounds,
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+
+    let sessions_by_status = counts(
+        conn,
+        &format!("SELECT s.status, COUNT(*) FROM sessions s WHERE {SESSION_FILTER} GROUP BY s.status ORDER BY 2 DESC, 1"),
+        bounds,
+    )?;
+
+    Ok(DbInsights {
+        total_sessions,
+        total_tokens,
+        total_cost_usd,
+        sessions_by_project: counts(
+            conn,
+            &format!(
+                "SELECT p.name, COUNT(*) FROM sessions s JOIN projects p ON p.id = s.project_id
+                 WHERE {SESSION_FILTER} GROUP BY s.project_id ORDER BY 2 DESC, 1"
+            ),
+            bounds,
+        )?,
+        sessions_by_runtime: counts(
+            conn,
+            &format!("SELECT s.runtime, COUNT(*) FROM sessions s WHERE {SESSION_FILTER} GROUP BY s.runtime ORDER BY 2 DESC, 1"),
+            bounds,
+        )?,
+        outcomes: outcome_rates(&sessions_by_status),
+        sessions_by_status,
+        failure_reasons: counts(
+            conn,
+            &format!(
+                "SELECT s.failure_reason, COUNT(*) FROM sessions s
+                 WHERE {SESSION_FILTER} AND s.failure_reason IS NOT NULL
+                 GROUP BY s.failure_reason ORDER BY 2 DESC, 1"
+            ),
+            bounds,
+        )?,
+        daily_usage: daily_usage(conn, filter)?,
+        template_durations: template_durations(conn, filter)?,
+        top_roles: counts(
+            conn,
+            &format!(
+                "SELECT e.role, COUNT(*) FROM elves e JOIN sessions s ON s.id = e.session_id
+                 WHERE {SESSION_FILTER} AND e.role IS NOT NULL
+                 GROUP BY e.role ORDER BY 2 DESC, 1 LIMIT {TOP_ROLES}"
+            ),
+            bounds,
+        )?,
+        memory_growth: memory_growth(conn, filter)?,
+        workspaces: workspace_ship_rate(conn, filter)?,
+    })
+}
+
+/// Run a query selecting a name and a count.
+fn counts(conn: &Connection, sql: &str, params: impl Params) -> Result<Vec<InsightCount>, DbError> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, |row| {
+        Ok(InsightCount {
+            name: row.get(0)?,
+            count: row.get(1)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+}
+
+fn outcome_rates(by_status: &[InsightCount]) -> OutcomeRates {
+    let mut rates = OutcomeRates::default();
+    for status in by_status {
+        match status.name.as_str() {
+            "completed" => rates.completed += status.count,
+            "failed" | "error" | "timed_out" => rates.failed += status.count,
+            "cancelled" | "interrupted" => rates.stopped += status.count,
+            _ => {}
+        }
+    }
+    let decided = rates.completed + rates.failed;
+    if decided > 0 {
+        rates.success_rate = rates.completed as f64 / decided as f64;
+    }
+    rates
+}
+
+fn daily_usage(conn: &Connection, filter: &InsightsFilter) -> Result<Vec<DailyUsage>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT date(s.started_at, 'unixepoch') AS day, COUNT(*), COALESCE(SUM(s.tokens_used), 0),
+                COALESCE(SUM(s.cost_estimate), 0.0)
+         FROM sessions s WHERE {SESSION_FILTER} GROUP BY day ORDER BY day"
+    ))?;
+    let rows = stmt.query_map(params![filter.since, filter.until, filter.project_id], |row| {
+        Ok(DailyUsage {
+            date: row.get(0)?,
+            sessions: row.get(1)?,
+            tokens: row.get(2)?,
+            cost_usd: row.get(3)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+}
+
+fn template_durations(conn: &Connection, filter: &InsightsFilter) -> Result<Vec<TemplateDuration>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT s.template_id, COALESCE(t.name, s.template_id), COUNT(*),
+                AVG(s.ended_at - s.started_at)
+         FROM sessions s LEFT JOIN templates t ON t.id = s.template_id
+         WHERE {SESSION_FILTER} AND s.template_id IS NOT NULL AND s.ended_at IS NOT NULL
+         GROUP BY s.template_id ORDER BY 3 DESC, 2"
+    ))?;
+    let rows = stmt.query_map(params![filter.since, filter.until, filter.project_id], |row| {
+        Ok(TemplateDuration {
+            template_id: row.get(0)?,
+            name: row.get(1)?,
+            sessions: row.get(2)?,
+            avg_duration_secs: row.get(3)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+}
+
+/// Memories added per day in the range. Totals include memories added before it, and
+/// global memories unless a project is selected.
+fn memory_growth(conn: &Connection, filter: &InsightsFilter) -> Result<Vec<MemoryGrowth>, DbError> {
+    let mut total: i64 = match filter.since {
+        Some(since) => conn.query_row(
+            "SELECT COUNT(*) FROM memory WHERE created_at < ?1 AND (?2 IS NULL OR project_id = ?2)",
+            params![since, filter.project_id],
+            |row| row.get(0),
+        )?,
+        None => 0,
+    };
+    let mut stmt = conn.prepare(
+        "SELECT date(created_at, 'unixepoch') AS day, COUNT(*) FROM memory
+         WHERE (?1 IS NULL OR created_at >= ?1) AND (?2 IS NULL OR created_at < ?2)
+           AND (?3 IS NULL OR project_id = ?3)
+         GROUP BY day ORDER BY day",
+    )?;
+    let days = stmt
+        .query_map(params![filter.since, filter.until, filter.project_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(days
+        .into_iter()
+        .map(|(date, added)| {
+            total += added;
+            MemoryGrowth { date, added, total }
+        })
+        .collect())
+}
+
+/// Workspaces of the filtered sessions, and how many of them have been shipped, at any
+/// time since.
+fn workspace_ship_rate(conn: &Connection, filter: &InsightsFilter) -> Result<WorkspaceShipRate, DbError> {
+    let (workspaces, shipped): (i64, i64) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*), COALESCE(SUM(EXISTS (
+                 SELECT 1 FROM workspace_ships w WHERE w.project_id = ws.project_id AND w.slug = ws.slug
+             )), 0)
+             FROM (SELECT DISTINCT s.project_id, s.worktree_slug AS slug FROM sessions s
+                   WHERE {SESSION_FILTER} AND s.worktree_slug IS NOT NULL) ws"
+        ),
+        params![filter.since, filter.until, filter.project_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let ship_rate = if workspaces > 0 { shipped as f64 / workspaces as f64 } else { 0.0 };
+    Ok(WorkspaceShipRate { workspaces, shipped, ship_rate })
+}
+
+/// Record that a project's workspace was shipped. `action` is "merge", "pr" or
+/// "complete" (the Ship It flow).
+pub fn record_workspace_ship(conn: &Connection, project_id: &str, slug: &str, action: &str) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT INTO workspace_ships (project_id, slug, action, shipped_at) VALUES (?1, ?2, ?3, ?4)",
+        params![project_id, slug, action, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{elves, schema, sessions};
+
+    const DAY: i64 = 86_400;
+    /// 2025-01-01T00:00:00Z.
+    const JAN_1: i64 = 1_735_689_600;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        conn
+    }
+
+    fn seed_project(conn: &Connection, id: &str, name: &str) {
+        conn.execute(
+            "INSERT INTO projects (id, name, path, default_runtime, created_at, updated_at)
+             VALUES (?1, ?2, ?3, 'claude-code', 0, 0)",
+            params![id, name, format!("/tmp/{id}")],
+        )
+        .expect("Should seed project");
+    }
+
+    /// Insert a session that started at `started` and ran for `secs`.
+    fn seed_session(conn: &Connection, id: &str, project_id: &str, runtime: &str, status: &str, started: i64, secs: i64) {
+        sessions::create_session(conn, id, project_id, "Task", runtime, None).unwrap();
+        conn.execute(
+            "UPDATE sessions SET status = ?2, started_at = ?3, ended_at = ?4, tokens_used = 100, cost_estimate = 0.5
+             WHERE id = ?1",
+            params![id, status, started, started + secs],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn sessions_are_counted_by_project_runtime_status_and_day() {
+        let conn = test_conn();
+        seed_project(&conn, "p1", "Alpha");
+        seed_project(&conn, "p2", "Beta");
+        seed_session(&conn, "s1", "p1", "claude-code", "completed", JAN_1, 60);
+        seed_session(&conn, "s2", "p1", "codex", "failed", JAN_1 + 10, 60);
+        seed_session(&conn, "s3", "p2", "claude-code", "completed", JAN_1 + DAY, 60);
+        seed_session(&conn, "s4", "p2", "claude-code", "completed", JAN_1 + 5 * DAY, 60);
+        conn.execute("UPDATE sessions SET failure_reason = 'crash' WHERE id = 's2'", []).unwrap();
+
+        let filter = InsightsFilter { since: Some(JAN_1), until: Some(JAN_1 + 2 * DAY), project_id: None };
+        let insights = load_insights(&conn, &filter).unwrap();
+        assert_eq!(insights.total_sessions, 3);
+        assert_eq!(insights.total_tokens, 300);
+        assert_eq!(insights.sessions_by_project[0], InsightCount { name: "Alpha".into(), count: 2 });
+        assert_eq!(insights.sessions_by_runtime[0], InsightCount { name: "claude-code".into(), count: 2 });
+        assert_eq!(insights.outcomes.completed, 2);
+        assert_eq!(insights.outcomes.failed, 1);
+        assert!((insights.outcomes.success_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(insights.failure_reasons, vec![InsightCount { name: "crash".into(), count: 1 }]);
+        assert_eq!(insights.daily_usage.len(), 2);
+        assert_eq!(insights.daily_usage[0].date, "2025-01-01");
+        assert_eq!(insights.daily_usage[0].sessions, 2);
+        assert_eq!(insights.daily_usage[0].cost_usd, 1.0);
+
+        let only_beta = InsightsFilter { project_id: Some("p2".into()), ..InsightsFilter::default() };
+        assert_eq!(load_insights(&conn, &only_beta).unwrap().total_sessions, 2);
+    }
+
+    #[test]
+    fn templates_roles_memory_and_workspaces_are_summarized() {
+        let conn = test_conn();
+        seed_project(&conn, "p1", "Alpha");
+        conn.execute(
+            "INSERT INTO templates (id, name, plan, built_in, created_at) VALUES ('t1', 'Review', '{}', 0, 0)",
+            [],
+        )
+        .unwrap();
+        seed_session(&conn, "s1", "p1", "claude-code", "completed", JAN_1, 60);
+        seed_session(&conn, "s2", "p1", "claude-code", "completed", JAN_1, 120);
+        sessions::set_session_template(&conn, "s1", "t1").unwrap();
+        sessions::set_session_template(&conn, "s2", "t1").unwrap();
+        elves::create_elf(&conn, "e1", "s1", "Ada", Some("Reviewer"), "a", "#fff", None, "claude-code").unwrap();
+        elves::create_elf(&conn, "e2", "s2", "Bo", Some("Reviewer"), "a", "#fff", None, "claude-code").unwrap();
+        elves::create_elf(&conn, "e3", "s2", "Cy", Some("Tester"), "a", "#fff", None, "claude-code").unwrap();
+
+        for created_at in [JAN_1 - DAY, JAN_1, JAN_1 + 10] {
+            conn.execute(
+                "INSERT INTO memory (project_id, category, content, created_at, accessed_at) VALUES ('p1', 'fact', 'x', ?1, ?1)",
+                params![created_at],
+            )
+            .unwrap();
+        }
+
+        conn.execute("UPDATE sessions SET worktree_slug = 'fix-a' WHERE id = 's1'", []).unwrap();
+        conn.execute("UPDATE sessions SET worktree_slug = 'fix-b' WHERE id = 's2'", []).unwrap();
+        record_workspace_ship(&conn, "p1", "fix-a", "merge").unwrap();
+        record_workspace_ship(&conn, "p1", "fix-a", "pr").unwrap();
+
+        let insights = load_insights(&conn, &InsightsFilter { since: Some(JAN_1), ..InsightsFilter::default() }).unwrap();
+        assert_eq!(
+            insights.template_durations,
+            vec![TemplateDuration { template_id: "t1".into(), name: "Review".into(), sessions: 2, avg_duration_secs: 90.0 }]
+        );
+        assert_eq!(insights.top_roles[0], InsightCount { name: "Reviewer".into(), count: 2 });
+        assert_eq!(insights.top_roles.len(), 2);
+        assert_eq!(insights.memory_growth, vec![MemoryGrowth { date: "2025-01-01".into(), added: 2, total: 3 }]);
+        assert_eq!(insights.workspaces, WorkspaceShipRate { workspaces: 2, shipped: 1, ship_rate: 0.5 });
+    }
+}
@@ -0,0 +1,375 @@
+// Database maintenance — event retention compaction, size statistics, and VACUUM/ANALYZE.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::DbError;
+
+/// Sessions whose old events retention may compact: finished and not pinned.
+const COMPACTABLE_SESSIONS: &str =
+    "SELECT id FROM sessions WHERE pinned = 0 AND status != 'active'";
+
+/// Old events that compaction deletes outright: bulky content (thinking, text, tool output,
+/// init) and raw lines, except raw rows that carry tool-call or error metadata.
+const DELETABLE_EVENT: &str = "(kind IN ('thinking', 'text', 'tool_result', 'init')
+     OR (kind IS NULL AND event_type NOT IN ('tool_call', 'tool_use', 'function_call', 'exec', 'error', 'stderr')))";
+
+/// Old typed tool calls whose input compaction drops, keeping the tool name and id.
+const TRIMMABLE_EVENT: &str =
+    "(kind = 'tool_call' AND json_valid(payload) AND json_extract(payload, '$.input') IS NOT NULL)";
+
+/// Outcome of one retention pass.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    pub sessions_compacted: i64,
+    pub events_deleted: i64,
+    pub events_trimmed: i64,
+}
+
+/// Space used by one table, including its indexes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSize {
+    pub name: String,
+    pub bytes: i64,
+}
+
+/// Session and event volume for one project.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSize {
+    pub project_id: String,
+    /// None when the project row no longer exists.
+    pub project_name: Option<String>,
+    pub sessions: i64,
+    pub events: i64,
+    /// Total size of the project's event payloads.
+    pub event_bytes: i64,
+}
+
+/// Overall database size, broken down per table and per project.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStats {
+    /// Size of the main database file (page_count × page_size for in-memory databases).
+    pub file_bytes: i64,
+    /// Size of the `-wal` file; changes not yet checkpointed into the main file.
+    pub wal_bytes: i64,
+    /// Space held by free pages, reclaimable with VACUUM.
+    pub free_bytes: i64,
+    pub tables: Vec<TableSize>,
+    pub projects: Vec<ProjectSize>,
+}
+
+/// Database size before and after a VACUUM.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumReport {
+    pub bytes_before: i64,
+    pub bytes_after: i64,
+}
+
+/// Compact events older than `cutoff` (unix seconds) in finished, unpinned sessions.
+///
+/// Thinking, text, tool output and init events are deleted; tool calls keep their name
+/// and id but lose their input; file edits, errors, usage and results are kept, so the
+/// session still shows what was done and what it cost. Compacted sessions get
+/// `compacted_at` set. Running it again over the same range is a no-op.
+pub fn compact_events(conn: &Connection, cutoff: i64) -> Result<CompactionReport, DbError> {
+    let tx = conn.unchecked_transaction()?;
+
+    let sessions_compacted = tx.execute(
+        &format!(
+            "UPDATE sessions SET compacted_at = ?2
+             WHERE id IN ({COMPACTABLE_SESSIONS})
+               AND id IN (SELECT session_id FROM events
+                          WHERE timestamp < ?1 AND ({DELETABLE_EVENT} OR {TRIMMABLE_EVENT}))"
+        ),
+        params![cutoff, chrono::Utc::now().timestamp()],
+    )? as i64;
+
+    let events_deleted = tx.execute(
+        &format!(
+            "DELETE FROM events
+             WHERE timestamp < ?1 AND session_id IN ({COMPACTABLE_SESSIONS}) AND {DELETABLE_EVENT}"
+        ),
+        params![cutoff],
+    )? as i64;
+
+    let events_trimmed = tx.execute(
+        &format!(
+            "UPDATE events
+             SET payload = json_object(
+                 'kind', 'tool_call',
+                 'id', json_extract(payload, '$.id'),
+                 'name', json_extract(payload, '$.name'),
+                 'input', NULL)
+             WHERE timestamp < ?1 AND session_id IN ({COMPACTABLE_SESSIONS}) AND {TRIMMABLE_EVENT}"
+        ),
+        params![cutoff],
+    )? as i64;
+
+    tx.commit()?;
+
+    Ok(CompactionReport {
+        sessions_compacted,
+        events_deleted,
+        events_trimmed,
+    })
+}
+
+/// Collect database size statistics: file and WAL size, bytes per table, and event
+/// volume per project.
+pub fn database_stats(conn: &Connection) -> Result<DatabaseStats, DbError> {
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    let free_pages: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+
+    // FTS shadow tables and internal tables are reported under their own names
+    let mut stmt = conn.prepare(
+        "SELECT coalesce(m.tbl_name, d.name) AS table_name, SUM(d.pgsize) AS bytes
+         FROM dbstat d
+         LEFT JOIN sqlite_master m ON m.name = d.name
+         GROUP BY table_name
+         ORDER BY bytes DESC, table_name",
+    )?;
+    let tables = stmt
+        .query_map([], |row| {
+            Ok(TableSize {
+                name: row.get(0)?,
+                bytes: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT s.project_id, p.name, COUNT(*), coalesce(SUM(e.events), 0), coalesce(SUM(e.bytes), 0)
+         FROM sessions s
+         LEFT JOIN projects p ON p.id = s.project_id
+         LEFT JOIN (
+             SELECT session_id, COUNT(*) AS events, SUM(length(CAST(payload AS BLOB))) AS bytes
+             FROM events GROUP BY session_id
+         ) e ON e.session_id = s.id
+         GROUP BY s.project_id
+         ORDER BY 5 DESC, s.project_id",
+    )?;
+    let projects = stmt
+        .query_map([], |row| {
+            Ok(ProjectSize {
+                project_id: row.get(0)?,
+                project_name: row.get(1)?,
+                sessions: row.get(2)?,
+                events: row.get(3)?,
+                event_bytes: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (file_bytes, wal_bytes) = file_sizes(conn)?;
+
+    Ok(DatabaseStats {
+        file_bytes,
+        wal_bytes,
+        free_bytes: free_pages * page_size,
+        tables,
+        projects,
+    })
+}
+
+/// Rebuild the database file to reclaim free space.
+///
+/// Under WAL mode the log is checkpointed and truncated first, so VACUUM works on an
+/// up-to-date main file, and again afterwards, since VACUUM writes its result through
+/// the WAL. FTS indexes are merged beforehand so their freed segments are reclaimed too.
+pub fn vacuum_database(conn: &Connection) -> Result<VacuumReport, DbError> {
+    let (file_before, wal_before) = file_sizes(conn)?;
+
+    checkpoint(conn)?;
+    conn.execute_batch(
+        "INSERT INTO session_fts(session_fts) VALUES ('optimize');
+         INSERT INTO event_fts(event_fts) VALUES ('optimize');
+         INSERT INTO memory_fts(memory_fts) VALUES ('optimize');
+         VACUUM;",
+    )?;
+    checkpoint(conn)?;
+
+    let (file_after, wal_after) = file_sizes(conn)?;
+    Ok(VacuumReport {
+        bytes_before: file_before + wal_before,
+        bytes_after: file_after + wal_after,
+    })
+}
+
+/// Refresh the query planner statistics.
+pub fn analyze_database(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch("ANALYZE; PRAGMA optimize;")?;
+    Ok(())
+}
+
+/// Checkpoint the WAL into the main file and truncate it. A no-op outside WAL mode.
+fn checkpoint(conn: &Connection) -> Result<(), DbError> {
+    // Returns (busy, log pages, checkpointed pages); a busy checkpoint is retried by the next one
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    Ok(())
+}
+
+/// Sizes of the main database file and its WAL. In-memory databases have no file, so the
+/// main size is computed from the page count.
+fn file_sizes(conn: &Connection) -> Result<(i64, i64), DbError> {
+    let path = conn.path().filter(|p| !p.is_empty()).map(std::path::PathBuf::from);
+    let Some(path) = path else {
+        let pages: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        return Ok((pages * page_size, 0));
+    };
+
+    let size_of = |p: &std::path::Path| std::fs::metadata(p).map(|m| m.len() as i64).unwrap_or(0);
+    let mut wal = path.clone().into_os_string();
+    wal.push("-wal");
+    Ok((size_of(&path), size_of(std::path::Path::new(&wal))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{events, schema, sessions};
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        conn
+    }
+
+    fn seed_session(conn: &Connection, id: &str, status: &str) {
+        conn.execute(
+            "INSERT OR IGNORE INTO projects (id, name, path, created_at, updated_at)
+             VALUES ('proj-1', 'Project', '/tmp/p', 0, 0)",
+            [],
+        )
+        .unwrap();
+        sessions::create_session(conn, id, "proj-1", "Task", "claude-code", None).unwrap();
+        if status != "active" {
+            sessions::update_session_status(conn, id, status, None).unwrap();
+        }
+    }
+
+    /// Insert one of each interesting kind of event, then age them all to `timestamp`.
+    fn seed_events(conn: &Connection, session_id: &str, timestamp: i64) {
+        for (kind, payload) in [
+            ("thinking", r#"{"kind":"thinking","text":"hmm"}"#),
+            ("text", r#"{"kind":"text","text":"Done"}"#),
+            ("tool_call", r#"{"kind":"tool_call","id":"t1","name":"Read","input":{"file_path":"a.rs"}}"#),
+            ("tool_result", r#"{"kind":"tool_result","id":"t1","isError":false,"content":"fn main() {}"}"#),
+            ("file_edit", r#"{"kind":"file_edit","path":"a.rs"}"#),
+            ("result", r#"{"kind":"result","text":"Done","isError":false}"#),
+        ] {
+            events::insert_typed_event(conn, session_id, None, kind, payload).unwrap();
+        }
+        events::insert_event(conn, session_id, None, "system", r#"{"type":"system"}"#, None).unwrap();
+        events::insert_event(conn, session_id, None, "error", "boom", None).unwrap();
+        conn.execute(
+            "UPDATE events SET timestamp = ?1 WHERE session_id = ?2",
+            params![timestamp, session_id],
+        )
+        .unwrap();
+    }
+
+    fn kinds(conn: &Connection, session_id: &str) -> Vec<String> {
+        events::list_events(conn, session_id)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.kind.unwrap_or(e.event_type))
+            .collect()
+    }
+
+    #[test]
+    fn compact_keeps_metadata_and_trims_tool_input() {
+        let conn = test_conn();
+        seed_session(&conn, "old", "completed");
+        seed_events(&conn, "old", 100);
+
+        let report = compact_events(&conn, 1000).unwrap();
+        assert_eq!(
+            report,
+            CompactionReport { sessions_compacted: 1, events_deleted: 4, events_trimmed: 1 }
+        );
+        assert_eq!(kinds(&conn, "old"), vec!["tool_call", "file_edit", "result", "error"]);
+
+        let tool_call = &events::list_events(&conn, "old").unwrap()[0];
+        let payload: serde_json::Value = serde_json::from_str(&tool_call.payload).unwrap();
+        assert_eq!(payload["name"], "Read");
+        assert!(payload["input"].is_null());
+        assert!(sessions::get_session(&conn, "old").unwrap().unwrap().compacted_at.is_some());
+
+        // A second pass has nothing left to do
+        assert_eq!(compact_events(&conn, 1000).unwrap(), CompactionReport::default());
+    }
+
+    #[test]
+    fn compact_skips_pinned_active_and_recent_sessions() {
+        let conn = test_conn();
+        seed_session(&conn, "pinned", "completed");
+        seed_session(&conn, "active", "active");
+        seed_session(&conn, "recent", "completed");
+        sessions::set_session_pinned(&conn, "pinned", true).unwrap();
+        seed_events(&conn, "pinned", 100);
+        seed_events(&conn, "active", 100);
+        seed_events(&conn, "recent", 5000);
+
+        assert_eq!(compact_events(&conn, 1000).unwrap(), CompactionReport::default());
+        for id in ["pinned", "active", "recent"] {
+            assert_eq!(kinds(&conn, id).len(), 8, "{id} should be untouched");
+            assert!(sessions::get_session(&conn, id).unwrap().unwrap().compacted_at.is_none());
+        }
+    }
+
+    #[test]
+    fn compact_removes_deleted_events_from_search() {
+        let conn = test_conn();
+        seed_session(&conn, "old", "completed");
+        seed_events(&conn, "old", 100);
+
+        compact_events(&conn, 1000).unwrap();
+        let matches: i64 = conn
+            .query_row("SELECT COUNT(*) FROM event_fts WHERE event_fts MATCH 'hmm'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(matches, 0);
+    }
+
+    #[test]
+    fn database_stats_reports_tables_and_projects() {
+        let conn = test_conn();
+        seed_session(&conn, "s1", "completed");
+        seed_events(&conn, "s1", 100);
+
+        let stats = database_stats(&conn).unwrap();
+        assert!(stats.file_bytes > 0);
+        assert_eq!(stats.wal_bytes, 0);
+        assert!(stats.tables.iter().any(|t| t.name == "events" && t.bytes > 0));
+
+        assert_eq!(stats.projects.len(), 1);
+        let project = &stats.projects[0];
+        assert_eq!(project.project_id, "proj-1");
+        assert_eq!(project.project_name.as_deref(), Some("Project"));
+        assert_eq!(project.sessions, 1);
+        assert_eq!(project.events, 8);
+        assert!(project.event_bytes > 0);
+    }
+
+    #[test]
+    fn vacuum_and_analyze_run_on_a_wal_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = crate::db::open_database(&dir.path().join("elves.db")).unwrap();
+        seed_session(&conn, "s1", "completed");
+        seed_events(&conn, "s1", 100);
+        conn.execute("DELETE FROM events", []).unwrap();
+
+        let report = vacuum_database(&conn).unwrap();
+        assert!(report.bytes_before > 0);
+        assert!(report.bytes_after > 0);
+        assert_eq!(database_stats(&conn).unwrap().wal_bytes, 0);
+
+        analyze_database(&conn).unwrap();
+    }
+}
@@ -1,7 +1,8 @@
 // MCP server CRUD operations — manage Model Context Protocol server configurations.
 //
-// MCP servers provide tool integrations for agents. Each server has a command,
-// args, environment variables, a scope (global or project), and an enabled flag.
+// MCP servers provide tool integrations for agents. Stdio servers have a command,
+// args, and environment variables; remote servers (http, sse) have a URL and headers.
+// Every server has a scope (global or project) and an enabled flag.
 
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
@@ -26,9 +27,16 @@ pub struct McpRow {
     pub enabled: bool,
     /// Unix timestamp of the last successful health check, or None if never checked.
     pub last_health_check: Option<i64>,
+    /// "stdio" (spawned process), "http" (Streamable HTTP), or "sse" (legacy HTTP+SSE).
+    pub transport: String,
+    /// Server URL for http/sse transports, None for stdio.
+    pub url: Option<String>,
+    /// JSON object of HTTP headers sent with every request. Values may contain
+    /// `${VAR}` references that are expanded when connecting.
+    pub headers: String,
 }
 
-/// Insert a new MCP server. Returns the created row.
+/// Insert a new stdio MCP server. Returns the created row.
 pub fn insert_mcp_server(
     conn: &Connection,
     id: &str,
@@ -47,10 +55,30 @@ pub fn insert_mcp_server(
     get_mcp_server(conn, id)?.ok_or_else(|| DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))
 }
 
+/// Insert a new remote MCP server reached over HTTP. `transport` is "http" or "sse";
+/// the command is stored as the URL so list views have something to show.
+pub fn insert_remote_mcp_server(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    transport: &str,
+    url: &str,
+    headers: &str,
+    scope: &str,
+) -> Result<McpRow, DbError> {
+    conn.execute(
+        "INSERT INTO mcp_servers (id, name, command, args, env, scope, enabled, last_health_check, transport, url, headers)
+         VALUES (?1, ?2, ?3, '[]', '{}', ?4, 1, NULL, ?5, ?3, ?6)",
+        params![id, name, url, scope, transport, headers],
+    )?;
+
+    get_mcp_server(conn, id)?.ok_or_else(|| DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))
+}
+
 /// Retrieve a single MCP server by ID. Returns None if not found.
 pub fn get_mcp_server(conn: &Connection, id: &str) -> Result<Option<McpRow>, DbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, command, args, env, scope, enabled, last_health_check
+        "SELECT id, name, command, args, env, scope, enabled, last_health_check, transport, url, headers
          FROM mcp_servers WHERE id = ?1",
     )?;
 
@@ -64,7 +92,7 @@ pub fn get_mcp_server(conn: &Connection, id: &str) -> Result<Option<McpRow>, DbE
 /// List all MCP servers, ordered by name ascending.
 pub fn list_mcp_servers(conn: &Connection) -> Result<Vec<McpRow>, DbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, command, args, env, scope, enabled, last_health_check
+        "SELECT id, name, command, args, env, scope, enabled, last_health_check, transport, url, headers
          FROM mcp_servers ORDER BY name ASC",
     )?;
 
@@ -84,6 +112,38 @@ pub fn toggle_mcp_server(conn: &Connection, id: &str, enabled: bool) -> Result<b
     Ok(rows_affected > 0)
 }
 
+/// Replace a stdio server's launch settings. Returns true if updated.
+pub fn update_mcp_server_launch(
+    conn: &Connection,
+    id: &str,
+    command: &str,
+    args: &str,
+    env: &str,
+) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE mcp_servers SET transport = 'stdio', command = ?1, args = ?2, env = ?3, url = NULL, headers = '{}'
+         WHERE id = ?4",
+        params![command, args, env, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Replace a remote server's transport, URL, and headers. Returns true if updated.
+pub fn update_mcp_server_remote(
+    conn: &Connection,
+    id: &str,
+    transport: &str,
+    url: &str,
+    headers: &str,
+) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE mcp_servers SET transport = ?1, command = ?2, url = ?2, headers = ?3, args = '[]', env = '{}'
+         WHERE id = ?4",
+        params![transport, url, headers, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
 /// Update the last health check timestamp for an MCP server. Returns true if updated.
 pub fn update_health_check(conn: &Connection, id: &str) -> Result<bool, DbError> {
     let now = chrono::Utc::now().timestamp();
@@ -111,6 +171,9 @@ fn map_mcp_row(row: &rusqlite::Row<'_>) -> Result<McpRow, rusqlite::Error> {
         scope: row.get(5)?,
         enabled: row.get(6)?,
         last_health_check: row.get(7)?,
+        transport: row.get(8)?,
+        url: row.get(9)?,
+        headers: row.get(10)?,
     })
 }
 
@@ -280,4 +343,55 @@ mod tests {
         assert!(json.contains("lastHealthCheck"));
         assert!(!json.contains("last_health_check"));
     }
+
+    #[test]
+    fn stdio_servers_default_to_stdio_transport() {
+        let conn = test_conn();
+        let server = insert_mcp_server(&conn, "mcp-1", "test", "cmd", "[]", "{}", "global").unwrap();
+        assert_eq!(server.transport, "stdio");
+        assert!(server.url.is_none());
+        assert_eq!(server.headers, "{}");
+    }
+
+    #[test]
+    fn insert_remote_mcp_server_stores_url_and_headers() {
+        let conn = test_conn();
+        let server = insert_remote_mcp_server(
+            &conn,
+            "mcp-2",
+            "linear",
+            "http",
+            "https://mcp.linear.app/mcp",
+            r#"{"Authorization": "Bearer ${LINEAR_TOKEN}"}"#,
+            "global",
+        )
+        .expect("Should insert remote server");
+
+        assert_eq!(server.transport, "http");
+        assert_eq!(server.url.as_deref(), Some("https://mcp.linear.app/mcp"));
+        assert_eq!(server.command, "https://mcp.linear.app/mcp");
+        assert!(server.headers.contains("LINEAR_TOKEN"));
+        assert_eq!(server.args, "[]");
+    }
+
+    #[test]
+    fn update_launch_and_remote_switch_transport() {
+        let conn = test_conn();
+        insert_mcp_server(&conn, "mcp-1", "docs", "npx", r#"["-y", "a"]"#, "{}", "global").unwrap();
+
+        assert!(update_mcp_server_remote(&conn, "mcp-1", "sse", "https://x/sse", r#"{"A": "b"}"#).unwrap());
+        let remote = get_mcp_server(&conn, "mcp-1").unwrap().unwrap();
+        assert_eq!(remote.transport, "sse");
+        assert_eq!(remote.url.as_deref(), Some("https://x/sse"));
+        assert_eq!(remote.args, "[]");
+
+        assert!(update_mcp_server_launch(&conn, "mcp-1", "uvx", r#"["docs"]"#, r#"{"K": "v"}"#).unwrap());
+        let local = get_mcp_server(&conn, "mcp-1").unwrap().unwrap();
+        assert_eq!(local.transport, "stdio");
+        assert_eq!(local.command, "uvx");
+        assert!(local.url.is_none());
+        assert_eq!(local.headers, "{}");
+
+        assert!(!update_mcp_server_launch(&conn, "nope", "x", "[]", "{}").unwrap());
+    }
 }
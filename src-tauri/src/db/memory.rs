@@ -319,6 +319,108 @@ pub fn count_memories(conn: &Connection, project_id: Option<&str>) -> Result<i64
     Ok(count)
 }
 
+/// A request, made by an agent, for the user to pin a memory.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinRequestRow {
+    pub id: i64,
+    pub memory_id: i64,
+    /// ELVES session the requesting agent was running in.
+    pub session_id: Option<String>,
+    pub reason: Option<String>,
+    /// One of: "pending", "approved", "dismissed".
+    pub status: String,
+    pub created_at: i64,
+    pub resolved_at: Option<i64>,
+    /// Content of the memory the request refers to.
+    pub content: String,
+}
+
+/// Record a pin request for a memory. Returns the created row.
+pub fn insert_pin_request(
+    conn: &Connection,
+    memory_id: i64,
+    session_id: Option<&str>,
+    reason: Option<&str>,
+) -> Result<PinRequestRow, DbError> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO memory_pin_requests (memory_id, session_id, reason, status, created_at)
+         VALUES (?1, ?2, ?3, 'pending', ?4)",
+        params![memory_id, session_id, reason, now],
+    )?;
+
+    let row_id = conn.last_insert_rowid();
+    get_pin_request(conn, row_id)?.ok_or_else(|| DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))
+}
+
+/// Retrieve a single pin request by ID. Returns None if it does not exist.
+pub fn get_pin_request(conn: &Connection, id: i64) -> Result<Option<PinRequestRow>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.memory_id, r.session_id, r.reason, r.status, r.created_at, r.resolved_at, m.content
+         FROM memory_pin_requests r
+         JOIN memory m ON m.id = r.memory_id
+         WHERE r.id = ?1",
+    )?;
+    let result = stmt.query_row(params![id], map_pin_request_row).optional()?;
+    Ok(result)
+}
+
+/// List pending pin requests, oldest first. With a project ID, only requests for that
+/// project's memories (and global memories) are returned.
+pub fn list_pending_pin_requests(
+    conn: &Connection,
+    project_id: Option<&str>,
+) -> Result<Vec<PinRequestRow>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.memory_id, r.session_id, r.reason, r.status, r.created_at, r.resolved_at, m.content
+         FROM memory_pin_requests r
+         JOIN memory m ON m.id = r.memory_id
+         WHERE r.status = 'pending'
+           AND (?1 IS NULL OR m.project_id = ?1 OR m.project_id IS NULL)
+         ORDER BY r.created_at ASC, r.id ASC",
+    )?;
+    let rows = stmt
+        .query_map(params![project_id], map_pin_request_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Approve (pinning the memory) or dismiss a pending pin request.
+/// Returns false if the request does not exist or was already resolved.
+pub fn resolve_pin_request(conn: &Connection, id: i64, approve: bool) -> Result<bool, DbError> {
+    let Some(request) = get_pin_request(conn, id)? else {
+        return Ok(false);
+    };
+    if request.status != "pending" {
+        return Ok(false);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let status = if approve { "approved" } else { "dismissed" };
+    conn.execute(
+        "UPDATE memory_pin_requests SET status = ?1, resolved_at = ?2 WHERE id = ?3",
+        params![status, now, id],
+    )?;
+    if approve {
+        pin_memory(conn, request.memory_id)?;
+    }
+    Ok(true)
+}
+
+fn map_pin_request_row(row: &rusqlite::Row) -> Result<PinRequestRow, rusqlite::Error> {
+    Ok(PinRequestRow {
+        id: row.get(0)?,
+        memory_id: row.get(1)?,
+        session_id: row.get(2)?,
+        reason: row.get(3)?,
+        status: row.get(4)?,
+        created_at: row.get(5)?,
+        resolved_at: row.get(6)?,
+        content: row.get(7)?,
+    })
+}
+
 /// Use rusqlite's optional() extension for query_row.
 trait OptionalExt<T> {
     fn optional(self) -> Result<Option<T>, rusqlite::Error>;
@@ -717,4 +819,43 @@ mod tests {
         assert!(json.contains("accessedAt"));
         assert!(json.contains("relevanceScore"));
     }
+
+    #[test]
+    fn pin_request_lifecycle() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-pin");
+        let mem = insert_memory(&conn, Some("proj-pin"), "decision", "Use WAL mode", Some("agent:s1"), "[]")
+            .expect("Should insert memory");
+
+        let request = insert_pin_request(&conn, mem.id, Some("s1"), Some("Applies to every session"))
+            .expect("Should insert pin request");
+        assert_eq!(request.status, "pending");
+        assert_eq!(request.content, "Use WAL mode");
+
+        let pending = list_pending_pin_requests(&conn, Some("proj-pin")).expect("Should list");
+        assert_eq!(pending.len(), 1);
+        assert!(list_pending_pin_requests(&conn, Some("other")).expect("Should list").is_empty());
+
+        assert!(resolve_pin_request(&conn, request.id, true).expect("Should resolve"));
+        assert!(!resolve_pin_request(&conn, request.id, false).expect("Already resolved"));
+        assert!(list_pending_pin_requests(&conn, None).expect("Should list").is_empty());
+
+        let pinned = get_memory(&conn, mem.id).expect("Should get").expect("Should exist");
+        assert_eq!(pinned.source.as_deref(), Some("pinned"));
+    }
+
+    #[test]
+    fn dismissed_pin_request_leaves_memory_unpinned() {
+        let conn = test_conn();
+        let mem = insert_memory(&conn, None, "learning", "Tests need a tempdir", None, "[]")
+            .expect("Should insert memory");
+        let request = insert_pin_request(&conn, mem.id, None, None).expect("Should insert");
+
+        assert!(resolve_pin_request(&conn, request.id, false).expect("Should resolve"));
+        let fetched = get_pin_request(&conn, request.id).expect("Should get").expect("Should exist");
+        assert_eq!(fetched.status, "dismissed");
+        assert!(fetched.resolved_at.is_some());
+        let memory = get_memory(&conn, mem.id).expect("Should get").expect("Should exist");
+        assert!(memory.source.is_none());
+    }
 }
@@ -2,14 +2,20 @@
 
 pub mod schema;
 pub mod projects;
+pub mod queue;
+pub mod search;
 pub mod sessions;
 pub mod events;
+pub mod insights;
+pub mod maintenance;
+pub mod recordings;
 pub mod elves;
 pub mod mcp;
 pub mod memory;
 pub mod skill_sources;
 pub mod skills;
 pub mod templates;
+pub mod usage;
 
 use rusqlite::Connection;
 use std::path::Path;
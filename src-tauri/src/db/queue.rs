@@ -0,0 +1,414 @@
+// Task queue operations — persist tasks waiting to run and pick the ones the concurrency
+// caps allow to start.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::DbError;
+
+/// A task_queue row, serialized to camelCase JSON for the frontend.
+///
+/// Waiting tasks start in `position` order. A task's priority decides where it is
+/// inserted when enqueued; reordering the queue overrides it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedTask {
+    pub id: String,
+    pub project_id: String,
+    pub task: String,
+    pub runtime: String,
+    /// Directory to run in, e.g. a worktree. None runs in the project root.
+    pub working_dir: Option<String>,
+    pub worktree_slug: Option<String>,
+    /// JSON-encoded spawn options.
+    pub options: Option<String>,
+    /// JSON-encoded team plan. Set for team tasks.
+    pub plan: Option<String>,
+    /// Start in an interactive PTY rather than print mode.
+    pub pty: bool,
+    pub priority: i32,
+    pub position: i64,
+    /// One of: "queued", "started", "cancelled", "failed".
+    pub status: String,
+    /// Session the task started as, once started.
+    pub session_id: Option<String>,
+    /// Why the task failed to start, or why a waiting task is being held back.
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+}
+
+/// A task to add to the queue.
+#[derive(Debug, Clone, Default)]
+pub struct NewQueuedTask<'a> {
+    pub project_id: &'a str,
+    pub task: &'a str,
+    pub runtime: &'a str,
+    pub working_dir: Option<&'a str>,
+    pub worktree_slug: Option<&'a str>,
+    pub options: Option<&'a str>,
+    pub plan: Option<&'a str>,
+    pub pty: bool,
+    pub priority: i32,
+}
+
+const QUEUE_COLUMNS: &str = "id, project_id, task, runtime, working_dir, worktree_slug, options, plan, \
+     priority, position, status, session_id, error, created_at, started_at, pty";
+
+/// Add a task to the queue. It goes after every waiting task of equal or higher priority
+/// and ahead of those with lower priority. Returns the created row.
+pub fn enqueue(conn: &Connection, id: &str, new: &NewQueuedTask) -> Result<QueuedTask, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let position: i64 = tx.query_row(
+        "SELECT COALESCE(
+             (SELECT MIN(position) FROM task_queue WHERE status = 'queued' AND priority < ?1),
+             (SELECT MAX(position) + 1 FROM task_queue WHERE status = 'queued'),
+             0)",
+        params![new.priority],
+        |row| row.get(0),
+    )?;
+    tx.execute(
+        "UPDATE task_queue SET position = position + 1 WHERE status = 'queued' AND position >= ?1",
+        params![position],
+    )?;
+    tx.execute(
+        "INSERT INTO task_queue (id, project_id, task, runtime, working_dir, worktree_slug, options, plan,
+                                 priority, position, status, created_at, pty)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'queued', ?11, ?12)",
+        params![
+            id,
+            new.project_id,
+            new.task,
+            new.runtime,
+            new.working_dir,
+            new.worktree_slug,
+            new.options,
+            new.plan,
+            new.priority,
+            position,
+            chrono::Utc::now().timestamp(),
+            new.pty,
+        ],
+    )?;
+    tx.commit()?;
+
+    get_queued_task(conn, id)?.ok_or_else(|| DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))
+}
+
+/// Get a queue entry by ID. Returns None if not found.
+pub fn get_queued_task(conn: &Connection, id: &str) -> Result<Option<QueuedTask>, DbError> {
+    let mut stmt = conn.prepare(&format!("SELECT {QUEUE_COLUMNS} FROM task_queue WHERE id = ?1"))?;
+    let result = stmt.query_row(params![id], map_queue_row).optional()?;
+    Ok(result)
+}
+
+/// Tasks still waiting to start, in the order they will start. Optionally limited to
+/// one project.
+pub fn list_queue(conn: &Connection, project_id: Option<&str>) -> Result<Vec<QueuedTask>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {QUEUE_COLUMNS} FROM task_queue
+         WHERE status = 'queued' AND (?1 IS NULL OR project_id = ?1)
+         ORDER BY position, created_at"
+    ))?;
+    let rows = stmt
+        .query_map(params![project_id], map_queue_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Move the given waiting tasks to the front of the queue, in the given order. Waiting
+/// tasks not listed keep their relative order behind them; unknown or no longer waiting
+/// IDs are ignored.
+pub fn reorder_queue(conn: &Connection, ids: &[String]) -> Result<(), DbError> {
+    let waiting: Vec<String> = list_queue(conn, None)?.into_iter().map(|task| task.id).collect();
+    let order = ids
+        .iter()
+        .filter(|id| waiting.contains(id))
+        .chain(waiting.iter().filter(|id| !ids.contains(id)));
+
+    let tx = conn.unchecked_transaction()?;
+    for (position, id) in order.enumerate() {
+        tx.execute(
+            "UPDATE task_queue SET position = ?1 WHERE id = ?2",
+            params![position as i64, id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Cancel a waiting task. Returns false if it was not waiting.
+pub fn cancel_queued(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE task_queue SET status = 'cancelled' WHERE id = ?1 AND status = 'queued'",
+        params![id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Record that a waiting task started as `session_id`.
+pub fn mark_started(conn: &Connection, id: &str, session_id: &str) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE task_queue SET status = 'started', session_id = ?1, started_at = ?2, error = NULL WHERE id = ?3",
+        params![session_id, chrono::Utc::now().timestamp(), id],
+    )?;
+    Ok(())
+}
+
+/// Record that a waiting task could not be started.
+pub fn mark_failed(conn: &Connection, id: &str, error: &str) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE task_queue SET status = 'failed', error = ?1 WHERE id = ?2",
+        params![error, id],
+    )?;
+    Ok(())
+}
+
+/// Note why a waiting task is being held back (None clears it). The task stays queued.
+/// Returns true if the note changed.
+pub fn set_waiting_reason(conn: &Connection, id: &str, reason: Option<&str>) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE task_queue SET error = ?1 WHERE id = ?2 AND status = 'queued' AND error IS NOT ?1",
+        params![reason, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Number of active sessions per project.
+pub fn active_session_counts(conn: &Connection) -> Result<HashMap<String, u32>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT project_id, COUNT(*) FROM sessions WHERE status = 'active' GROUP BY project_id",
+    )?;
+    let counts = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)))?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(counts)
+}
+
+/// Which cap, if any, stops one more session from starting in `project_id`: `global_cap`
+/// active sessions overall or `project_cap` in the project. None when there is room.
+pub fn cap_reached(
+    conn: &Connection,
+    project_id: &str,
+    global_cap: u32,
+    project_cap: Option<u32>,
+) -> Result<Option<String>, DbError> {
+    let active = active_session_counts(conn)?;
+    let total: u32 = active.values().sum();
+    if total >= global_cap {
+        return Ok(Some(format!("{total} sessions are already running (limit {global_cap})")));
+    }
+    let running = active.get(project_id).copied().unwrap_or(0);
+    Ok(project_cap
+        .filter(|cap| running >= *cap)
+        .map(|cap| format!("{running} sessions are already running in this project (limit {cap})")))
+}
+
+/// Waiting tasks that may start now, in queue order, without more than `global_cap`
+/// sessions active overall or more than `project_cap(project_id)` active in a project.
+///
+/// Tasks of a project at its cap are skipped, so they don't hold up other projects.
+pub fn startable_tasks(
+    conn: &Connection,
+    global_cap: u32,
+    mut project_cap: impl FnMut(&str) -> Option<u32>,
+) -> Result<Vec<QueuedTask>, DbError> {
+    let mut active = active_session_counts(conn)?;
+    let mut total: u32 = active.values().sum();
+    let mut startable = Vec::new();
+    for task in list_queue(conn, None)? {
+        if total >= global_cap {
+            break;
+        }
+        let running = active.entry(task.project_id.clone()).or_insert(0);
+        if project_cap(&task.project_id).is_some_and(|cap| *running >= cap) {
+            continue;
+        }
+        *running += 1;
+        total += 1;
+        startable.push(task);
+    }
+    Ok(startable)
+}
+
+fn map_queue_row(row: &rusqlite::Row) -> rusqlite::Result<QueuedTask> {
+    Ok(QueuedTask {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        task: row.get(2)?,
+        runtime: row.get(3)?,
+        working_dir: row.get(4)?,
+        worktree_slug: row.get(5)?,
+        options: row.get(6)?,
+        plan: row.get(7)?,
+        priority: row.get(8)?,
+        position: row.get(9)?,
+        status: row.get(10)?,
+        session_id: row.get(11)?,
+        error: row.get(12)?,
+        created_at: row.get(13)?,
+        started_at: row.get(14)?,
+        pty: row.get(15)?,
+    })
+}
+
+/// Use rusqlite's optional() extension for query_row.
+trait OptionalExt<T> {
+    fn optional(self) -> Result<Option<T>, rusqlite::Error>;
+}
+
+impl<T> OptionalExt<T> for Result<T, rusqlite::Error> {
+    fn optional(self) -> Result<Option<T>, rusqlite::Error> {
+        match self {
+            Ok(val) => Ok(Some(val)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{schema, sessions};
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        conn
+    }
+
+    /// Insert a project so foreign key constraints are satisfied.
+    fn seed_project(conn: &Connection, id: &str) {
+        let now = chrono::Utc::now().timestamp();
+        let path = format!("/tmp/test-{id}");
+        conn.execute(
+            "INSERT INTO projects (id, name, path, default_runtime, created_at, updated_at)
+             VALUES (?1, 'Test Project', ?4, 'claude-code', ?2, ?3)",
+            params![id, now, now, path],
+        )
+        .expect("Should seed project");
+    }
+
+    fn add(conn: &Connection, id: &str, project_id: &str, priority: i32) -> QueuedTask {
+        let new = NewQueuedTask {
+            project_id,
+            task: id,
+            runtime: "claude-code",
+            priority,
+            ..NewQueuedTask::default()
+        };
+        enqueue(conn, id, &new).expect("Should enqueue")
+    }
+
+    fn queued_ids(conn: &Connection) -> Vec<String> {
+        list_queue(conn, None).unwrap().into_iter().map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn priority_decides_insert_position() {
+        let conn = test_conn();
+        seed_project(&conn, "p1");
+        add(&conn, "a", "p1", 0);
+        add(&conn, "b", "p1", 0);
+        add(&conn, "urgent", "p1", 5);
+        add(&conn, "c", "p1", 0);
+        add(&conn, "high", "p1", 5);
+        assert_eq!(queued_ids(&conn), vec!["urgent", "high", "a", "b", "c"]);
+
+        let task = get_queued_task(&conn, "a").unwrap().unwrap();
+        assert_eq!(task.status, "queued");
+        assert_eq!(task.session_id, None);
+    }
+
+    #[test]
+    fn reorder_moves_listed_tasks_to_the_front() {
+        let conn = test_conn();
+        seed_project(&conn, "p1");
+        for id in ["a", "b", "c", "d"] {
+            add(&conn, id, "p1", 0);
+        }
+        reorder_queue(&conn, &["c".to_string(), "missing".to_string(), "a".to_string()]).unwrap();
+        assert_eq!(queued_ids(&conn), vec!["c", "a", "b", "d"]);
+    }
+
+    #[test]
+    fn cancelled_and_started_tasks_leave_the_queue() {
+        let conn = test_conn();
+        seed_project(&conn, "p1");
+        add(&conn, "a", "p1", 0);
+        add(&conn, "b", "p1", 0);
+        add(&conn, "c", "p1", 0);
+
+        assert!(cancel_queued(&conn, "a").unwrap());
+        assert!(!cancel_queued(&conn, "a").unwrap());
+        mark_started(&conn, "b", "sess-1").unwrap();
+        mark_failed(&conn, "c", "Project not found").unwrap();
+        assert!(queued_ids(&conn).is_empty());
+
+        let started = get_queued_task(&conn, "b").unwrap().unwrap();
+        assert_eq!(started.status, "started");
+        assert_eq!(started.session_id.as_deref(), Some("sess-1"));
+        assert!(started.started_at.is_some());
+        let failed = get_queued_task(&conn, "c").unwrap().unwrap();
+        assert_eq!(failed.error.as_deref(), Some("Project not found"));
+        assert!(!cancel_queued(&conn, "b").unwrap());
+    }
+
+    #[test]
+    fn startable_tasks_respect_global_and_project_caps() {
+        let conn = test_conn();
+        seed_project(&conn, "p1");
+        seed_project(&conn, "p2");
+        sessions::create_session(&conn, "running", "p1", "Busy", "claude-code", None).unwrap();
+        add(&conn, "p1-a", "p1", 0);
+        add(&conn, "p1-b", "p1", 0);
+        add(&conn, "p2-a", "p2", 0);
+        add(&conn, "p2-b", "p2", 0);
+
+        let cap_p1 = |project_id: &str| (project_id == "p1").then_some(1);
+        let ids = |tasks: Vec<QueuedTask>| tasks.into_iter().map(|t| t.id).collect::<Vec<_>>();
+
+        // p1 is at its cap, so only p2 tasks start, up to the global cap
+        assert_eq!(ids(startable_tasks(&conn, 3, cap_p1).unwrap()), vec!["p2-a", "p2-b"]);
+        assert_eq!(ids(startable_tasks(&conn, 2, cap_p1).unwrap()), vec!["p2-a"]);
+        assert!(startable_tasks(&conn, 1, cap_p1).unwrap().is_empty());
+
+        // Without a project cap the queue order decides
+        assert_eq!(ids(startable_tasks(&conn, 3, |_| None).unwrap()), vec!["p1-a", "p1-b"]);
+
+        let counts = active_session_counts(&conn).unwrap();
+        assert_eq!(counts.get("p1"), Some(&1));
+        assert_eq!(counts.get("p2"), None);
+    }
+
+    #[test]
+    fn cap_reached_names_the_cap() {
+        let conn = test_conn();
+        seed_project(&conn, "p1");
+        seed_project(&conn, "p2");
+        sessions::create_session(&conn, "running", "p1", "Busy", "claude-code", None).unwrap();
+
+        assert_eq!(cap_reached(&conn, "p1", 2, None).unwrap(), None);
+        assert!(cap_reached(&conn, "p2", 1, None).unwrap().unwrap().contains("limit 1"));
+        assert!(cap_reached(&conn, "p1", 5, Some(1)).unwrap().unwrap().contains("in this project"));
+        assert_eq!(cap_reached(&conn, "p2", 5, Some(1)).unwrap(), None);
+    }
+
+    #[test]
+    fn waiting_reason_keeps_the_task_queued() {
+        let conn = test_conn();
+        seed_project(&conn, "p1");
+        add(&conn, "a", "p1", 0);
+
+        assert!(set_waiting_reason(&conn, "a", Some("Budget exceeded")).unwrap());
+        assert!(!set_waiting_reason(&conn, "a", Some("Budget exceeded")).unwrap());
+        let task = get_queued_task(&conn, "a").unwrap().unwrap();
+        assert_eq!(task.status, "queued");
+        assert_eq!(task.error.as_deref(), Some("Budget exceeded"));
+        assert!(set_waiting_reason(&conn, "a", None).unwrap());
+        assert_eq!(queued_ids(&conn), vec!["a"]);
+    }
+}
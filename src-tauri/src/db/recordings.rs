@@ -0,0 +1,80 @@
+// Session recording operations — asciicast files of a session's PTY runs, one per solo
+// run or team role (see agents/recording.rs). The files live outside the database.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::DbError;
+
+/// A session_recordings row, serialized to camelCase JSON for the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingRow {
+    pub id: i64,
+    pub session_id: String,
+    /// Elf the recorded PTY ran as in team runs. None for solo runs.
+    pub elf_id: Option<String>,
+    /// Absolute path of the `.cast` file.
+    pub path: String,
+    pub created_at: i64,
+}
+
+/// Link a recording file to a session. Returns the new row's ID.
+pub fn insert_recording(conn: &Connection, session_id: &str, elf_id: Option<&str>, path: &str) -> Result<i64, DbError> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO session_recordings (session_id, elf_id, path, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![session_id, elf_id, path, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// A session's recordings, oldest first.
+pub fn list_recordings(conn: &Connection, session_id: &str) -> Result<Vec<RecordingRow>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, session_id, elf_id, path, created_at
+         FROM session_recordings WHERE session_id = ?1
+         ORDER BY created_at, id",
+    )?;
+    let rows = stmt.query_map(params![session_id], |row| {
+        Ok(RecordingRow {
+            id: row.get(0)?,
+            session_id: row.get(1)?,
+            elf_id: row.get(2)?,
+            path: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{projects, schema, sessions};
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        projects::create_project(&conn, "p1", "Test", "/tmp/test-p1").unwrap();
+        sessions::create_session(&conn, "s1", "p1", "Task", "claude-code", None).unwrap();
+        conn
+    }
+
+    #[test]
+    fn recordings_are_listed_per_session_and_removed_with_it() {
+        let conn = test_conn();
+        insert_recording(&conn, "s1", None, "/home/u/.elves/recordings/s1.cast").unwrap();
+        insert_recording(&conn, "s1", Some("e1"), "/home/u/.elves/recordings/s1-e1.cast").unwrap();
+
+        let recordings = list_recordings(&conn, "s1").unwrap();
+        assert_eq!(recordings.len(), 2);
+        assert_eq!(recordings[0].elf_id, None);
+        assert_eq!(recordings[1].elf_id.as_deref(), Some("e1"));
+        assert!(list_recordings(&conn, "other").unwrap().is_empty());
+
+        conn.execute("DELETE FROM sessions WHERE id = 's1'", []).unwrap();
+        assert!(list_recordings(&conn, "s1").unwrap().is_empty());
+    }
+}
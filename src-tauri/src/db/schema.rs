@@ -5,7 +5,7 @@ use rusqlite::Connection;
 use super::DbError;
 
 /// Current schema version. Increment this when adding new migrations.
-const CURRENT_VERSION: i32 = 6;
+const CURRENT_VERSION: i32 = 19;
 
 /// Run all pending migrations up to CURRENT_VERSION.
 /// Uses a schema_version table to track which migrations have been applied.
@@ -44,6 +44,45 @@ pub fn run_migrations(conn: &Connection) -> Result<(), DbError> {
     if current < 6 {
         migrate_v6(conn)?;
     }
+    if current < 7 {
+        migrate_v7(conn)?;
+    }
+    if current < 8 {
+        migrate_v8(conn)?;
+    }
+    if current < 9 {
+        migrate_v9(conn)?;
+    }
+    if current < 10 {
+        migrate_v10(conn)?;
+    }
+    if current < 11 {
+        migrate_v11(conn)?;
+    }
+    if current < 12 {
+        migrate_v12(conn)?;
+    }
+    if current < 13 {
+        migrate_v13(conn)?;
+    }
+    if current < 14 {
+        migrate_v14(conn)?;
+    }
+    if current < 15 {
+        migrate_v15(conn)?;
+    }
+    if current < 16 {
+        migrate_v16(conn)?;
+    }
+    if current < 17 {
+        migrate_v17(conn)?;
+    }
+    if current < 18 {
+        migrate_v18(conn)?;
+    }
+    if current < 19 {
+        migrate_v19(conn)?;
+    }
 
     Ok(())
 }
@@ -391,6 +430,401 @@ fn migrate_v6(conn: &Connection) -> Result<(), DbError> {
     Ok(())
 }
 
+/// Migration v7: Add remote transports to mcp_servers — a transport kind (stdio, http, sse),
+/// the server URL, and request headers for HTTP-based servers.
+fn migrate_v7(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        ALTER TABLE mcp_servers ADD COLUMN transport TEXT NOT NULL DEFAULT 'stdio';
+        ALTER TABLE mcp_servers ADD COLUMN url TEXT;
+        ALTER TABLE mcp_servers ADD COLUMN headers TEXT NOT NULL DEFAULT '{}';
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (7);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 7,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v8: Add memory_pin_requests — agents ask (through the ELVES MCP server) for a
+/// memory to be pinned, and the user approves or dismisses the request.
+fn migrate_v8(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS memory_pin_requests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            memory_id INTEGER NOT NULL REFERENCES memory(id) ON DELETE CASCADE,
+            session_id TEXT,
+            reason TEXT,
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at INTEGER NOT NULL,
+            resolved_at INTEGER
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_pin_requests_status ON memory_pin_requests(status);
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (8);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 8,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v9: Add the typed event kind to events (thinking, text, tool_call, ...).
+/// Rows written before typed events existed keep a NULL kind.
+fn migrate_v9(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        ALTER TABLE events ADD COLUMN kind TEXT;
+
+        CREATE INDEX IF NOT EXISTS idx_events_session_kind ON events(session_id, kind);
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (9);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 9,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v10: Index events by (session_id, event_type) so filtered event pages and
+/// the per-session type histogram don't scan every event of a long session.
+fn migrate_v10(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        CREATE INDEX IF NOT EXISTS idx_events_session_type ON events(session_id, event_type);
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (10);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 10,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v11: Full-text search over sessions and event content.
+///
+/// `session_fts` indexes each session's task and summary; `event_fts` indexes the readable
+/// text of each event (rowid = event id), as extracted by the `event_search_text` view:
+/// the text of typed events, and the common text fields of raw or legacy rows. Triggers
+/// keep both in sync, and existing rows are backfilled.
+fn migrate_v11(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        CREATE VIRTUAL TABLE IF NOT EXISTS session_fts USING fts5(
+            session_id UNINDEXED,
+            task,
+            summary
+        );
+
+        CREATE VIRTUAL TABLE IF NOT EXISTS event_fts USING fts5(
+            text,
+            session_id UNINDEXED
+        );
+
+        CREATE VIEW IF NOT EXISTS event_search_text AS
+        SELECT id, session_id,
+            CASE
+                WHEN json_valid(payload) = 0 THEN payload
+                WHEN kind IN ('text', 'thinking', 'result') THEN json_extract(payload, '$.text')
+                WHEN kind = 'tool_call' THEN
+                    json_extract(payload, '$.name') || ' ' || coalesce(json_extract(payload, '$.input'), '')
+                WHEN kind = 'tool_result' THEN json_extract(payload, '$.content')
+                WHEN kind = 'file_edit' THEN json_extract(payload, '$.path')
+                WHEN kind = 'error' THEN json_extract(payload, '$.message')
+                WHEN kind IS NOT NULL THEN NULL
+                ELSE coalesce(
+                    json_extract(payload, '$.text'),
+                    json_extract(payload, '$.message'),
+                    json_extract(payload, '$.result'),
+                    json_extract(payload, '$.content')
+                )
+            END AS text
+        FROM events;
+
+        CREATE TRIGGER IF NOT EXISTS sessions_fts_ai AFTER INSERT ON sessions BEGIN
+            INSERT INTO session_fts(session_id, task, summary) VALUES (new.id, new.task, new.summary);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sessions_fts_au AFTER UPDATE OF task, summary ON sessions BEGIN
+            DELETE FROM session_fts WHERE session_id = old.id;
+            INSERT INTO session_fts(session_id, task, summary) VALUES (new.id, new.task, new.summary);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sessions_fts_ad AFTER DELETE ON sessions BEGIN
+            DELETE FROM session_fts WHERE session_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS events_fts_ai AFTER INSERT ON events BEGIN
+            INSERT INTO event_fts(rowid, text, session_id)
+            SELECT id, text, session_id FROM event_search_text
+            WHERE id = new.id AND text IS NOT NULL AND text != '';
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS events_fts_au AFTER UPDATE OF payload, kind ON events BEGIN
+            DELETE FROM event_fts WHERE rowid = old.id;
+            INSERT INTO event_fts(rowid, text, session_id)
+            SELECT id, text, session_id FROM event_search_text
+            WHERE id = new.id AND text IS NOT NULL AND text != '';
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS events_fts_ad AFTER DELETE ON events BEGIN
+            DELETE FROM event_fts WHERE rowid = old.id;
+        END;
+
+        -- Backfill existing sessions and events
+        INSERT INTO session_fts(session_id, task, summary) SELECT id, task, summary FROM sessions;
+        INSERT INTO event_fts(rowid, text, session_id)
+        SELECT id, text, session_id FROM event_search_text WHERE text IS NOT NULL AND text != '';
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (11);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 11,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v12: Event retention. `pinned` exempts a session from compaction;
+/// `compacted_at` records when its old events were last compacted.
+fn migrate_v12(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        ALTER TABLE sessions ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE sessions ADD COLUMN compacted_at INTEGER;
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (12);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 12,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v13: How an agent process ended — its exit code and, for failed runs,
+/// the classified reason (auth, rate_limit, budget_exceeded, crash, killed).
+fn migrate_v13(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        ALTER TABLE sessions ADD COLUMN exit_code INTEGER;
+        ALTER TABLE sessions ADD COLUMN failure_reason TEXT;
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (13);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 13,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v14: Retry attempts. `sessions.attempt` is the current attempt number;
+/// `session_attempts` records each attempt's outcome and whether it resumed the
+/// previous attempt's Claude conversation.
+fn migrate_v14(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        ALTER TABLE sessions ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;
+
+        CREATE TABLE IF NOT EXISTS session_attempts (
+            session_id TEXT NOT NULL,
+            attempt INTEGER NOT NULL,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER,
+            exit_code INTEGER,
+            failure_reason TEXT,
+            resumed_from TEXT,
+            PRIMARY KEY (session_id, attempt),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (14);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 14,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v15: Persistent task queue. Tasks wait here until the dispatcher starts
+/// them within the global and per-project concurrency caps.
+fn migrate_v15(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS task_queue (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            task TEXT NOT NULL,
+            runtime TEXT NOT NULL,
+            working_dir TEXT,
+            worktree_slug TEXT,
+            options TEXT,
+            plan TEXT,
+            priority INTEGER NOT NULL DEFAULT 0,
+            position INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'queued',
+            session_id TEXT,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            started_at INTEGER,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_task_queue_status ON task_queue(status, priority, position);
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (15);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 15,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v16: Token usage and cost per model of each session, and per elf in team
+/// runs. A session's `tokens_used` and `cost_estimate` are the totals of its rows.
+fn migrate_v16(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS session_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            elf_id TEXT,
+            model TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_write_tokens INTEGER NOT NULL DEFAULT 0,
+            cost_usd REAL NOT NULL DEFAULT 0.0,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_session_usage_session ON session_usage(session_id);
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (16);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 16,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v17: The template a session was started from, and a log of workspaces
+/// shipped (merged or opened as a pull request), for insights.
+fn migrate_v17(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        ALTER TABLE sessions ADD COLUMN template_id TEXT;
+
+        CREATE TABLE IF NOT EXISTS workspace_ships (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            action TEXT NOT NULL,
+            shipped_at INTEGER NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_workspace_ships_project ON workspace_ships(project_id, shipped_at);
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (17);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 17,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v18: Terminal recordings (asciicast files) of a session's PTY runs, one per
+/// solo run or team role.
+fn migrate_v18(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS session_recordings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            elf_id TEXT,
+            path TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_session_recordings_session ON session_recordings(session_id);
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (18);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 18,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Migration v19: Queued tasks can start in an interactive PTY instead of print mode.
+fn migrate_v19(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "
+        ALTER TABLE task_queue ADD COLUMN pty INTEGER NOT NULL DEFAULT 0;
+
+        -- Record this migration
+        INSERT INTO schema_version (version) VALUES (19);
+        ",
+    )
+    .map_err(|e| DbError::Migration {
+        version: 19,
+        message: e.to_string(),
+    })?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -437,6 +871,11 @@ mod tests {
             "mcp_servers",
             "events",
             "templates",
+            "memory_pin_requests",
+            "task_queue",
+            "session_usage",
+            "workspace_ships",
+            "session_recordings",
             "schema_version",
         ];
 
@@ -452,6 +891,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn search_index_backfills_existing_rows() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                applied_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );",
+        )
+        .unwrap();
+        for version in 1..=10 {
+            match version {
+                1 => migrate_v1(&conn),
+                2 => migrate_v2(&conn),
+                3 => migrate_v3(&conn),
+                4 => migrate_v4(&conn),
+                5 => migrate_v5(&conn),
+                6 => migrate_v6(&conn),
+                7 => migrate_v7(&conn),
+                8 => migrate_v8(&conn),
+                9 => migrate_v9(&conn),
+                _ => migrate_v10(&conn),
+            }
+            .expect("Earlier migrations should succeed");
+        }
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, path, default_runtime, created_at, updated_at)
+             VALUES ('p', 'P', '/tmp/p', 'claude-code', 0, 0);
+             INSERT INTO sessions (id, project_id, task, runtime, status, agent_count, started_at, tokens_used, cost_estimate)
+             VALUES ('s', 'p', 'Old task about tokenizers', 'claude-code', 'completed', 1, 0, 0, 0.0);
+             INSERT INTO events (session_id, event_type, payload, timestamp)
+             VALUES ('s', 'output', '{\"text\":\"legacy heuristics\"}', 0);",
+        )
+        .unwrap();
+
+        run_migrations(&conn).expect("Search migration should succeed");
+        let sessions: i64 = conn
+            .query_row("SELECT COUNT(*) FROM session_fts WHERE session_fts MATCH 'tokenizers'", [], |row| row.get(0))
+            .unwrap();
+        let events: i64 = conn
+            .query_row("SELECT COUNT(*) FROM event_fts WHERE event_fts MATCH 'heuristics'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!((sessions, events), (1, 1));
+    }
+
     #[test]
     fn fts_virtual_table_created() {
         let conn = test_conn();
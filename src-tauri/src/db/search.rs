@@ -0,0 +1,339 @@
+// Session search — full-text search across session tasks, summaries, and event content.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::sessions::{self, SessionRow};
+use super::DbError;
+
+/// Snippet highlight markers wrapped around matched terms. The rest of a snippet is
+/// HTML-escaped, so snippets can be rendered as HTML.
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+/// Markers SQLite puts around matched terms, replaced by the highlight markers once the
+/// snippet is escaped. Control characters, so they don't clash with indexed text.
+const MATCH_START: &str = "\u{2}";
+const MATCH_END: &str = "\u{3}";
+
+/// Event matches considered per search, best first. Bounds the work for common terms.
+const MAX_EVENT_MATCHES: i64 = 2000;
+/// Event snippets returned per session.
+const EVENT_SNIPPETS_PER_SESSION: usize = 3;
+/// Matching event ids returned per session.
+const EVENT_IDS_PER_SESSION: usize = 50;
+/// A task or summary match counts this much more than a single event match.
+const SESSION_MATCH_WEIGHT: f64 = 2.0;
+
+/// Search text and optional filters for `search_sessions`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchQuery {
+    pub query: String,
+    pub project_id: Option<String>,
+    pub runtime: Option<String>,
+    pub status: Option<String>,
+    /// Only sessions started at or after this Unix timestamp (seconds).
+    pub started_after: Option<i64>,
+    /// Only sessions started at or before this Unix timestamp (seconds).
+    pub started_before: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// A highlighted excerpt of one matching event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSnippet {
+    pub event_id: i64,
+    pub snippet: String,
+}
+
+/// One session matching a search, with highlighted excerpts.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchHit {
+    pub session: SessionRow,
+    /// Relevance, higher is better. Combines the task/summary match with event matches.
+    pub score: f64,
+    /// Highlighted excerpt of the task or summary, when either matched.
+    pub snippet: Option<String>,
+    /// Highlighted excerpts of the best-matching events.
+    pub event_snippets: Vec<EventSnippet>,
+    /// Ids of matching events in chronological order, for jumping into the replay.
+    pub matching_event_ids: Vec<i64>,
+}
+
+/// Accumulated matches for one session while ranking.
+#[derive(Default)]
+struct Candidate {
+    session_score: f64,
+    snippet: Option<String>,
+    best_event_score: f64,
+    event_snippets: Vec<EventSnippet>,
+    event_ids: Vec<i64>,
+}
+
+/// Search sessions by task, summary, and event text, applying the query's filters.
+///
+/// Words in the query must all match (in the task, summary, or any one event); the last
+/// word also matches as a prefix, so results appear while the user is still typing.
+/// Returns sessions ranked by relevance, best first.
+pub fn search_sessions(
+    conn: &Connection,
+    query: &SessionSearchQuery,
+) -> Result<Vec<SessionSearchHit>, DbError> {
+    let Some(fts) = fts_query(&query.query) else {
+        return Ok(Vec::new());
+    };
+    let limit = query.limit.unwrap_or(20).clamp(1, 200) as usize;
+    let mut candidates: HashMap<String, Candidate> = HashMap::new();
+
+    // Filters shared by both queries, applied through the sessions join
+    const FILTERS: &str = "(?2 IS NULL OR s.project_id = ?2)
+           AND (?3 IS NULL OR s.runtime = ?3)
+           AND (?4 IS NULL OR s.status = ?4)
+           AND (?5 IS NULL OR s.started_at >= ?5)
+           AND (?6 IS NULL OR s.started_at <= ?6)";
+    let filter_params = (
+        query.project_id.as_deref(),
+        query.runtime.as_deref(),
+        query.status.as_deref(),
+        query.started_after,
+        query.started_before,
+    );
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT f.session_id, bm25(session_fts), snippet(session_fts, -1, ?7, ?8, '…', 16)
+         FROM session_fts f JOIN sessions s ON s.id = f.session_id
+         WHERE session_fts MATCH ?1 AND {FILTERS}"
+    ))?;
+    let rows = stmt.query_map(
+        params![
+            fts,
+            filter_params.0,
+            filter_params.1,
+            filter_params.2,
+            filter_params.3,
+            filter_params.4,
+            MATCH_START,
+            MATCH_END
+        ],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?, row.get::<_, String>(2)?)),
+    )?;
+    for row in rows {
+        let (session_id, bm25, snippet) = row?;
+        let candidate = candidates.entry(session_id).or_default();
+        // bm25() is lower for better matches; flip it so higher is better
+        candidate.session_score = -bm25 * SESSION_MATCH_WEIGHT;
+        candidate.snippet = Some(highlight(&snippet));
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT f.rowid, f.session_id, bm25(event_fts), snippet(event_fts, 0, ?7, ?8, '…', 16)
+         FROM event_fts f JOIN sessions s ON s.id = f.session_id
+         WHERE event_fts MATCH ?1 AND {FILTERS}
+         ORDER BY bm25(event_fts) LIMIT ?9"
+    ))?;
+    let rows = stmt.query_map(
+        params![
+            fts,
+            filter_params.0,
+            filter_params.1,
+            filter_params.2,
+            filter_params.3,
+            filter_params.4,
+            MATCH_START,
+            MATCH_END,
+            MAX_EVENT_MATCHES
+        ],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        },
+    )?;
+    for row in rows {
+        let (event_id, session_id, bm25, snippet) = row?;
+        let candidate = candidates.entry(session_id).or_default();
+        // Rows arrive best first, so the first event seen is the session's best
+        if candidate.event_ids.is_empty() {
+            candidate.best_event_score = -bm25;
+        }
+        if candidate.event_snippets.len() < EVENT_SNIPPETS_PER_SESSION {
+            candidate.event_snippets.push(EventSnippet {
+                event_id,
+                snippet: highlight(&snippet),
+            });
+        }
+        candidate.event_ids.push(event_id);
+    }
+
+    let mut ranked: Vec<(String, Candidate, f64)> = candidates
+        .into_iter()
+        .map(|(session_id, c)| {
+            // More matching events nudge the score up, with diminishing returns
+            let breadth = (c.event_ids.len() as f64).ln_1p();
+            let score = c.session_score + c.best_event_score + breadth;
+            (session_id, c, score)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(limit);
+
+    let mut hits = Vec::with_capacity(ranked.len());
+    for (session_id, mut candidate, score) in ranked {
+        let Some(session) = sessions::get_session(conn, &session_id)? else {
+            continue;
+        };
+        candidate.event_ids.sort_unstable();
+        candidate.event_ids.truncate(EVENT_IDS_PER_SESSION);
+        hits.push(SessionSearchHit {
+            session,
+            score,
+            snippet: candidate.snippet,
+            event_snippets: candidate.event_snippets,
+            matching_event_ids: candidate.event_ids,
+        });
+    }
+    Ok(hits)
+}
+
+/// HTML-escape a snippet from SQLite, then swap its match markers for the highlight
+/// markers. Indexed text is agent output, so it must not reach the page as markup.
+fn highlight(snippet: &str) -> String {
+    let mut out = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out.replace(MATCH_START, HIGHLIGHT_START).replace(MATCH_END, HIGHLIGHT_END)
+}
+
+/// Turn free text into an FTS5 query: every word quoted (so punctuation and FTS syntax in
+/// the input are literal) and required, with the last word also matched as a prefix.
+/// Returns None when the text has no searchable words.
+fn fts_query(text: &str) -> Option<String> {
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .collect();
+    let (last, rest) = words.split_last()?;
+    let mut terms: Vec<String> = rest.iter().map(|word| format!("\"{word}\"")).collect();
+    terms.push(format!("\"{last}\"*"));
+    Some(terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{events, projects, schema};
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        projects::create_project(&conn, "proj-1", "One", "/tmp/one").unwrap();
+        projects::create_project(&conn, "proj-2", "Two", "/tmp/two").unwrap();
+        conn
+    }
+
+    fn query(text: &str) -> SessionSearchQuery {
+        SessionSearchQuery {
+            query: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fts_query_quotes_words_and_prefixes_last() {
+        assert_eq!(fts_query("pty utf-8").as_deref(), Some("\"pty\" \"utf\" \"8\"*"));
+        assert_eq!(fts_query("\"OR\" NEAR(").as_deref(), Some("\"OR\" \"NEAR\"*"));
+        assert_eq!(fts_query(" -- "), None);
+    }
+
+    #[test]
+    fn finds_sessions_by_task_summary_and_events() {
+        let conn = test_conn();
+        sessions::create_session(&conn, "s1", "proj-1", "Fix the PTY UTF-8 bug", "claude-code", None).unwrap();
+        sessions::create_session(&conn, "s2", "proj-1", "Refactor sidebar", "codex", None).unwrap();
+        sessions::update_session_status(&conn, "s2", "completed", Some("Moved the sidebar state into a store")).unwrap();
+        let typed = r#"{"kind":"text","text":"The reader split a multi-byte UTF-8 sequence"}"#;
+        let hit = events::insert_typed_event(&conn, "s2", None, "text", typed).unwrap();
+        events::insert_typed_event(&conn, "s2", None, "usage", r#"{"kind":"usage","inputTokens":1}"#).unwrap();
+
+        let results = search_sessions(&conn, &query("utf")).unwrap();
+        let ids: Vec<&str> = results.iter().map(|h| h.session.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        let s1 = results.iter().find(|h| h.session.id == "s1").unwrap();
+        assert!(s1.snippet.as_deref().unwrap().contains("<mark>UTF</mark>"));
+        assert!(s1.matching_event_ids.is_empty());
+        let s2 = results.iter().find(|h| h.session.id == "s2").unwrap();
+        assert_eq!(s2.matching_event_ids, vec![hit.id]);
+        assert!(s2.event_snippets[0].snippet.contains("<mark>UTF</mark>"));
+
+        let summary = search_sessions(&conn, &query("store")).unwrap();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].session.id, "s2");
+
+        // All words must match
+        assert!(search_sessions(&conn, &query("sidebar pty")).unwrap().is_empty());
+        assert!(search_sessions(&conn, &query("   ")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn snippets_escape_html_around_the_highlights() {
+        let conn = test_conn();
+        sessions::create_session(&conn, "s1", "proj-1", "Task", "claude-code", None).unwrap();
+        events::insert_event(&conn, "s1", None, "text", "<img src=x onerror=alert(1)> & payload", None).unwrap();
+
+        let results = search_sessions(&conn, &query("payload")).unwrap();
+        assert_eq!(
+            results[0].event_snippets[0].snippet,
+            "&lt;img src=x onerror=alert(1)&gt; &amp; <mark>payload</mark>"
+        );
+    }
+
+    #[test]
+    fn applies_filters() {
+        let conn = test_conn();
+        sessions::create_session(&conn, "s1", "proj-1", "Deploy pipeline", "claude-code", None).unwrap();
+        sessions::create_session(&conn, "s2", "proj-2", "Deploy docs", "codex", None).unwrap();
+        conn.execute("UPDATE sessions SET started_at = 1000 WHERE id = 's1'", []).unwrap();
+
+        let project = SessionSearchQuery { project_id: Some("proj-2".into()), ..query("deploy") };
+        assert_eq!(search_sessions(&conn, &project).unwrap()[0].session.id, "s2");
+
+        let runtime = SessionSearchQuery { runtime: Some("claude-code".into()), ..query("deploy") };
+        assert_eq!(search_sessions(&conn, &runtime).unwrap()[0].session.id, "s1");
+
+        let dated = SessionSearchQuery { started_before: Some(2000), ..query("deploy") };
+        let results = search_sessions(&conn, &dated).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session.id, "s1");
+
+        let status = SessionSearchQuery { status: Some("completed".into()), ..query("deploy") };
+        assert!(search_sessions(&conn, &status).unwrap().is_empty());
+    }
+
+    #[test]
+    fn deleted_events_leave_the_index() {
+        let conn = test_conn();
+        sessions::create_session(&conn, "s1", "proj-1", "Task", "claude-code", None).unwrap();
+        events::insert_event(&conn, "s1", None, "error", "Connection refused on port 5432", None).unwrap();
+        assert_eq!(search_sessions(&conn, &query("refused")).unwrap().len(), 1);
+
+        conn.execute("DELETE FROM events WHERE session_id = 's1'", []).unwrap();
+        assert!(search_sessions(&conn, &query("refused")).unwrap().is_empty());
+    }
+}
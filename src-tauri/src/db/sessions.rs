@@ -13,7 +13,7 @@ pub struct SessionRow {
     pub project_id: String,
     pub task: String,
     pub runtime: String,
-    /// One of: "active", "completed", "error", "cancelled".
+    /// One of: "active", "completed", "failed", "interrupted", "timed_out", "error", "cancelled".
     pub status: String,
     /// Optional JSON string representing the agent execution plan.
     pub plan: Option<String>,
@@ -27,8 +27,38 @@ pub struct SessionRow {
     pub claude_session_id: Option<String>,
     /// Workspace slug linking this session to a worktree-based workspace.
     pub worktree_slug: Option<String>,
+    /// Pinned sessions are never touched by event retention.
+    pub pinned: bool,
+    /// When retention last compacted this session's events, if ever.
+    pub compacted_at: Option<i64>,
+    /// Exit code of the agent process, once it has exited normally.
+    pub exit_code: Option<i32>,
+    /// Why a "failed" session failed: auth, rate_limit, budget_exceeded, crash, or killed.
+    pub failure_reason: Option<String>,
+    /// Current attempt number; above 1 once a failed run has been retried.
+    pub attempt: i32,
+    /// Template the session's plan was loaded from, if any.
+    pub template_id: Option<String>,
 }
 
+/// One attempt at running a session's task, from the `session_attempts` table.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAttempt {
+    pub session_id: String,
+    pub attempt: i32,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    pub exit_code: Option<i32>,
+    pub failure_reason: Option<String>,
+    /// Claude session ID this attempt resumed with `--resume`, or None if it restarted.
+    pub resumed_from: Option<String>,
+}
+
+const SESSION_COLUMNS: &str = "id, project_id, task, runtime, status, plan, agent_count, started_at, \
+     ended_at, tokens_used, cost_estimate, summary, claude_session_id, worktree_slug, pinned, compacted_at, \
+     exit_code, failure_reason, attempt, template_id";
+
 /// Insert a new session into the database. Returns the created session row.
 ///
 /// The session starts with status "active", agent_count 1, and zero token usage.
@@ -55,32 +85,12 @@ pub fn create_session(
 
 /// Retrieve a single session by ID. Returns None if the session does not exist.
 pub fn get_session(conn: &Connection, id: &str) -> Result<Option<SessionRow>, DbError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, task, runtime, status, plan, agent_count,
-                started_at, ended_at, tokens_used, cost_estimate, summary, claude_session_id,
-                worktree_slug
-         FROM sessions WHERE id = ?1",
-    )?;
-
-    let result = stmt
-        .query_row(params![id], |row| {
-            Ok(SessionRow {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                task: row.get(2)?,
-                runtime: row.get(3)?,
-                status: row.get(4)?,
-                plan: row.get(5)?,
-                agent_count: row.get(6)?,
-                started_at: row.get(7)?,
-                ended_at: row.get(8)?,
-                tokens_used: row.get(9)?,
-                cost_estimate: row.get(10)?,
-                summary: row.get(11)?,
-                claude_session_id: row.get(12)?,
-                worktree_slug: row.get(13)?,
-            })
-        })
+    let result = conn
+        .query_row(
+            &format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE id = ?1"),
+            params![id],
+            map_session_row,
+        )
         .optional()?;
 
     Ok(result)
@@ -91,32 +101,12 @@ pub fn list_sessions(
     conn: &Connection,
     project_id: &str,
 ) -> Result<Vec<SessionRow>, DbError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, task, runtime, status, plan, agent_count,
-                started_at, ended_at, tokens_used, cost_estimate, summary, claude_session_id,
-                worktree_slug
-         FROM sessions WHERE project_id = ?1 ORDER BY started_at DESC",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SESSION_COLUMNS} FROM sessions WHERE project_id = ?1 ORDER BY started_at DESC"
+    ))?;
 
     let rows = stmt
-        .query_map(params![project_id], |row| {
-            Ok(SessionRow {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                task: row.get(2)?,
-                runtime: row.get(3)?,
-                status: row.get(4)?,
-                plan: row.get(5)?,
-                agent_count: row.get(6)?,
-                started_at: row.get(7)?,
-                ended_at: row.get(8)?,
-                tokens_used: row.get(9)?,
-                cost_estimate: row.get(10)?,
-                summary: row.get(11)?,
-                claude_session_id: row.get(12)?,
-                worktree_slug: row.get(13)?,
-            })
-        })?
+        .query_map(params![project_id], map_session_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(rows)
@@ -131,7 +121,7 @@ pub fn update_session_status(
     status: &str,
     summary: Option<&str>,
 ) -> Result<bool, DbError> {
-    let is_terminal = matches!(status, "completed" | "error" | "cancelled");
+    let is_terminal = matches!(status, "completed" | "failed" | "interrupted" | "timed_out" | "error" | "cancelled");
     let ended_at: Option<i64> = if is_terminal {
         Some(chrono::Utc::now().timestamp())
     } else {
@@ -163,6 +153,18 @@ pub fn update_session_usage(
     Ok(rows_affected > 0)
 }
 
+/// Total recorded cost of sessions started at or after `since` (Unix seconds), for one
+/// project or all of them.
+pub fn total_cost_since(conn: &Connection, project_id: Option<&str>, since: i64) -> Result<f64, DbError> {
+    let total = conn.query_row(
+        "SELECT COALESCE(SUM(cost_estimate), 0.0) FROM sessions
+         WHERE started_at >= ?1 AND (?2 IS NULL OR project_id = ?2)",
+        params![since, project_id],
+        |row| row.get(0),
+    )?;
+    Ok(total)
+}
+
 /// Store the Claude Code session ID for a session. Used for `claude --resume` support.
 /// Returns true if a row was updated.
 pub fn update_claude_session_id(
@@ -177,19 +179,175 @@ pub fn update_claude_session_id(
     Ok(rows > 0)
 }
 
-/// Mark all "active" sessions as "failed" — called on app startup to clean up
-/// sessions from previous runs that were never completed (e.g., app crash, force quit).
-/// Returns the number of sessions cleaned up.
-pub fn cleanup_stale_sessions(conn: &Connection) -> Result<usize, DbError> {
+/// The session of a project that ran (or imported) a Claude Code session, if any.
+pub fn find_by_claude_session_id(
+    conn: &Connection,
+    project_id: &str,
+    claude_session_id: &str,
+) -> Result<Option<SessionRow>, DbError> {
+    let result = conn
+        .query_row(
+            &format!(
+                "SELECT {SESSION_COLUMNS} FROM sessions WHERE project_id = ?1 AND claude_session_id = ?2
+                 ORDER BY started_at DESC LIMIT 1"
+            ),
+            params![project_id, claude_session_id],
+            map_session_row,
+        )
+        .optional()?;
+    Ok(result)
+}
+
+/// Mark an imported session "completed" with the times of the run it was imported from.
+pub fn finish_imported_session(conn: &Connection, id: &str, started_at: i64, ended_at: i64) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE sessions SET status = 'completed', started_at = ?1, ended_at = ?2 WHERE id = ?3",
+        params![started_at, ended_at, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Record the template a session's plan was loaded from.
+pub fn set_session_template(conn: &Connection, id: &str, template_id: &str) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE sessions SET template_id = ?1 WHERE id = ?2",
+        params![template_id, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Set a session's summary unless it already has one, e.g. from a PTY run's transcript.
+/// Returns true if the summary was set.
+pub fn set_summary_if_missing(conn: &Connection, id: &str, summary: &str) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE sessions SET summary = ?1 WHERE id = ?2 AND summary IS NULL",
+        params![summary, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Record the exit code of a session's agent process.
+pub fn update_session_exit_code(conn: &Connection, id: &str, exit_code: i32) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE sessions SET exit_code = ?1 WHERE id = ?2",
+        params![exit_code, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Mark a session "failed" with a classified reason (see `agents::failure::FailureReason`).
+/// Sets `ended_at`, and the summary when one is given. Returns true if a row was updated.
+pub fn fail_session(
+    conn: &Connection,
+    id: &str,
+    reason: &str,
+    summary: Option<&str>,
+) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE sessions SET status = 'failed', failure_reason = ?1, ended_at = ?2,
+             summary = COALESCE(?3, summary)
+         WHERE id = ?4",
+        params![reason, chrono::Utc::now().timestamp(), summary, id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Start attempt `attempt` of a session: record it and make the session active again.
+///
+/// Clears the previous attempt's end time, exit code, and failure reason on the session.
+pub fn begin_attempt(
+    conn: &Connection,
+    session_id: &str,
+    attempt: i32,
+    resumed_from: Option<&str>,
+) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO session_attempts (session_id, attempt, started_at, resumed_from)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![session_id, attempt, chrono::Utc::now().timestamp(), resumed_from],
+    )?;
+    conn.execute(
+        "UPDATE sessions SET attempt = ?1, status = 'active', ended_at = NULL, exit_code = NULL,
+             failure_reason = NULL
+         WHERE id = ?2",
+        params![attempt, session_id],
+    )?;
+    Ok(())
+}
+
+/// Record how attempt `attempt` of a session ended.
+pub fn end_attempt(
+    conn: &Connection,
+    session_id: &str,
+    attempt: i32,
+    exit_code: Option<i32>,
+    failure_reason: Option<&str>,
+) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE session_attempts SET ended_at = ?1, exit_code = ?2, failure_reason = ?3
+         WHERE session_id = ?4 AND attempt = ?5",
+        params![chrono::Utc::now().timestamp(), exit_code, failure_reason, session_id, attempt],
+    )?;
+    Ok(())
+}
+
+/// List a session's attempts, first attempt first.
+pub fn list_session_attempts(conn: &Connection, session_id: &str) -> Result<Vec<SessionAttempt>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT session_id, attempt, started_at, ended_at, exit_code, failure_reason, resumed_from
+         FROM session_attempts WHERE session_id = ?1 ORDER BY attempt",
+    )?;
+    let rows = stmt
+        .query_map(params![session_id], |row| {
+            Ok(SessionAttempt {
+                session_id: row.get(0)?,
+                attempt: row.get(1)?,
+                started_at: row.get(2)?,
+                ended_at: row.get(3)?,
+                exit_code: row.get(4)?,
+                failure_reason: row.get(5)?,
+                resumed_from: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Mark all "active" sessions as "interrupted" — called on app startup for sessions
+/// from previous runs that were never completed (e.g., app crash, force quit).
+/// Interrupted sessions are offered for resume; see `list_interrupted_sessions`.
+/// Returns the number of sessions marked.
+pub fn mark_interrupted_sessions(conn: &Connection) -> Result<usize, DbError> {
     let now = chrono::Utc::now().timestamp();
     let rows = conn.execute(
-        "UPDATE sessions SET status = 'failed', ended_at = ?1, summary = 'Session interrupted (app restarted)'
+        "UPDATE sessions SET status = 'interrupted', ended_at = ?1, summary = 'Session interrupted (app restarted)'
          WHERE status = 'active'",
         params![now],
     )?;
     Ok(rows)
 }
 
+/// List sessions interrupted by an app restart, most recent first, across all projects.
+pub fn list_interrupted_sessions(conn: &Connection) -> Result<Vec<SessionRow>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SESSION_COLUMNS} FROM sessions WHERE status = 'interrupted' ORDER BY started_at DESC"
+    ))?;
+    let rows = stmt
+        .query_map([], map_session_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Make a finished session active again, e.g. when it is resumed in a terminal.
+/// Returns true if a row was updated.
+pub fn reopen_session(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    let rows_affected = conn.execute(
+        "UPDATE sessions SET status = 'active', ended_at = NULL, failure_reason = NULL WHERE id = ?1",
+        params![id],
+    )?;
+    Ok(rows_affected > 0)
+}
+
 /// Retrieve the most recent session for a given project + workspace slug combination.
 /// Used to offer "Resume" on workspace cards when a previous session exists.
 pub fn get_last_session_for_workspace(
@@ -197,38 +355,54 @@ pub fn get_last_session_for_workspace(
     project_id: &str,
     worktree_slug: &str,
 ) -> Result<Option<SessionRow>, DbError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, task, runtime, status, plan, agent_count,
-                started_at, ended_at, tokens_used, cost_estimate, summary, claude_session_id,
-                worktree_slug
-         FROM sessions WHERE project_id = ?1 AND worktree_slug = ?2
-         ORDER BY started_at DESC LIMIT 1",
-    )?;
-
-    let result = stmt
-        .query_row(params![project_id, worktree_slug], |row| {
-            Ok(SessionRow {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                task: row.get(2)?,
-                runtime: row.get(3)?,
-                status: row.get(4)?,
-                plan: row.get(5)?,
-                agent_count: row.get(6)?,
-                started_at: row.get(7)?,
-                ended_at: row.get(8)?,
-                tokens_used: row.get(9)?,
-                cost_estimate: row.get(10)?,
-                summary: row.get(11)?,
-                claude_session_id: row.get(12)?,
-                worktree_slug: row.get(13)?,
-            })
-        })
+    let result = conn
+        .query_row(
+            &format!(
+                "SELECT {SESSION_COLUMNS} FROM sessions WHERE project_id = ?1 AND worktree_slug = ?2
+                 ORDER BY started_at DESC LIMIT 1"
+            ),
+            params![project_id, worktree_slug],
+            map_session_row,
+        )
         .optional()?;
 
     Ok(result)
 }
 
+/// Pin or unpin a session. Pinned sessions keep their full event log regardless of retention.
+pub fn set_session_pinned(conn: &Connection, id: &str, pinned: bool) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE sessions SET pinned = ?1 WHERE id = ?2",
+        params![pinned, id],
+    )?;
+    Ok(())
+}
+
+fn map_session_row(row: &rusqlite::Row) -> rusqlite::Result<SessionRow> {
+    Ok(SessionRow {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        task: row.get(2)?,
+        runtime: row.get(3)?,
+        status: row.get(4)?,
+        plan: row.get(5)?,
+        agent_count: row.get(6)?,
+        started_at: row.get(7)?,
+        ended_at: row.get(8)?,
+        tokens_used: row.get(9)?,
+        cost_estimate: row.get(10)?,
+        summary: row.get(11)?,
+        claude_session_id: row.get(12)?,
+        worktree_slug: row.get(13)?,
+        pinned: row.get(14)?,
+        compacted_at: row.get(15)?,
+        exit_code: row.get(16)?,
+        failure_reason: row.get(17)?,
+        attempt: row.get(18)?,
+        template_id: row.get(19)?,
+    })
+}
+
 /// Use rusqlite's optional() extension for query_row.
 trait OptionalExt<T> {
     fn optional(self) -> Result<Option<T>, rusqlite::Error>;
@@ -268,6 +442,21 @@ mod tests {
         .expect("Should seed project");
     }
 
+    #[test]
+    fn imported_sessions_are_found_by_claude_session_id() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-1");
+        create_session(&conn, "sess-1", "proj-1", "Imported", "claude-code", None).unwrap();
+        assert!(find_by_claude_session_id(&conn, "proj-1", "claude-abc").unwrap().is_none());
+
+        update_claude_session_id(&conn, "sess-1", "claude-abc").unwrap();
+        finish_imported_session(&conn, "sess-1", 1_000, 1_600).unwrap();
+        let session = find_by_claude_session_id(&conn, "proj-1", "claude-abc").unwrap().unwrap();
+        assert_eq!(session.id, "sess-1");
+        assert_eq!(session.status, "completed");
+        assert_eq!((session.started_at, session.ended_at), (1_000, Some(1_600)));
+    }
+
     #[test]
     fn create_and_get_session() {
         let conn = test_conn();
@@ -339,6 +528,19 @@ mod tests {
         assert_eq!(session.summary.as_deref(), Some("All done"));
     }
 
+    #[test]
+    fn set_summary_if_missing_keeps_existing_summaries() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-1");
+        create_session(&conn, "s1", "proj-1", "Task A", "claude-code", None).unwrap();
+
+        assert!(set_summary_if_missing(&conn, "s1", "From transcript").unwrap());
+        assert!(!set_summary_if_missing(&conn, "s1", "Later").unwrap());
+        update_session_status(&conn, "s1", "completed", None).unwrap();
+        let session = get_session(&conn, "s1").unwrap().unwrap();
+        assert_eq!(session.summary.as_deref(), Some("From transcript"));
+    }
+
     #[test]
     fn update_session_status_to_error() {
         let conn = test_conn();
@@ -411,6 +613,25 @@ mod tests {
         assert!((session.cost_estimate - 0.0342).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn total_cost_since_filters_by_project_and_start() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-1");
+        seed_project(&conn, "proj-2");
+        create_session(&conn, "s1", "proj-1", "Task A", "claude-code", None).unwrap();
+        create_session(&conn, "s2", "proj-1", "Task B", "claude-code", None).unwrap();
+        create_session(&conn, "s3", "proj-2", "Task C", "codex", None).unwrap();
+        super::update_session_usage(&conn, "s1", 100, 1.5).unwrap();
+        super::update_session_usage(&conn, "s2", 100, 0.25).unwrap();
+        super::update_session_usage(&conn, "s3", 100, 2.0).unwrap();
+        conn.execute("UPDATE sessions SET started_at = 1000 WHERE id = 's2'", []).unwrap();
+
+        assert_eq!(total_cost_since(&conn, Some("proj-1"), 0).unwrap(), 1.75);
+        assert_eq!(total_cost_since(&conn, Some("proj-1"), 2000).unwrap(), 1.5);
+        assert_eq!(total_cost_since(&conn, None, 2000).unwrap(), 3.5);
+        assert_eq!(total_cost_since(&conn, Some("proj-3"), 0).unwrap(), 0.0);
+    }
+
     #[test]
     fn update_session_usage_nonexistent_returns_false() {
         let conn = test_conn();
@@ -488,4 +709,82 @@ mod tests {
             .expect("Should query without error");
         assert!(result.is_none());
     }
+
+    #[test]
+    fn set_session_pinned_round_trips() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-1");
+        let session = create_session(&conn, "s1", "proj-1", "Task", "claude-code", None).unwrap();
+        assert!(!session.pinned);
+        assert!(session.compacted_at.is_none());
+
+        set_session_pinned(&conn, "s1", true).unwrap();
+        assert!(get_session(&conn, "s1").unwrap().unwrap().pinned);
+
+        set_session_pinned(&conn, "s1", false).unwrap();
+        assert!(!get_session(&conn, "s1").unwrap().unwrap().pinned);
+    }
+
+    #[test]
+    fn fail_session_records_reason_and_exit_code() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-1");
+        create_session(&conn, "s1", "proj-1", "Task", "claude-code", None).unwrap();
+
+        assert!(update_session_exit_code(&conn, "s1", 1).unwrap());
+        assert!(fail_session(&conn, "s1", "rate_limit", Some("429 Too Many Requests")).unwrap());
+
+        let session = get_session(&conn, "s1").unwrap().unwrap();
+        assert_eq!(session.status, "failed");
+        assert_eq!(session.exit_code, Some(1));
+        assert_eq!(session.failure_reason.as_deref(), Some("rate_limit"));
+        assert_eq!(session.summary.as_deref(), Some("429 Too Many Requests"));
+        assert!(session.ended_at.is_some());
+    }
+
+    #[test]
+    fn attempts_are_recorded_in_order() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-1");
+        create_session(&conn, "s1", "proj-1", "Task", "claude-code", None).unwrap();
+
+        begin_attempt(&conn, "s1", 1, None).unwrap();
+        end_attempt(&conn, "s1", 1, Some(1), Some("rate_limit")).unwrap();
+        fail_session(&conn, "s1", "rate_limit", None).unwrap();
+
+        begin_attempt(&conn, "s1", 2, Some("claude-abc")).unwrap();
+        let session = get_session(&conn, "s1").unwrap().unwrap();
+        assert_eq!(session.attempt, 2);
+        assert_eq!(session.status, "active");
+        assert!(session.failure_reason.is_none());
+
+        let attempts = list_session_attempts(&conn, "s1").unwrap();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].failure_reason.as_deref(), Some("rate_limit"));
+        assert_eq!(attempts[0].exit_code, Some(1));
+        assert!(attempts[0].ended_at.is_some());
+        assert_eq!(attempts[1].resumed_from.as_deref(), Some("claude-abc"));
+        assert!(attempts[1].ended_at.is_none());
+    }
+
+    #[test]
+    fn interrupted_sessions_are_listed_and_reopened() {
+        let conn = test_conn();
+        seed_project(&conn, "proj-1");
+        create_session(&conn, "running", "proj-1", "Task", "claude-code", None).unwrap();
+        create_session(&conn, "done", "proj-1", "Task", "claude-code", None).unwrap();
+        update_session_status(&conn, "done", "completed", None).unwrap();
+
+        assert_eq!(mark_interrupted_sessions(&conn).unwrap(), 1);
+        let interrupted = list_interrupted_sessions(&conn).unwrap();
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].id, "running");
+        assert!(interrupted[0].ended_at.is_some());
+
+        assert!(reopen_session(&conn, "running").unwrap());
+        let session = get_session(&conn, "running").unwrap().unwrap();
+        assert_eq!(session.status, "active");
+        assert!(session.ended_at.is_none());
+        assert!(list_interrupted_sessions(&conn).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,173 @@
+// Session usage operations — token counts and cost per model of each session, and per
+// elf in team runs. A session's `tokens_used` and `cost_estimate` are kept as the totals
+// of its usage rows.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::DbError;
+
+/// A session_usage row, serialized to camelCase JSON for the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUsage {
+    pub session_id: String,
+    /// Elf the usage is attributed to in team runs. None for solo runs.
+    pub elf_id: Option<String>,
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cost_usd: f64,
+}
+
+/// Add usage to a session's row for the same elf and model, creating it if needed.
+pub fn add_session_usage(conn: &Connection, usage: &SessionUsage) -> Result<(), DbError> {
+    let updated = conn.execute(
+        "UPDATE session_usage SET
+             input_tokens = input_tokens + ?4,
+             output_tokens = output_tokens + ?5,
+             cache_read_tokens = cache_read_tokens + ?6,
+             cache_write_tokens = cache_write_tokens + ?7,
+             cost_usd = cost_usd + ?8
+         WHERE session_id = ?1 AND elf_id IS ?2 AND model = ?3",
+        params![
+            usage.session_id,
+            usage.elf_id,
+            usage.model,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_read_tokens,
+            usage.cache_write_tokens,
+            usage.cost_usd,
+        ],
+    )?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO session_usage
+                 (session_id, elf_id, model, input_tokens, output_tokens, cache_read_tokens, cache_write_tokens, cost_usd)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                usage.session_id,
+                usage.elf_id,
+                usage.model,
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.cache_read_tokens,
+                usage.cache_write_tokens,
+                usage.cost_usd,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// A session's usage rows, solo usage first, then by elf and model.
+pub fn list_session_usage(conn: &Connection, session_id: &str) -> Result<Vec<SessionUsage>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT session_id, elf_id, model, input_tokens, output_tokens, cache_read_tokens, cache_write_tokens, cost_usd
+         FROM session_usage WHERE session_id = ?1
+         ORDER BY elf_id IS NOT NULL, elf_id, model",
+    )?;
+    let rows = stmt.query_map(params![session_id], |row| {
+        Ok(SessionUsage {
+            session_id: row.get(0)?,
+            elf_id: row.get(1)?,
+            model: row.get(2)?,
+            input_tokens: row.get(3)?,
+            output_tokens: row.get(4)?,
+            cache_read_tokens: row.get(5)?,
+            cache_write_tokens: row.get(6)?,
+            cost_usd: row.get(7)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+}
+
+/// Set a session's `tokens_used` (input plus output) and `cost_estimate` to the totals
+/// of its usage rows. Returns the totals.
+pub fn sync_session_totals(conn: &Connection, session_id: &str) -> Result<(i64, f64), DbError> {
+    let (tokens, cost): (i64, f64) = conn.query_row(
+        "SELECT COALESCE(SUM(input_tokens + output_tokens), 0), COALESCE(SUM(cost_usd), 0.0)
+         FROM session_usage WHERE session_id = ?1",
+        params![session_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    super::sessions::update_session_usage(conn, session_id, tokens, cost)?;
+    Ok((tokens, cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{schema, sessions};
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        conn
+    }
+
+    /// Insert a project and a session so foreign key constraints are satisfied.
+    fn seed_session(conn: &Connection, id: &str) {
+        let now = chrono::Utc::now().timestamp();
+        conn.execute(
+            "INSERT INTO projects (id, name, path, default_runtime, created_at, updated_at)
+             VALUES ('p1', 'Test Project', '/tmp/test-p1', 'claude-code', ?1, ?1)",
+            params![now],
+        )
+        .expect("Should seed project");
+        sessions::create_session(conn, id, "p1", "Task", "claude-code", None).expect("Should seed session");
+    }
+
+    fn usage(elf_id: Option<&str>, model: &str, input: i64, cost: f64) -> SessionUsage {
+        SessionUsage {
+            session_id: "s1".into(),
+            elf_id: elf_id.map(String::from),
+            model: model.into(),
+            input_tokens: input,
+            output_tokens: 10,
+            cache_read_tokens: 5,
+            cache_write_tokens: 0,
+            cost_usd: cost,
+        }
+    }
+
+    #[test]
+    fn usage_adds_up_per_elf_and_model() {
+        let conn = test_conn();
+        seed_session(&conn, "s1");
+
+        add_session_usage(&conn, &usage(None, "gpt-5", 100, 0.5)).unwrap();
+        add_session_usage(&conn, &usage(None, "gpt-5", 50, 0.25)).unwrap();
+        add_session_usage(&conn, &usage(Some("elf-1"), "gpt-5", 1, 0.1)).unwrap();
+        add_session_usage(&conn, &usage(None, "claude-haiku-4-5", 1, 0.1)).unwrap();
+
+        let rows = list_session_usage(&conn, "s1").unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].model, "claude-haiku-4-5");
+        assert_eq!(rows[1].model, "gpt-5");
+        assert_eq!(rows[1].input_tokens, 150);
+        assert_eq!(rows[1].output_tokens, 20);
+        assert_eq!(rows[1].cache_read_tokens, 10);
+        assert_eq!(rows[1].cost_usd, 0.75);
+        assert_eq!(rows[2].elf_id.as_deref(), Some("elf-1"));
+    }
+
+    #[test]
+    fn session_totals_are_the_sum_of_usage_rows() {
+        let conn = test_conn();
+        seed_session(&conn, "s1");
+        assert_eq!(sync_session_totals(&conn, "s1").unwrap(), (0, 0.0));
+
+        add_session_usage(&conn, &usage(None, "gpt-5", 100, 0.5)).unwrap();
+        add_session_usage(&conn, &usage(Some("elf-1"), "o3", 200, 1.5)).unwrap();
+        assert_eq!(sync_session_totals(&conn, "s1").unwrap(), (320, 2.0));
+
+        let session = sessions::get_session(&conn, "s1").unwrap().unwrap();
+        assert_eq!(session.tokens_used, 320);
+        assert_eq!(session.cost_estimate, 2.0);
+    }
+}
@@ -3,17 +3,64 @@
 mod agents;
 mod commands;
 mod db;
+mod mcp;
 mod project;
 mod registry;
+mod secrets;
+mod settings;
 
+use agents::budget::BudgetTracker;
 use agents::process::ProcessManager;
+use agents::run_state::{self, RunDir};
+use agents::transcript::TranscriptClaims;
+use agents::watchdog::Watchdog;
 use commands::projects::DbState;
 use commands::pty::PtyManager;
+use commands::secrets::SecretsState;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::Emitter;
 
+/// Clean up after a previous app run that ended without stopping its agents (crash,
+/// force quit). Runs once the single-instance check has passed.
+fn recover_previous_run(conn: &rusqlite::Connection, run_dir: &RunDir) {
+    // Mark sessions left "active" as interrupted so they can be offered for resume
+    if let Ok(count) = db::sessions::mark_interrupted_sessions(conn) {
+        if count > 0 {
+            log::info!("Marked {count} session(s) from previous run as interrupted");
+        }
+    }
+
+    // Kill agents the previous run left behind. Only processes ELVES recorded in its
+    // PID files are touched, never agents the user started some other way
+    for survivor in run_state::clean_up_survivors(run_dir) {
+        log::info!("Killed surviving agent process group {} (session {})", survivor.pgid, survivor.session_id);
+    }
+}
+
+/// Compact old session events when retention is enabled. Runs once the single-instance
+/// check has passed, so a second launch never compacts the running app's database.
+fn apply_retention(conn: &rusqlite::Connection) {
+    match settings::config::read_app_settings(&settings::config::default_settings_path()) {
+        Ok(app_settings) if app_settings.retention.enabled => {
+            let cutoff = commands::maintenance::retention_cutoff(app_settings.retention.full_event_days);
+            match db::maintenance::compact_events(conn, cutoff) {
+                Ok(report) if report.sessions_compacted > 0 => log::info!(
+                    "Compacted events of {} session(s): {} deleted, {} trimmed",
+                    report.sessions_compacted,
+                    report.events_deleted,
+                    report.events_trimmed
+                ),
+                Ok(_) => {}
+                Err(e) => log::warn!("Event retention failed: {e}"),
+            }
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Could not read app settings: {e}"),
+    }
+}
+
 /// Build the native macOS/desktop menu bar with File, Edit, View, and Help menus.
 /// Menu item clicks emit `menu:<id>` events to the frontend for dispatch.
 fn build_app_menu(app: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
@@ -83,30 +130,75 @@ pub fn run() {
         .format_timestamp_secs()
         .init();
 
+    // `elves mcp-server ...` serves project memory to an agent over stdio, and
+    // `elves mcp-gateway ...` proxies one MCP server for an agent, instead of opening the app
+    if let Some(context) = mcp::server::ServerContext::from_args(std::env::args().skip(1)) {
+        std::process::exit(mcp::server::run_stdio(context));
+    }
+    if let Some(context) = mcp::gateway::GatewayContext::from_args(std::env::args().skip(1)) {
+        std::process::exit(mcp::gateway::run_stdio(context));
+    }
+
     // Resolve full user PATH for macOS .app bundles (Finder/Dock get minimal PATH)
     agents::runtime::ensure_full_path();
 
     let db_path = db::default_db_path();
     let conn = db::open_database(&db_path).expect("Failed to open ELVES database");
 
-    // Clean up any sessions left "active" from a previous run (crash, force quit, etc.)
-    if let Ok(count) = db::sessions::cleanup_stale_sessions(&conn) {
-        if count > 0 {
-            log::info!("Cleaned up {count} stale active session(s) from previous run");
-        }
-    }
+    let run_dir = RunDir::new(run_state::default_run_dir());
 
     tauri::Builder::default()
+        // Must come first: a second launch hands off to the running app and exits before
+        // setup, so it never mistakes the first instance's live sessions for leftovers
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            use tauri::Manager;
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .manage(DbState(Mutex::new(conn)))
-        .manage(ProcessManager::new())
+        .manage(ProcessManager::with_run_dir(run_dir.clone()))
         .manage(PtyManager::new())
-        .setup(|app| {
+        .manage(Watchdog::new())
+        .manage(BudgetTracker::new())
+        .manage(TranscriptClaims::new())
+        .manage(commands::queue::TaskQueue::new())
+        .manage(commands::codex_insights::CodexSessionCache::new())
+        .manage(SecretsState(Mutex::new(secrets::store::SecretStore::open(
+            secrets::store::SecretStore::default_path(),
+            &secrets::keyring::OsKeyring,
+        ))))
+        .setup(move |app| {
             let menu = build_app_menu(app.handle())?;
             app.set_menu(menu)?;
 
+            {
+                use tauri::Manager;
+                let db = app.state::<DbState>();
+                let conn = db.0.lock().map_err(|e| format!("Lock error: {e}"))?;
+                recover_previous_run(&conn, &run_dir);
+            }
+
+            // Compaction can take a while on large databases, so keep it off the main thread
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                use tauri::Manager;
+                let db = handle.state::<DbState>();
+                if let Ok(conn) = db.0.lock() {
+                    apply_retention(&conn);
+                };
+            });
+
+            // Enforce session run-time, idle, and event limits
+            commands::watchdog::spawn_watchdog(app.handle().clone());
+
+            // Start queued tasks as the concurrency caps allow
+            commands::queue::spawn_queue_dispatcher(app.handle().clone());
+
             // Workaround for WKWebView bug where the webview gets stuck at the wrong
             // size after minimize/restore on macOS (tauri-apps/tauri#14843).
             // On re-focus, nudge the window size by 1px and immediately restore it,
@@ -175,8 +267,31 @@ pub fn run() {
             commands::sessions::get_session,
             commands::sessions::get_last_workspace_session,
             commands::sessions::list_session_events,
+            commands::sessions::list_session_events_page,
+            commands::sessions::get_session_usage,
+            commands::sessions::list_session_recordings,
+            commands::sessions::get_session_event_stats,
+            commands::sessions::search_sessions,
             commands::sessions::complete_session,
             commands::sessions::update_claude_session_id,
+            commands::sessions::set_session_pinned,
+            commands::external_sessions::list_external_claude_sessions,
+            commands::external_sessions::import_claude_session,
+            commands::sessions::list_session_attempts,
+            commands::recovery::list_interrupted_sessions,
+            commands::recovery::dismiss_interrupted_session,
+            commands::watchdog::set_session_limits,
+            commands::queue::enqueue_task,
+            commands::queue::list_task_queue,
+            commands::queue::reorder_queue,
+            commands::queue::cancel_queued,
+            commands::budget::get_budget_status,
+            commands::maintenance::database_stats,
+            commands::maintenance::vacuum_database,
+            commands::maintenance::analyze_database,
+            commands::maintenance::compact_session_events,
+            commands::settings::get_app_settings,
+            commands::settings::update_app_settings,
             commands::tasks::start_task,
             commands::tasks::start_task_pty,
             commands::tasks::stop_task,
@@ -192,6 +307,8 @@ pub fn run() {
             commands::memory::pin_memory,
             commands::memory::unpin_memory,
             commands::memory::search_memories,
+            commands::memory::list_memory_pin_requests,
+            commands::memory::resolve_memory_pin_request,
             commands::memory::decay_memories,
             commands::memory::get_memory_count,
             commands::memory::extract_session_memories,
@@ -209,8 +326,20 @@ pub fn run() {
             commands::mcp::health_check_mcp,
             commands::mcp::delete_mcp_server,
             commands::mcp::import_mcp_from_claude,
+            commands::mcp::preview_mcp_sync,
+            commands::mcp::apply_mcp_sync,
             commands::mcp::load_mcp_catalog,
             commands::mcp::list_mcp_tools,
+            commands::mcp::inspect_mcp_server,
+            commands::mcp::read_mcp_resource,
+            commands::mcp::get_mcp_prompt,
+            commands::mcp::call_mcp_tool,
+            commands::secrets::get_secrets_status,
+            commands::secrets::unlock_secrets,
+            commands::secrets::lock_secrets,
+            commands::secrets::set_secret,
+            commands::secrets::delete_secret,
+            commands::secrets::migrate_mcp_secrets,
             commands::templates::list_templates,
             commands::templates::save_template,
             commands::templates::delete_template,
@@ -223,6 +352,8 @@ pub fn run() {
             commands::pty::resize_pty,
             commands::pty::kill_pty,
             commands::pty::check_pty_exists,
+            commands::pty::read_pty_scrollback,
+            commands::pty::search_pty_scrollback,
             commands::filesystem::list_directory,
             commands::filesystem::git_status,
             commands::git::git_branch,
@@ -274,7 +405,18 @@ pub fn run() {
             commands::registry::install_skill,
             commands::registry::search_github_catalog,
             commands::insights::load_insights,
+            commands::insights::load_db_insights,
         ])
-        .run(tauri::generate_context!())
-        .expect("Error while running ELVES application");
+        .build(tauri::generate_context!())
+        .expect("Error while building ELVES application")
+        .run(|app, event| {
+            // Don't leave agents running once the app is gone
+            if let tauri::RunEvent::Exit = event {
+                use tauri::Manager;
+                let killed = app.state::<ProcessManager>().kill_all();
+                if killed > 0 {
+                    log::info!("Killed {killed} agent process(es) on shutdown");
+                }
+            }
+        });
 }
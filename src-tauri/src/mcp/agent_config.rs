@@ -0,0 +1,261 @@
+// Agent MCP config — the MCP servers ELVES registers with each spawned agent.
+//
+// Every session gets the ELVES memory server (`server`). When the project enables the
+// MCP gateway, every enabled ELVES-managed server in scope is also registered, wrapped
+// in `gateway` so its tool calls are audited and filtered. Claude takes the result as
+// inline `--mcp-config` JSON; Codex takes `-c mcp_servers.<name>.*` overrides.
+
+use rusqlite::Connection;
+use serde_json::{json, Map, Value};
+
+use super::gateway::GatewayContext;
+use super::server::{self, ServerContext};
+use crate::db;
+use crate::project::config;
+
+/// One stdio server to register with a spawned agent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentMcpEntry {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+/// The servers registered with one agent process.
+#[derive(Debug, Clone, Default)]
+pub struct AgentMcpConfig {
+    pub entries: Vec<AgentMcpEntry>,
+    /// Gateway mode: the agent should see only these servers, so no tool call bypasses
+    /// the gateway through a copy of the same server in the agent's own config.
+    pub exclusive: bool,
+}
+
+impl AgentMcpConfig {
+    /// Build the config for an agent in `session_id`. `elf_id` attributes gateway-recorded
+    /// tool calls to a single elf when the process runs exactly one.
+    pub fn for_session(
+        conn: &Connection,
+        project_id: &str,
+        session_id: &str,
+        elf_id: Option<&str>,
+    ) -> Self {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe.to_string_lossy().to_string(),
+            Err(e) => {
+                log::warn!("Cannot locate ELVES executable for agent MCP servers: {e}");
+                return Self::default();
+            }
+        };
+
+        let memory_server = ServerContext {
+            project_id: Some(project_id.to_string()),
+            session_id: Some(session_id.to_string()),
+        };
+        let mut config = Self {
+            entries: vec![AgentMcpEntry {
+                name: server::SERVER_NAME.to_string(),
+                command: exe.clone(),
+                args: memory_server.launch_args(),
+            }],
+            exclusive: false,
+        };
+
+        if !gateway_enabled(conn, project_id) {
+            return config;
+        }
+        let rows = match db::mcp::list_mcp_servers(conn) {
+            Ok(rows) => rows,
+            Err(e) => {
+                log::warn!("Failed to list MCP servers for the gateway: {e}");
+                return config;
+            }
+        };
+
+        // Project-scoped servers shadow global ones with the same name
+        let mut in_scope: Vec<_> = rows
+            .into_iter()
+            .filter(|r| r.enabled && (r.scope == project_id || r.scope == "global"))
+            .filter(|r| r.name != server::SERVER_NAME)
+            .collect();
+        in_scope.sort_by_key(|r| r.scope == "global");
+        for row in in_scope {
+            if config.entries.iter().any(|e| e.name == row.name) {
+                continue;
+            }
+            let gateway = GatewayContext {
+                project_id: project_id.to_string(),
+                session_id: session_id.to_string(),
+                elf_id: elf_id.map(String::from),
+                server_id: row.id,
+            };
+            config.entries.push(AgentMcpEntry {
+                name: row.name,
+                command: exe.clone(),
+                args: gateway.launch_args(),
+            });
+        }
+        config.exclusive = true;
+        config
+    }
+
+    /// Claude CLI flags: `--mcp-config <json>`, plus `--strict-mcp-config` in gateway mode.
+    pub fn claude_args(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            return Vec::new();
+        }
+        let servers: Map<String, Value> = self
+            .entries
+            .iter()
+            .map(|e| {
+                let spec = json!({ "type": "stdio", "command": e.command, "args": e.args });
+                (e.name.clone(), spec)
+            })
+            .collect();
+
+        let mut args = vec![
+            "--mcp-config".to_string(),
+            json!({ "mcpServers": servers }).to_string(),
+        ];
+        if self.exclusive {
+            args.push("--strict-mcp-config".to_string());
+        }
+        args
+    }
+
+    /// Codex CLI `-c key=value` overrides. An entry replaces any server of the same name
+    /// in `~/.codex/config.toml`; in gateway mode the user's servers are cleared first, as
+    /// Codex has no `--strict-mcp-config`. Overrides apply in order.
+    pub fn codex_args(&self) -> Vec<String> {
+        let mut args = Vec::with_capacity(self.entries.len() * 4 + 2);
+        if self.exclusive {
+            args.push("-c".to_string());
+            args.push("mcp_servers={}".to_string());
+        }
+        for entry in &self.entries {
+            // Override values are parsed as TOML, so quote them the way toml_edit would
+            let command = toml_edit::Value::from(entry.command.as_str());
+            let launch_args = toml_edit::Value::Array(entry.args.iter().map(String::as_str).collect());
+            let key = toml_key(&entry.name);
+            args.push("-c".to_string());
+            args.push(format!("mcp_servers.{key}.command={}", command.to_string().trim()));
+            args.push("-c".to_string());
+            args.push(format!("mcp_servers.{key}.args={}", launch_args.to_string().trim()));
+        }
+        args
+    }
+}
+
+/// Whether the project's `.elves/config.json` turns the gateway on.
+fn gateway_enabled(conn: &Connection, project_id: &str) -> bool {
+    let Ok(Some(project)) = db::projects::get_project(conn, project_id) else {
+        return false;
+    };
+    match config::read_project_config(&project.path) {
+        Ok(config) => config.mcp_gateway.enabled,
+        Err(e) => {
+            log::warn!("Failed to read project config for the MCP gateway: {e}");
+            false
+        }
+    }
+}
+
+/// A server name as a TOML dotted-key segment, quoted unless it is a bare key.
+fn toml_key(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        name.to_string()
+    } else {
+        toml_edit::Value::from(name).to_string().trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn entry(name: &str, command: &str, args: &[&str]) -> AgentMcpEntry {
+        AgentMcpEntry {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn claude_args_embed_servers_and_strict_flag() {
+        let config = AgentMcpConfig {
+            entries: vec![entry("elves", "/Apps/ELVES", &["mcp-server", "--project", "p \"1\""])],
+            exclusive: false,
+        };
+        let args = config.claude_args();
+        assert_eq!(args.len(), 2);
+        let parsed: Value = serde_json::from_str(&args[1]).unwrap();
+        assert_eq!(parsed["mcpServers"]["elves"]["command"], "/Apps/ELVES");
+        assert_eq!(parsed["mcpServers"]["elves"]["args"][2], "p \"1\"");
+
+        let strict = AgentMcpConfig { exclusive: true, ..config };
+        assert_eq!(strict.claude_args().last().map(String::as_str), Some("--strict-mcp-config"));
+        assert!(AgentMcpConfig::default().claude_args().is_empty());
+    }
+
+    #[test]
+    fn codex_args_are_valid_toml_overrides() {
+        let config = AgentMcpConfig {
+            entries: vec![entry("my server", "/Apps/E L V E S", &["mcp-gateway", "--server", "a\"b"])],
+            exclusive: false,
+        };
+        let args = config.codex_args();
+        assert_eq!(args[0], "-c");
+        assert_eq!(args[1], "mcp_servers.\"my server\".command=\"/Apps/E L V E S\"");
+
+        let toml = format!("{}\n{}", args[1], args[3]);
+        let parsed: toml_edit::DocumentMut = toml.parse().expect("Overrides should be valid TOML");
+        let server = &parsed["mcp_servers"]["my server"];
+        assert_eq!(server["command"].as_str(), Some("/Apps/E L V E S"));
+        assert_eq!(server["args"].as_array().unwrap().get(2).unwrap().as_str(), Some("a\"b"));
+    }
+
+    #[test]
+    fn exclusive_codex_args_clear_the_users_servers_first() {
+        let config = AgentMcpConfig {
+            entries: vec![entry("elves", "/Apps/ELVES", &["mcp-server"])],
+            exclusive: true,
+        };
+        let args = config.codex_args();
+        assert_eq!(&args[..2], ["-c", "mcp_servers={}"]);
+        assert_eq!(args[3], "mcp_servers.elves.command=\"/Apps/ELVES\"");
+        assert_eq!(args.len(), 6);
+
+        let shared = AgentMcpConfig { exclusive: false, ..config };
+        assert!(!shared.codex_args().iter().any(|arg| arg == "mcp_servers={}"));
+    }
+
+    #[test]
+    fn gateway_wraps_enabled_servers_when_project_opts_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let conn = Connection::open_in_memory().unwrap();
+        schema::run_migrations(&conn).unwrap();
+        db::projects::create_project(&conn, "proj", "Test", &path).unwrap();
+        db::mcp::insert_mcp_server(&conn, "g1", "github", "npx", "[]", "{}", "global").unwrap();
+        db::mcp::insert_mcp_server(&conn, "p1", "github", "node", "[]", "{}", "proj").unwrap();
+        db::mcp::insert_mcp_server(&conn, "o1", "other", "npx", "[]", "{}", "other-proj").unwrap();
+
+        let config = AgentMcpConfig::for_session(&conn, "proj", "sess", Some("elf"));
+        assert!(!config.exclusive);
+        assert_eq!(config.entries.len(), 1);
+        assert_eq!(config.entries[0].name, "elves");
+
+        let mut project_config = config::ProjectConfig::default();
+        project_config.mcp_gateway.enabled = true;
+        config::write_project_config(&path, &project_config).unwrap();
+
+        let config = AgentMcpConfig::for_session(&conn, "proj", "sess", Some("elf"));
+        assert!(config.exclusive);
+        let names: Vec<&str> = config.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["elves", "github"]);
+        let gateway = GatewayContext::from_args(config.entries[1].args.clone()).unwrap();
+        assert_eq!(gateway.server_id, "p1");
+        assert_eq!(gateway.elf_id.as_deref(), Some("elf"));
+    }
+}
@@ -0,0 +1,691 @@
+// MCP client — runs the initialize handshake plus the discovery, read, and tool calls.
+//
+// The wire format (stdio, Streamable HTTP, legacy SSE) lives in `transport`. The
+// client only issues one request at a time, so responses are matched by id and any
+// interleaved notifications or server-initiated requests are handled by the transport.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use super::transport::{ServerEndpoint, Transport};
+use super::types::{
+    McpPrompt, McpPromptMessage, McpPromptResult, McpResource, McpResourceContent,
+    McpResourceTemplate, McpServerInventory, McpTool, McpToolCallResult, ServerInfo,
+};
+
+/// Protocol version sent in the `initialize` request. Servers that only speak
+/// 2024-11-05 answer with that version and the rest of the exchange is unchanged.
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Upper bound on `nextCursor` pages followed for a single list call.
+/// Protects against servers that return the same cursor forever.
+const MAX_LIST_PAGES: usize = 50;
+
+/// A live connection to one MCP server.
+///
+/// Create with `connect` (any configured transport) or `from_streams` (tests), then call `initialize` before any other request. Call
+/// `close` when done so the server process is killed or the HTTP session ended.
+pub struct McpClient {
+    transport: Transport,
+    next_id: u64,
+    server: Option<ServerInfo>,
+}
+
+impl McpClient {
+    /// Open a connection to a configured server using its transport.
+    pub async fn connect(endpoint: &ServerEndpoint) -> Result<Self, String> {
+        Ok(Self::with_transport(Transport::connect(endpoint).await?))
+    }
+
+    /// Build a stdio client over arbitrary byte streams (used by tests with in-memory pipes).
+    #[cfg(test)]
+    pub fn from_streams(
+        reader: super::transport::BoxedReader,
+        writer: super::transport::BoxedWriter,
+    ) -> Self {
+        Self::with_transport(Transport::Stdio(super::transport::StdioTransport::from_streams(reader, writer)))
+    }
+
+    fn with_transport(transport: Transport) -> Self {
+        Self {
+            transport,
+            next_id: 1,
+            server: None,
+        }
+    }
+
+    /// Run the `initialize` request and send `notifications/initialized`.
+    /// Returns the parsed server info, which is also cached on the client.
+    pub async fn initialize(&mut self) -> Result<ServerInfo, String> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "elves", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+
+        let info = ServerInfo::from_json(&result);
+        self.transport.set_protocol_version(info.protocol_version.clone());
+
+        // MCP requires this notification before any further requests
+        self.notify("notifications/initialized", json!({})).await?;
+
+        self.server = Some(info.clone());
+        Ok(info)
+    }
+
+    /// Call `tools/list`, following pagination.
+    pub async fn list_tools(&mut self) -> Result<Vec<McpTool>, String> {
+        let items = self.list_paginated("tools/list", "tools").await?;
+        Ok(items
+            .iter()
+            .filter_map(|tool| {
+                let name = tool.get("name")?.as_str()?.to_string();
+                let description = tool
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .map(|s| s.to_string());
+                Some(McpTool { name, description })
+            })
+            .collect())
+    }
+
+    /// Call `resources/list`, following pagination.
+    pub async fn list_resources(&mut self) -> Result<Vec<McpResource>, String> {
+        let items = self.list_paginated("resources/list", "resources").await?;
+        Ok(parse_items(items))
+    }
+
+    /// Call `resources/templates/list`, following pagination.
+    pub async fn list_resource_templates(&mut self) -> Result<Vec<McpResourceTemplate>, String> {
+        let items = self
+            .list_paginated("resources/templates/list", "resourceTemplates")
+            .await?;
+        Ok(parse_items(items))
+    }
+
+    /// Call `prompts/list`, following pagination.
+    pub async fn list_prompts(&mut self) -> Result<Vec<McpPrompt>, String> {
+        let items = self.list_paginated("prompts/list", "prompts").await?;
+        Ok(parse_items(items))
+    }
+
+    /// Read a single resource by URI via `resources/read`.
+    pub async fn read_resource(&mut self, uri: &str) -> Result<Vec<McpResourceContent>, String> {
+        let result = self.request("resources/read", json!({ "uri": uri })).await?;
+        let contents = result
+            .get("contents")
+            .and_then(|c| c.as_array())
+            .ok_or("resources/read response missing result.contents array")?;
+        Ok(parse_items(contents.clone()))
+    }
+
+    /// Render a prompt with arguments via `prompts/get`.
+    pub async fn get_prompt(
+        &mut self,
+        name: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<McpPromptResult, String> {
+        let result = self
+            .request("prompts/get", json!({ "name": name, "arguments": arguments }))
+            .await?;
+        Ok(parse_prompt_result(&result))
+    }
+
+    /// Invoke a tool via `tools/call`.
+    ///
+    /// A tool that ran but failed comes back as `Ok` with `is_error` set — only
+    /// protocol-level failures (unknown tool, bad params) are returned as `Err`.
+    pub async fn call_tool(&mut self, name: &str, arguments: Value) -> Result<McpToolCallResult, String> {
+        let arguments = if arguments.is_null() { json!({}) } else { arguments };
+        let result = self
+            .request("tools/call", json!({ "name": name, "arguments": arguments }))
+            .await?;
+        Ok(McpToolCallResult::from_json(&result))
+    }
+
+    /// Query every list the server advertised in its capabilities.
+    /// Must be called after `initialize`. Unsupported lists come back empty.
+    pub async fn inventory(&mut self) -> Result<McpServerInventory, String> {
+        let server = self
+            .server
+            .clone()
+            .ok_or("MCP client used before initialize")?;
+        let caps = &server.capabilities;
+
+        let tools = if caps.tools { self.list_tools().await? } else { Vec::new() };
+        let (resources, resource_templates) = if caps.resources {
+            // Templates are optional even for resource-capable servers — tolerate method-not-found
+            let templates = self.list_resource_templates().await.unwrap_or_else(|e| {
+                log::debug!("resources/templates/list unavailable: {e}");
+                Vec::new()
+            });
+            (self.list_resources().await?, templates)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        let prompts = if caps.prompts { self.list_prompts().await? } else { Vec::new() };
+
+        Ok(McpServerInventory {
+            server,
+            tools,
+            resources,
+            resource_templates,
+            prompts,
+        })
+    }
+
+    /// Kill the server process, or end the HTTP session.
+    pub async fn close(self) {
+        self.transport.close().await;
+    }
+
+    /// Send a request and wait for the matching response. Returns `result`,
+    /// or the server's `error` object formatted as a string.
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let response = self
+            .transport
+            .round_trip(&message, id)
+            .await
+            .map_err(|e| format!("Failed to send {method}: {e}"))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("MCP {method} error: {error}"));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Send a notification (no id, no response).
+    pub async fn notify(&mut self, method: &str, params: Value) -> Result<(), String> {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        self.transport
+            .notify(&message)
+            .await
+            .map_err(|e| format!("Failed to send {method}: {e}"))
+    }
+
+    /// Repeatedly call a list method, concatenating `result[key]` across pages.
+    async fn list_paginated(&mut self, method: &str, key: &str) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_LIST_PAGES {
+            let params = match cursor {
+                Some(ref c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let result = self.request(method, params).await?;
+            let page = result
+                .get(key)
+                .and_then(|v| v.as_array())
+                .ok_or_else(|| format!("{method} response missing result.{key} array"))?;
+            items.extend(page.iter().cloned());
+
+            cursor = result
+                .get("nextCursor")
+                .and_then(|v| v.as_str())
+                .filter(|c| !c.is_empty())
+                .map(|c| c.to_string());
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+/// Deserialize list entries, skipping any that don't match the expected shape.
+fn parse_items<T: serde::de::DeserializeOwned>(items: Vec<Value>) -> Vec<T> {
+    items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item).ok())
+        .collect()
+}
+
+/// Parse a `prompts/get` result into description + messages, extracting text content.
+fn parse_prompt_result(result: &Value) -> McpPromptResult {
+    let messages = result
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .map(|messages| {
+            messages
+                .iter()
+                .map(|message| {
+                    let content = message.get("content").cloned().unwrap_or(Value::Null);
+                    let text = match content.get("type").and_then(|t| t.as_str()) {
+                        Some("text") => content.get("text").and_then(|t| t.as_str()).map(|s| s.to_string()),
+                        Some("resource") => content
+                            .get("resource")
+                            .and_then(|r| r.get("text"))
+                            .and_then(|t| t.as_str())
+                            .map(|s| s.to_string()),
+                        _ => None,
+                    };
+                    McpPromptMessage {
+                        role: message
+                            .get("role")
+                            .and_then(|r| r.as_str())
+                            .unwrap_or("user")
+                            .to_string(),
+                        text,
+                        content,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    McpPromptResult {
+        description: result
+            .get("description")
+            .and_then(|d| d.as_str())
+            .map(|s| s.to_string()),
+        messages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    /// Run a scripted fake server on the other end of an in-memory pipe.
+    /// `handler` maps (method, params) to a `result` value, or None for method-not-found.
+    fn fake_server<F>(handler: F) -> McpClient
+    where
+        F: Fn(&str, &Value) -> Option<Value> + Send + 'static,
+    {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (client_read, client_write) = tokio::io::split(client_io);
+        let (server_read, mut server_write) = tokio::io::split(server_io);
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(server_read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let msg: Value = serde_json::from_str(&line).unwrap();
+                let Some(id) = msg.get("id").cloned() else { continue };
+                let method = msg["method"].as_str().unwrap_or("");
+                let reply = match handler(method, &msg["params"]) {
+                    Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    None => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32601, "message": "nope" } }),
+                };
+                let out = format!("{reply}\n");
+                if server_write.write_all(out.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        McpClient::from_streams(Box::new(client_read), Box::new(client_write))
+    }
+
+    fn full_server(method: &str, params: &Value) -> Option<Value> {
+        match method {
+            "initialize" => Some(json!({
+                "protocolVersion": "2024-11-05",
+                "serverInfo": { "name": "docs", "version": "0.3.0" },
+                "capabilities": {
+                    "tools": {},
+                    "resources": { "subscribe": true, "listChanged": true },
+                    "prompts": {}
+                }
+            })),
+            "tools/list" => Some(json!({ "tools": [{ "name": "search", "description": "Search docs" }] })),
+            "resources/list" => {
+                if params.get("cursor").is_none() {
+                    Some(json!({
+                        "resources": [{ "uri": "file:///a.md", "name": "a", "mimeType": "text/markdown" }],
+                        "nextCursor": "page2"
+                    }))
+                } else {
+                    Some(json!({ "resources": [{ "uri": "file:///b.md", "name": "b" }] }))
+                }
+            }
+            "resources/templates/list" => Some(json!({
+                "resourceTemplates": [{ "uriTemplate": "docs://{slug}", "name": "doc" }]
+            })),
+            "resources/read" => Some(json!({
+                "contents": [{ "uri": params["uri"], "mimeType": "text/plain", "text": "hello" }]
+            })),
+            "prompts/list" => Some(json!({
+                "prompts": [{
+                    "name": "review",
+                    "description": "Code review",
+                    "arguments": [{ "name": "file", "required": true }]
+                }]
+            })),
+            "prompts/get" => Some(json!({
+                "description": "Review prompt",
+                "messages": [{
+                    "role": "user",
+                    "content": { "type": "text", "text": format!("Review {}", params["arguments"]["file"].as_str().unwrap_or("")) }
+                }]
+            })),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn initialize_parses_capabilities_and_server_info() {
+        let mut client = fake_server(full_server);
+        let info = client.initialize().await.expect("Should initialize");
+
+        assert_eq!(info.name.as_deref(), Some("docs"));
+        assert_eq!(info.version.as_deref(), Some("0.3.0"));
+        assert!(info.capabilities.tools);
+        assert!(info.capabilities.resources);
+        assert!(info.capabilities.resources_subscribe);
+        assert!(info.capabilities.prompts);
+        assert!(!info.capabilities.logging);
+        assert_eq!(info.capabilities.list_changed, vec!["resources".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn inventory_collects_all_lists_and_follows_cursors() {
+        let mut client = fake_server(full_server);
+        client.initialize().await.unwrap();
+        let inventory = client.inventory().await.expect("Should build inventory");
+
+        assert_eq!(inventory.tools.len(), 1);
+        assert_eq!(inventory.resources.len(), 2, "Both pages should be collected");
+        assert_eq!(inventory.resources[0].mime_type.as_deref(), Some("text/markdown"));
+        assert_eq!(inventory.resource_templates[0].uri_template, "docs://{slug}");
+        assert_eq!(inventory.prompts[0].arguments.len(), 1);
+        assert!(inventory.prompts[0].arguments[0].required);
+    }
+
+    #[tokio::test]
+    async fn inventory_skips_lists_not_advertised() {
+        let mut client = fake_server(|method, _| match method {
+            "initialize" => Some(json!({ "capabilities": { "tools": {} } })),
+            "tools/list" => Some(json!({ "tools": [] })),
+            // Any other list call would be a bug — fail loudly via method-not-found
+            _ => None,
+        });
+        client.initialize().await.unwrap();
+        let inventory = client.inventory().await.expect("Should not call unsupported lists");
+
+        assert!(inventory.resources.is_empty());
+        assert!(inventory.prompts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn inventory_tolerates_missing_resource_templates() {
+        let mut client = fake_server(|method, _| match method {
+            "initialize" => Some(json!({ "capabilities": { "resources": {} } })),
+            "resources/list" => Some(json!({ "resources": [{ "uri": "x://1", "name": "one" }] })),
+            _ => None,
+        });
+        client.initialize().await.unwrap();
+        let inventory = client.inventory().await.expect("Templates are optional");
+
+        assert_eq!(inventory.resources.len(), 1);
+        assert!(inventory.resource_templates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_resource_returns_text_contents() {
+        let mut client = fake_server(full_server);
+        client.initialize().await.unwrap();
+        let contents = client.read_resource("file:///a.md").await.expect("Should read");
+
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[0].uri, "file:///a.md");
+        assert_eq!(contents[0].text.as_deref(), Some("hello"));
+        assert!(contents[0].blob.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_prompt_renders_arguments() {
+        let mut client = fake_server(full_server);
+        client.initialize().await.unwrap();
+        let mut args = HashMap::new();
+        args.insert("file".to_string(), "main.rs".to_string());
+        let prompt = client.get_prompt("review", &args).await.expect("Should render");
+
+        assert_eq!(prompt.description.as_deref(), Some("Review prompt"));
+        assert_eq!(prompt.messages[0].role, "user");
+        assert_eq!(prompt.messages[0].text.as_deref(), Some("Review main.rs"));
+    }
+
+    #[test]
+    fn prompt_messages_take_text_from_text_blocks_and_text_resources() {
+        let prompt = parse_prompt_result(&json!({ "messages": [
+            { "role": "user", "content": { "type": "resource",
+                "resource": { "uri": "file:///a.rs", "text": "fn main() {}" } } },
+            { "role": "user", "content": { "type": "resource",
+                "resource": { "uri": "file:///a.png", "blob": "iVBO" } } },
+            { "role": "assistant", "content": { "type": "image", "data": "iVBO", "mimeType": "image/png" } },
+        ] }));
+        assert_eq!(prompt.messages[0].text.as_deref(), Some("fn main() {}"));
+        assert_eq!(prompt.messages[1].text, None);
+        assert_eq!(prompt.messages[2].text, None);
+        assert_eq!(prompt.messages[2].role, "assistant");
+    }
+
+    #[tokio::test]
+    async fn request_surfaces_server_errors() {
+        let mut client = fake_server(full_server);
+        client.initialize().await.unwrap();
+        let err = client.request("bogus/method", json!({})).await.unwrap_err();
+        assert!(err.contains("bogus/method"));
+    }
+
+    #[tokio::test]
+    async fn call_tool_returns_text_and_error_flag() {
+        let mut client = fake_server(|method, params| match method {
+            "initialize" => Some(json!({ "capabilities": { "tools": {} } })),
+            "tools/call" => Some(json!({
+                "content": [
+                    { "type": "text", "text": format!("hits for {}", params["arguments"]["q"].as_str().unwrap_or("")) },
+                    { "type": "image", "data": "AAAA", "mimeType": "image/png" },
+                    { "type": "text", "text": "page 1" }
+                ],
+                "isError": params["name"] == "broken"
+            })),
+            _ => None,
+        });
+        client.initialize().await.unwrap();
+
+        let ok = client.call_tool("search", json!({ "q": "rust" })).await.expect("Should call");
+        assert_eq!(ok.text, "hits for rust\npage 1");
+        assert_eq!(ok.content.len(), 3);
+        assert!(!ok.is_error);
+
+        let failed = client.call_tool("broken", Value::Null).await.expect("Tool errors are results");
+        assert!(failed.is_error);
+    }
+
+    // --- in-process HTTP server for the remote transports ---
+
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::{TcpListener, TcpStream};
+
+    struct TestRequest {
+        method: String,
+        path: String,
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    type Handler = Arc<dyn Fn(TestRequest, TcpStream) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+    /// Accept connections on a random local port and hand each parsed request, plus
+    /// the socket, to `handler`. Returns the base URL.
+    async fn http_server(handler: Handler) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    if let Some(request) = read_request(&mut stream).await {
+                        handler(request, stream).await;
+                    }
+                });
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    async fn read_request(stream: &mut TcpStream) -> Option<TestRequest> {
+        let mut raw = Vec::new();
+        let mut byte = [0u8; 1];
+        while !raw.ends_with(b"\r\n\r\n") {
+            if stream.read(&mut byte).await.ok()? == 0 {
+                return None;
+            }
+            raw.push(byte[0]);
+        }
+        let head = String::from_utf8_lossy(&raw).to_string();
+        let mut lines = head.lines();
+        let mut request_line = lines.next()?.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let path = request_line.next()?.to_string();
+        let headers: HashMap<String, String> = lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+            .collect();
+        let length: usize = headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let mut body = vec![0u8; length];
+        stream.read_exact(&mut body).await.ok()?;
+        Some(TestRequest { method, path, headers, body: String::from_utf8_lossy(&body).to_string() })
+    }
+
+    async fn respond(mut stream: TcpStream, status: &str, extra_headers: &str, content_type: &str, body: &str) {
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n{extra_headers}Connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+
+    fn reply_for(message: &Value) -> Value {
+        let result = match message["method"].as_str().unwrap_or("") {
+            "initialize" => json!({
+                "protocolVersion": "2025-03-26",
+                "serverInfo": { "name": "remote" },
+                "capabilities": { "tools": {} }
+            }),
+            "tools/list" => json!({ "tools": [{ "name": "echo" }] }),
+            "tools/call" => json!({ "content": [{ "type": "text", "text": message["params"]["arguments"]["msg"] }] }),
+            _ => return json!({ "jsonrpc": "2.0", "id": message["id"], "error": { "code": -32601, "message": "nope" } }),
+        };
+        json!({ "jsonrpc": "2.0", "id": message["id"], "result": result })
+    }
+
+    #[tokio::test]
+    async fn streamable_http_handles_json_and_sse_responses() {
+        let deleted = Arc::new(Mutex::new(false));
+        let deleted_flag = deleted.clone();
+        let handler: Handler = Arc::new(move |req, stream| {
+            let deleted = deleted_flag.clone();
+            Box::pin(async move {
+                assert_eq!(req.path, "/mcp");
+                assert_eq!(req.headers.get("authorization").map(|s| s.as_str()), Some("Bearer t0k"));
+                if req.method == "DELETE" {
+                    *deleted.lock().unwrap() = true;
+                    return respond(stream, "200 OK", "", "text/plain", "").await;
+                }
+                let message: Value = serde_json::from_str(&req.body).unwrap();
+                let method = message["method"].as_str().unwrap_or("");
+                if method != "initialize" && req.headers.get("mcp-session-id").map(|s| s.as_str()) != Some("sess-1") {
+                    return respond(stream, "400 Bad Request", "", "text/plain", "missing session").await;
+                }
+                if message.get("id").is_none() {
+                    return respond(stream, "202 Accepted", "", "text/plain", "").await;
+                }
+                let reply = reply_for(&message);
+                match method {
+                    "initialize" => {
+                        respond(stream, "200 OK", "Mcp-Session-Id: sess-1\r\n", "application/json", &reply.to_string()).await
+                    }
+                    // Stream the reply after a progress notification and a server ping
+                    "tools/list" => {
+                        let body = format!(
+                            "event: message\r\ndata: {}\r\n\r\ndata: {}\r\n\r\n",
+                            json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": {} }),
+                            reply
+                        );
+                        respond(stream, "200 OK", "", "text/event-stream", &body).await
+                    }
+                    _ => respond(stream, "200 OK", "", "application/json", &reply.to_string()).await,
+                }
+            })
+        });
+        let base = http_server(handler).await;
+
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), "Bearer t0k".to_string());
+        let endpoint = ServerEndpoint::Http { url: format!("{base}/mcp"), headers };
+        let mut client = McpClient::connect(&endpoint).await.expect("Should connect");
+
+        let info = client.initialize().await.expect("Should initialize");
+        assert_eq!(info.name.as_deref(), Some("remote"));
+        let tools = client.list_tools().await.expect("Should list over SSE response");
+        assert_eq!(tools[0].name, "echo");
+        let result = client.call_tool("echo", json!({ "msg": "hi" })).await.expect("Should call");
+        assert_eq!(result.text, "hi");
+
+        client.close().await;
+        assert!(*deleted.lock().unwrap(), "Closing should DELETE the session");
+    }
+
+    #[tokio::test]
+    async fn legacy_sse_posts_to_endpoint_and_reads_stream() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Value>();
+        let rx = Arc::new(Mutex::new(Some(rx)));
+        let handler: Handler = Arc::new(move |req, mut stream| {
+            let tx = tx.clone();
+            let rx = rx.clone();
+            Box::pin(async move {
+                match (req.method.as_str(), req.path.as_str()) {
+                    ("GET", "/sse") => {
+                        let mut rx = rx.lock().unwrap().take().expect("Single SSE stream");
+                        let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n";
+                        let _ = stream.write_all(head.as_bytes()).await;
+                        let _ = stream.write_all(b": hello\n\nevent: endpoint\ndata: /messages?session=abc\n\n").await;
+                        while let Some(message) = rx.recv().await {
+                            let event = format!("event: message\ndata: {message}\n\n");
+                            if stream.write_all(event.as_bytes()).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    ("POST", "/messages?session=abc") => {
+                        let message: Value = serde_json::from_str(&req.body).unwrap();
+                        if message.get("id").is_some() {
+                            tx.send(reply_for(&message)).unwrap();
+                        }
+                        respond(stream, "202 Accepted", "", "text/plain", "").await;
+                    }
+                    _ => respond(stream, "404 Not Found", "", "text/plain", "").await,
+                }
+            })
+        });
+        let base = http_server(handler).await;
+
+        let endpoint = ServerEndpoint::Sse { url: format!("{base}/sse"), headers: HashMap::new() };
+        let mut client = McpClient::connect(&endpoint).await.expect("Should receive endpoint event");
+
+        client.initialize().await.expect("Should initialize");
+        let tools = client.list_tools().await.expect("Should list");
+        assert_eq!(tools.len(), 1);
+        let result = client.call_tool("echo", json!({ "msg": "over sse" })).await.unwrap();
+        assert_eq!(result.text, "over sse");
+        client.close().await;
+    }
+}
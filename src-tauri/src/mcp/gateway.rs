@@ -0,0 +1,569 @@
+// MCP gateway — an auditing proxy between an agent and one ELVES-managed MCP server.
+//
+// When a project enables the gateway, each server in a session's MCP config is launched
+// as `elves mcp-gateway --server <id> ...` instead of directly. The gateway connects to
+// the real server over its configured transport, forwards JSON-RPC in both directions,
+// records every `tools/call` to the session's event log, and refuses tools the
+// project's allow/deny lists block. Requests are forwarded one at a time, in order.
+//
+// The gateway opens the secrets store itself, which only unlocks through the OS keyring.
+// When a server needs stored secrets and the store stays locked, the gateway answers
+// every request with a "secrets locked" error instead of exiting, so the agent sees why.
+
+use std::time::Instant;
+
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use super::transport::{ServerEndpoint, Transport};
+use crate::db;
+use crate::project::config::{self, McpGatewayConfig};
+use crate::secrets::keyring::OsKeyring;
+use crate::secrets::store::SecretStore;
+
+/// First CLI argument that switches the ELVES binary into gateway mode.
+const SUBCOMMAND: &str = "mcp-gateway";
+
+/// Event type for recorded tool calls.
+pub const TOOL_CALL_EVENT: &str = "mcp_tool_call";
+
+/// Which session a gateway instance records for, and which server it fronts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GatewayContext {
+    pub project_id: String,
+    pub session_id: String,
+    pub elf_id: Option<String>,
+    pub server_id: String,
+}
+
+impl GatewayContext {
+    /// Parse `mcp-gateway --project <id> --session <id> [--elf <id>] --server <id>` from the
+    /// process arguments (program name already skipped). Returns None when the binary was
+    /// launched any other way.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Option<Self> {
+        let mut args = args.into_iter();
+        if args.next().as_deref() != Some(SUBCOMMAND) {
+            return None;
+        }
+
+        let mut context = GatewayContext::default();
+        while let Some(flag) = args.next() {
+            let value = args.next();
+            match flag.as_str() {
+                "--project" => context.project_id = value.unwrap_or_default(),
+                "--session" => context.session_id = value.unwrap_or_default(),
+                "--elf" => context.elf_id = value,
+                "--server" => context.server_id = value.unwrap_or_default(),
+                other => log::warn!("Ignoring unknown mcp-gateway argument: {other}"),
+            }
+        }
+        Some(context)
+    }
+
+    /// Arguments (after the ELVES executable) that launch the gateway for this context.
+    pub fn launch_args(&self) -> Vec<String> {
+        let mut args = vec![
+            SUBCOMMAND.to_string(),
+            "--project".to_string(),
+            self.project_id.clone(),
+            "--session".to_string(),
+            self.session_id.clone(),
+        ];
+        if let Some(ref elf_id) = self.elf_id {
+            args.push("--elf".to_string());
+            args.push(elf_id.clone());
+        }
+        args.push("--server".to_string());
+        args.push(self.server_id.clone());
+        args
+    }
+}
+
+/// A project's tool allow/deny lists.
+#[derive(Debug, Clone, Default)]
+pub struct ToolPolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl ToolPolicy {
+    pub fn from_config(config: &McpGatewayConfig) -> Self {
+        Self {
+            allow: config.allow.clone(),
+            deny: config.deny.clone(),
+        }
+    }
+
+    /// Whether `tool` on `server` may be listed and called. Deny always wins; an empty
+    /// allow list allows everything else.
+    pub fn permits(&self, server: &str, tool: &str) -> bool {
+        let matches = |pattern: &String| {
+            glob_match(pattern, tool) || glob_match(pattern, &format!("{server}/{tool}"))
+        };
+        if self.deny.iter().any(matches) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(matches)
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return false;
+    }
+    // Middle pieces must appear in order between the fixed prefix and suffix
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Proxy stdin/stdout to the configured server until the agent closes stdin.
+/// Returns the process exit code.
+pub fn run_stdio(context: GatewayContext) -> i32 {
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            log::error!("MCP gateway could not start a runtime: {e}");
+            return 1;
+        }
+    };
+    match runtime.block_on(serve(context)) {
+        Ok(()) => 0,
+        Err(e) => {
+            log::error!("MCP gateway stopped: {e}");
+            1
+        }
+    }
+}
+
+async fn serve(context: GatewayContext) -> Result<(), String> {
+    let conn = db::open_database(&db::default_db_path()).map_err(|e| format!("Database error: {e}"))?;
+    let row = db::mcp::get_mcp_server(&conn, &context.server_id)
+        .map_err(|e| format!("Database error: {e}"))?
+        .ok_or_else(|| format!("MCP server {} not found", context.server_id))?;
+    let project = db::projects::get_project(&conn, &context.project_id)
+        .map_err(|e| format!("Database error: {e}"))?
+        .ok_or_else(|| format!("Project {} not found", context.project_id))?;
+    let policy = ToolPolicy::from_config(&config::read_project_config(&project.path)?.mcp_gateway);
+
+    let secrets = SecretStore::open(SecretStore::default_path(), &OsKeyring);
+    let endpoint = match ServerEndpoint::from_row(&row, &secrets) {
+        Ok(endpoint) => endpoint,
+        Err(e) if !secrets.is_unlocked() => {
+            let message = format!(
+                "Secrets locked: MCP server '{}' needs stored secrets, which the ELVES gateway can \
+                 only unlock through the OS keyring ({e})",
+                row.name
+            );
+            return refuse_locked(&conn, &context, &message).await;
+        }
+        Err(e) => return Err(e),
+    };
+    let transport = Transport::connect(&endpoint).await?;
+
+    let mut gateway = Gateway::new(transport, row.name, policy, conn, context);
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await.map_err(|e| format!("Read error: {e}"))? {
+        let Some(reply) = gateway.handle_line(&line).await else {
+            continue;
+        };
+        let sent = stdout.write_all(format!("{reply}\n").as_bytes()).await;
+        if sent.is_err() || stdout.flush().await.is_err() {
+            break;
+        }
+    }
+    gateway.close().await;
+    Ok(())
+}
+
+/// Answer every request from the agent with `message` until it closes stdin, after
+/// recording the error to the session's event log.
+async fn refuse_locked(conn: &Connection, context: &GatewayContext, message: &str) -> Result<(), String> {
+    log::error!("MCP gateway: {message}");
+    let payload = json!({ "message": message }).to_string();
+    if let Err(e) = db::events::insert_event(conn, &context.session_id, context.elf_id.as_deref(), "error", &payload, None) {
+        log::warn!("MCP gateway failed to record error: {e}");
+    }
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await.map_err(|e| format!("Read error: {e}"))? {
+        let Some(reply) = refusal(&line, message) else {
+            continue;
+        };
+        let sent = stdout.write_all(format!("{reply}\n").as_bytes()).await;
+        if sent.is_err() || stdout.flush().await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The error replies to the requests in one line from the agent, or None if it holds
+/// only notifications.
+fn refusal(line: &str, message: &str) -> Option<String> {
+    let reply = |request: &Value| request.get("id").map(|id| error_reply(id.clone(), -32603, message));
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let reply = match serde_json::from_str::<Value>(trimmed) {
+        Ok(Value::Array(batch)) => {
+            let replies: Vec<Value> = batch.iter().filter_map(reply).collect();
+            (!replies.is_empty()).then_some(Value::Array(replies))
+        }
+        Ok(request) => reply(&request),
+        Err(e) => Some(error_reply(Value::Null, -32700, &format!("Parse error: {e}"))),
+    };
+    reply.map(|r| r.to_string())
+}
+
+/// Forwards one agent's JSON-RPC traffic to one upstream server.
+pub struct Gateway {
+    transport: Transport,
+    next_id: u64,
+    server_name: String,
+    policy: ToolPolicy,
+    conn: Connection,
+    context: GatewayContext,
+}
+
+impl Gateway {
+    pub fn new(
+        transport: Transport,
+        server_name: String,
+        policy: ToolPolicy,
+        conn: Connection,
+        context: GatewayContext,
+    ) -> Self {
+        Self {
+            transport,
+            next_id: 1,
+            server_name,
+            policy,
+            conn,
+            context,
+        }
+    }
+
+    /// Handle one line from the agent. Returns the serialized reply, if any.
+    pub async fn handle_line(&mut self, line: &str) -> Option<String> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let reply = match serde_json::from_str::<Value>(trimmed) {
+            Ok(Value::Array(batch)) => {
+                let mut replies = Vec::new();
+                for message in batch {
+                    replies.extend(self.handle_message(message).await);
+                }
+                (!replies.is_empty()).then_some(Value::Array(replies))
+            }
+            Ok(message) => self.handle_message(message).await,
+            Err(e) => Some(error_reply(Value::Null, -32700, &format!("Parse error: {e}"))),
+        };
+        reply.map(|r| r.to_string())
+    }
+
+    /// Handle one JSON-RPC message from the agent.
+    pub async fn handle_message(&mut self, message: Value) -> Option<Value> {
+        // Responses from the agent would answer server requests, which the transport
+        // already answers itself
+        let method = message.get("method").and_then(Value::as_str)?.to_string();
+        let Some(id) = message.get("id").cloned() else {
+            if let Err(e) = self.transport.notify(&message).await {
+                log::warn!("MCP gateway failed to forward {method}: {e}");
+            }
+            return None;
+        };
+
+        Some(match method.as_str() {
+            "tools/call" => self.call_tool(message, id).await,
+            "tools/list" => {
+                let mut reply = self.forward(message, id).await;
+                if let Some(tools) = reply.pointer_mut("/result/tools").and_then(Value::as_array_mut) {
+                    tools.retain(|tool| {
+                        let name = tool.get("name").and_then(Value::as_str).unwrap_or_default();
+                        self.policy.permits(&self.server_name, name)
+                    });
+                }
+                reply
+            }
+            "initialize" => {
+                let reply = self.forward(message, id).await;
+                let version = reply
+                    .pointer("/result/protocolVersion")
+                    .and_then(Value::as_str)
+                    .map(String::from);
+                self.transport.set_protocol_version(version);
+                reply
+            }
+            _ => self.forward(message, id).await,
+        })
+    }
+
+    async fn call_tool(&mut self, message: Value, id: Value) -> Value {
+        let tool = message
+            .pointer("/params/name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let arguments = message.pointer("/params/arguments").cloned().unwrap_or(Value::Null);
+
+        if !self.policy.permits(&self.server_name, &tool) {
+            self.record(json!({
+                "server": self.server_name,
+                "tool": tool,
+                "arguments": arguments,
+                "denied": true,
+            }));
+            let text = format!("Tool `{tool}` on `{}` is blocked by this project's MCP policy.", self.server_name);
+            return json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": { "content": [{ "type": "text", "text": text }], "isError": true },
+            });
+        }
+
+        let started = Instant::now();
+        let reply = self.forward(message, id).await;
+        let result = reply.get("result");
+        self.record(json!({
+            "server": self.server_name,
+            "tool": tool,
+            "arguments": arguments,
+            "denied": false,
+            "durationMs": started.elapsed().as_millis() as u64,
+            "resultBytes": result.map(|r| r.to_string().len()).unwrap_or(0),
+            "isError": result
+                .and_then(|r| r.get("isError"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            "error": reply.pointer("/error/message"),
+        }));
+        reply
+    }
+
+    /// Send a request upstream under a gateway-assigned id, then restore the agent's id.
+    async fn forward(&mut self, mut message: Value, id: Value) -> Value {
+        let upstream_id = self.next_id;
+        self.next_id += 1;
+        message["id"] = json!(upstream_id);
+
+        match self.transport.round_trip(&message, upstream_id).await {
+            Ok(mut reply) => {
+                reply["id"] = id;
+                reply
+            }
+            Err(e) => error_reply(id, -32603, &format!("Upstream MCP server error: {e}")),
+        }
+    }
+
+    fn record(&self, payload: Value) {
+        if let Err(e) = db::events::insert_event(
+            &self.conn,
+            &self.context.session_id,
+            self.context.elf_id.as_deref(),
+            TOOL_CALL_EVENT,
+            &payload.to_string(),
+            None,
+        ) {
+            log::warn!("MCP gateway failed to record tool call: {e}");
+        }
+    }
+
+    pub async fn close(self) {
+        self.transport.close().await;
+    }
+}
+
+fn error_reply(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+    use crate::mcp::transport::StdioTransport;
+
+    fn policy(allow: &[&str], deny: &[&str]) -> ToolPolicy {
+        ToolPolicy {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Gateway in front of a fake upstream that echoes tool calls and lists two tools.
+    fn test_gateway(policy: ToolPolicy) -> Gateway {
+        let (gateway_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (gateway_read, gateway_write) = tokio::io::split(gateway_io);
+        let (server_read, mut server_write) = tokio::io::split(server_io);
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(server_read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let msg: Value = serde_json::from_str(&line).unwrap();
+                let Some(id) = msg.get("id").cloned() else { continue };
+                let result = match msg["method"].as_str().unwrap_or("") {
+                    "tools/list" => json!({ "tools": [{ "name": "read_file" }, { "name": "delete_repo" }] }),
+                    "tools/call" => json!({
+                        "content": [{ "type": "text", "text": format!("ran {}", msg["params"]["name"]) }],
+                        "isError": false,
+                    }),
+                    _ => json!({ "protocolVersion": "2025-03-26" }),
+                };
+                let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                if server_write.write_all(format!("{reply}\n").as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, path, default_runtime, created_at, updated_at)
+                 VALUES ('proj', 'Test', '/tmp/gateway-test', 'claude-code', 0, 0);
+             INSERT INTO sessions (id, project_id, task, runtime, status, agent_count, started_at, tokens_used, cost_estimate)
+                 VALUES ('sess', 'proj', 'Task', 'claude-code', 'active', 1, 0, 0, 0.0);",
+        )
+        .expect("Should seed session");
+
+        let transport = Transport::Stdio(StdioTransport::from_streams(Box::new(gateway_read), Box::new(gateway_write)));
+        let context = GatewayContext {
+            project_id: "proj".to_string(),
+            session_id: "sess".to_string(),
+            elf_id: None,
+            server_id: "srv".to_string(),
+        };
+        Gateway::new(transport, "github".to_string(), policy, conn, context)
+    }
+
+    fn recorded(gateway: &Gateway) -> Vec<Value> {
+        db::events::list_events(&gateway.conn, "sess")
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.event_type == TOOL_CALL_EVENT)
+            .map(|e| serde_json::from_str(&e.payload).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn locked_secrets_refuse_every_request() {
+        let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+        let reply: Value = serde_json::from_str(&refusal(initialize, "Secrets locked").unwrap()).unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"]["message"], "Secrets locked");
+
+        let initialized = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert_eq!(refusal(initialized, "Secrets locked"), None);
+        let batch = format!("[{initialized},{}]", r#"{"jsonrpc":"2.0","id":"a","method":"tools/list"}"#);
+        let replies: Value = serde_json::from_str(&refusal(&batch, "Secrets locked").unwrap()).unwrap();
+        assert_eq!(replies.as_array().map(Vec::len), Some(1));
+        assert_eq!(replies[0]["id"], "a");
+    }
+
+    #[test]
+    fn context_round_trips_through_args() {
+        let context = GatewayContext {
+            project_id: "p".to_string(),
+            session_id: "s".to_string(),
+            elf_id: Some("e".to_string()),
+            server_id: "srv".to_string(),
+        };
+        assert_eq!(GatewayContext::from_args(context.launch_args()), Some(context));
+        assert_eq!(GatewayContext::from_args(vec!["mcp-server".to_string()]), None);
+    }
+
+    #[test]
+    fn policy_deny_wins_and_allow_restricts() {
+        let open = policy(&[], &[]);
+        assert!(open.permits("github", "anything"));
+
+        let restricted = policy(&["read_*", "github/create_issue"], &["github/*_secret", "read_env"]);
+        assert!(restricted.permits("fs", "read_file"));
+        assert!(restricted.permits("github", "create_issue"));
+        assert!(!restricted.permits("gitlab", "create_issue"));
+        assert!(!restricted.permits("fs", "read_env"));
+        assert!(!restricted.permits("github", "read_secret"));
+        assert!(!restricted.permits("fs", "write_file"));
+    }
+
+    #[test]
+    fn glob_match_handles_wildcards() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*c", "abc"));
+        assert!(glob_match("a*b*c", "a-b-c"));
+        assert!(!glob_match("a*b*c", "a-c"));
+        assert!(!glob_match("ab*ba", "aba"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[tokio::test]
+    async fn forwards_and_records_tool_calls() {
+        let mut gateway = test_gateway(ToolPolicy::default());
+        let reply = gateway
+            .handle_line(r#"{"jsonrpc":"2.0","id":"call-1","method":"tools/call","params":{"name":"read_file","arguments":{"path":"a.rs"}}}"#)
+            .await
+            .expect("Requests get a reply");
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["id"], "call-1");
+        assert_eq!(reply["result"]["content"][0]["text"], "ran \"read_file\"");
+
+        let events = recorded(&gateway);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["tool"], "read_file");
+        assert_eq!(events[0]["arguments"]["path"], "a.rs");
+        assert_eq!(events[0]["denied"], false);
+        assert!(events[0]["resultBytes"].as_u64().unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn denied_tools_are_hidden_and_refused() {
+        let mut gateway = test_gateway(policy(&[], &["delete_*"]));
+
+        let listed = gateway
+            .handle_message(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+            .await
+            .unwrap();
+        let names: Vec<&str> = listed["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["read_file"]);
+
+        let refused = gateway
+            .handle_message(json!({
+                "jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                "params": { "name": "delete_repo", "arguments": {} }
+            }))
+            .await
+            .unwrap();
+        assert_eq!(refused["id"], 2);
+        assert_eq!(refused["result"]["isError"], true);
+
+        let events = recorded(&gateway);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["denied"], true);
+    }
+}
@@ -0,0 +1,9 @@
+// MCP client — speaks JSON-RPC to configured Model Context Protocol servers.
+
+pub mod agent_config;
+pub mod client;
+pub mod gateway;
+pub mod server;
+pub mod sync;
+pub mod transport;
+pub mod types;
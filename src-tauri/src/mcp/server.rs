@@ -0,0 +1,588 @@
+// ELVES MCP server — exposes project memory and session history to running agents over stdio.
+//
+// Every spawned session gets this server in its MCP config (see `agent_config`), so
+// agents can search memory and record decisions while they work instead of relying on
+// post-session extraction. The ELVES binary serves it when
+// launched as `elves mcp-server --project <id> --session <id>`, against the same SQLite
+// database the app uses — WAL mode lets both processes write.
+
+use std::io::{BufRead, Write};
+
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+use crate::db;
+
+/// Name the server is registered under in agent MCP configs.
+pub const SERVER_NAME: &str = "elves";
+
+/// First CLI argument that switches the ELVES binary into MCP server mode.
+const SUBCOMMAND: &str = "mcp-server";
+
+/// Protocol versions this server speaks, newest first. A client asking for one of these
+/// gets it echoed back; anything else gets the newest.
+const SUPPORTED_PROTOCOL_VERSIONS: [&str; 2] = ["2025-03-26", "2024-11-05"];
+
+/// Default and maximum number of rows returned by the list/search tools.
+const DEFAULT_LIMIT: i64 = 10;
+const MAX_LIMIT: i64 = 50;
+
+/// Memory categories agents may write, matching the memory table's categories.
+const MEMORY_CATEGORIES: [&str; 5] = ["context", "decision", "learning", "preference", "fact"];
+
+/// Which project and session a server instance acts for.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerContext {
+    pub project_id: Option<String>,
+    pub session_id: Option<String>,
+}
+
+impl ServerContext {
+    /// Parse `mcp-server [--project <id>] [--session <id>]` from the process arguments
+    /// (program name already skipped). Returns None when the binary was launched normally.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Option<Self> {
+        let mut args = args.into_iter();
+        if args.next().as_deref() != Some(SUBCOMMAND) {
+            return None;
+        }
+
+        let mut context = ServerContext::default();
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "--project" => context.project_id = args.next(),
+                "--session" => context.session_id = args.next(),
+                other => log::warn!("Ignoring unknown mcp-server argument: {other}"),
+            }
+        }
+        Some(context)
+    }
+
+    /// Arguments (after the ELVES executable) that launch the server for this context.
+    pub fn launch_args(&self) -> Vec<String> {
+        let mut args = vec![SUBCOMMAND.to_string()];
+        if let Some(ref project_id) = self.project_id {
+            args.push("--project".to_string());
+            args.push(project_id.clone());
+        }
+        if let Some(ref session_id) = self.session_id {
+            args.push("--session".to_string());
+            args.push(session_id.clone());
+        }
+        args
+    }
+}
+
+/// Serve MCP on stdin/stdout until the client closes stdin. Returns the process exit code.
+pub fn run_stdio(context: ServerContext) -> i32 {
+    let conn = match db::open_database(&db::default_db_path()) {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::error!("ELVES MCP server could not open the database: {e}");
+            return 1;
+        }
+    };
+    let server = MemoryServer::new(conn, context);
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                log::error!("ELVES MCP server failed to read stdin: {e}");
+                return 1;
+            }
+        };
+        let Some(reply) = server.handle_line(&line) else {
+            continue;
+        };
+        if writeln!(stdout, "{reply}").and_then(|_| stdout.flush()).is_err() {
+            // Client went away
+            return 0;
+        }
+    }
+    0
+}
+
+/// JSON-RPC handler for the ELVES memory tools.
+pub struct MemoryServer {
+    conn: Connection,
+    context: ServerContext,
+}
+
+impl MemoryServer {
+    pub fn new(conn: Connection, context: ServerContext) -> Self {
+        Self { conn, context }
+    }
+
+    /// Handle one line of input. Returns the serialized reply, if the message needs one.
+    pub fn handle_line(&self, line: &str) -> Option<String> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let reply = match serde_json::from_str::<Value>(trimmed) {
+            Ok(Value::Array(batch)) => {
+                let replies: Vec<Value> = batch.iter().filter_map(|m| self.handle_message(m)).collect();
+                (!replies.is_empty()).then_some(Value::Array(replies))
+            }
+            Ok(message) => self.handle_message(&message),
+            Err(e) => Some(error_reply(Value::Null, -32700, &format!("Parse error: {e}"))),
+        };
+        reply.map(|r| r.to_string())
+    }
+
+    /// Handle a single JSON-RPC message. Notifications and responses get no reply.
+    pub fn handle_message(&self, message: &Value) -> Option<Value> {
+        let method = message.get("method").and_then(Value::as_str)?;
+        let id = message.get("id")?.clone();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        Some(match self.dispatch(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, text)) => error_reply(id, code, &text),
+        })
+    }
+
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => {
+                let requested = params.get("protocolVersion").and_then(Value::as_str);
+                let version = requested
+                    .filter(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(v))
+                    .unwrap_or(SUPPORTED_PROTOCOL_VERSIONS[0]);
+                Ok(json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+                    "instructions": "Project memory from ELVES. Search it before making decisions that \
+                        may already have been made, and record decisions and learnings with memory_add \
+                        as you make them.",
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => {
+                let name = params
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or((-32602, "Missing tool name".to_string()))?;
+                let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                // Tool failures are reported to the model as results, not protocol errors
+                Ok(match self.call_tool(name, &arguments) {
+                    Ok(value) => tool_result(&value, false),
+                    Err(e) => tool_result(&Value::String(e), true),
+                })
+            }
+            other => Err((-32601, format!("Method not found: {other}"))),
+        }
+    }
+
+    fn call_tool(&self, name: &str, args: &Value) -> Result<Value, String> {
+        match name {
+            "memory_search" => self.memory_search(args),
+            "memory_add" => self.memory_add(args),
+            "memory_pin_request" => self.memory_pin_request(args),
+            "list_recent_sessions" => self.list_recent_sessions(args),
+            "get_session_summary" => self.get_session_summary(args),
+            other => Err(format!("Unknown tool: {other}")),
+        }
+    }
+
+    fn memory_search(&self, args: &Value) -> Result<Value, String> {
+        let query = required_str(args, "query")?;
+        let limit = limit_arg(args);
+        let category = args.get("category").and_then(Value::as_str);
+
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(json!({ "memories": [] }));
+        };
+        let memories = db::memory::search_memories(&self.conn, self.context.project_id.as_deref(), &fts_query, MAX_LIMIT)
+            .map_err(|e| format!("Database error: {e}"))?;
+
+        let memories: Vec<_> = memories
+            .into_iter()
+            .filter(|m| category.is_none_or(|c| m.category == c))
+            .take(limit as usize)
+            .collect();
+        // Reading a memory counts as using it, the same as context injection
+        for memory in &memories {
+            let _ = db::memory::update_relevance(&self.conn, memory.id);
+        }
+        Ok(json!({ "memories": memories }))
+    }
+
+    fn memory_add(&self, args: &Value) -> Result<Value, String> {
+        let content = required_str(args, "content")?.trim();
+        if content.is_empty() {
+            return Err("content must not be empty".to_string());
+        }
+        let category = args.get("category").and_then(Value::as_str).unwrap_or("learning");
+        if !MEMORY_CATEGORIES.contains(&category) {
+            return Err(format!("category must be one of: {}", MEMORY_CATEGORIES.join(", ")));
+        }
+        let tags: Vec<&str> = args
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| tags.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let tags = serde_json::to_string(&tags).map_err(|e| format!("Serialize error: {e}"))?;
+
+        // Agent-written memories are marked so the user can tell them from extracted ones
+        let source = match self.context.session_id {
+            Some(ref session_id) => format!("agent:{session_id}"),
+            None => "agent".to_string(),
+        };
+        let memory = db::memory::insert_memory(
+            &self.conn,
+            self.context.project_id.as_deref(),
+            category,
+            content,
+            Some(&source),
+            &tags,
+        )
+        .map_err(|e| format!("Database error: {e}"))?;
+        Ok(json!({ "memory": memory }))
+    }
+
+    fn memory_pin_request(&self, args: &Value) -> Result<Value, String> {
+        let memory_id = args
+            .get("memory_id")
+            .and_then(Value::as_i64)
+            .ok_or("memory_id is required")?;
+        let reason = args.get("reason").and_then(Value::as_str);
+
+        let memory = db::memory::get_memory(&self.conn, memory_id)
+            .map_err(|e| format!("Database error: {e}"))?
+            .ok_or_else(|| format!("Memory {memory_id} not found"))?;
+        if let (Some(memory_project), Some(project)) = (&memory.project_id, &self.context.project_id) {
+            if memory_project != project {
+                return Err(format!("Memory {memory_id} belongs to another project"));
+            }
+        }
+        if memory.source.as_deref() == Some("pinned") {
+            return Ok(json!({ "status": "already_pinned", "memoryId": memory_id }));
+        }
+
+        let request = db::memory::insert_pin_request(&self.conn, memory_id, self.context.session_id.as_deref(), reason)
+            .map_err(|e| format!("Database error: {e}"))?;
+        Ok(json!({
+            "status": "requested",
+            "request": request,
+            "note": "The user will be asked to approve pinning this memory.",
+        }))
+    }
+
+    fn list_recent_sessions(&self, args: &Value) -> Result<Value, String> {
+        let project_id = self.project_id()?;
+        let limit = limit_arg(args) as usize;
+        let sessions = db::sessions::list_sessions(&self.conn, project_id)
+            .map_err(|e| format!("Database error: {e}"))?;
+
+        let sessions: Vec<Value> = sessions
+            .iter()
+            .filter(|s| self.context.session_id.as_deref() != Some(s.id.as_str()))
+            .take(limit)
+            .map(|s| {
+                json!({
+                    "id": s.id,
+                    "task": s.task,
+                    "runtime": s.runtime,
+                    "status": s.status,
+                    "startedAt": s.started_at,
+                    "endedAt": s.ended_at,
+                    "hasSummary": s.summary.is_some(),
+                })
+            })
+            .collect();
+        Ok(json!({ "sessions": sessions }))
+    }
+
+    fn get_session_summary(&self, args: &Value) -> Result<Value, String> {
+        let session_id = required_str(args, "session_id")?;
+        let session = db::sessions::get_session(&self.conn, session_id)
+            .map_err(|e| format!("Database error: {e}"))?
+            .filter(|s| self.context.project_id.as_deref().is_none_or(|p| s.project_id == p))
+            .ok_or_else(|| format!("Session {session_id} not found in this project"))?;
+
+        Ok(json!({
+            "id": session.id,
+            "task": session.task,
+            "runtime": session.runtime,
+            "status": session.status,
+            "startedAt": session.started_at,
+            "endedAt": session.ended_at,
+            "tokensUsed": session.tokens_used,
+            "costEstimate": session.cost_estimate,
+            "summary": session.summary,
+        }))
+    }
+
+    fn project_id(&self) -> Result<&str, String> {
+        self.context
+            .project_id
+            .as_deref()
+            .ok_or_else(|| "This ELVES MCP server was started without a project".to_string())
+    }
+}
+
+/// Tool definitions advertised by `tools/list`.
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "memory_search",
+            "description": "Full-text search over this project's ELVES memory (decisions, learnings, preferences, context).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words to search for" },
+                    "category": { "type": "string", "enum": MEMORY_CATEGORIES },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_LIMIT }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "memory_add",
+            "description": "Record a decision, learning, preference, or fact in project memory so future sessions see it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "content": { "type": "string", "description": "One self-contained statement" },
+                    "category": { "type": "string", "enum": MEMORY_CATEGORIES },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["content"]
+            }
+        },
+        {
+            "name": "memory_pin_request",
+            "description": "Ask the user to pin a memory so it never decays and is always included in context.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "memory_id": { "type": "integer" },
+                    "reason": { "type": "string", "description": "Why this memory should always be kept" }
+                },
+                "required": ["memory_id"]
+            }
+        },
+        {
+            "name": "list_recent_sessions",
+            "description": "List this project's most recent ELVES sessions, newest first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_LIMIT }
+                }
+            }
+        },
+        {
+            "name": "get_session_summary",
+            "description": "Get the task, outcome, usage, and summary of a previous ELVES session.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": { "type": "string" }
+                },
+                "required": ["session_id"]
+            }
+        }
+    ])
+}
+
+/// Wrap a tool's output as an MCP `CallToolResult` with a single text block.
+fn tool_result(value: &Value, is_error: bool) -> Value {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    };
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+fn error_reply(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("{key} is required"))
+}
+
+fn limit_arg(args: &Value) -> i64 {
+    args.get("limit")
+        .and_then(Value::as_i64)
+        .unwrap_or(DEFAULT_LIMIT)
+        .clamp(1, MAX_LIMIT)
+}
+
+/// Turn free text into an FTS5 query matching any of its words. Each word is quoted so
+/// punctuation in agent queries can't produce FTS syntax errors.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\""))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn test_server(project_id: Option<&str>, session_id: Option<&str>) -> MemoryServer {
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory db");
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        schema::run_migrations(&conn).expect("Migrations should succeed");
+        for id in ["proj-a", "proj-b"] {
+            conn.execute(
+                "INSERT INTO projects (id, name, path, default_runtime, created_at, updated_at)
+                 VALUES (?1, 'Test', '/tmp/' || ?1, 'claude-code', 0, 0)",
+                [id],
+            )
+            .expect("Should seed project");
+        }
+        MemoryServer::new(
+            conn,
+            ServerContext {
+                project_id: project_id.map(String::from),
+                session_id: session_id.map(String::from),
+            },
+        )
+    }
+
+    fn call(server: &MemoryServer, name: &str, arguments: Value) -> (Value, bool) {
+        let reply = server
+            .handle_message(&json!({
+                "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                "params": { "name": name, "arguments": arguments }
+            }))
+            .expect("Requests get a reply");
+        let result = &reply["result"];
+        let text = result["content"][0]["text"].as_str().unwrap_or_default();
+        let is_error = result["isError"].as_bool().unwrap_or(false);
+        let value = serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()));
+        (value, is_error)
+    }
+
+    #[test]
+    fn from_args_only_matches_subcommand() {
+        assert_eq!(ServerContext::from_args(Vec::<String>::new()), None);
+        assert_eq!(ServerContext::from_args(vec!["--foo".to_string()]), None);
+
+        let args = ["mcp-server", "--project", "p1", "--session", "s1"].map(String::from);
+        let context = ServerContext::from_args(args.clone()).expect("Should parse");
+        assert_eq!(context.project_id.as_deref(), Some("p1"));
+        assert_eq!(context.session_id.as_deref(), Some("s1"));
+        assert_eq!(context.launch_args(), args.to_vec());
+    }
+
+    #[test]
+    fn initialize_and_list_tools() {
+        let server = test_server(Some("proj-a"), None);
+        let reply = server
+            .handle_message(&json!({
+                "jsonrpc": "2.0", "id": 0, "method": "initialize",
+                "params": { "protocolVersion": "2024-11-05", "capabilities": {} }
+            }))
+            .unwrap();
+        assert_eq!(reply["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(reply["result"]["serverInfo"]["name"], "elves");
+
+        assert!(server
+            .handle_message(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .is_none());
+
+        let reply = server
+            .handle_message(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+            .unwrap();
+        let names: Vec<&str> = reply["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["memory_search", "memory_add", "memory_pin_request", "list_recent_sessions", "get_session_summary"]
+        );
+
+        let reply = server
+            .handle_message(&json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/list" }))
+            .unwrap();
+        assert_eq!(reply["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn memory_add_then_search() {
+        let server = test_server(Some("proj-a"), Some("sess-1"));
+        let (added, is_error) = call(
+            &server,
+            "memory_add",
+            json!({ "content": "Keep queries in rusqlite with bundled SQLite", "category": "decision", "tags": ["db"] }),
+        );
+        assert!(!is_error);
+        assert_eq!(added["memory"]["source"], "agent:sess-1");
+        assert_eq!(added["memory"]["projectId"], "proj-a");
+        assert_eq!(added["memory"]["tags"], "[\"db\"]");
+
+        let (found, is_error) = call(&server, "memory_search", json!({ "query": "rusqlite: queries?" }));
+        assert!(!is_error);
+        assert_eq!(found["memories"].as_array().unwrap().len(), 1);
+
+        let (found, _) = call(&server, "memory_search", json!({ "query": "rusqlite", "category": "learning" }));
+        assert!(found["memories"].as_array().unwrap().is_empty());
+
+        let (error, is_error) = call(&server, "memory_add", json!({ "content": "x", "category": "gossip" }));
+        assert!(is_error);
+        assert!(error.as_str().unwrap().contains("category"));
+    }
+
+    #[test]
+    fn memory_pin_request_records_pending_request() {
+        let server = test_server(Some("proj-a"), Some("sess-1"));
+        let (added, _) = call(&server, "memory_add", json!({ "content": "Always run clippy" }));
+        let memory_id = added["memory"]["id"].as_i64().unwrap();
+
+        let (result, is_error) = call(&server, "memory_pin_request", json!({ "memory_id": memory_id, "reason": "CI" }));
+        assert!(!is_error);
+        assert_eq!(result["status"], "requested");
+
+        let pending = db::memory::list_pending_pin_requests(&server.conn, Some("proj-a")).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].session_id.as_deref(), Some("sess-1"));
+
+        let (_, is_error) = call(&server, "memory_pin_request", json!({ "memory_id": 9999 }));
+        assert!(is_error);
+    }
+
+    #[test]
+    fn sessions_are_scoped_to_project() {
+        let server = test_server(Some("proj-a"), Some("current"));
+        db::sessions::create_session(&server.conn, "current", "proj-a", "Now", "claude-code", None).unwrap();
+        db::sessions::create_session(&server.conn, "earlier", "proj-a", "Fix the build", "claude-code", None).unwrap();
+        db::sessions::create_session(&server.conn, "elsewhere", "proj-b", "Other", "codex", None).unwrap();
+        db::sessions::update_session_status(&server.conn, "earlier", "completed", Some("Fixed the linker flags"))
+            .unwrap();
+
+        let (listed, _) = call(&server, "list_recent_sessions", json!({}));
+        let ids: Vec<&str> = listed["sessions"].as_array().unwrap().iter().map(|s| s["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["earlier"]);
+
+        let (summary, is_error) = call(&server, "get_session_summary", json!({ "session_id": "earlier" }));
+        assert!(!is_error);
+        assert_eq!(summary["summary"], "Fixed the linker flags");
+
+        let (_, is_error) = call(&server, "get_session_summary", json!({ "session_id": "elsewhere" }));
+        assert!(is_error);
+    }
+
+    #[test]
+    fn fts_query_quotes_words() {
+        assert_eq!(fts_query("auth: token-refresh").as_deref(), Some("\"auth\" OR \"token\" OR \"refresh\""));
+        assert_eq!(fts_query(" ?! "), None);
+    }
+}
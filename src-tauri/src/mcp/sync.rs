@@ -0,0 +1,634 @@
+// MCP config sync — compare and exchange server definitions with Claude Code and Codex.
+//
+// External configs live in three places: `~/.claude.json` (user-level `mcpServers`,
+// plus per-project `projects.<path>.mcpServers`), a project's checked-in `.mcp.json`,
+// and Codex's `~/.codex/config.toml` (`[mcp_servers.<name>]`). Each is read into a
+// normalized `ServerSpec` map keyed by server name so it can be diffed against ELVES
+// rows in the same scope. Writes only upsert the named servers and leave everything
+// else in the file untouched.
+//
+// ELVES rows may hold `secret://` references where the external files hold the
+// plaintext value, so comparisons and exports use the resolved ELVES spec.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::transport::{split_command, TransportKind};
+use crate::db::mcp::McpRow;
+use crate::secrets::store::SecretStore;
+
+/// An external config file that can be synced with ELVES.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncTarget {
+    /// `~/.claude.json` top-level `mcpServers` — Claude Code "user" scope.
+    ClaudeUser,
+    /// `~/.claude.json` `projects.<path>.mcpServers` — Claude Code "local" scope.
+    ClaudeLocal,
+    /// `<project>/.mcp.json` — Claude Code "project" scope, shared via the repo.
+    McpJson,
+    /// `~/.codex/config.toml` `[mcp_servers]`.
+    Codex,
+}
+
+impl SyncTarget {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "claude" => Ok(Self::ClaudeUser),
+            "claude-local" => Ok(Self::ClaudeLocal),
+            "mcp-json" => Ok(Self::McpJson),
+            "codex" => Ok(Self::Codex),
+            other => Err(format!(
+                "Unknown sync target '{other}' (expected claude, claude-local, mcp-json, or codex)"
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ClaudeUser => "claude",
+            Self::ClaudeLocal => "claude-local",
+            Self::McpJson => "mcp-json",
+            Self::Codex => "codex",
+        }
+    }
+
+    /// Whether servers in this file belong to a single project rather than the user.
+    pub fn is_project_scoped(&self) -> bool {
+        matches!(self, Self::ClaudeLocal | Self::McpJson)
+    }
+
+    /// Location of the config file. Project-scoped targets need the project path.
+    pub fn file_path(&self, home: &Path, project_path: Option<&str>) -> Result<PathBuf, String> {
+        match self {
+            Self::ClaudeUser | Self::ClaudeLocal => Ok(home.join(".claude.json")),
+            Self::Codex => Ok(home.join(".codex").join("config.toml")),
+            Self::McpJson => project_path
+                .map(|p| Path::new(p).join(".mcp.json"))
+                .ok_or_else(|| "The .mcp.json target needs a project".to_string()),
+        }
+    }
+}
+
+/// A transport-agnostic server definition used for comparison and writing.
+///
+/// Stdio commands are normalized so "npx -y @pkg" with no args and "npx" with
+/// ["-y", "@pkg"] compare equal. Maps are ordered so diffs are stable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSpec {
+    pub transport: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub url: Option<String>,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl ServerSpec {
+    /// Build a spec from an ELVES DB row.
+    pub fn from_row(row: &McpRow) -> Self {
+        let args: Vec<String> = serde_json::from_str(&row.args).unwrap_or_default();
+        let env: BTreeMap<String, String> = serde_json::from_str(&row.env).unwrap_or_default();
+        let headers: BTreeMap<String, String> = serde_json::from_str(&row.headers).unwrap_or_default();
+        if row.transport == TransportKind::Stdio.as_str() {
+            Self::stdio(&row.command, args, env)
+        } else {
+            Self::remote(&row.transport, row.url.clone().unwrap_or_default(), headers)
+        }
+    }
+
+    /// Copy of this spec with `secret://` references in env values, the URL, and
+    /// header values replaced by the stored secrets.
+    pub fn resolve_secrets(&self, secrets: &SecretStore) -> Result<Self, String> {
+        let resolve_map = |map: &BTreeMap<String, String>| {
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), secrets.resolve_refs(v)?)))
+                .collect::<Result<BTreeMap<_, _>, String>>()
+        };
+        Ok(Self {
+            env: resolve_map(&self.env)?,
+            url: self.url.as_deref().map(|u| secrets.resolve_refs(u)).transpose()?,
+            headers: resolve_map(&self.headers)?,
+            ..self.clone()
+        })
+    }
+
+    fn stdio(command: &str, args: Vec<String>, env: BTreeMap<String, String>) -> Self {
+        let (command, mut full_args) = split_command(command);
+        full_args.extend(args);
+        Self {
+            transport: TransportKind::Stdio.as_str().to_string(),
+            command,
+            args: full_args,
+            env,
+            url: None,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    fn remote(transport: &str, url: String, headers: BTreeMap<String, String>) -> Self {
+        Self {
+            transport: transport.to_string(),
+            command: String::new(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            url: Some(url),
+            headers,
+        }
+    }
+
+    /// Parse a Claude Code / `.mcp.json` server entry. Returns None for entries that
+    /// have neither a command nor a URL, or an unsupported `type`.
+    pub fn from_claude_json(config: &Value) -> Option<Self> {
+        let string_map = |key: &str| -> BTreeMap<String, String> {
+            config
+                .get(key)
+                .and_then(|v| v.as_object())
+                .map(|obj| {
+                    obj.iter()
+                        .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        if let Some(url) = config.get("url").and_then(|v| v.as_str()) {
+            let kind = TransportKind::parse(config.get("type").and_then(|v| v.as_str()).unwrap_or("http")).ok()?;
+            if kind == TransportKind::Stdio {
+                return None;
+            }
+            return Some(Self::remote(kind.as_str(), url.to_string(), string_map("headers")));
+        }
+
+        let command = config.get("command").and_then(|v| v.as_str()).filter(|c| !c.is_empty())?;
+        let args = config
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|s| s.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        Some(Self::stdio(command, args, string_map("env")))
+    }
+
+    /// Render as a Claude Code / `.mcp.json` server entry.
+    pub fn to_claude_json(&self) -> Value {
+        if self.transport == TransportKind::Stdio.as_str() {
+            json!({ "type": "stdio", "command": self.command, "args": self.args, "env": self.env })
+        } else {
+            json!({ "type": self.transport, "url": self.url, "headers": self.headers })
+        }
+    }
+
+    /// Parse a Codex `[mcp_servers.<name>]` table. Codex only speaks stdio and
+    /// Streamable HTTP, so entries with a `url` are treated as http.
+    pub fn from_codex_table(table: &dyn toml_edit::TableLike) -> Option<Self> {
+        let string_map = |key: &str| -> BTreeMap<String, String> {
+            table
+                .get(key)
+                .and_then(|item| item.as_table_like())
+                .map(|t| {
+                    t.iter()
+                        .filter_map(|(k, v)| Some((k.to_string(), v.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        if let Some(url) = table.get("url").and_then(|v| v.as_str()) {
+            return Some(Self::remote(
+                TransportKind::Http.as_str(),
+                url.to_string(),
+                string_map("http_headers"),
+            ));
+        }
+
+        let command = table.get("command").and_then(|v| v.as_str()).filter(|c| !c.is_empty())?;
+        let args = table
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|s| s.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        Some(Self::stdio(command, args, string_map("env")))
+    }
+
+    /// Render as a Codex server table. Returns None for SSE servers, which Codex can't reach.
+    pub fn to_codex_table(&self) -> Option<toml_edit::Table> {
+        let inline_map = |map: &BTreeMap<String, String>| {
+            let mut inline = toml_edit::InlineTable::new();
+            for (k, v) in map {
+                inline.insert(k, v.as_str().into());
+            }
+            toml_edit::value(inline)
+        };
+
+        let mut table = toml_edit::Table::new();
+        match self.transport.as_str() {
+            "stdio" => {
+                table["command"] = toml_edit::value(self.command.as_str());
+                let args: toml_edit::Array = self.args.iter().map(|a| a.as_str()).collect();
+                table["args"] = toml_edit::value(args);
+                if !self.env.is_empty() {
+                    table["env"] = inline_map(&self.env);
+                }
+            }
+            "http" => {
+                table["url"] = toml_edit::value(self.url.as_deref().unwrap_or_default());
+                if !self.headers.is_empty() {
+                    table["http_headers"] = inline_map(&self.headers);
+                }
+            }
+            _ => return None,
+        }
+        Some(table)
+    }
+
+    /// Fields whose values differ between `self` (ELVES) and `other` (external).
+    pub fn changes(&self, other: &Self) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        let mut compare = |field: &str, ours: Value, theirs: Value| {
+            if ours != theirs {
+                changes.push(FieldChange { field: field.to_string(), elves: ours, external: theirs });
+            }
+        };
+        compare("transport", json!(self.transport), json!(other.transport));
+        compare("command", json!(self.command), json!(other.command));
+        compare("args", json!(self.args), json!(other.args));
+        compare("env", json!(self.env), json!(other.env));
+        compare("url", json!(self.url), json!(other.url));
+        compare("headers", json!(self.headers), json!(other.headers));
+        changes
+    }
+}
+
+/// One field that differs between the ELVES and external definitions of a server.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub elves: Value,
+    pub external: Value,
+}
+
+/// How a server compares across ELVES and the external file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    /// Configured in ELVES only — exporting would add it to the file.
+    ElvesOnly,
+    /// Present in the file only — importing would add it to ELVES.
+    ExternalOnly,
+    /// Same name on both sides but a different command, args, env, URL, or headers.
+    Drift,
+    InSync,
+}
+
+/// One row of a sync preview.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncEntry {
+    pub name: String,
+    pub status: SyncStatus,
+    pub elves: Option<ServerSpec>,
+    pub external: Option<ServerSpec>,
+    /// Field-level differences, populated only for `Drift`.
+    pub changes: Vec<FieldChange>,
+}
+
+/// Compare ELVES servers against an external file's servers (both for the same scope).
+/// Entries are sorted by name.
+///
+/// `resolved` holds the ELVES specs with secret references filled in; status is
+/// decided on those so a `secret://` reference matching the file's plaintext value
+/// counts as in sync. Entries and field changes still show the unresolved ELVES
+/// values so secrets never leave the store through a preview.
+pub fn diff_servers(
+    elves: &BTreeMap<String, ServerSpec>,
+    resolved: &BTreeMap<String, ServerSpec>,
+    external: &BTreeMap<String, ServerSpec>,
+) -> Vec<SyncEntry> {
+    let mut names: Vec<&String> = elves.keys().chain(external.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let ours = elves.get(name).cloned();
+            let theirs = external.get(name).cloned();
+            let (status, changes) = match (&ours, &theirs) {
+                (Some(o), Some(t)) => {
+                    let compared = resolved.get(name).unwrap_or(o);
+                    let changed: Vec<String> = compared.changes(t).into_iter().map(|c| c.field).collect();
+                    if changed.is_empty() {
+                        (SyncStatus::InSync, Vec::new())
+                    } else {
+                        let mut changes = o.changes(t);
+                        changes.retain(|c| changed.contains(&c.field));
+                        (SyncStatus::Drift, changes)
+                    }
+                }
+                (Some(_), None) => (SyncStatus::ElvesOnly, Vec::new()),
+                _ => (SyncStatus::ExternalOnly, Vec::new()),
+            };
+            SyncEntry { name: name.clone(), status, elves: ours, external: theirs, changes }
+        })
+        .collect()
+}
+
+/// Read every server defined in the target file. A missing file reads as empty.
+/// `project_path` selects the project entry for `ClaudeLocal`.
+pub fn read_external(
+    target: SyncTarget,
+    file: &Path,
+    project_path: Option<&str>,
+) -> Result<BTreeMap<String, ServerSpec>, String> {
+    let contents = match fs::read_to_string(file) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", file.display())),
+    };
+
+    if target == SyncTarget::Codex {
+        let doc: toml_edit::DocumentMut = contents
+            .parse()
+            .map_err(|e| format!("Failed to parse {}: {e}", file.display()))?;
+        let Some(servers) = doc.get("mcp_servers").and_then(|s| s.as_table_like()) else {
+            return Ok(BTreeMap::new());
+        };
+        return Ok(servers
+            .iter()
+            .filter_map(|(name, item)| {
+                Some((name.to_string(), ServerSpec::from_codex_table(item.as_table_like()?)?))
+            })
+            .collect());
+    }
+
+    let json: Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {e}", file.display()))?;
+    let servers = match target {
+        SyncTarget::ClaudeLocal => {
+            let path = project_path.ok_or("The claude-local target needs a project")?;
+            json.get("projects").and_then(|p| p.get(path)).and_then(|p| p.get("mcpServers"))
+        }
+        _ => json.get("mcpServers"),
+    };
+
+    Ok(servers
+        .and_then(|s| s.as_object())
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(name, config)| Some((name.clone(), ServerSpec::from_claude_json(config)?)))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Add or replace the given servers in the target file, creating it if needed.
+/// Servers not named in `servers` are left as they are. Returns the names that were
+/// skipped because the target can't represent them (SSE servers in Codex).
+pub fn write_external(
+    target: SyncTarget,
+    file: &Path,
+    project_path: Option<&str>,
+    servers: &BTreeMap<String, ServerSpec>,
+) -> Result<Vec<String>, String> {
+    let existing = match fs::read_to_string(file) {
+        Ok(c) => Some(c),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read {}: {e}", file.display())),
+    };
+    let mut skipped = Vec::new();
+
+    let output = if target == SyncTarget::Codex {
+        let mut doc: toml_edit::DocumentMut = existing
+            .as_deref()
+            .unwrap_or("")
+            .parse()
+            .map_err(|e| format!("Failed to parse {}: {e}", file.display()))?;
+        if !doc.contains_table("mcp_servers") {
+            let mut table = toml_edit::Table::new();
+            table.set_implicit(true);
+            doc["mcp_servers"] = toml_edit::Item::Table(table);
+        }
+        let table = doc["mcp_servers"]
+            .as_table_mut()
+            .ok_or_else(|| format!("mcp_servers in {} is not a table", file.display()))?;
+        for (name, spec) in servers {
+            match spec.to_codex_table() {
+                Some(server) => {
+                    table.insert(name, toml_edit::Item::Table(server));
+                }
+                None => skipped.push(name.clone()),
+            }
+        }
+        doc.to_string()
+    } else {
+        let mut json: Value = match existing {
+            Some(ref c) if !c.trim().is_empty() => serde_json::from_str(c)
+                .map_err(|e| format!("Failed to parse {}: {e}", file.display()))?,
+            _ => json!({}),
+        };
+        let root = json
+            .as_object_mut()
+            .ok_or_else(|| format!("{} is not a JSON object", file.display()))?;
+        let holder = if target == SyncTarget::ClaudeLocal {
+            let path = project_path.ok_or("The claude-local target needs a project")?;
+            object_entry(object_entry(root, "projects")?, path)?
+        } else {
+            root
+        };
+        let mcp_servers = object_entry(holder, "mcpServers")?;
+        for (name, spec) in servers {
+            mcp_servers.insert(name.clone(), spec.to_claude_json());
+        }
+        let mut rendered = serde_json::to_string_pretty(&json)
+            .map_err(|e| format!("Failed to serialize {}: {e}", file.display()))?;
+        rendered.push('\n');
+        rendered
+    };
+
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    // Write to a sibling temp file and rename so a crash never leaves a truncated config
+    let tmp = file.with_extension("elves-tmp");
+    fs::write(&tmp, output).map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+    // These files hold credentials, so keep the original's mode (often 0600) rather
+    // than the temp file's default
+    if let Ok(metadata) = fs::metadata(file) {
+        fs::set_permissions(&tmp, metadata.permissions())
+            .map_err(|e| format!("Failed to set permissions on {}: {e}", tmp.display()))?;
+    }
+    fs::rename(&tmp, file).map_err(|e| format!("Failed to replace {}: {e}", file.display()))?;
+
+    Ok(skipped)
+}
+
+/// Get `obj[key]` as a JSON object, inserting an empty one if missing.
+fn object_entry<'a>(
+    obj: &'a mut serde_json::Map<String, Value>,
+    key: &str,
+) -> Result<&'a mut serde_json::Map<String, Value>, String> {
+    obj.entry(key.to_string())
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or_else(|| format!("Expected '{key}' to be a JSON object"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdio_spec(command: &str, args: &[&str]) -> ServerSpec {
+        ServerSpec::stdio(command, args.iter().map(|s| s.to_string()).collect(), BTreeMap::new())
+    }
+
+    #[test]
+    fn embedded_command_args_compare_equal() {
+        assert_eq!(stdio_spec("npx -y @pkg", &[]), stdio_spec("npx", &["-y", "@pkg"]));
+    }
+
+    #[test]
+    fn diff_reports_each_status() {
+        let mut elves = BTreeMap::new();
+        elves.insert("same".to_string(), stdio_spec("uvx", &["a"]));
+        elves.insert("drifted".to_string(), stdio_spec("npx", &["-y", "v1"]));
+        elves.insert("ours".to_string(), stdio_spec("node", &[]));
+        let mut external = BTreeMap::new();
+        external.insert("same".to_string(), stdio_spec("uvx", &["a"]));
+        let mut drifted = stdio_spec("npx", &["-y", "v2"]);
+        drifted.env.insert("TOKEN".to_string(), "x".to_string());
+        external.insert("drifted".to_string(), drifted);
+        external.insert("theirs".to_string(), stdio_spec("bunx", &[]));
+
+        let entries = diff_servers(&elves, &elves, &external);
+        let status: Vec<(&str, SyncStatus)> = entries.iter().map(|e| (e.name.as_str(), e.status)).collect();
+        assert_eq!(
+            status,
+            vec![
+                ("drifted", SyncStatus::Drift),
+                ("ours", SyncStatus::ElvesOnly),
+                ("same", SyncStatus::InSync),
+                ("theirs", SyncStatus::ExternalOnly),
+            ]
+        );
+        let fields: Vec<&str> = entries[0].changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["args", "env"]);
+    }
+
+    #[test]
+    fn diff_compares_resolved_secret_references() {
+        let store = SecretStore::for_tests(&[("gh", "ghp_live_value")]);
+        let mut ours = stdio_spec("npx", &["-y", "@gh"]);
+        ours.env.insert("GITHUB_TOKEN".to_string(), "secret://gh".to_string());
+        let mut theirs = ours.clone();
+        theirs.env.insert("GITHUB_TOKEN".to_string(), "ghp_live_value".to_string());
+
+        let elves = BTreeMap::from([("gh".to_string(), ours.clone())]);
+        let resolved = BTreeMap::from([("gh".to_string(), ours.resolve_secrets(&store).unwrap())]);
+        let mut external = BTreeMap::from([("gh".to_string(), theirs.clone())]);
+        let entries = diff_servers(&elves, &resolved, &external);
+        assert_eq!(entries[0].status, SyncStatus::InSync);
+        assert_eq!(entries[0].elves.as_ref().unwrap().env["GITHUB_TOKEN"], "secret://gh");
+
+        theirs.env.insert("GITHUB_TOKEN".to_string(), "ghp_rotated".to_string());
+        external.insert("gh".to_string(), theirs);
+        let entries = diff_servers(&elves, &resolved, &external);
+        assert_eq!(entries[0].status, SyncStatus::Drift);
+        assert_eq!(entries[0].changes[0].elves["GITHUB_TOKEN"], "secret://gh");
+    }
+
+    #[test]
+    fn claude_json_round_trips_and_preserves_other_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".claude.json");
+        fs::write(
+            &file,
+            r#"{"numStartups": 4, "mcpServers": {"old": {"command": "uvx", "args": ["old"]}},
+                "projects": {"/repo": {"allowedTools": []}}}"#,
+        )
+        .unwrap();
+
+        let mut servers = BTreeMap::new();
+        servers.insert("remote".to_string(), ServerSpec::remote("sse", "https://x/sse".into(), BTreeMap::new()));
+        write_external(SyncTarget::ClaudeUser, &file, None, &servers).unwrap();
+        let mut local = BTreeMap::new();
+        local.insert("fs".to_string(), stdio_spec("npx -y @fs", &[]));
+        write_external(SyncTarget::ClaudeLocal, &file, Some("/repo"), &local).unwrap();
+
+        let user = read_external(SyncTarget::ClaudeUser, &file, None).unwrap();
+        assert_eq!(user.len(), 2);
+        assert_eq!(user["remote"].transport, "sse");
+        assert_eq!(user["old"].args, vec!["old"]);
+
+        let project = read_external(SyncTarget::ClaudeLocal, &file, Some("/repo")).unwrap();
+        assert_eq!(project["fs"], stdio_spec("npx", &["-y", "@fs"]));
+
+        let raw: Value = serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(raw["numStartups"], 4);
+        assert!(raw["projects"]["/repo"]["allowedTools"].is_array());
+    }
+
+    #[test]
+    fn codex_toml_upserts_without_touching_other_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config.toml");
+        fs::write(
+            &file,
+            "# my codex config\nmodel = \"o3\"\n\n[mcp_servers.docs]\ncommand = \"uvx\"\nargs = [\"docs\"]\n",
+        )
+        .unwrap();
+
+        let mut servers = BTreeMap::new();
+        let mut github = stdio_spec("npx", &["-y", "@gh"]);
+        github.env.insert("GITHUB_TOKEN".to_string(), "ghp_test".to_string());
+        servers.insert("github".to_string(), github.clone());
+        servers.insert("legacy".to_string(), ServerSpec::remote("sse", "https://x/sse".into(), BTreeMap::new()));
+        let skipped = write_external(SyncTarget::Codex, &file, None, &servers).unwrap();
+        assert_eq!(skipped, vec!["legacy"]);
+
+        let written = fs::read_to_string(&file).unwrap();
+        assert!(written.starts_with("# my codex config\nmodel = \"o3\""));
+
+        let read = read_external(SyncTarget::Codex, &file, None).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read["github"], github);
+        assert_eq!(read["docs"].command, "uvx");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_keeps_original_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".claude.json");
+        fs::write(&file, "{}").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let servers = BTreeMap::from([("fs".to_string(), stdio_spec("npx", &["-y", "@fs"]))]);
+        write_external(SyncTarget::ClaudeUser, &file, None, &servers).unwrap();
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn missing_files_read_as_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("nope.json");
+        assert!(read_external(SyncTarget::McpJson, &file, Some("/p")).unwrap().is_empty());
+        assert!(read_external(SyncTarget::Codex, &dir.path().join("c.toml"), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn mcp_json_target_requires_project() {
+        assert!(SyncTarget::McpJson.file_path(Path::new("/home/u"), None).is_err());
+        assert_eq!(
+            SyncTarget::McpJson.file_path(Path::new("/home/u"), Some("/repo")).unwrap(),
+            PathBuf::from("/repo/.mcp.json")
+        );
+        assert!(SyncTarget::McpJson.is_project_scoped());
+        assert!(!SyncTarget::Codex.is_project_scoped());
+    }
+}
@@ -0,0 +1,766 @@
+// MCP transports — stdio child processes, Streamable HTTP, and legacy HTTP+SSE.
+//
+// Each transport exposes the same two operations to the client: a request/response
+// round trip matched by JSON-RPC id, and a fire-and-forget notification. Messages the
+// server pushes while we wait (notifications, server-initiated requests) are handled
+// here so the client never sees them.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, BufWriter, Lines};
+
+use crate::db::mcp::McpRow;
+use crate::secrets::store::SecretStore;
+
+/// Connect timeout for HTTP-based transports. There is no overall request timeout
+/// because SSE streams stay open — callers wrap whole sessions in `tokio::time::timeout`.
+const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub type BoxedReader = Box<dyn AsyncRead + Unpin + Send>;
+pub type BoxedWriter = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// The wire transport configured for an MCP server (`mcp_servers.transport`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportKind {
+    Stdio,
+    Http,
+    Sse,
+}
+
+impl TransportKind {
+    /// Parse a transport name as stored in the DB. Accepts "streamable-http" as an alias.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "stdio" => Ok(Self::Stdio),
+            "http" | "streamable-http" => Ok(Self::Http),
+            "sse" => Ok(Self::Sse),
+            other => Err(format!("Unknown MCP transport '{other}' (expected stdio, http, or sse)")),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stdio => "stdio",
+            Self::Http => "http",
+            Self::Sse => "sse",
+        }
+    }
+}
+
+/// Everything needed to open a connection to one configured server.
+#[derive(Debug, Clone)]
+pub enum ServerEndpoint {
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+    },
+    Http {
+        url: String,
+        headers: HashMap<String, String>,
+    },
+    Sse {
+        url: String,
+        headers: HashMap<String, String>,
+    },
+}
+
+impl ServerEndpoint {
+    /// Resolve a DB row into a connectable endpoint.
+    ///
+    /// Stdio commands are shell-split (e.g., "npx -y @pkg") with the DB args appended.
+    /// Remote URLs and header values get `${VAR}` / `${VAR:-default}` expansion from the
+    /// process environment — the same syntax `.mcp.json` files use. Env values, URLs,
+    /// and header values then have `secret://name` references resolved from `secrets`.
+    pub fn from_row(row: &McpRow, secrets: &SecretStore) -> Result<Self, String> {
+        match TransportKind::parse(&row.transport)? {
+            TransportKind::Stdio => {
+                let db_args: Vec<String> = serde_json::from_str(&row.args).unwrap_or_default();
+                let env: HashMap<String, String> = serde_json::from_str(&row.env).unwrap_or_default();
+                let env = env
+                    .into_iter()
+                    .map(|(name, value)| Ok((name, secrets.resolve_refs(&value)?)))
+                    .collect::<Result<HashMap<_, _>, String>>()?;
+                let (command, mut args) = split_command(&row.command);
+                args.extend(db_args);
+                Ok(Self::Stdio { command, args, env })
+            }
+            kind => {
+                let url = row
+                    .url
+                    .as_deref()
+                    .filter(|u| !u.trim().is_empty())
+                    .ok_or_else(|| format!("MCP server '{}' has no URL configured", row.name))?;
+                let url = secrets.resolve_refs(&expand_env_refs(url))?;
+                let headers: HashMap<String, String> = serde_json::from_str(&row.headers)
+                    .map_err(|e| format!("Invalid headers JSON for MCP server '{}': {e}", row.name))?;
+                let headers = headers
+                    .into_iter()
+                    .map(|(name, value)| Ok((name, secrets.resolve_refs(&expand_env_refs(&value))?)))
+                    .collect::<Result<HashMap<_, _>, String>>()?;
+                if kind == TransportKind::Http {
+                    Ok(Self::Http { url, headers })
+                } else {
+                    Ok(Self::Sse { url, headers })
+                }
+            }
+        }
+    }
+}
+
+/// Split a command string that may contain embedded arguments (e.g., "npx -y @pkg")
+/// into (binary, extra_args). If the command has no spaces, returns (command, []).
+pub fn split_command(command: &str) -> (String, Vec<String>) {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        [] => (command.to_string(), vec![]),
+        [binary] => (binary.to_string(), vec![]),
+        [binary, rest @ ..] => (binary.to_string(), rest.iter().map(|s| s.to_string()).collect()),
+    }
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` references from the process environment.
+/// Unset variables without a default expand to an empty string.
+pub fn expand_env_refs(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            // Unterminated reference — keep the remainder verbatim
+            output.push_str(&rest[start..]);
+            return output;
+        };
+        let reference = &after[..end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        match std::env::var(name) {
+            Ok(v) if !v.is_empty() => output.push_str(&v),
+            _ => output.push_str(default.unwrap_or("")),
+        }
+        rest = &after[end + 1..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// What a message read off the wire means for a caller waiting on `expected_id`.
+enum Incoming {
+    /// The response we are waiting for.
+    Response(Value),
+    /// A server-initiated request — send this reply, then keep waiting.
+    Reply(Value),
+    /// A notification, unrelated response, or junk — ignore it.
+    Ignore,
+}
+
+fn classify(message: Value, expected_id: u64) -> Incoming {
+    if let Some(method) = message.get("method").and_then(|m| m.as_str()) {
+        return match message.get("id") {
+            Some(id) => Incoming::Reply(server_request_reply(id, method)),
+            None => Incoming::Ignore,
+        };
+    }
+    if message.get("id").and_then(|v| v.as_u64()) == Some(expected_id) {
+        Incoming::Response(message)
+    } else {
+        Incoming::Ignore
+    }
+}
+
+/// Build the reply to a request the server sent us mid-conversation.
+/// `ping` succeeds; anything else is rejected with method-not-found so the server
+/// doesn't wait on us forever.
+pub fn server_request_reply(id: &Value, method: &str) -> Value {
+    if method == "ping" {
+        json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+    } else {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("Method not supported by client: {method}") },
+        })
+    }
+}
+
+/// An open connection using one of the supported transports.
+pub enum Transport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+    Sse(SseTransport),
+}
+
+impl Transport {
+    /// Open a connection to the endpoint. For SSE this performs the GET and waits
+    /// for the server's `endpoint` event; stdio spawns the server process.
+    pub async fn connect(endpoint: &ServerEndpoint) -> Result<Self, String> {
+        match endpoint {
+            ServerEndpoint::Stdio { command, args, env } => {
+                Ok(Self::Stdio(StdioTransport::spawn(command, args, env)?))
+            }
+            ServerEndpoint::Http { url, headers } => Ok(Self::Http(HttpTransport::new(url, headers)?)),
+            ServerEndpoint::Sse { url, headers } => Ok(Self::Sse(SseTransport::connect(url, headers).await?)),
+        }
+    }
+
+    /// Send a request and return the full JSON-RPC response with the matching id.
+    pub async fn round_trip(&mut self, message: &Value, id: u64) -> Result<Value, String> {
+        match self {
+            Self::Stdio(t) => t.round_trip(message, id).await,
+            Self::Http(t) => t.round_trip(message, id).await,
+            Self::Sse(t) => t.round_trip(message, id).await,
+        }
+    }
+
+    /// Send a notification. No response is expected.
+    pub async fn notify(&mut self, message: &Value) -> Result<(), String> {
+        match self {
+            Self::Stdio(t) => t.write_message(message).await.map_err(|e| e.to_string()),
+            Self::Http(t) => t.post(message).await.map(|_| ()),
+            Self::Sse(t) => t.post(message).await,
+        }
+    }
+
+    /// Record the negotiated protocol version (sent as a header on HTTP transports).
+    pub fn set_protocol_version(&mut self, version: Option<String>) {
+        if let Self::Http(t) = self {
+            t.protocol_version = version;
+        }
+    }
+
+    /// Tear down the connection: kill the child, or end the HTTP session.
+    pub async fn close(self) {
+        match self {
+            Self::Stdio(t) => t.close().await,
+            Self::Http(t) => t.close().await,
+            Self::Sse(_) => {}
+        }
+    }
+}
+
+// --- stdio ---
+
+/// Newline-delimited JSON-RPC over a child process's stdin/stdout.
+pub struct StdioTransport {
+    lines: Lines<BufReader<BoxedReader>>,
+    writer: BufWriter<BoxedWriter>,
+    child: Option<tokio::process::Child>,
+}
+
+impl StdioTransport {
+    /// Spawn an MCP server process and connect to its stdin/stdout.
+    /// Stderr is discarded — servers log freely there and we never read it.
+    pub fn spawn(
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self, String> {
+        let mut child = tokio::process::Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to spawn MCP server `{command}`: {e}"))?;
+
+        let stdin = child.stdin.take().ok_or("Failed to acquire MCP server stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to acquire MCP server stdout")?;
+
+        let mut transport = Self::from_streams(Box::new(stdout), Box::new(stdin));
+        transport.child = Some(child);
+        Ok(transport)
+    }
+
+    /// Build a transport over arbitrary byte streams (used by tests with in-memory pipes).
+    pub fn from_streams(reader: BoxedReader, writer: BoxedWriter) -> Self {
+        Self {
+            lines: BufReader::new(reader).lines(),
+            writer: BufWriter::new(writer),
+            child: None,
+        }
+    }
+
+    async fn write_message(&mut self, message: &Value) -> std::io::Result<()> {
+        self.writer.write_all(format!("{message}\n").as_bytes()).await?;
+        self.writer.flush().await
+    }
+
+    async fn round_trip(&mut self, message: &Value, id: u64) -> Result<Value, String> {
+        self.write_message(message)
+            .await
+            .map_err(|e| format!("Failed to write to MCP server: {e}"))?;
+
+        loop {
+            let line = self
+                .lines
+                .next_line()
+                .await
+                .map_err(|e| format!("Read error: {e}"))?
+                .ok_or("MCP server closed stdout unexpectedly")?;
+
+            // Skip non-JSON lines (e.g., debug output)
+            let Ok(json) = serde_json::from_str::<Value>(&line) else { continue };
+            match classify(json, id) {
+                Incoming::Response(response) => return Ok(response),
+                Incoming::Reply(reply) => self
+                    .write_message(&reply)
+                    .await
+                    .map_err(|e| format!("Failed to reply to server request: {e}"))?,
+                Incoming::Ignore => {}
+            }
+        }
+    }
+
+    async fn close(mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill().await;
+        }
+    }
+}
+
+// --- shared HTTP helpers ---
+
+fn build_http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .connect_timeout(HTTP_CONNECT_TIMEOUT)
+        .user_agent("ELVES-Desktop/1.0")
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+}
+
+fn apply_headers(
+    mut request: reqwest::RequestBuilder,
+    headers: &HashMap<String, String>,
+) -> reqwest::RequestBuilder {
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request
+}
+
+/// Incremental parser for `text/event-stream` bodies.
+///
+/// Feed raw chunks with `push` and drain complete events with `next_event`.
+/// Carriage returns are dropped so CRLF and LF streams parse the same way.
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+/// One dispatched server-sent event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    /// Event name, defaulting to "message" per the SSE spec.
+    pub event: String,
+    pub data: String,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend(chunk.iter().filter(|b| **b != b'\r'));
+    }
+
+    /// Pop the next complete event, skipping comment-only and empty blocks.
+    pub fn next_event(&mut self) -> Option<SseEvent> {
+        loop {
+            let end = self.buffer.windows(2).position(|w| w == b"\n\n")?;
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let block = String::from_utf8_lossy(&block[..end]);
+
+            let mut event = String::from("message");
+            let mut data_lines: Vec<&str> = Vec::new();
+            for line in block.lines() {
+                if line.starts_with(':') {
+                    continue;
+                }
+                let (field, value) = match line.split_once(':') {
+                    Some((f, v)) => (f, v.strip_prefix(' ').unwrap_or(v)),
+                    None => (line, ""),
+                };
+                match field {
+                    "event" => event = value.to_string(),
+                    "data" => data_lines.push(value),
+                    _ => {}
+                }
+            }
+
+            if !data_lines.is_empty() {
+                return Some(SseEvent { event, data: data_lines.join("\n") });
+            }
+        }
+    }
+}
+
+/// Read the next SSE event from a streaming response, pulling chunks as needed.
+/// Returns None when the stream ends.
+async fn read_sse_event(
+    response: &mut reqwest::Response,
+    parser: &mut SseParser,
+) -> Result<Option<SseEvent>, String> {
+    loop {
+        if let Some(event) = parser.next_event() {
+            return Ok(Some(event));
+        }
+        match response.chunk().await.map_err(|e| format!("SSE read error: {e}"))? {
+            Some(chunk) => parser.push(&chunk),
+            None => return Ok(None),
+        }
+    }
+}
+
+// --- Streamable HTTP ---
+
+/// Streamable HTTP transport (MCP 2025-03-26): every client message is a POST to one
+/// URL; the server answers with plain JSON or an SSE stream carrying the response.
+pub struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+    /// `Mcp-Session-Id` assigned by the server during initialize, echoed on every request.
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+}
+
+impl HttpTransport {
+    pub fn new(url: &str, headers: &HashMap<String, String>) -> Result<Self, String> {
+        Ok(Self {
+            client: build_http_client()?,
+            url: url.to_string(),
+            headers: headers.clone(),
+            session_id: None,
+            protocol_version: None,
+        })
+    }
+
+    /// POST one JSON-RPC message and return the raw response after checking status.
+    async fn post(&mut self, message: &Value) -> Result<reqwest::Response, String> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json, text/event-stream")
+            .header("Content-Type", "application/json");
+        request = apply_headers(request, &self.headers);
+        if let Some(ref session_id) = self.session_id {
+            request = request.header("Mcp-Session-Id", session_id);
+        }
+        if let Some(ref version) = self.protocol_version {
+            request = request.header("MCP-Protocol-Version", version);
+        }
+
+        let response = request
+            .body(message.to_string())
+            .send()
+            .await
+            .map_err(|e| format!("MCP HTTP request failed: {e}"))?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|v| v.to_str().ok())
+        {
+            self.session_id = Some(session_id.to_string());
+        }
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND && self.session_id.is_some() {
+            return Err("MCP session expired (server returned 404)".to_string());
+        }
+        if !status.is_success() {
+            return Err(format!("MCP server returned HTTP {status}"));
+        }
+        Ok(response)
+    }
+
+    async fn round_trip(&mut self, message: &Value, id: u64) -> Result<Value, String> {
+        let mut response = self.post(message).await?;
+
+        let is_stream = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+
+        if !is_stream {
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| format!("Invalid JSON from MCP server: {e}"))?;
+            // A POST may be answered with a single message or a batch
+            let messages = match body {
+                Value::Array(items) => items,
+                single => vec![single],
+            };
+            for message in messages {
+                if let Incoming::Response(response) = classify(message, id) {
+                    return Ok(response);
+                }
+            }
+            return Err("MCP server response did not include the request id".to_string());
+        }
+
+        let mut parser = SseParser::default();
+        while let Some(event) = read_sse_event(&mut response, &mut parser).await? {
+            let Ok(message) = serde_json::from_str::<Value>(&event.data) else { continue };
+            match classify(message, id) {
+                Incoming::Response(response) => return Ok(response),
+                Incoming::Reply(reply) => {
+                    self.post(&reply).await?;
+                }
+                Incoming::Ignore => {}
+            }
+        }
+        Err("MCP server closed the response stream before replying".to_string())
+    }
+
+    /// End the session with a DELETE, as the spec asks. Servers may refuse (405) — ignored.
+    async fn close(self) {
+        let Some(session_id) = self.session_id else { return };
+        let request = apply_headers(self.client.delete(&self.url), &self.headers)
+            .header("Mcp-Session-Id", session_id);
+        let _ = request.send().await;
+    }
+}
+
+// --- legacy HTTP+SSE ---
+
+/// Legacy HTTP+SSE transport (MCP 2024-11-05): a long-lived GET stream delivers every
+/// server message; the first `endpoint` event names the URL to POST client messages to.
+pub struct SseTransport {
+    client: reqwest::Client,
+    headers: HashMap<String, String>,
+    /// Absolute URL from the server's `endpoint` event.
+    post_url: String,
+    stream: reqwest::Response,
+    parser: SseParser,
+}
+
+impl SseTransport {
+    /// Open the event stream and wait for the `endpoint` event.
+    pub async fn connect(url: &str, headers: &HashMap<String, String>) -> Result<Self, String> {
+        let client = build_http_client()?;
+        let request = apply_headers(client.get(url), headers).header("Accept", "text/event-stream");
+        let mut stream = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to open MCP SSE stream: {e}"))?;
+        if !stream.status().is_success() {
+            return Err(format!("MCP SSE stream returned HTTP {}", stream.status()));
+        }
+
+        let mut parser = SseParser::default();
+        let post_url = loop {
+            let event = read_sse_event(&mut stream, &mut parser)
+                .await?
+                .ok_or("MCP SSE stream ended before sending an endpoint")?;
+            if event.event == "endpoint" {
+                let base = reqwest::Url::parse(url).map_err(|e| format!("Invalid MCP URL: {e}"))?;
+                let resolved = base
+                    .join(event.data.trim())
+                    .map_err(|e| format!("Invalid MCP endpoint '{}': {e}", event.data))?;
+                break resolved.to_string();
+            }
+        };
+
+        Ok(Self {
+            client,
+            headers: headers.clone(),
+            post_url,
+            stream,
+            parser,
+        })
+    }
+
+    async fn post(&self, message: &Value) -> Result<(), String> {
+        let request = apply_headers(self.client.post(&self.post_url), &self.headers)
+            .header("Content-Type", "application/json")
+            .body(message.to_string());
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("MCP SSE post failed: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!("MCP SSE endpoint returned HTTP {}", response.status()));
+        }
+        Ok(())
+    }
+
+    async fn round_trip(&mut self, message: &Value, id: u64) -> Result<Value, String> {
+        self.post(message).await?;
+        while let Some(event) = read_sse_event(&mut self.stream, &mut self.parser).await? {
+            if event.event != "message" {
+                continue;
+            }
+            let Ok(message) = serde_json::from_str::<Value>(&event.data) else { continue };
+            match classify(message, id) {
+                Incoming::Response(response) => return Ok(response),
+                Incoming::Reply(reply) => self.post(&reply).await?,
+                Incoming::Ignore => {}
+            }
+        }
+        Err("MCP SSE stream closed before replying".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(transport: &str, url: Option<&str>, headers: &str) -> McpRow {
+        McpRow {
+            id: "mcp-1".to_string(),
+            name: "remote".to_string(),
+            command: "npx -y @pkg".to_string(),
+            args: r#"["--flag"]"#.to_string(),
+            env: r#"{"A": "1"}"#.to_string(),
+            scope: "global".to_string(),
+            enabled: true,
+            last_health_check: None,
+            transport: transport.to_string(),
+            url: url.map(|u| u.to_string()),
+            headers: headers.to_string(),
+        }
+    }
+
+    #[test]
+    fn transport_kind_parses_known_values() {
+        assert_eq!(TransportKind::parse("stdio").unwrap(), TransportKind::Stdio);
+        assert_eq!(TransportKind::parse("http").unwrap(), TransportKind::Http);
+        assert_eq!(TransportKind::parse("streamable-http").unwrap(), TransportKind::Http);
+        assert_eq!(TransportKind::parse("sse").unwrap(), TransportKind::Sse);
+        assert!(TransportKind::parse("websocket").is_err());
+    }
+
+    #[test]
+    fn stdio_endpoint_splits_command_and_appends_args() {
+        let endpoint = ServerEndpoint::from_row(&row("stdio", None, "{}"), &SecretStore::for_tests(&[])).unwrap();
+        match endpoint {
+            ServerEndpoint::Stdio { command, args, env } => {
+                assert_eq!(command, "npx");
+                assert_eq!(args, vec!["-y", "@pkg", "--flag"]);
+                assert_eq!(env.get("A").map(|s| s.as_str()), Some("1"));
+            }
+            other => panic!("Expected stdio endpoint, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn remote_endpoint_requires_url() {
+        let err = ServerEndpoint::from_row(&row("http", None, "{}"), &SecretStore::for_tests(&[])).unwrap_err();
+        assert!(err.contains("no URL"));
+    }
+
+    #[test]
+    fn remote_endpoint_expands_header_references() {
+        std::env::set_var("ELVES_TEST_MCP_TOKEN", "tok-123");
+        let endpoint = ServerEndpoint::from_row(
+            &row(
+                "sse",
+                Some("https://example.com/sse"),
+                r#"{"Authorization": "Bearer ${ELVES_TEST_MCP_TOKEN}"}"#,
+            ),
+            &SecretStore::for_tests(&[]),
+        )
+        .unwrap();
+        match endpoint {
+            ServerEndpoint::Sse { url, headers } => {
+                assert_eq!(url, "https://example.com/sse");
+                assert_eq!(headers["Authorization"], "Bearer tok-123");
+            }
+            other => panic!("Expected SSE endpoint, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn endpoint_resolves_secret_references() {
+        let secrets = SecretStore::for_tests(&[("exa", "exa-key-55"), ("linear", "lin-tok-77")]);
+        let mut stdio = row("stdio", None, "{}");
+        stdio.env = r#"{"EXA_API_KEY": "secret://exa"}"#.to_string();
+        match ServerEndpoint::from_row(&stdio, &secrets).unwrap() {
+            ServerEndpoint::Stdio { env, .. } => assert_eq!(env["EXA_API_KEY"], "exa-key-55"),
+            other => panic!("Expected stdio endpoint, got {other:?}"),
+        }
+
+        let remote = row("http", Some("https://x/mcp"), r#"{"Authorization": "Bearer secret://linear"}"#);
+        match ServerEndpoint::from_row(&remote, &secrets).unwrap() {
+            ServerEndpoint::Http { headers, .. } => assert_eq!(headers["Authorization"], "Bearer lin-tok-77"),
+            other => panic!("Expected http endpoint, got {other:?}"),
+        }
+
+        stdio.env = r#"{"K": "secret://nope"}"#.to_string();
+        assert!(ServerEndpoint::from_row(&stdio, &secrets).unwrap_err().contains("nope"));
+    }
+
+    #[test]
+    fn expand_env_refs_handles_defaults_and_unterminated() {
+        std::env::remove_var("ELVES_TEST_MCP_UNSET");
+        assert_eq!(expand_env_refs("${ELVES_TEST_MCP_UNSET:-fallback}"), "fallback");
+        assert_eq!(expand_env_refs("a${ELVES_TEST_MCP_UNSET}b"), "ab");
+        assert_eq!(expand_env_refs("plain"), "plain");
+        assert_eq!(expand_env_refs("broken ${OOPS"), "broken ${OOPS");
+    }
+
+    #[test]
+    fn sse_parser_handles_split_chunks_and_multiline_data() {
+        let mut parser = SseParser::default();
+        parser.push(b": keepalive\n\nevent: endpoint\r\ndata: /mes");
+        assert!(parser.next_event().is_none(), "Incomplete event must not dispatch");
+        parser.push(b"sages?id=1\r\n\r\ndata: line one\ndata: line two\n\n");
+
+        let first = parser.next_event().unwrap();
+        assert_eq!(first.event, "endpoint");
+        assert_eq!(first.data, "/messages?id=1");
+
+        let second = parser.next_event().unwrap();
+        assert_eq!(second.event, "message");
+        assert_eq!(second.data, "line one\nline two");
+        assert!(parser.next_event().is_none());
+    }
+
+    #[test]
+    fn classify_matches_id_and_answers_server_requests() {
+        assert!(matches!(
+            classify(json!({"jsonrpc": "2.0", "id": 3, "result": {}}), 3),
+            Incoming::Response(_)
+        ));
+        assert!(matches!(
+            classify(json!({"jsonrpc": "2.0", "id": 4, "result": {}}), 3),
+            Incoming::Ignore
+        ));
+        assert!(matches!(
+            classify(json!({"jsonrpc": "2.0", "method": "notifications/progress"}), 3),
+            Incoming::Ignore
+        ));
+        match classify(json!({"jsonrpc": "2.0", "id": "s1", "method": "ping"}), 3) {
+            Incoming::Reply(reply) => assert_eq!(reply["id"], "s1"),
+            _ => panic!("ping should produce a reply"),
+        }
+    }
+
+    #[test]
+    fn server_request_reply_answers_ping() {
+        let reply = server_request_reply(&json!(7), "ping");
+        assert_eq!(reply["id"], 7);
+        assert!(reply.get("result").is_some());
+
+        let reply = server_request_reply(&json!(8), "roots/list");
+        assert_eq!(reply["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn split_command_handles_embedded_args() {
+        assert_eq!(split_command("npx"), ("npx".to_string(), vec![]));
+        assert_eq!(
+            split_command("npx -y @pkg"),
+            ("npx".to_string(), vec!["-y".to_string(), "@pkg".to_string()])
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Types for the MCP client — server capabilities, tools, resources, and prompts.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Capabilities a server advertised in its `initialize` response.
+///
+/// Only the presence of each capability object matters for discovery; the
+/// sub-flags (`listChanged`, `subscribe`) are kept so the UI can show them.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCapabilities {
+    pub tools: bool,
+    pub resources: bool,
+    pub resources_subscribe: bool,
+    pub prompts: bool,
+    pub logging: bool,
+    pub list_changed: Vec<String>,
+}
+
+impl ServerCapabilities {
+    /// Parse the `capabilities` object from an `initialize` result.
+    pub fn from_json(capabilities: &Value) -> Self {
+        let has = |key: &str| capabilities.get(key).is_some_and(|v| v.is_object());
+        let list_changed = ["tools", "resources", "prompts"]
+            .iter()
+            .filter(|key| {
+                capabilities
+                    .get(**key)
+                    .and_then(|c| c.get("listChanged"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+            })
+            .map(|key| key.to_string())
+            .collect();
+
+        Self {
+            tools: has("tools"),
+            resources: has("resources"),
+            resources_subscribe: capabilities
+                .get("resources")
+                .and_then(|r| r.get("subscribe"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            prompts: has("prompts"),
+            logging: has("logging"),
+            list_changed,
+        }
+    }
+}
+
+/// Server identity and negotiated protocol from the `initialize` handshake.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerInfo {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub protocol_version: Option<String>,
+    pub instructions: Option<String>,
+    pub capabilities: ServerCapabilities,
+}
+
+impl ServerInfo {
+    /// Parse the `result` object of an `initialize` response.
+    pub fn from_json(result: &Value) -> Self {
+        let str_at = |v: &Value, key: &str| v.get(key).and_then(|s| s.as_str()).map(|s| s.to_string());
+        let server_info = result.get("serverInfo").cloned().unwrap_or(Value::Null);
+        Self {
+            name: str_at(&server_info, "name"),
+            version: str_at(&server_info, "version"),
+            protocol_version: str_at(result, "protocolVersion"),
+            instructions: str_at(result, "instructions"),
+            capabilities: ServerCapabilities::from_json(
+                result.get("capabilities").unwrap_or(&Value::Null),
+            ),
+        }
+    }
+}
+
+/// A tool exposed by an MCP server, returned from the tools/list JSON-RPC call.
+#[derive(Debug, Clone, Serialize)]
+pub struct McpTool {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// A concrete resource returned from `resources/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// A parameterized resource returned from `resources/templates/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// One argument accepted by a prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// A prompt template returned from `prompts/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPrompt {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+/// One content item from `resources/read`. Exactly one of `text` or `blob` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResourceContent {
+    pub uri: String,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Base64-encoded binary content.
+    #[serde(default)]
+    pub blob: Option<String>,
+}
+
+/// One rendered message from `prompts/get`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPromptMessage {
+    pub role: String,
+    /// Text of the message: the text of a text block, or of an embedded text resource.
+    /// None for images, audio and binary resources.
+    pub text: Option<String>,
+    /// Raw content block as returned by the server.
+    pub content: Value,
+}
+
+/// A prompt rendered with arguments via `prompts/get`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPromptResult {
+    pub description: Option<String>,
+    pub messages: Vec<McpPromptMessage>,
+}
+
+/// Everything a server offers — tools, resources, templates, and prompts — in one snapshot.
+/// Lists for capabilities the server did not advertise are left empty.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerInventory {
+    pub server: ServerInfo,
+    pub tools: Vec<McpTool>,
+    pub resources: Vec<McpResource>,
+    pub resource_templates: Vec<McpResourceTemplate>,
+    pub prompts: Vec<McpPrompt>,
+}
+
+/// Outcome of a `tools/call` request.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolCallResult {
+    /// Raw content blocks (text, image, resource) as returned by the server.
+    pub content: Vec<Value>,
+    /// True when the tool itself reported a failure (`isError`).
+    pub is_error: bool,
+    /// All text blocks joined with newlines, for display and logging.
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+}
+
+impl McpToolCallResult {
+    /// Parse the `result` object of a `tools/call` response.
+    pub fn from_json(result: &Value) -> Self {
+        let content: Vec<Value> = result
+            .get("content")
+            .and_then(|c| c.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let text = content
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self {
+            content,
+            is_error: result.get("isError").and_then(|v| v.as_bool()).unwrap_or(false),
+            text,
+            structured_content: result.get("structuredContent").cloned(),
+        }
+    }
+}
@@ -1,12 +1,18 @@
 // Project configuration stored at <project_root>/.elves/config.json.
 //
 // Each ELVES project has an optional `.elves/config.json` that stores per-project
-// settings: default runtime, MCP server entries, and memory preferences.
+// settings: default runtime, MCP server entries, memory preferences, the MCP
+// gateway's tool policy, the retry policy for failed runs, session watchdog limits, how
+// many of the project's sessions the task queue runs at once, and spending caps.
 
+use crate::agents::budget::BudgetLimits;
+use crate::agents::failure::FailureReason;
+use crate::agents::watchdog::WatchdogLimits;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 /// Per-project configuration persisted at `.elves/config.json`.
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -18,6 +24,86 @@ pub struct ProjectConfig {
     pub mcp_servers: Vec<McpServerEntry>,
     /// Whether persistent memory is enabled for this project.
     pub memory_enabled: bool,
+    /// Routing agent MCP traffic through the ELVES gateway, and which tools it lets through.
+    #[serde(default)]
+    pub mcp_gateway: McpGatewayConfig,
+    /// Whether and how failed print-mode runs are retried.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Run-time, idle, and event limits for this project's sessions.
+    #[serde(default)]
+    pub watchdog: WatchdogLimits,
+    /// Most sessions of this project the task queue runs at once. None leaves only the
+    /// app-wide cap.
+    #[serde(default)]
+    pub max_concurrent_sessions: Option<u32>,
+    /// Spending caps per session and per day and month for this project.
+    #[serde(default)]
+    pub budget: BudgetLimits,
+}
+
+/// MCP gateway settings. When enabled, sessions reach ELVES-managed MCP servers only
+/// through the gateway, which records every tool call and applies the allow/deny lists.
+///
+/// List entries match a tool name (`read_file`) or a server-qualified name
+/// (`github/create_issue`); `*` matches any run of characters (`github/*`).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct McpGatewayConfig {
+    pub enabled: bool,
+    /// If non-empty, only matching tools may be called.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Matching tools are always refused, even if they are also allowed.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Automatic retry of failed print-mode runs. A retry resumes the Claude conversation
+/// when its session ID was captured, and otherwise restarts the task.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    pub enabled: bool,
+    /// Total attempts, including the first run.
+    pub max_attempts: u32,
+    /// Delay before the first retry; each later retry waits `backoff_multiplier` times longer.
+    pub initial_backoff_secs: u64,
+    pub backoff_multiplier: f64,
+    /// Upper bound on any single delay.
+    pub max_backoff_secs: u64,
+    /// Failure classes worth retrying.
+    pub retry_on: Vec<FailureReason>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attempts: 3,
+            initial_backoff_secs: 30,
+            backoff_multiplier: 2.0,
+            max_backoff_secs: 600,
+            retry_on: vec![FailureReason::RateLimit, FailureReason::Crash],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retrying after `attempt` (1-based) failed for `reason`,
+    /// or None when the run should not be retried.
+    pub fn delay_before_retry(&self, attempt: u32, reason: FailureReason) -> Option<Duration> {
+        if !self.enabled || attempt >= self.max_attempts || !self.retry_on.contains(&reason) {
+            return None;
+        }
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let secs = self.initial_backoff_secs as f64 * self.backoff_multiplier.max(1.0).powi(exponent);
+        // Very large configured caps overflow a float Duration; fall back to the exact cap
+        Some(
+            Duration::try_from_secs_f64(secs.min(self.max_backoff_secs as f64))
+                .unwrap_or(Duration::from_secs(self.max_backoff_secs)),
+        )
+    }
 }
 
 /// A single MCP server entry in the project configuration.
@@ -42,6 +128,11 @@ impl Default for ProjectConfig {
             default_runtime: "claude".to_string(),
             mcp_servers: Vec::new(),
             memory_enabled: true,
+            mcp_gateway: McpGatewayConfig::default(),
+            retry: RetryPolicy::default(),
+            watchdog: WatchdogLimits::default(),
+            max_concurrent_sessions: None,
+            budget: BudgetLimits::default(),
         }
     }
 }
@@ -81,3 +172,59 @@ pub fn write_project_config(project_path: &str, config: &ProjectConfig) -> Resul
     fs::write(&config_path, json)
         .map_err(|e| format!("Failed to write .elves/config.json: {e}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            enabled: true,
+            max_attempts: 5,
+            max_backoff_secs: 100,
+            ..RetryPolicy::default()
+        };
+        let delay = |attempt| policy.delay_before_retry(attempt, FailureReason::RateLimit);
+        assert_eq!(delay(1), Some(Duration::from_secs(30)));
+        assert_eq!(delay(2), Some(Duration::from_secs(60)));
+        assert_eq!(delay(3), Some(Duration::from_secs(100)));
+        assert_eq!(delay(5), None);
+    }
+
+    #[test]
+    fn huge_backoff_settings_do_not_panic() {
+        let policy = RetryPolicy {
+            enabled: true,
+            max_attempts: 40,
+            initial_backoff_secs: u64::MAX,
+            backoff_multiplier: f64::MAX,
+            max_backoff_secs: u64::MAX,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(
+            policy.delay_before_retry(30, FailureReason::RateLimit),
+            Some(Duration::from_secs(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn retry_respects_enabled_and_failure_classes() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay_before_retry(1, FailureReason::RateLimit), None);
+
+        let policy = RetryPolicy { enabled: true, ..RetryPolicy::default() };
+        assert!(policy.delay_before_retry(1, FailureReason::Crash).is_some());
+        assert_eq!(policy.delay_before_retry(1, FailureReason::Auth), None);
+    }
+
+    #[test]
+    fn config_without_retry_section_uses_defaults() {
+        let json = r#"{"defaultRuntime":"claude","mcpServers":[],"memoryEnabled":true}"#;
+        let config: ProjectConfig = serde_json::from_str(json).unwrap();
+        assert!(!config.retry.enabled);
+        assert_eq!(config.retry.max_attempts, 3);
+        assert_eq!(config.max_concurrent_sessions, None);
+        assert!(!config.budget.enabled);
+    }
+}
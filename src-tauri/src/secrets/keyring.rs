@@ -0,0 +1,109 @@
+// OS keyring access — holds the random key that encrypts the secrets file.
+//
+// macOS uses the login Keychain. Linux uses the freedesktop Secret Service through
+// libsecret's `secret-tool` CLI, which is present wherever GNOME Keyring or KWallet
+// is. When neither is reachable the store falls back to a user passphrase.
+
+/// Keychain service name for the secrets key.
+const SERVICE: &str = "ELVES";
+/// Keychain account name for the secrets key.
+const ACCOUNT: &str = "secrets-key";
+
+/// Somewhere to keep the store's 32-byte data key between launches.
+pub trait KeyProvider: Send {
+    /// Fetch the stored key, or None if there is none or the keyring is unavailable.
+    fn load(&self) -> Option<Vec<u8>>;
+    /// Save a newly generated key. Errors mean the keyring is unusable.
+    fn store(&self, key: &[u8]) -> Result<(), String>;
+    /// Whether this provider can hold a key at all on this machine.
+    fn is_available(&self) -> bool;
+}
+
+/// The platform keyring, if there is one.
+pub struct OsKeyring;
+
+#[cfg(target_os = "macos")]
+impl KeyProvider for OsKeyring {
+    fn load(&self) -> Option<Vec<u8>> {
+        security_framework::passwords::get_generic_password(SERVICE, ACCOUNT).ok()
+    }
+
+    fn store(&self, key: &[u8]) -> Result<(), String> {
+        security_framework::passwords::set_generic_password(SERVICE, ACCOUNT, key)
+            .map_err(|e| format!("Keychain write failed: {e}"))
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl KeyProvider for OsKeyring {
+    fn load(&self) -> Option<Vec<u8>> {
+        let output = std::process::Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "account", ACCOUNT])
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        // Stored as base64 text since secret-tool deals in strings
+        use base64::Engine;
+        let text = String::from_utf8(output.stdout).ok()?;
+        base64::engine::general_purpose::STANDARD.decode(text.trim()).ok()
+    }
+
+    fn store(&self, key: &[u8]) -> Result<(), String> {
+        use base64::Engine;
+        use std::io::Write;
+
+        // The secret goes over stdin so it never shows up in the process list
+        let mut child = std::process::Command::new("secret-tool")
+            .args(["store", "--label=ELVES secrets key", "service", SERVICE, "account", ACCOUNT])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run secret-tool: {e}"))?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(key);
+        child
+            .stdin
+            .take()
+            .ok_or("Failed to open secret-tool stdin")?
+            .write_all(encoded.as_bytes())
+            .map_err(|e| format!("Failed to write to secret-tool: {e}"))?;
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("secret-tool failed: {e}"))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "secret-tool store failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        // Whether a Secret Service is actually running only shows when `store` is tried
+        which::which("secret-tool").is_ok()
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+impl KeyProvider for OsKeyring {
+    fn load(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn store(&self, _key: &[u8]) -> Result<(), String> {
+        Err("No OS keyring available on this platform".to_string())
+    }
+
+    fn is_available(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,5 @@
+// Secrets — encrypted storage for API keys and tokens referenced as `secret://name`.
+
+pub mod keyring;
+pub mod redact;
+pub mod store;
@@ -0,0 +1,204 @@
+// Secret redaction — scrubs stored secret values out of text and JSON before it is
+// emitted to the frontend, persisted as an event, or exported.
+//
+// The store registers every value it decrypts here, so redaction works from any
+// module without threading the store through. Values stay registered after the
+// store is locked — they may still be sitting in agent output.
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use serde_json::Value;
+
+/// Values shorter than this are never redacted — replacing "1" or "true" everywhere
+/// would mangle output without protecting anything.
+const MIN_REDACT_LEN: usize = 6;
+
+/// Credentials the agent CLIs themselves need. These stay in an agent's environment
+/// even when the same value is also kept in the secrets store.
+pub const AGENT_CREDENTIAL_VARS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+    "OPENAI_API_KEY",
+    "CODEX_API_KEY",
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "AWS_BEARER_TOKEN_BEDROCK",
+];
+
+/// Secret name -> value for every secret the store has decrypted this run.
+static KNOWN_SECRETS: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+/// Register (or update) a secret value for redaction.
+pub fn remember(name: &str, value: &str) {
+    if let Ok(mut known) = KNOWN_SECRETS.write() {
+        known.insert(name.to_string(), value.to_string());
+    }
+}
+
+/// Stop redacting a deleted secret.
+pub fn forget(name: &str) {
+    if let Ok(mut known) = KNOWN_SECRETS.write() {
+        known.remove(name);
+    }
+}
+
+/// Replace every known secret value in `text` with `[redacted:<name>]`. Values are also
+/// matched as they appear escaped inside JSON strings, so serialized JSON can be redacted
+/// as text. Longer values are replaced first so a secret containing another isn't
+/// half-masked.
+pub fn redact(text: &str) -> String {
+    let Ok(known) = KNOWN_SECRETS.read() else {
+        return text.to_string();
+    };
+    let mut patterns: Vec<(String, &String)> = Vec::new();
+    for (name, value) in known.iter().filter(|(_, value)| value.len() >= MIN_REDACT_LEN) {
+        let escaped = json_escaped(value);
+        if escaped != *value {
+            patterns.push((escaped, name));
+        }
+        patterns.push((value.clone(), name));
+    }
+    patterns.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+
+    let mut output = text.to_string();
+    for (pattern, name) in patterns {
+        if output.contains(pattern.as_str()) {
+            output = output.replace(pattern.as_str(), &format!("[redacted:{name}]"));
+        }
+    }
+    output
+}
+
+/// A value as serde_json writes it inside a string literal, without the quotes.
+fn json_escaped(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Redact every string (keys included) inside a JSON value.
+pub fn redact_json(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(redact(s)),
+        Value::Array(items) => Value::Array(items.iter().map(redact_json).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (redact(k), redact_json(v)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Names of inherited environment variables that carry a stored secret and should
+/// be removed from an agent's environment. Variables in `keep` (the runtime's own
+/// credentials, like ANTHROPIC_API_KEY for Claude) are left alone.
+pub fn inherited_secret_vars(keep: &[&str]) -> Vec<String> {
+    let Ok(known) = KNOWN_SECRETS.read() else {
+        return Vec::new();
+    };
+    if known.is_empty() {
+        return Vec::new();
+    }
+    std::env::vars()
+        .filter(|(name, value)| {
+            !keep.contains(&name.as_str())
+                && value.len() >= MIN_REDACT_LEN
+                && known.values().any(|secret| secret == value)
+        })
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Remove inherited env vars that carry a stored secret from an agent command, so
+/// agents only see secrets that were deliberately passed to them.
+pub fn strip_inherited_secrets(cmd: &mut std::process::Command) {
+    for name in inherited_secret_vars(AGENT_CREDENTIAL_VARS) {
+        cmd.env_remove(name);
+    }
+}
+
+/// Forgets the named secrets when dropped, so tests that register secrets (directly or
+/// through the store) don't leave them redacting other tests' output.
+#[cfg(test)]
+pub(crate) struct ForgetOnDrop(pub &'static [&'static str]);
+
+#[cfg(test)]
+impl Drop for ForgetOnDrop {
+    fn drop(&mut self) {
+        for name in self.0 {
+            forget(name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redact_replaces_known_values_longest_first() {
+        let _forget = ForgetOnDrop(&["redact_test_short", "redact_test_long"]);
+        remember("redact_test_short", "abc123secret");
+        remember("redact_test_long", "abc123secret-and-more");
+        let text = "token=abc123secret-and-more other=abc123secret";
+        assert_eq!(
+            redact(text),
+            "token=[redacted:redact_test_long] other=[redacted:redact_test_short]"
+        );
+        forget("redact_test_short");
+        assert_eq!(redact("abc123secret"), "abc123secret");
+    }
+
+    #[test]
+    fn redact_ignores_short_values() {
+        let _forget = ForgetOnDrop(&["redact_test_tiny"]);
+        remember("redact_test_tiny", "yes");
+        assert_eq!(redact("yes yes"), "yes yes");
+    }
+
+    #[test]
+    fn redact_matches_values_escaped_in_json() {
+        let _forget = ForgetOnDrop(&["redact_test_escaped"]);
+        remember("redact_test_escaped", "pa\"ss\\word\n42");
+        let json = json!({ "output": "login pa\"ss\\word\n42 ok" }).to_string();
+        let redacted = redact(&json);
+        assert_eq!(redacted, r#"{"output":"login [redacted:redact_test_escaped] ok"}"#);
+        assert_eq!(redact("pa\"ss\\word\n42"), "[redacted:redact_test_escaped]");
+    }
+
+    #[test]
+    fn redact_json_walks_nested_values() {
+        let _forget = ForgetOnDrop(&["redact_test_json"]);
+        remember("redact_test_json", "sk-json-98765");
+        let value = json!({
+            "input": { "headers": ["Authorization: Bearer sk-json-98765"] },
+            "count": 3,
+        });
+        let redacted = redact_json(&value);
+        assert_eq!(redacted["input"]["headers"][0], "Authorization: Bearer [redacted:redact_test_json]");
+        assert_eq!(redacted["count"], 3);
+    }
+
+    #[test]
+    fn inherited_secret_vars_respects_keep_list() {
+        let _forget = ForgetOnDrop(&["redact_test_env"]);
+        remember("redact_test_env", "env-secret-value-4242");
+        std::env::set_var("ELVES_REDACT_TEST_A", "env-secret-value-4242");
+        std::env::set_var("ELVES_REDACT_TEST_B", "env-secret-value-4242");
+
+        let vars = inherited_secret_vars(&["ELVES_REDACT_TEST_B"]);
+        assert!(vars.contains(&"ELVES_REDACT_TEST_A".to_string()));
+        assert!(!vars.contains(&"ELVES_REDACT_TEST_B".to_string()));
+
+        std::env::remove_var("ELVES_REDACT_TEST_A");
+        std::env::remove_var("ELVES_REDACT_TEST_B");
+    }
+}
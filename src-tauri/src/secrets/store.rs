@@ -0,0 +1,498 @@
+// Encrypted secrets file — a name -> value map sealed with AES-256-GCM at
+// ~/.elves/secrets.enc.
+//
+// The data key is either a random key kept in the OS keyring, or derived from a
+// user passphrase with PBKDF2 when no keyring is available. The file header records
+// which, so a store created with a passphrase keeps asking for it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use super::keyring::KeyProvider;
+use super::redact;
+
+/// Prefix that marks a config value as a reference into the store.
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Name fragments that mark an env var or header as holding a credential.
+const CREDENTIAL_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "AUTHORIZATION", "CREDENTIAL"];
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const FILE_VERSION: u32 = 1;
+
+/// PBKDF2-HMAC-SHA256 rounds for passphrase-derived keys (OWASP 2023 guidance).
+/// Tests use far fewer so they stay fast in debug builds.
+const PBKDF2_ITERATIONS: u32 = if cfg!(test) { 1_000 } else { 600_000 };
+
+/// Where the data key comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    Keyring,
+    Passphrase,
+}
+
+/// On-disk layout of the secrets file. Only `ciphertext` is secret.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope {
+    version: u32,
+    key_source: KeySource,
+    /// Base64 PBKDF2 salt (passphrase stores only).
+    #[serde(default)]
+    salt: Option<String>,
+    #[serde(default)]
+    iterations: Option<u32>,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The secrets store. Values are only held in memory while unlocked.
+pub struct SecretStore {
+    path: PathBuf,
+    key_source: KeySource,
+    key: Option<[u8; KEY_LEN]>,
+    /// PBKDF2 salt and rounds for passphrase stores, reused on every save.
+    salt: Vec<u8>,
+    iterations: u32,
+    secrets: BTreeMap<String, String>,
+}
+
+impl SecretStore {
+    /// Default location: ~/.elves/secrets.enc
+    pub fn default_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Could not determine home directory");
+        home.join(".elves").join("secrets.enc")
+    }
+
+    /// Open the store at `path`, unlocking it through the keyring when possible.
+    ///
+    /// A keyring-backed store with its key present opens unlocked. With no file yet
+    /// and a working keyring, a new key is generated and saved there. Otherwise the
+    /// store starts locked in passphrase mode and waits for `unlock_with_passphrase`.
+    pub fn open(path: PathBuf, keyring: &dyn KeyProvider) -> Self {
+        let mut store = Self {
+            path,
+            key_source: KeySource::Passphrase,
+            key: None,
+            salt: Vec::new(),
+            iterations: PBKDF2_ITERATIONS,
+            secrets: BTreeMap::new(),
+        };
+
+        match store.read_envelope() {
+            Ok(Some(envelope)) => {
+                store.key_source = envelope.key_source;
+                if envelope.key_source == KeySource::Keyring {
+                    match keyring.load().and_then(|k| <[u8; KEY_LEN]>::try_from(k).ok()) {
+                        Some(key) => {
+                            if let Err(e) = store.decrypt_with(key, &envelope) {
+                                log::warn!("Secrets file could not be decrypted with the keyring key: {e}");
+                            }
+                        }
+                        None => log::warn!("Secrets key missing from the OS keyring — store stays locked"),
+                    }
+                }
+            }
+            Ok(None) if keyring.is_available() => {
+                let mut key = [0u8; KEY_LEN];
+                if SystemRandom::new().fill(&mut key).is_ok() && keyring.store(&key).is_ok() {
+                    store.key_source = KeySource::Keyring;
+                    store.key = Some(key);
+                } else {
+                    log::info!("OS keyring unavailable — secrets will use a passphrase");
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to read secrets file: {e}"),
+        }
+
+        store
+    }
+
+    pub fn key_source(&self) -> KeySource {
+        self.key_source
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Whether the encrypted file exists on disk yet.
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Unlock a passphrase store. If no file exists yet, this sets the passphrase.
+    pub fn unlock_with_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+        if self.key_source != KeySource::Passphrase {
+            return Err("This secrets store is unlocked by the OS keyring".to_string());
+        }
+        if passphrase.is_empty() {
+            return Err("Passphrase must not be empty".to_string());
+        }
+
+        match self.read_envelope()? {
+            Some(envelope) => {
+                let salt = BASE64
+                    .decode(envelope.salt.as_deref().unwrap_or_default())
+                    .map_err(|e| format!("Corrupt secrets file salt: {e}"))?;
+                let iterations = envelope.iterations.unwrap_or(PBKDF2_ITERATIONS);
+                let key = derive_key(passphrase, &salt, iterations)?;
+                self.decrypt_with(key, &envelope)
+                    .map_err(|_| "Wrong passphrase".to_string())?;
+                self.salt = salt;
+                self.iterations = iterations;
+                Ok(())
+            }
+            None => {
+                let mut salt = vec![0u8; SALT_LEN];
+                SystemRandom::new()
+                    .fill(&mut salt)
+                    .map_err(|_| "Failed to generate salt".to_string())?;
+                self.key = Some(derive_key(passphrase, &salt, self.iterations)?);
+                self.salt = salt;
+                self.save()
+            }
+        }
+    }
+
+    /// Forget the key and decrypted values. Keyring stores re-unlock on next launch.
+    pub fn lock(&mut self) {
+        self.key = None;
+        self.secrets.clear();
+    }
+
+    /// Names of all stored secrets, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.secrets.keys().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.secrets.get(name).map(|s| s.as_str())
+    }
+
+    /// Add or replace a secret and write the file.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.require_unlocked()?;
+        validate_name(name)?;
+        self.secrets.insert(name.to_string(), value.to_string());
+        redact::remember(name, value);
+        self.save()
+    }
+
+    /// Delete a secret. Returns true if it existed.
+    pub fn remove(&mut self, name: &str) -> Result<bool, String> {
+        self.require_unlocked()?;
+        let existed = self.secrets.remove(name).is_some();
+        if existed {
+            redact::forget(name);
+            self.save()?;
+        }
+        Ok(existed)
+    }
+
+    /// Replace every `secret://name` reference in `value` with the stored secret.
+    ///
+    /// References may be the whole value or embedded (e.g., "Bearer secret://token").
+    /// Values without references pass through untouched, even while locked.
+    pub fn resolve_refs(&self, value: &str) -> Result<String, String> {
+        if !value.contains(SECRET_SCHEME) {
+            return Ok(value.to_string());
+        }
+
+        let mut output = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find(SECRET_SCHEME) {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + SECRET_SCHEME.len()..];
+            let end = after.find(|c: char| !is_name_char(c)).unwrap_or(after.len());
+            let name = &after[..end];
+            if !self.is_unlocked() {
+                return Err(format!("Secrets store is locked — unlock it to use secret://{name}"));
+            }
+            let secret = self
+                .get(name)
+                .ok_or_else(|| format!("Secret '{name}' is not set"))?;
+            output.push_str(secret);
+            rest = &after[end..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    /// An unlocked store with the given values that never touches disk unless written to.
+    #[cfg(test)]
+    pub fn for_tests(entries: &[(&str, &str)]) -> Self {
+        let mut key = [0u8; KEY_LEN];
+        SystemRandom::new().fill(&mut key).unwrap();
+        Self {
+            path: std::env::temp_dir().join(format!("elves-secrets-{}.enc", uuid::Uuid::new_v4())),
+            key_source: KeySource::Keyring,
+            key: Some(key),
+            salt: Vec::new(),
+            iterations: PBKDF2_ITERATIONS,
+            secrets: entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    fn require_unlocked(&self) -> Result<(), String> {
+        if self.is_unlocked() {
+            Ok(())
+        } else {
+            Err("Secrets store is locked".to_string())
+        }
+    }
+
+    fn read_envelope(&self) -> Result<Option<Envelope>, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map(Some)
+                .map_err(|e| format!("Corrupt secrets file: {e}")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {e}", self.path.display())),
+        }
+    }
+
+    /// Decrypt the envelope with `key`; on success adopt the key and values.
+    fn decrypt_with(&mut self, key: [u8; KEY_LEN], envelope: &Envelope) -> Result<(), String> {
+        if envelope.version != FILE_VERSION {
+            return Err(format!("Unsupported secrets file version {}", envelope.version));
+        }
+        let nonce = BASE64.decode(&envelope.nonce).map_err(|e| format!("Corrupt nonce: {e}"))?;
+        let mut data = BASE64
+            .decode(&envelope.ciphertext)
+            .map_err(|e| format!("Corrupt ciphertext: {e}"))?;
+        let nonce = Nonce::try_assume_unique_for_key(&nonce).map_err(|_| "Corrupt nonce".to_string())?;
+        let plaintext = aead_key(&key)?
+            .open_in_place(nonce, Aad::empty(), &mut data)
+            .map_err(|_| "Decryption failed".to_string())?;
+        let secrets: BTreeMap<String, String> =
+            serde_json::from_slice(plaintext).map_err(|e| format!("Corrupt secrets payload: {e}"))?;
+
+        for (name, value) in &secrets {
+            redact::remember(name, value);
+        }
+        self.key = Some(key);
+        self.secrets = secrets;
+        Ok(())
+    }
+
+    /// Encrypt the current values with a fresh nonce and atomically replace the file.
+    fn save(&self) -> Result<(), String> {
+        let key = self.key.ok_or("Secrets store is locked")?;
+        let rng = SystemRandom::new();
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut nonce).map_err(|_| "Failed to generate nonce".to_string())?;
+
+        let mut data = serde_json::to_vec(&self.secrets).map_err(|e| format!("Serialize error: {e}"))?;
+        aead_key(&key)?
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| "Encryption failed".to_string())?;
+
+        let passphrase = self.key_source == KeySource::Passphrase;
+        let envelope = Envelope {
+            version: FILE_VERSION,
+            key_source: self.key_source,
+            salt: passphrase.then(|| BASE64.encode(&self.salt)),
+            iterations: passphrase.then_some(self.iterations),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(&data),
+        };
+        let json = serde_json::to_string_pretty(&envelope).map_err(|e| format!("Serialize error: {e}"))?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let tmp = self.path.with_extension("enc.tmp");
+        write_private(&tmp, json.as_bytes())?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("Failed to replace secrets file: {e}"))
+    }
+}
+
+/// Whether an env var or header name looks like it carries a credential
+/// (e.g., `GITHUB_TOKEN`, `X-Api-Key`, `Authorization`) rather than plain config.
+pub fn is_credential_key(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    CREDENTIAL_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+/// Secret names are used inside `secret://` references, so keep them URL-safe.
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return Err(format!(
+            "Invalid secret name '{name}' — use letters, digits, '_', '-', or '.'"
+        ));
+    }
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<[u8; KEY_LEN], String> {
+    let rounds = NonZeroU32::new(iterations).ok_or("Invalid PBKDF2 iteration count")?;
+    let mut key = [0u8; KEY_LEN];
+    ring::pbkdf2::derive(ring::pbkdf2::PBKDF2_HMAC_SHA256, rounds, salt, passphrase.as_bytes(), &mut key);
+    Ok(key)
+}
+
+fn aead_key(key: &[u8; KEY_LEN]) -> Result<LessSafeKey, String> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Invalid secrets key".to_string())
+}
+
+/// Write a file readable only by the current user.
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        file.write_all(contents)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+    #[cfg(not(unix))]
+    {
+        fs::write(path, contents).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::redact::ForgetOnDrop;
+    use std::sync::Mutex;
+
+    /// In-memory keyring for tests. `available: false` simulates a machine without one.
+    struct FakeKeyring {
+        key: Mutex<Option<Vec<u8>>>,
+        available: bool,
+    }
+
+    impl FakeKeyring {
+        fn new(available: bool) -> Self {
+            Self { key: Mutex::new(None), available }
+        }
+    }
+
+    impl KeyProvider for FakeKeyring {
+        fn load(&self) -> Option<Vec<u8>> {
+            self.key.lock().unwrap().clone()
+        }
+        fn store(&self, key: &[u8]) -> Result<(), String> {
+            if !self.available {
+                return Err("unavailable".to_string());
+            }
+            *self.key.lock().unwrap() = Some(key.to_vec());
+            Ok(())
+        }
+        fn is_available(&self) -> bool {
+            self.available
+        }
+    }
+
+    #[test]
+    fn keyring_store_round_trips_and_reopens_unlocked() {
+        let _forget = ForgetOnDrop(&["EXA_API_KEY"]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.enc");
+        let keyring = FakeKeyring::new(true);
+
+        let mut store = SecretStore::open(path.clone(), &keyring);
+        assert_eq!(store.key_source(), KeySource::Keyring);
+        assert!(store.is_unlocked());
+        store.set("EXA_API_KEY", "exa-live-123456").unwrap();
+
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("exa-live-123456"), "Values must be encrypted at rest");
+
+        let reopened = SecretStore::open(path, &keyring);
+        assert!(reopened.is_unlocked());
+        assert_eq!(reopened.get("EXA_API_KEY"), Some("exa-live-123456"));
+    }
+
+    #[test]
+    fn passphrase_store_requires_correct_passphrase() {
+        let _forget = ForgetOnDrop(&["A"]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.enc");
+        let no_keyring = FakeKeyring::new(false);
+
+        let mut store = SecretStore::open(path.clone(), &no_keyring);
+        assert_eq!(store.key_source(), KeySource::Passphrase);
+        assert!(!store.is_unlocked());
+        assert!(store.set("A", "value-aaaaaa").is_err(), "Locked store must refuse writes");
+
+        store.unlock_with_passphrase("correct horse").unwrap();
+        store.set("A", "value-aaaaaa").unwrap();
+
+        let mut reopened = SecretStore::open(path, &no_keyring);
+        assert!(!reopened.is_unlocked());
+        assert_eq!(reopened.unlock_with_passphrase("wrong").unwrap_err(), "Wrong passphrase");
+        reopened.unlock_with_passphrase("correct horse").unwrap();
+        assert_eq!(reopened.get("A"), Some("value-aaaaaa"));
+
+        reopened.lock();
+        assert!(reopened.names().is_empty());
+    }
+
+    #[test]
+    fn resolve_refs_substitutes_whole_and_embedded_references() {
+        let _forget = ForgetOnDrop(&["gh.token"]);
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SecretStore::open(dir.path().join("s.enc"), &FakeKeyring::new(true));
+        store.set("gh.token", "ghp_resolve_test").unwrap();
+
+        assert_eq!(store.resolve_refs("secret://gh.token").unwrap(), "ghp_resolve_test");
+        assert_eq!(
+            store.resolve_refs("Bearer secret://gh.token, ok").unwrap(),
+            "Bearer ghp_resolve_test, ok"
+        );
+        assert_eq!(store.resolve_refs("plain").unwrap(), "plain");
+        assert!(store.resolve_refs("secret://missing").unwrap_err().contains("missing"));
+
+        store.lock();
+        assert!(store.resolve_refs("secret://gh.token").unwrap_err().contains("locked"));
+        assert_eq!(store.resolve_refs("plain").unwrap(), "plain");
+    }
+
+    #[test]
+    fn credential_keys_are_recognized() {
+        for key in ["GITHUB_TOKEN", "EXA_API_KEY", "Authorization", "x-api-key", "DB_PASSWORD"] {
+            assert!(is_credential_key(key), "{key} should be a credential");
+        }
+        for key in ["LOG_LEVEL", "NODE_ENV", "PORT", "Accept"] {
+            assert!(!is_credential_key(key), "{key} should not be a credential");
+        }
+    }
+
+    #[test]
+    fn remove_and_name_validation() {
+        let _forget = ForgetOnDrop(&["ok_name"]);
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SecretStore::open(dir.path().join("s.enc"), &FakeKeyring::new(true));
+        assert!(store.set("bad name", "x").is_err());
+        store.set("ok_name", "remove-me-123").unwrap();
+        assert_eq!(store.names(), vec!["ok_name"]);
+        assert!(store.remove("ok_name").unwrap());
+        assert!(!store.remove("ok_name").unwrap());
+    }
+}
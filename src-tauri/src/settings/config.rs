@@ -0,0 +1,196 @@
+// App settings stored at ~/.elves/settings.json.
+//
+// Unlike the per-project `.elves/config.json`, these settings apply to the whole app,
+// e.g. how long full session event logs are kept, how agents are stopped, how many
+// queued tasks may run at once, spending caps across all projects, the prices
+// sessions are costed with, whether agent terminals are recorded, and how much terminal
+// output is kept for reattaching views.
+
+use crate::agents::budget::BudgetLimits;
+use crate::agents::pricing::PricingTable;
+use crate::agents::scrollback::DEFAULT_SCROLLBACK_BYTES;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// App-wide settings persisted at `~/.elves/settings.json`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    /// When old session events are compacted.
+    #[serde(default)]
+    pub retention: RetentionSettings,
+    /// How running agents are stopped.
+    #[serde(default)]
+    pub stop: StopSettings,
+    /// How many sessions the task queue lets run at once.
+    #[serde(default)]
+    pub queue: QueueSettings,
+    /// Spending caps across all projects. Its session cap applies to sessions of
+    /// projects that set none.
+    #[serde(default)]
+    pub budget: BudgetLimits,
+    /// Per-model prices overriding the built-in ones, used to cost every session.
+    #[serde(default)]
+    pub pricing: PricingTable,
+    /// Whether agent terminals are recorded.
+    #[serde(default)]
+    pub recording: RecordingSettings,
+    /// How much output each terminal keeps.
+    #[serde(default)]
+    pub scrollback: ScrollbackSettings,
+}
+
+/// Event retention. Once enabled, events older than `full_event_days` in finished,
+/// unpinned sessions are compacted to tool-call and outcome metadata at startup.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionSettings {
+    pub enabled: bool,
+    /// How many days sessions keep their full event log.
+    pub full_event_days: u32,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            full_event_days: 30,
+        }
+    }
+}
+
+/// Graceful stop. Stopping an agent sends SIGINT to its process group, then SIGTERM
+/// after `grace_period_secs`, then SIGKILL after another `terminate_timeout_secs`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StopSettings {
+    /// How long an interrupted agent gets to write its final result and exit.
+    pub grace_period_secs: u64,
+    /// How long a terminated agent gets before it is killed.
+    pub terminate_timeout_secs: u64,
+}
+
+impl Default for StopSettings {
+    fn default() -> Self {
+        Self {
+            grace_period_secs: 10,
+            terminate_timeout_secs: 5,
+        }
+    }
+}
+
+/// Task queue concurrency. Queued tasks only start while fewer than
+/// `max_concurrent_sessions` sessions are active across all projects.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueueSettings {
+    pub max_concurrent_sessions: u32,
+}
+
+impl Default for QueueSettings {
+    fn default() -> Self {
+        Self { max_concurrent_sessions: 3 }
+    }
+}
+
+/// Terminal recording. Once enabled, the PTY output of every agent run is recorded as an
+/// asciicast file under `~/.elves/recordings/` and embedded in the session's replay.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingSettings {
+    pub enabled: bool,
+}
+
+/// Terminal scrollback. Each PTY keeps its last `max_bytes` of output, so reloaded or
+/// reopened terminal views get their history back. 0 keeps none.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScrollbackSettings {
+    pub max_bytes: usize,
+}
+
+impl Default for ScrollbackSettings {
+    fn default() -> Self {
+        Self { max_bytes: DEFAULT_SCROLLBACK_BYTES }
+    }
+}
+
+/// Get the default settings path: ~/.elves/settings.json
+pub fn default_settings_path() -> PathBuf {
+    let home = dirs::home_dir().expect("Could not determine home directory");
+    home.join(".elves").join("settings.json")
+}
+
+/// Read the app settings from `path`.
+///
+/// Returns the default settings if the file does not exist. Returns an error
+/// only if the file exists but cannot be read or parsed.
+pub fn read_app_settings(path: &Path) -> Result<AppSettings, String> {
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read settings.json: {e}"))?;
+
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse settings.json: {e}"))
+}
+
+/// Write the app settings to `path`, creating its directory if needed.
+pub fn write_app_settings(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {e}"))?;
+    }
+
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {e}"))?;
+
+    fs::write(path, json).map_err(|e| format!("Failed to write settings.json: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn missing_file_returns_defaults() {
+        let dir = tempdir().unwrap();
+        let settings = read_app_settings(&dir.path().join("settings.json")).unwrap();
+        assert!(!settings.retention.enabled);
+        assert_eq!(settings.retention.full_event_days, 30);
+        assert_eq!(settings.stop.grace_period_secs, 10);
+        assert_eq!(settings.stop.terminate_timeout_secs, 5);
+        assert_eq!(settings.queue.max_concurrent_sessions, 3);
+        assert!(!settings.budget.enabled);
+        assert_eq!(settings.budget.warn_at, vec![0.8]);
+        assert!(settings.pricing.models.is_empty());
+    }
+
+    #[test]
+    fn settings_round_trip_and_fill_missing_fields() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("settings.json");
+
+        let mut settings = AppSettings::default();
+        settings.retention.enabled = true;
+        settings.retention.full_event_days = 7;
+        write_app_settings(&path, &settings).unwrap();
+        let read = read_app_settings(&path).unwrap();
+        assert!(read.retention.enabled);
+        assert_eq!(read.retention.full_event_days, 7);
+
+        fs::write(&path, r#"{"retention":{"enabled":true}}"#).unwrap();
+        let read = read_app_settings(&path).unwrap();
+        assert_eq!(read.retention.full_event_days, 30);
+        assert_eq!(read.stop.grace_period_secs, 10);
+        assert_eq!(read.queue.max_concurrent_sessions, 3);
+
+        fs::write(&path, r#"{"pricing":{"models":{"my-model":{"input":2.0,"output":4.0}}}}"#).unwrap();
+        let read = read_app_settings(&path).unwrap();
+        assert_eq!(read.pricing.price("my-model-v2").output, 4.0);
+        assert_eq!(read.pricing.fallback, PricingTable::default().fallback);
+    }
+}
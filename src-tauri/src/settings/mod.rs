@@ -0,0 +1,3 @@
+// App-wide settings — preferences that apply across projects, stored at ~/.elves/settings.json.
+
+pub mod config;
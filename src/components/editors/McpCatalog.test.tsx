@@ -155,6 +155,9 @@ describe("McpCatalog", () => {
           scope: "global",
           enabled: true,
           lastHealthCheck: null,
+          transport: "stdio",
+          url: null,
+          headers: "{}",
         },
       ],
     });
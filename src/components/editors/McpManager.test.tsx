@@ -36,6 +36,9 @@ function createTestServer(overrides?: Partial<McpServer>): McpServer {
     scope: "global",
     enabled: true,
     lastHealthCheck: null,
+    transport: "stdio",
+    url: null,
+    headers: "{}",
     ...overrides,
   };
 }
@@ -141,6 +141,11 @@ export function InsightsAnalysis({ data }: InsightsAnalysisProps): React.JSX.Ele
                 <div className="flex items-center justify-between">
                   <div className="flex items-center gap-2">
                     <span className="font-mono text-xs font-bold">{session.project}</span>
+                    {session.runtime === "codex" && (
+                      <span className="border-[1px] border-border/40 px-1 font-display text-[9px] font-bold uppercase tracking-wider text-text-muted">
+                        Codex
+                      </span>
+                    )}
                     <span className="font-mono text-[10px] text-text-muted">
                       {new Date(session.startTime).toLocaleDateString()}
                     </span>
@@ -0,0 +1,69 @@
+/* Tests for the InsightsElves component. */
+
+import { render, screen, fireEvent } from "@testing-library/react";
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { InsightsElves } from "./InsightsElves";
+import { useProjectStore } from "@/stores/project";
+import type { DbInsights, InsightsFilter } from "@/types/insights";
+
+const mockUseDbInsights = vi.fn();
+
+vi.mock("@/hooks/useDbInsights", () => ({
+  useDbInsights: (filter: InsightsFilter) => mockUseDbInsights(filter),
+}));
+
+function dbInsights(overrides: Partial<DbInsights> = {}): DbInsights {
+  return {
+    totalSessions: 4,
+    totalTokens: 1200,
+    totalCostUsd: 1.5,
+    sessionsByProject: [{ name: "Alpha", count: 4 }],
+    sessionsByRuntime: [{ name: "codex", count: 4 }],
+    sessionsByStatus: [{ name: "completed", count: 3 }, { name: "failed", count: 1 }],
+    outcomes: { completed: 3, failed: 1, stopped: 0, successRate: 0.75 },
+    failureReasons: [{ name: "crash", count: 1 }],
+    dailyUsage: [{ date: "2026-10-01", sessions: 4, tokens: 1200, costUsd: 1.5 }],
+    templateDurations: [{ templateId: "t1", name: "Code Review", sessions: 2, avgDurationSecs: 130 }],
+    topRoles: [{ name: "Reviewer", count: 2 }],
+    memoryGrowth: [{ date: "2026-10-01", added: 2, total: 7 }],
+    workspaces: { workspaces: 4, shipped: 1, shipRate: 0.25 },
+    ...overrides,
+  };
+}
+
+beforeEach(() => {
+  vi.clearAllMocks();
+  useProjectStore.setState({ projects: [] });
+  mockUseDbInsights.mockReturnValue({ data: dbInsights(), isLoading: false, error: null, reload: vi.fn() });
+});
+
+describe("InsightsElves", () => {
+  it("shows stats, template durations and memory growth", () => {
+    render(<InsightsElves />);
+
+    expect(screen.getAllByTestId("stat-card")).toHaveLength(6);
+    expect(screen.getByText("75%")).toBeInTheDocument();
+    expect(screen.getByText("25% (1/4)")).toBeInTheDocument();
+    expect(screen.getByText("Code Review")).toBeInTheDocument();
+    expect(screen.getByText("2m 10s")).toBeInTheDocument();
+    expect(screen.getByText("7 total")).toBeInTheDocument();
+  });
+
+  it("filters by date range and project", () => {
+    useProjectStore.setState({ projects: [{ id: "p1", name: "Alpha", path: "/tmp/alpha", defaultRuntime: "claude-code", createdAt: 0, updatedAt: 0, settings: {} }] });
+    render(<InsightsElves />);
+
+    expect(mockUseDbInsights).toHaveBeenLastCalledWith(expect.objectContaining({ since: expect.any(Number), projectId: undefined }));
+
+    fireEvent.change(screen.getByTestId("insights-range"), { target: { value: "all" } });
+    fireEvent.change(screen.getByTestId("insights-project"), { target: { value: "p1" } });
+    expect(mockUseDbInsights).toHaveBeenLastCalledWith({ since: undefined, projectId: "p1" });
+  });
+
+  it("shows load errors", () => {
+    mockUseDbInsights.mockReturnValue({ data: null, isLoading: false, error: "Lock error", reload: vi.fn() });
+    render(<InsightsElves />);
+
+    expect(screen.getByText("Lock error")).toBeInTheDocument();
+  });
+});
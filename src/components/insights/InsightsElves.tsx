@@ -0,0 +1,203 @@
+/* InsightsElves — insights computed from the ELVES database: sessions by project, runtime and
+ * status, outcomes, daily spend, template durations, roles, memory growth and ship rate.
+ * Filterable by date range and project. */
+
+import { useMemo, useState } from "react";
+import { useDbInsights } from "@/hooks/useDbInsights";
+import { useProjectStore } from "@/stores/project";
+import type { DbInsights, InsightsFilter } from "@/types/insights";
+import { BarChart } from "./charts/BarChart";
+
+/** Date ranges offered by the filter, in days back from now (null for all time). */
+const RANGES: readonly { readonly id: string; readonly label: string; readonly days: number | null }[] = [
+  { id: "7d", label: "Last 7 days", days: 7 },
+  { id: "30d", label: "Last 30 days", days: 30 },
+  { id: "90d", label: "Last 90 days", days: 90 },
+  { id: "all", label: "All time", days: null },
+];
+
+/** Format a fraction as a whole percentage. */
+function pct(fraction: number): string {
+  return `${Math.round(fraction * 100)}%`;
+}
+
+/** Format seconds as a short duration (e.g., "4m 10s", "1h 5m"). */
+function fmtSecs(secs: number): string {
+  const total = Math.round(secs);
+  if (total < 60) return `${total}s`;
+  const minutes = Math.floor(total / 60);
+  if (minutes < 60) return `${minutes}m ${total % 60}s`;
+  return `${Math.floor(minutes / 60)}h ${minutes % 60}m`;
+}
+
+/** A single stat card with label and value. */
+function StatCard({ label, value, color }: { readonly label: string; readonly value: string; readonly color?: string }): React.JSX.Element {
+  return (
+    <div className="border-[3px] border-border bg-surface-elevated p-4 shadow-brutal" data-testid="stat-card">
+      <p className="font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">{label}</p>
+      <p className="mt-1 font-mono text-2xl font-black" style={color ? { color } : undefined}>{value}</p>
+    </div>
+  );
+}
+
+/** A titled card. */
+function Panel({ title, children }: { readonly title: string; readonly children: React.ReactNode }): React.JSX.Element {
+  return (
+    <div className="border-[3px] border-border bg-surface-elevated p-5 shadow-brutal">
+      <h3 className="mb-3 font-display text-sm font-bold uppercase tracking-wider">{title}</h3>
+      {children}
+    </div>
+  );
+}
+
+/** Message shown in place of an empty table. */
+function NoData(): React.JSX.Element {
+  return <p className="py-4 text-center font-body text-sm text-text-muted">No data</p>;
+}
+
+/** The stats and charts for loaded insights. */
+function DbInsightsPanels({ data }: { readonly data: DbInsights }): React.JSX.Element {
+  return (
+    <div className="flex flex-col gap-6">
+      <div className="grid grid-cols-3 gap-3" data-testid="db-stat-grid">
+        <StatCard label="Sessions" value={data.totalSessions.toLocaleString()} color="#4D96FF" />
+        <StatCard label="Success Rate" value={pct(data.outcomes.successRate)} color="#6BCB77" />
+        <StatCard label="Failed" value={data.outcomes.failed.toLocaleString()} color="#FF6B6B" />
+        <StatCard label="Tokens" value={data.totalTokens.toLocaleString()} />
+        <StatCard label="Cost" value={`$${data.totalCostUsd.toFixed(2)}`} />
+        <StatCard
+          label="Ship Rate"
+          value={`${pct(data.workspaces.shipRate)} (${data.workspaces.shipped}/${data.workspaces.workspaces})`}
+          color="#6BCB77"
+        />
+      </div>
+
+      <div className="grid grid-cols-2 gap-6">
+        <Panel title="Sessions by Project">
+          <BarChart items={data.sessionsByProject} color="#4D96FF" />
+        </Panel>
+        <Panel title="Sessions by Runtime">
+          <BarChart items={data.sessionsByRuntime} />
+        </Panel>
+        <Panel title="Sessions by Status">
+          <BarChart items={data.sessionsByStatus} color="#6BCB77" />
+        </Panel>
+        <Panel title="Failure Reasons">
+          <BarChart items={data.failureReasons} color="#FF6B6B" />
+        </Panel>
+        <Panel title="Most-Used Roles">
+          <BarChart items={data.topRoles} color="#FF8B3D" />
+        </Panel>
+        <Panel title="Average Duration by Template">
+          {data.templateDurations.length > 0 ? (
+            <table className="w-full font-mono text-xs" data-testid="template-durations">
+              <tbody>
+                {data.templateDurations.map((entry) => (
+                  <tr key={entry.templateId} className="border-b border-border/20">
+                    <td className="py-1.5 font-bold">{entry.name}</td>
+                    <td className="py-1.5 text-right text-text-muted">{entry.sessions} runs</td>
+                    <td className="py-1.5 text-right">{fmtSecs(entry.avgDurationSecs)}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          ) : (
+            <NoData />
+          )}
+        </Panel>
+      </div>
+
+      <Panel title="Cost and Tokens by Day">
+        {data.dailyUsage.length > 0 ? (
+          <table className="w-full font-mono text-xs" data-testid="daily-usage">
+            <thead>
+              <tr className="border-b-[2px] border-border text-left text-text-muted">
+                <th className="py-1.5">Date</th>
+                <th className="py-1.5 text-right">Sessions</th>
+                <th className="py-1.5 text-right">Tokens</th>
+                <th className="py-1.5 text-right">Cost</th>
+              </tr>
+            </thead>
+            <tbody>
+              {data.dailyUsage.map((day) => (
+                <tr key={day.date} className="border-b border-border/20">
+                  <td className="py-1.5 font-bold">{day.date}</td>
+                  <td className="py-1.5 text-right">{day.sessions}</td>
+                  <td className="py-1.5 text-right">{day.tokens.toLocaleString()}</td>
+                  <td className="py-1.5 text-right">${day.costUsd.toFixed(2)}</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        ) : (
+          <NoData />
+        )}
+      </Panel>
+
+      <Panel title="Memory Growth">
+        {data.memoryGrowth.length > 0 ? (
+          <table className="w-full font-mono text-xs" data-testid="memory-growth">
+            <tbody>
+              {data.memoryGrowth.map((day) => (
+                <tr key={day.date} className="border-b border-border/20">
+                  <td className="py-1.5 font-bold">{day.date}</td>
+                  <td className="py-1.5 text-right text-success">+{day.added}</td>
+                  <td className="py-1.5 text-right">{day.total} total</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        ) : (
+          <NoData />
+        )}
+      </Panel>
+    </div>
+  );
+}
+
+/** ELVES tab — filter bar and database insights. */
+export function InsightsElves(): React.JSX.Element {
+  const projects = useProjectStore((s) => s.projects);
+  const [rangeId, setRangeId] = useState("30d");
+  const [projectId, setProjectId] = useState("");
+
+  const filter = useMemo((): InsightsFilter => {
+    const days = RANGES.find((range) => range.id === rangeId)?.days ?? null;
+    return {
+      since: days === null ? undefined : Math.floor(Date.now() / 1000) - days * 86_400,
+      projectId: projectId || undefined,
+    };
+  }, [rangeId, projectId]);
+  const { data, error } = useDbInsights(filter);
+
+  const selectClass = "border-[2px] border-border bg-surface-elevated px-3 py-1.5 font-display text-xs font-bold uppercase tracking-wider outline-none";
+
+  return (
+    <div className="flex flex-col gap-6" data-testid="insights-elves">
+      <div className="flex gap-3">
+        <select value={rangeId} onChange={(event) => setRangeId(event.target.value)} className={selectClass} data-testid="insights-range">
+          {RANGES.map((range) => (
+            <option key={range.id} value={range.id}>{range.label}</option>
+          ))}
+        </select>
+        <select value={projectId} onChange={(event) => setProjectId(event.target.value)} className={selectClass} data-testid="insights-project">
+          <option value="">All projects</option>
+          {projects.map((project) => (
+            <option key={project.id} value={project.id}>{project.name}</option>
+          ))}
+        </select>
+      </div>
+
+      {error ? (
+        <div className="border-[3px] border-border bg-error/10 p-6 shadow-brutal">
+          <p className="font-display text-sm font-bold uppercase text-error">Failed to load insights</p>
+          <p className="mt-1 font-body text-sm text-text-muted">{error}</p>
+        </div>
+      ) : data ? (
+        <DbInsightsPanels data={data} />
+      ) : (
+        <p className="py-16 text-center font-display text-sm font-bold uppercase tracking-wider text-text-muted">Loading insights…</p>
+      )}
+    </div>
+  );
+}
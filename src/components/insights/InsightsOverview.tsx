@@ -1,4 +1,4 @@
-/* InsightsOverview — KPI grid, model usage breakdown, and project summary table. */
+/* InsightsOverview — KPI grid, runtime comparison, model usage breakdown, and project summary table. */
 
 import type { InsightsData } from "@/types/insights";
 
@@ -27,6 +27,20 @@ function fmtTokens(value: number): string {
   return value.toLocaleString();
 }
 
+/** Display name of a runtime. */
+function runtimeLabel(runtime: string): string {
+  return runtime === "codex" ? "Codex" : "Claude Code";
+}
+
+/** Small runtime tag shown next to model and project names. */
+function RuntimeTag({ runtime }: { readonly runtime: string }): React.JSX.Element {
+  return (
+    <span className="ml-2 border-[1px] border-border/40 px-1 py-0.5 font-display text-[9px] font-bold uppercase tracking-wider text-text-muted">
+      {runtimeLabel(runtime)}
+    </span>
+  );
+}
+
 /** A single stat card with label and value. */
 function StatCard({ label, value, color }: { readonly label: string; readonly value: string; readonly color?: string }): React.JSX.Element {
   return (
@@ -37,7 +51,7 @@ function StatCard({ label, value, color }: { readonly label: string; readonly va
   );
 }
 
-/** Overview tab — 9 KPI cards, model usage table, project summary table. */
+/** Overview tab — 9 KPI cards, runtime comparison, model usage table, project summary table. */
 export function InsightsOverview({ data }: InsightsOverviewProps): React.JSX.Element {
   return (
     <div className="flex flex-col gap-6">
@@ -54,6 +68,41 @@ export function InsightsOverview({ data }: InsightsOverviewProps): React.JSX.Ele
         <StatCard label="Files" value={fmt(data.filesChanged)} />
       </div>
 
+      {/* Runtime comparison — only when both runtimes were used */}
+      {data.runtimes.length > 1 && (
+        <div className="border-[3px] border-border bg-surface-elevated p-5 shadow-brutal">
+          <h3 className="mb-3 font-display text-sm font-bold uppercase tracking-wider">Runtimes</h3>
+          <div className="overflow-x-auto">
+            <table className="w-full text-left">
+              <thead>
+                <tr className="border-b-[2px] border-border">
+                  <th className="pb-2 font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">Runtime</th>
+                  <th className="pb-2 text-right font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">Sessions</th>
+                  <th className="pb-2 text-right font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">Messages</th>
+                  <th className="pb-2 text-right font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">Input</th>
+                  <th className="pb-2 text-right font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">Output</th>
+                  <th className="pb-2 text-right font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">Tool Calls</th>
+                  <th className="pb-2 text-right font-display text-[10px] font-bold uppercase tracking-widest text-text-muted">Duration</th>
+                </tr>
+              </thead>
+              <tbody>
+                {data.runtimes.map((runtime) => (
+                  <tr key={runtime.runtime} className="border-b border-border/30" data-testid={`runtime-row-${runtime.runtime}`}>
+                    <td className="py-2 font-mono text-xs font-bold">{runtimeLabel(runtime.runtime)}</td>
+                    <td className="py-2 text-right font-mono text-xs">{fmt(runtime.sessions)}</td>
+                    <td className="py-2 text-right font-mono text-xs">{fmt(runtime.messages)}</td>
+                    <td className="py-2 text-right font-mono text-xs">{fmtTokens(runtime.inputTokens)}</td>
+                    <td className="py-2 text-right font-mono text-xs">{fmtTokens(runtime.outputTokens)}</td>
+                    <td className="py-2 text-right font-mono text-xs">{fmt(runtime.toolCalls)}</td>
+                    <td className="py-2 text-right font-mono text-xs">{fmtDuration(runtime.durationMinutes)}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        </div>
+      )}
+
       {/* Model Usage */}
       {data.modelUsage.length > 0 && (
         <div className="border-[3px] border-border bg-surface-elevated p-5 shadow-brutal">
@@ -71,8 +120,8 @@ export function InsightsOverview({ data }: InsightsOverviewProps): React.JSX.Ele
               </thead>
               <tbody>
                 {data.modelUsage.map((model) => (
-                  <tr key={model.model} className="border-b border-border/30">
-                    <td className="py-2 font-mono text-xs font-bold">{model.model}</td>
+                  <tr key={`${model.runtime}:${model.model}`} className="border-b border-border/30">
+                    <td className="py-2 font-mono text-xs font-bold"><span>{model.model}</span><RuntimeTag runtime={model.runtime} /></td>
                     <td className="py-2 text-right font-mono text-xs">{fmtTokens(model.inputTokens)}</td>
                     <td className="py-2 text-right font-mono text-xs">{fmtTokens(model.outputTokens)}</td>
                     <td className="py-2 text-right font-mono text-xs">{fmtTokens(model.cacheReadTokens)}</td>
@@ -115,8 +164,8 @@ export function InsightsOverview({ data }: InsightsOverviewProps): React.JSX.Ele
               </thead>
               <tbody>
                 {data.projects.map((project) => (
-                  <tr key={project.name} className="border-b border-border/30">
-                    <td className="py-2 font-mono text-xs font-bold">{project.name}</td>
+                  <tr key={`${project.runtime}:${project.name}`} className="border-b border-border/30">
+                    <td className="py-2 font-mono text-xs font-bold"><span>{project.name}</span><RuntimeTag runtime={project.runtime} /></td>
                     <td className="py-2 text-right font-mono text-xs">{project.sessions}</td>
                     <td className="py-2 text-right font-mono text-xs">{fmt(project.linesAdded)}</td>
                     <td className="py-2 text-right font-mono text-xs">{project.commits}</td>
@@ -40,7 +40,7 @@ describe("InsightsView", () => {
 
   it("shows model usage table when models are present", () => {
     mockReturn.data = insightsData({
-      modelUsage: [{ model: "opus-4-6", inputTokens: 1, outputTokens: 1, cacheReadTokens: 0, cacheCreationTokens: 0, cacheHitRate: 0 }],
+      modelUsage: [{ model: "opus-4-6", runtime: "claude-code", inputTokens: 1, outputTokens: 1, cacheReadTokens: 0, cacheCreationTokens: 0, cacheHitRate: 0 }],
     });
     render(<InsightsView />);
 
@@ -50,7 +50,7 @@ describe("InsightsView", () => {
 
   it("shows projects table when projects are present", () => {
     mockReturn.data = insightsData({
-      projects: [{ name: "myapp", sessions: 1, linesAdded: 0, commits: 0, durationMinutes: 0, tokens: 0 }],
+      projects: [{ name: "myapp", runtime: "claude-code", sessions: 1, linesAdded: 0, commits: 0, durationMinutes: 0, tokens: 0 }],
     });
     render(<InsightsView />);
 
@@ -70,7 +70,7 @@ describe("InsightsView", () => {
 
   it("switches to analysis tab on click", () => {
     mockReturn.data = insightsData({
-      recentSessions: [{ sessionId: "s1", project: "x", startTime: "", durationMinutes: 0, firstPrompt: "", outcome: "ok", briefSummary: "", linesAdded: 0, tokens: 0, commits: 0 }],
+      recentSessions: [{ sessionId: "s1", project: "x", runtime: "claude-code", startTime: "", durationMinutes: 0, firstPrompt: "", outcome: "ok", briefSummary: "", linesAdded: 0, tokens: 0, commits: 0 }],
     });
     render(<InsightsView />);
 
@@ -124,11 +124,24 @@ describe("InsightsView", () => {
     expect(mockReload).toHaveBeenCalledOnce();
   });
 
-  it("shows coming soon state for Codex runtime", () => {
+  it("shows insights for the Codex runtime too", () => {
     useAppStore.setState({ defaultRuntime: "codex" });
     render(<InsightsView />);
 
-    expect(screen.getByTestId("codex-coming-soon")).toBeInTheDocument();
-    expect(screen.getByText("Coming Soon")).toBeInTheDocument();
+    expect(screen.queryByTestId("codex-coming-soon")).not.toBeInTheDocument();
+    expect(screen.getAllByTestId("stat-card")).toHaveLength(9);
+  });
+
+  it("compares runtimes when both have sessions", () => {
+    mockReturn.data = insightsData({
+      runtimes: [
+        { runtime: "claude-code", sessions: 3, messages: 10, inputTokens: 100, outputTokens: 50, durationMinutes: 30, toolCalls: 7 },
+        { runtime: "codex", sessions: 2, messages: 6, inputTokens: 80, outputTokens: 20, durationMinutes: 12, toolCalls: 4 },
+      ],
+    });
+    render(<InsightsView />);
+
+    expect(screen.getByText("Runtimes")).toBeInTheDocument();
+    expect(screen.getByTestId("runtime-row-codex")).toBeInTheDocument();
   });
 });
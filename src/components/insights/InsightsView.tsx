@@ -1,28 +1,28 @@
 /* InsightsView — root view for the Insights Dashboard with tab navigation.
- * Reads Claude Code usage telemetry and presents Overview, Timeline, Analysis, and AI Report tabs. */
+ * Reads Claude Code and Codex usage telemetry and presents Overview, Timeline, Analysis, and AI Report tabs,
+ * plus an ELVES tab computed from the app's own database. */
 
 import { useState, useCallback } from "react";
 import { useInsights } from "@/hooks/useInsights";
-import { useAppStore } from "@/stores/app";
 import { InsightsOverview } from "./InsightsOverview";
 import { InsightsTimeline } from "./InsightsTimeline";
 import { InsightsAnalysis } from "./InsightsAnalysis";
 import { InsightsReport } from "./InsightsReport";
+import { InsightsElves } from "./InsightsElves";
 
 /** Available tabs in the insights dashboard. */
-type InsightsTab = "overview" | "timeline" | "analysis" | "report";
+type InsightsTab = "overview" | "timeline" | "analysis" | "report" | "elves";
 
 const TABS: readonly { readonly id: InsightsTab; readonly label: string }[] = [
   { id: "overview", label: "Overview" },
   { id: "timeline", label: "Timeline" },
   { id: "analysis", label: "Analysis" },
   { id: "report", label: "AI Report" },
+  { id: "elves", label: "ELVES" },
 ];
 
 /** Insights dashboard — tab bar + routed tab content panels. */
 export function InsightsView(): React.JSX.Element {
-  const defaultRuntime = useAppStore((s) => s.defaultRuntime);
-  const isCodex = defaultRuntime === "codex";
   const { data, isLoading, error, reload } = useInsights();
   const [activeTab, setActiveTab] = useState<InsightsTab>("overview");
 
@@ -65,13 +65,8 @@ export function InsightsView(): React.JSX.Element {
 
       {/* Content area */}
       <div className="flex-1 overflow-y-auto p-6">
-        {isCodex ? (
-          <div className="flex flex-col items-center justify-center py-24" data-testid="codex-coming-soon">
-            <p className="font-display text-2xl font-black uppercase tracking-tight text-text-muted">Coming Soon</p>
-            <p className="mt-2 max-w-md text-center font-body text-sm text-text-muted">
-              Codex insights are not yet available. Switch to Claude Code to view usage analytics, session history, and the AI-generated report.
-            </p>
-          </div>
+        {activeTab === "elves" ? (
+          <InsightsElves />
         ) : error ? (
           <div className="border-[3px] border-border bg-error/10 p-6 shadow-brutal">
             <p className="font-display text-sm font-bold uppercase text-error">Failed to load insights</p>
@@ -17,6 +17,8 @@ import { ShortcutOverlay } from "@/components/shared/ShortcutOverlay";
 import { ToastContainer } from "@/components/shared/Toast";
 import { NewProjectDialog } from "@/components/project/NewProjectDialog";
 import { FirstRunWizard } from "@/components/onboarding/FirstRunWizard";
+import { InterruptedSessionsDialog } from "@/components/session/InterruptedSessionsDialog";
+import { TaskQueuePanel } from "@/components/session/TaskQueuePanel";
 import { useSessionStore } from "@/stores/session";
 import { useAppStore } from "@/stores/app";
 import { useUiStore } from "@/stores/ui";
@@ -24,6 +26,7 @@ import { useTeamSession } from "@/hooks/useTeamSession";
 import { useMemoryActions } from "@/hooks/useMemoryActions";
 import { useKeyboardShortcuts } from "@/hooks/useKeyboardShortcuts";
 import { useSessionEvents } from "@/hooks/useSessionEvents";
+import { useQueueEvents } from "@/hooks/useQueueEvents";
 import { useProjectContext } from "@/hooks/useProjectContext";
 import { useCheckForUpdate } from "@/hooks/useCheckForUpdate";
 import { onEvent } from "@/lib/tauri";
@@ -91,6 +94,9 @@ export function Shell(): React.JSX.Element {
   /* Subscribe to Tauri backend events (elf:event, session:completed) */
   useSessionEvents();
 
+  /* Give queued tasks a floor as the dispatcher starts them */
+  useQueueEvents();
+
   /* Load project-scoped context (git state, etc.) when active project changes */
   useProjectContext();
 
@@ -196,6 +202,7 @@ export function Shell(): React.JSX.Element {
           </div>
         ) : activeView === "history" ? (
           <div className="flex flex-1 flex-col overflow-y-auto">
+            <TaskQueuePanel />
             <SessionHistory />
           </div>
         ) : activeView === "insights" ? (
@@ -225,6 +232,9 @@ export function Shell(): React.JSX.Element {
         onClose={() => setNewProjectDialogOpen(false)}
       />
 
+      {/* Startup recovery for sessions interrupted by the last app exit */}
+      <InterruptedSessionsDialog />
+
       {/* Toast notification stack — fixed position, bottom-left */}
       <ToastContainer />
     </div>
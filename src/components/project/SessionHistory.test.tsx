@@ -8,6 +8,9 @@ import type { Session } from "@/types/session";
 /* Mock the Tauri IPC layer so SessionEventViewer does not call the real invoke. */
 vi.mock("@/lib/tauri", () => ({
   listSessionEvents: vi.fn().mockResolvedValue([]),
+  listSessionEventsPage: vi.fn().mockResolvedValue({ events: [], nextAfterId: null }),
+  getSessionEventStats: vi.fn().mockResolvedValue({ total: 0, byType: [] }),
+  setSessionPinned: vi.fn().mockResolvedValue(undefined),
 }));
 
 /* Mock the hooks */
@@ -55,6 +58,11 @@ function createTestSession(overrides?: Partial<Session>): Session {
     costEstimate: 0.0234,
     summary: "Successfully built the login page with OAuth integration.",
     claudeSessionId: null,
+    pinned: false,
+    compactedAt: null,
+    exitCode: 0,
+    failureReason: null,
+    attempt: 1,
     ...overrides,
   };
 }
@@ -158,6 +166,30 @@ describe("SessionHistory", () => {
     expect(screen.getByText("$0.0234")).toBeInTheDocument();
   });
 
+  it("pins an unpinned session from the expanded detail", async () => {
+    const { setSessionPinned } = await import("@/lib/tauri");
+    mockSessions.push(createTestSession({ pinned: false }));
+    render(<SessionHistory />);
+    await act(async () => {
+      fireEvent.click(screen.getByTestId("session-card-header"));
+    });
+    const button = screen.getByTestId("pin-session-button");
+    expect(button).toHaveTextContent("Pin");
+    await act(async () => {
+      fireEvent.click(button);
+    });
+    expect(setSessionPinned).toHaveBeenCalledWith("session-1", true);
+  });
+
+  it("shows the failure reason and exit code for failed sessions", async () => {
+    mockSessions.push(createTestSession({ status: "failed", failureReason: "rate_limit", exitCode: 1 }));
+    render(<SessionHistory />);
+    await act(async () => {
+      fireEvent.click(screen.getByTestId("session-card-header"));
+    });
+    expect(screen.getByTestId("session-failure")).toHaveTextContent("rate_limit (exit 1)");
+  });
+
   it("shows 'no summary' message when summary is null", async () => {
     mockSessions.push(createTestSession({ summary: null }));
     render(<SessionHistory />);
@@ -2,18 +2,18 @@
 
 import { useCallback, useEffect, useRef, useState } from "react";
 import { useSessionHistory } from "@/hooks/useSessionHistory";
+import { useResumeSession } from "@/hooks/useResumeSession";
 import { useUiStore } from "@/stores/ui";
 import { useSessionStore } from "@/stores/session";
-import { useWorkspaceStore } from "@/stores/workspace";
 import { useComparisonStore } from "@/stores/comparison";
 import type { ActiveSession } from "@/stores/session";
 import { EmptyState } from "@/components/shared/EmptyState";
 import { ShareButton } from "@/components/project/ShareButton";
 import { getEmptyState } from "@/lib/funny-copy";
-import { listSessionEvents, startTaskPty } from "@/lib/tauri";
+import { getSessionEventStats, listSessionEvents, listSessionEventsPage, setSessionPinned } from "@/lib/tauri";
 import type { SessionEvent } from "@/lib/tauri";
 import { EVENT_TYPE_COLOR, summarizeEventPayload } from "@/lib/event-summary";
-import { generateElf, getStatusMessage } from "@/lib/elf-names";
+import { agentEventToFeed, parseStoredAgentEvent } from "@/lib/agent-events";
 import type { ElfEvent } from "@/types/elf";
 import type { Session, SessionStatus } from "@/types/session";
 
@@ -22,6 +22,8 @@ const STATUS_DOT_COLOR: Record<SessionStatus, string> = {
   active: "#4D96FF",
   completed: "#6BCB77",
   failed: "#FF6B6B",
+  interrupted: "#FFD93D",
+  timed_out: "#C77DFF",
   cancelled: "#FF8B3D",
 };
 
@@ -30,6 +32,8 @@ const STATUS_LABEL: Record<SessionStatus, string> = {
   active: "Active",
   completed: "Completed",
   failed: "Failed",
+  interrupted: "Interrupted",
+  timed_out: "Timed out",
   cancelled: "Cancelled",
 };
 
@@ -53,24 +57,51 @@ function formatDuration(startedAt: number, endedAt: number | null): string {
   return `${hours}h ${remainingMinutes}m`;
 }
 
-/** Displays session events in a condensed feed when a session row is expanded. */
+/** Events fetched per page in the expanded session feed. */
+const EVENT_PAGE_SIZE = 200;
+
+/** Displays session events in a condensed feed when a session row is expanded.
+ * Loads one page at a time so sessions with thousands of events open quickly. */
 function SessionEventViewer({ sessionId }: { readonly sessionId: string }): React.JSX.Element {
   const [events, setEvents] = useState<readonly SessionEvent[]>([]);
+  const [nextAfterId, setNextAfterId] = useState<number | null>(null);
+  const [total, setTotal] = useState<number | null>(null);
   const [isLoading, setIsLoading] = useState(true);
+  const [isLoadingMore, setIsLoadingMore] = useState(false);
 
   const loadEvents = useCallback(async (): Promise<void> => {
     setIsLoading(true);
     try {
-      const result = await listSessionEvents(sessionId);
-      setEvents(result);
+      const [page, stats] = await Promise.all([
+        listSessionEventsPage(sessionId, null, EVENT_PAGE_SIZE),
+        getSessionEventStats(sessionId),
+      ]);
+      setEvents(page.events);
+      setNextAfterId(page.nextAfterId);
+      setTotal(stats.total);
     } catch (error) {
       console.error("Failed to load session events:", error);
       setEvents([]);
+      setNextAfterId(null);
     } finally {
       setIsLoading(false);
     }
   }, [sessionId]);
 
+  const loadMore = useCallback(async (): Promise<void> => {
+    if (nextAfterId === null) return;
+    setIsLoadingMore(true);
+    try {
+      const page = await listSessionEventsPage(sessionId, nextAfterId, EVENT_PAGE_SIZE);
+      setEvents((previous) => [...previous, ...page.events]);
+      setNextAfterId(page.nextAfterId);
+    } catch (error) {
+      console.error("Failed to load more session events:", error);
+    } finally {
+      setIsLoadingMore(false);
+    }
+  }, [sessionId, nextAfterId]);
+
   useEffect(() => {
     void loadEvents();
   }, [loadEvents]);
@@ -95,7 +126,7 @@ function SessionEventViewer({ sessionId }: { readonly sessionId: string }): Reac
     <div className="border-token-thin border-border/20" data-testid="session-events">
       <div className="border-b-token-thin border-border/20 px-3 py-2">
         <p className="font-body text-xs text-label text-text-light/50">
-          Session Output ({events.length} events)
+          Session Output ({total ?? events.length} events)
         </p>
       </div>
       <div className="max-h-64 overflow-y-auto">
@@ -112,6 +143,16 @@ function SessionEventViewer({ sessionId }: { readonly sessionId: string }): Reac
             </p>
           </div>
         ))}
+        {nextAfterId !== null && (
+          <button
+            type="button"
+            onClick={() => void loadMore()}
+            disabled={isLoadingMore}
+            className="w-full cursor-pointer px-3 py-2 text-left font-mono text-xs font-bold text-text-light/60 hover:bg-border/5 disabled:cursor-wait"
+          >
+            {isLoadingMore ? "Loading..." : `Load more (${events.length} of ${total ?? "?"})`}
+          </button>
+        )}
       </div>
     </div>
   );
@@ -123,7 +164,7 @@ function SessionEventViewer({ sessionId }: { readonly sessionId: string }): Reac
  * Clicking a row expands it inline to show detail, events, and action buttons.
  */
 export function SessionHistory(): React.JSX.Element {
-  const { sessions, isLoading } = useSessionHistory();
+  const { sessions, isLoading, reload } = useSessionHistory();
   const [expandedId, setExpandedId] = useState<string | null>(null);
   const highlightedSessionId = useUiStore((state) => state.highlightedSessionId);
   const setHighlightedSessionId = useUiStore((state) => state.setHighlightedSessionId);
@@ -164,17 +205,28 @@ export function SessionHistory(): React.JSX.Element {
     async (session: Session): Promise<void> => {
       try {
         const dbEvents = await listSessionEvents(session.id);
-        /* Convert DB events to ElfEvent format for the floor */
-        const elfEvents: ElfEvent[] = dbEvents.map((event: SessionEvent) => ({
-          id: `hist-event-${event.id}`,
-          timestamp: event.timestamp,
-          elfId: event.elfId ?? "system",
-          elfName: event.elfId ?? "System",
-          runtime: session.runtime,
-          type: event.eventType as ElfEvent["type"],
-          payload: (() => { try { return JSON.parse(event.payload) as Record<string, unknown>; } catch { return {}; } })(),
-          funnyStatus: event.funnyStatus ?? undefined,
-        }));
+        /* Convert DB events to ElfEvent format for the floor. Typed rows map through the
+         * same feed conversion as live events; metadata-only kinds are skipped. */
+        const elfEvents: ElfEvent[] = dbEvents.flatMap((event: SessionEvent) => {
+          const agentEvent = parseStoredAgentEvent(event.kind, event.payload);
+          const entry = agentEvent
+            ? agentEventToFeed(agentEvent)
+            : {
+                type: event.eventType as ElfEvent["type"],
+                payload: (() => { try { return JSON.parse(event.payload) as Record<string, unknown>; } catch { return {}; } })(),
+              };
+          if (!entry) return [];
+          return [{
+            id: `hist-event-${event.id}`,
+            timestamp: event.timestamp,
+            elfId: event.elfId ?? "system",
+            elfName: event.elfId ?? "System",
+            runtime: session.runtime,
+            type: entry.type,
+            payload: entry.payload,
+            funnyStatus: event.funnyStatus ?? undefined,
+          }];
+        });
 
         const activeSession: ActiveSession = {
           id: session.id,
@@ -196,88 +248,27 @@ export function SessionHistory(): React.JSX.Element {
     [openHistoricalFloor, setActiveView],
   );
 
-  const createFloor = useSessionStore((state) => state.createFloor);
-  const startSessionOnStore = useSessionStore((state) => state.startSession);
-  const addElf = useSessionStore((state) => state.addElf);
-  const addEvent = useSessionStore((state) => state.addEvent);
-  const setFloorPtyId = useSessionStore((state) => state.setFloorPtyId);
-
-  /** Resume a completed session — spawn a new PTY with `--resume <claudeSessionId>`. */
-  const handleResume = useCallback(
+  /** Pin or unpin a session. Pinned sessions are exempt from event retention. */
+  const handleTogglePin = useCallback(
     async (session: Session): Promise<void> => {
-      if (!session.claudeSessionId) return;
-
       try {
-        /* Create a fresh floor for the resumed session */
-        const floorId = createFloor(session.task.slice(0, 30) || "Resume");
-
-        const { sessionId, ptyId } = await startTaskPty(
-          session.projectId,
-          "Resuming session...",
-          session.runtime,
-          undefined,
-          { resumeSessionId: session.claudeSessionId },
-        );
-
-        setFloorPtyId(floorId, ptyId);
-
-        /* Wire PTY to workspace store so WorkspaceTerminalView can render it */
-        const wsSlug = `resume-${sessionId.slice(0, 8)}`;
-        const wsStore = useWorkspaceStore.getState();
-        wsStore.addWorkspace({
-          slug: wsSlug,
-          path: "",
-          branch: "",
-          status: "active",
-          filesChanged: 0,
-          lastModified: new Date().toISOString(),
-        });
-        wsStore.setPtyId(wsSlug, ptyId);
-        wsStore.openWorkspace(wsSlug);
-
-        /* Start session on the new floor */
-        startSessionOnStore({
-          id: sessionId,
-          projectId: session.projectId,
-          task: session.task,
-          runtime: session.runtime,
-          plan: session.plan ?? undefined,
-        });
-
-        const personality = generateElf();
-        addElf({
-          id: `elf-${sessionId}`,
-          sessionId,
-          name: personality.name,
-          role: "Worker",
-          avatar: personality.avatar,
-          color: personality.color,
-          quirk: personality.quirk,
-          runtime: session.runtime,
-          status: "working",
-          spawnedAt: Date.now(),
-          finishedAt: null,
-          parentElfId: null,
-          toolsUsed: [],
-        });
-
-        addEvent({
-          id: `event-resume-${Date.now()}`,
-          timestamp: Date.now(),
-          elfId: `elf-${sessionId}`,
-          elfName: personality.name,
-          runtime: session.runtime,
-          type: "spawn",
-          payload: { role: "Worker" },
-          funnyStatus: getStatusMessage(personality.name, "working"),
-        });
-
-        setActiveView("workspace");
+        await setSessionPinned(session.id, !session.pinned);
+        await reload();
       } catch (error) {
-        console.error("Failed to resume session:", error);
+        console.error("Failed to update session pin:", error);
       }
     },
-    [createFloor, startSessionOnStore, addElf, addEvent, setFloorPtyId, setActiveView],
+    [reload],
+  );
+
+  const resumeSession = useResumeSession();
+
+  /** Resume a completed session — spawn a new PTY with `--resume <claudeSessionId>`. */
+  const handleResume = useCallback(
+    async (session: Session): Promise<void> => {
+      await resumeSession(session);
+    },
+    [resumeSession],
   );
 
   if (isLoading) {
@@ -388,6 +379,21 @@ export function SessionHistory(): React.JSX.Element {
                       <p className="font-mono text-xs text-text-light/50">Cost</p>
                       <p className="font-mono text-sm font-bold">${session.costEstimate.toFixed(4)}</p>
                     </div>
+                    {session.attempt > 1 && (
+                      <div className="border-token-thin border-border/20 px-3 py-1" data-testid="session-attempts">
+                        <p className="font-mono text-xs text-text-light/50">Attempts</p>
+                        <p className="font-mono text-sm font-bold">{session.attempt}</p>
+                      </div>
+                    )}
+                    {session.failureReason && (
+                      <div className="border-token-thin border-error/40 px-3 py-1" data-testid="session-failure">
+                        <p className="font-mono text-xs text-text-light/50">Failure</p>
+                        <p className="font-mono text-sm font-bold">
+                          {session.failureReason}
+                          {session.exitCode !== null && ` (exit ${session.exitCode})`}
+                        </p>
+                      </div>
+                    )}
                   </div>
 
                   {/* Session events */}
@@ -410,6 +416,17 @@ export function SessionHistory(): React.JSX.Element {
                       </button>
                     )}
                     <ShareButton sessionId={session.id} sessionTask={session.task} />
+                    <button
+                      onClick={(event) => {
+                        event.stopPropagation();
+                        void handleTogglePin(session);
+                      }}
+                      className="cursor-pointer border-token-normal border-border bg-surface-light rounded-token-sm px-3 py-1 font-display text-xs text-label shadow-brutal-sm transition-all duration-100 hover:translate-x-[2px] hover:translate-y-[2px] hover:shadow-none"
+                      title="Pinned sessions keep their full event log"
+                      data-testid="pin-session-button"
+                    >
+                      {session.pinned ? "Unpin" : "Pin"}
+                    </button>
                     <button
                       onClick={(event) => {
                         event.stopPropagation();
@@ -0,0 +1,102 @@
+/* Tests for InterruptedSessionsDialog — verifies listing, resume, and dismiss. */
+
+import { render, screen, fireEvent, waitFor } from "@testing-library/react";
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { InterruptedSessionsDialog } from "./InterruptedSessionsDialog";
+import type { InterruptedSession, Session } from "@/types/session";
+
+vi.mock("@/lib/tauri", () => ({
+  listInterruptedSessions: vi.fn(),
+  dismissInterruptedSession: vi.fn().mockResolvedValue(true),
+}));
+
+const mockResumeSession = vi.fn().mockResolvedValue(true);
+vi.mock("@/hooks/useResumeSession", () => ({
+  useResumeSession: () => mockResumeSession,
+}));
+
+import { dismissInterruptedSession, listInterruptedSessions } from "@/lib/tauri";
+
+const mockList = vi.mocked(listInterruptedSessions);
+const mockDismiss = vi.mocked(dismissInterruptedSession);
+
+function createEntry(id: string, overrides?: Partial<InterruptedSession>): InterruptedSession {
+  const session: Session = {
+    id,
+    projectId: "project-1",
+    task: `Task ${id}`,
+    runtime: "claude-code",
+    status: "interrupted",
+    plan: null,
+    agentCount: 1,
+    startedAt: Date.now() - 60000,
+    endedAt: Date.now(),
+    tokensUsed: 0,
+    costEstimate: 0,
+    summary: "Session interrupted (app restarted)",
+    claudeSessionId: `claude-${id}`,
+    pinned: false,
+    compactedAt: null,
+    exitCode: null,
+    failureReason: null,
+    attempt: 1,
+  };
+  return {
+    session,
+    projectName: "My Project",
+    workingDir: "/tmp/my-project/.claude/worktrees/feature",
+    workspaceExists: true,
+    resumable: true,
+    ...overrides,
+  };
+}
+
+describe("InterruptedSessionsDialog", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it("stays closed when nothing was interrupted", async () => {
+    mockList.mockResolvedValue([]);
+    render(<InterruptedSessionsDialog />);
+    await waitFor(() => expect(mockList).toHaveBeenCalled());
+    expect(screen.queryByText("Interrupted Sessions")).not.toBeInTheDocument();
+  });
+
+  it("lists sessions with workspace and Claude session id", async () => {
+    mockList.mockResolvedValue([createEntry("s1")]);
+    render(<InterruptedSessionsDialog />);
+    expect(await screen.findByText("Task s1")).toBeInTheDocument();
+    expect(screen.getByText("/tmp/my-project/.claude/worktrees/feature")).toBeInTheDocument();
+    expect(screen.getByText("Claude session claude-s1")).toBeInTheDocument();
+  });
+
+  it("resumes a session in its workspace", async () => {
+    const entry = createEntry("s1");
+    mockList.mockResolvedValue([entry]);
+    render(<InterruptedSessionsDialog />);
+    fireEvent.click(await screen.findByTestId("interrupted-resume-button"));
+    await waitFor(() => {
+      expect(mockResumeSession).toHaveBeenCalledWith(entry.session, entry.workingDir);
+    });
+  });
+
+  it("disables resume for sessions that cannot be resumed", async () => {
+    mockList.mockResolvedValue([createEntry("s1", { resumable: false, workspaceExists: false })]);
+    render(<InterruptedSessionsDialog />);
+    expect(await screen.findByTestId("interrupted-resume-button")).toBeDisabled();
+    expect(screen.getByText(/\(missing\)/)).toBeInTheDocument();
+  });
+
+  it("dismisses a session and removes it from the list", async () => {
+    mockList.mockResolvedValue([createEntry("s1"), createEntry("s2")]);
+    render(<InterruptedSessionsDialog />);
+    await screen.findByText("Task s1");
+    fireEvent.click(screen.getAllByTestId("interrupted-dismiss-button")[0]!);
+    await waitFor(() => {
+      expect(mockDismiss).toHaveBeenCalledWith("s1");
+      expect(screen.queryByText("Task s1")).not.toBeInTheDocument();
+    });
+    expect(screen.getByText("Task s2")).toBeInTheDocument();
+  });
+});
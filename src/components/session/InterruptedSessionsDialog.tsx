@@ -0,0 +1,117 @@
+/* InterruptedSessionsDialog — on startup, offers to resume sessions the last app exit interrupted. */
+
+import { useCallback, useEffect, useState } from "react";
+import { Dialog } from "@/components/shared/Dialog";
+import { Button } from "@/components/shared/Button";
+import { useResumeSession } from "@/hooks/useResumeSession";
+import { dismissInterruptedSession, listInterruptedSessions } from "@/lib/tauri";
+import type { InterruptedSession } from "@/types/session";
+
+/**
+ * Lists interrupted sessions with their project, workspace, and Claude session ID.
+ * Each can be resumed in a terminal with one click or dismissed (recorded as failed).
+ * Opens once on mount when there is anything to recover; closing it leaves the sessions
+ * interrupted, so they can still be resumed from history.
+ */
+export function InterruptedSessionsDialog(): React.JSX.Element {
+  const [entries, setEntries] = useState<readonly InterruptedSession[]>([]);
+  const [isOpen, setIsOpen] = useState(false);
+  const [busyId, setBusyId] = useState<string | null>(null);
+  const resumeSession = useResumeSession();
+
+  useEffect(() => {
+    listInterruptedSessions()
+      .then((sessions) => {
+        setEntries(sessions);
+        setIsOpen(sessions.length > 0);
+      })
+      .catch((error: unknown) => console.error("Failed to list interrupted sessions:", error));
+  }, []);
+
+  /** Drop an entry, closing the dialog once nothing is left. */
+  const removeEntry = useCallback((sessionId: string): void => {
+    setEntries((current) => {
+      const remaining = current.filter((entry) => entry.session.id !== sessionId);
+      if (remaining.length === 0) setIsOpen(false);
+      return remaining;
+    });
+  }, []);
+
+  const handleResume = useCallback(
+    async (entry: InterruptedSession): Promise<void> => {
+      setBusyId(entry.session.id);
+      const resumed = await resumeSession(entry.session, entry.workingDir);
+      setBusyId(null);
+      if (resumed) removeEntry(entry.session.id);
+    },
+    [resumeSession, removeEntry],
+  );
+
+  const handleDismiss = useCallback(
+    async (entry: InterruptedSession): Promise<void> => {
+      setBusyId(entry.session.id);
+      try {
+        await dismissInterruptedSession(entry.session.id);
+        removeEntry(entry.session.id);
+      } catch (error) {
+        console.error("Failed to dismiss interrupted session:", error);
+      } finally {
+        setBusyId(null);
+      }
+    },
+    [removeEntry],
+  );
+
+  return (
+    <Dialog isOpen={isOpen} onClose={() => setIsOpen(false)} title="Interrupted Sessions">
+      <p className="mb-4 font-body text-sm text-text-muted">
+        These sessions were still running when ELVES last closed.
+      </p>
+      <ul className="mb-4 flex max-h-80 flex-col gap-3 overflow-y-auto" data-testid="interrupted-session-list">
+        {entries.map((entry) => (
+          <li
+            key={entry.session.id}
+            className="border-token-thin border-border bg-surface-light p-3 rounded-token-sm"
+            data-testid={`interrupted-session-${entry.session.id}`}
+          >
+            <p className="truncate font-display text-sm font-bold text-text-light">{entry.session.task}</p>
+            <p className="font-body text-xs text-text-muted">{entry.projectName}</p>
+            <p className="truncate font-mono text-xs text-text-muted" title={entry.workingDir}>
+              {entry.workingDir}
+              {!entry.workspaceExists && " (missing)"}
+            </p>
+            {entry.session.claudeSessionId && (
+              <p className="truncate font-mono text-xs text-text-muted">
+                Claude session {entry.session.claudeSessionId}
+              </p>
+            )}
+            <div className="mt-2 flex gap-2">
+              <Button
+                variant="primary"
+                className="px-3 py-1 text-xs"
+                disabled={!entry.resumable || busyId !== null}
+                title={entry.resumable ? undefined : "No Claude session or workspace to resume"}
+                onClick={() => void handleResume(entry)}
+                data-testid="interrupted-resume-button"
+              >
+                Resume
+              </Button>
+              <Button
+                variant="secondary"
+                className="px-3 py-1 text-xs"
+                disabled={busyId !== null}
+                onClick={() => void handleDismiss(entry)}
+                data-testid="interrupted-dismiss-button"
+              >
+                Dismiss
+              </Button>
+            </div>
+          </li>
+        ))}
+      </ul>
+      <Button variant="ghost" onClick={() => setIsOpen(false)}>
+        Later
+      </Button>
+    </Dialog>
+  );
+}
@@ -0,0 +1,99 @@
+/* Tests for TaskQueuePanel — verifies listing, reordering, cancelling, and live updates. */
+
+import { render, screen, fireEvent, waitFor, act } from "@testing-library/react";
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { TaskQueuePanel } from "./TaskQueuePanel";
+import type { QueuedTask } from "@/types/queue";
+
+let queueUpdatedHandler: ((queue: QueuedTask[]) => void) | null = null;
+
+vi.mock("@/lib/tauri", () => ({
+  listTaskQueue: vi.fn(),
+  reorderQueue: vi.fn().mockResolvedValue(undefined),
+  cancelQueued: vi.fn().mockResolvedValue(true),
+  onEvent: vi.fn((_name: string, handler: (queue: QueuedTask[]) => void) => {
+    queueUpdatedHandler = handler;
+    return Promise.resolve(() => {});
+  }),
+  extractSessionMemories: vi.fn(),
+}));
+
+import { cancelQueued, listTaskQueue, reorderQueue } from "@/lib/tauri";
+
+const mockList = vi.mocked(listTaskQueue);
+const mockReorder = vi.mocked(reorderQueue);
+const mockCancel = vi.mocked(cancelQueued);
+
+function createTask(id: string, overrides?: Partial<QueuedTask>): QueuedTask {
+  return {
+    id,
+    projectId: "project-1",
+    task: `Task ${id}`,
+    runtime: "claude-code",
+    workingDir: null,
+    worktreeSlug: null,
+    options: null,
+    plan: null,
+    pty: false,
+    priority: 0,
+    position: 0,
+    status: "queued",
+    sessionId: null,
+    error: null,
+    createdAt: Date.now(),
+    startedAt: null,
+    ...overrides,
+  };
+}
+
+describe("TaskQueuePanel", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    queueUpdatedHandler = null;
+  });
+
+  it("renders nothing while the queue is empty", async () => {
+    mockList.mockResolvedValue([]);
+    render(<TaskQueuePanel />);
+    await waitFor(() => expect(mockList).toHaveBeenCalled());
+    expect(screen.queryByTestId("task-queue-panel")).not.toBeInTheDocument();
+  });
+
+  it("lists queued tasks in start order", async () => {
+    mockList.mockResolvedValue([createTask("a"), createTask("b", { plan: "{}" })]);
+    render(<TaskQueuePanel />);
+    expect(await screen.findByText("Task a")).toBeInTheDocument();
+    expect(screen.getByText("Queued Tasks (2)")).toBeInTheDocument();
+    expect(screen.getByText("team")).toBeInTheDocument();
+    expect(screen.getAllByTestId("queued-task-move-up")[0]).toBeDisabled();
+  });
+
+  it("moves a task up one place", async () => {
+    mockList.mockResolvedValue([createTask("a"), createTask("b"), createTask("c")]);
+    render(<TaskQueuePanel />);
+    await screen.findByText("Task c");
+    fireEvent.click(screen.getAllByTestId("queued-task-move-up")[2]!);
+    await waitFor(() => expect(mockReorder).toHaveBeenCalledWith(["a", "c", "b"]));
+  });
+
+  it("cancels a task and removes it from the list", async () => {
+    mockList.mockResolvedValue([createTask("a"), createTask("b")]);
+    render(<TaskQueuePanel />);
+    await screen.findByText("Task a");
+    fireEvent.click(screen.getAllByTestId("queued-task-cancel")[0]!);
+    await waitFor(() => {
+      expect(mockCancel).toHaveBeenCalledWith("a");
+      expect(screen.queryByText("Task a")).not.toBeInTheDocument();
+    });
+    expect(screen.getByText("Task b")).toBeInTheDocument();
+  });
+
+  it("follows queue:updated events as tasks start", async () => {
+    mockList.mockResolvedValue([createTask("a"), createTask("b")]);
+    render(<TaskQueuePanel />);
+    await screen.findByText("Task a");
+    act(() => queueUpdatedHandler?.([createTask("b")]));
+    expect(screen.queryByText("Task a")).not.toBeInTheDocument();
+    expect(screen.getByText("Task b")).toBeInTheDocument();
+  });
+});
@@ -0,0 +1,98 @@
+/* TaskQueuePanel — the tasks waiting for a free slot, with reorder and cancel controls. */
+
+import { useCallback, useEffect, useState } from "react";
+import { Button } from "@/components/shared/Button";
+import { subscribeSafe } from "@/hooks/useSessionEvents";
+import { cancelQueued, listTaskQueue, reorderQueue } from "@/lib/tauri";
+import type { QueuedTask } from "@/types/queue";
+
+/**
+ * Lists queued tasks in the order the dispatcher will start them. Tasks can be moved
+ * up one place or cancelled. Stays in sync through `queue:updated` events, so tasks
+ * disappear as they start. Renders nothing while the queue is empty.
+ */
+export function TaskQueuePanel(): React.JSX.Element | null {
+  const [queue, setQueue] = useState<readonly QueuedTask[]>([]);
+  const [isBusy, setIsBusy] = useState(false);
+
+  useEffect(() => {
+    listTaskQueue()
+      .then(setQueue)
+      .catch((error: unknown) => console.error("Failed to list task queue:", error));
+    return subscribeSafe<QueuedTask[]>("queue:updated", setQueue);
+  }, []);
+
+  const handleMoveUp = useCallback(
+    async (index: number): Promise<void> => {
+      if (index === 0) return;
+      const ids = queue.map((task) => task.id);
+      [ids[index - 1], ids[index]] = [ids[index]!, ids[index - 1]!];
+      setIsBusy(true);
+      try {
+        await reorderQueue(ids);
+      } catch (error) {
+        console.error("Failed to reorder task queue:", error);
+      } finally {
+        setIsBusy(false);
+      }
+    },
+    [queue],
+  );
+
+  const handleCancel = useCallback(async (id: string): Promise<void> => {
+    setIsBusy(true);
+    try {
+      if (await cancelQueued(id)) {
+        setQueue((current) => current.filter((task) => task.id !== id));
+      }
+    } catch (error) {
+      console.error("Failed to cancel queued task:", error);
+    } finally {
+      setIsBusy(false);
+    }
+  }, []);
+
+  if (queue.length === 0) return null;
+
+  return (
+    <section className="border-b-token-thin border-border px-6 py-4" data-testid="task-queue-panel">
+      <h3 className="mb-2 font-display text-sm font-bold uppercase tracking-wider text-text-light">
+        Queued Tasks ({queue.length})
+      </h3>
+      <ol className="flex flex-col gap-2">
+        {queue.map((task, index) => (
+          <li
+            key={task.id}
+            className="flex items-center gap-3 border-token-thin border-border bg-surface-light p-2 rounded-token-sm"
+            data-testid={`queued-task-${task.id}`}
+          >
+            <span className="font-mono text-xs text-text-muted">{index + 1}</span>
+            <p className="min-w-0 flex-1 truncate font-body text-sm text-text-light" title={task.task}>
+              {task.task}
+            </p>
+            {task.plan && <span className="font-mono text-xs text-text-muted">team</span>}
+            <span className="font-mono text-xs text-text-muted">{task.runtime}</span>
+            <Button
+              variant="ghost"
+              className="px-2 py-1 text-xs"
+              disabled={index === 0 || isBusy}
+              onClick={() => void handleMoveUp(index)}
+              data-testid="queued-task-move-up"
+            >
+              Up
+            </Button>
+            <Button
+              variant="secondary"
+              className="px-2 py-1 text-xs"
+              disabled={isBusy}
+              onClick={() => void handleCancel(task.id)}
+              data-testid="queued-task-cancel"
+            >
+              Cancel
+            </Button>
+          </li>
+        ))}
+      </ol>
+    </section>
+  );
+}
@@ -17,6 +17,7 @@ import { WorkspaceTerminalView } from "./WorkspaceTerminalView";
 import { WorkspaceTabBar } from "./WorkspaceTabBar";
 import { Input } from "@/components/shared/Input";
 import { DeployButton } from "@/components/shared/DeployButton";
+import { Button } from "@/components/shared/Button";
 import { useTeamSession } from "@/hooks/useTeamSession";
 import * as tauri from "@/lib/tauri";
 import type { WorkspaceInfo, MergeStrategy } from "@/types/workspace";
@@ -413,6 +414,18 @@ export function ProjectWorkspace(): React.JSX.Element {
     await analyzeAndDeploy(task);
   }, [taskText, activeProject?.path, analyzeAndDeploy]);
 
+  /** Queue the task instead — it starts in print mode once a session slot frees up. */
+  const handleQueue = useCallback(async (): Promise<void> => {
+    const task = taskText.trim();
+    if (!task || !activeProject) return;
+    try {
+      await tauri.enqueueTask(activeProject.id, task, defaultRuntime);
+      setTaskText("");
+    } catch (err) {
+      setError(`Failed to queue task: ${String(err)}`);
+    }
+  }, [taskText, activeProject, defaultRuntime, setError]);
+
   /** Refresh git state. */
   const handleRefreshGit = useCallback((): void => {
     if (!activeProject?.path) return;
@@ -494,6 +507,16 @@ export function ProjectWorkspace(): React.JSX.Element {
           <span className="shrink-0 border-[2px] border-border bg-[#4D96FF] px-2 py-1 font-mono text-[10px] font-bold uppercase text-white shadow-[2px_2px_0px_0px_#000]">
             {defaultRuntime === "codex" ? "CX" : "CC"}
           </span>
+          <Button
+            variant="secondary"
+            className="px-3 py-2 text-xs"
+            onClick={() => void handleQueue()}
+            disabled={!canSummon}
+            title="Start when a session slot is free"
+            data-testid="queue-task-button"
+          >
+            Queue
+          </Button>
           <DeployButton onClick={() => void handleSummon()} disabled={!canSummon} />
         </div>
         {!runtimeHealthy && (
@@ -12,6 +12,7 @@ import type { DetectedAgent, DetectedPermission } from "@/lib/pty-agent-detector
 import { useSessionStore } from "@/stores/session";
 import { generateElf } from "@/lib/elf-names";
 import { playSound } from "@/lib/sounds";
+import { unseenOutput } from "@/lib/pty-scrollback";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 
@@ -64,21 +65,41 @@ export function TerminalPane({
     detectorRef.current.reset();
     setHasExited(false);
 
+    /** Output streamed while the scrollback is read; null once the terminal is rehydrated. */
+    let pending: string[] | null = [];
+
+    function handleOutput(data: string): void {
+      terminalRef.current?.write(data);
+
+      const { agents, permissions } = detectorRef.current.feed(data);
+      for (const agent of agents) {
+        handleAgentDetected(agent, workspaceSlug);
+      }
+      for (const perm of permissions) {
+        setPendingPermission(perm);
+      }
+    }
+
     async function setup(): Promise<void> {
       try {
         unlistenData = await listen<string>(`pty:data:${ptyId}`, (event) => {
           if (!mounted) return;
-          terminalRef.current?.write(event.payload);
-
-          const { agents, permissions } = detectorRef.current.feed(event.payload);
-          for (const agent of agents) {
-            handleAgentDetected(agent, workspaceSlug);
-          }
-          for (const perm of permissions) {
-            setPendingPermission(perm);
+          if (pending) {
+            pending.push(event.payload);
+            return;
           }
+          handleOutput(event.payload);
         });
 
+        /* Rehydrate with the PTY's history (e.g. after a reload), then write what
+         * streamed meanwhile. History isn't fed to the detector, so old prompts don't pop up again. */
+        const scrollback = await invoke<string>("read_pty_scrollback", { ptyId }).catch(() => "");
+        if (!mounted) return;
+        terminalRef.current?.write(scrollback);
+        const unseen = unseenOutput(scrollback, pending.join(""));
+        pending = null;
+        if (unseen) handleOutput(unseen);
+
         unlistenExit = await listen<number>(`pty:exit:${ptyId}`, (event) => {
           if (!mounted) return;
           terminalRef.current?.writeln("");
@@ -76,7 +76,7 @@ export function WorkspaceCard({
   const canResume = workspace.status !== "active" && !!(
     workspace.status === "paused" || (
       lastSession?.claudeSessionId &&
-      ["completed", "cancelled", "error", "failed"].includes(lastSession.status)
+      ["completed", "cancelled", "error", "failed", "interrupted", "timed_out"].includes(lastSession.status)
     )
   );
 
@@ -16,7 +16,8 @@ import { PtyAgentDetector } from "@/lib/pty-agent-detector";
 import type { DetectedAgent, DetectedPermission } from "@/lib/pty-agent-detector";
 import { generateElf } from "@/lib/elf-names";
 import { playSound } from "@/lib/sounds";
-import { removeWorkspace as invokeRemoveWorkspace, completeSession, updateClaudeSessionId, getLastWorkspaceSession, createSession } from "@/lib/tauri";
+import { unseenOutput } from "@/lib/pty-scrollback";
+import { removeWorkspace as invokeRemoveWorkspace, completeSession, updateClaudeSessionId, getLastWorkspaceSession, createSession, readPtyScrollback } from "@/lib/tauri";
 import { useToastStore } from "@/stores/toast";
 import { Channel, invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
@@ -214,6 +215,35 @@ export function WorkspaceTerminalView({ workspace, isActiveTab }: WorkspaceTermi
     }
     setHasExited(false);
 
+    /** Event output streamed while the scrollback is read; null once the terminal is rehydrated. */
+    let pending: string[] | null = [];
+
+    function handleOutput(data: string): void {
+      terminalRef.current?.write(data);
+
+      const { agents, permissions, claudeSessionId: detectedSessionId } = detectorRef.current.feed(data);
+      for (const agent of agents) {
+        handleAgentDetected(agent, workspace.slug);
+      }
+      for (const perm of permissions) {
+        setPendingPermission(perm);
+      }
+
+      if (detectedSessionId) {
+        const sessionState = useSessionStore.getState();
+        const floorId = sessionState.activeFloorId;
+        if (floorId) {
+          const floor = sessionState.floors[floorId];
+          const dbSessionId = floor?.session?.id;
+          if (dbSessionId) {
+            updateClaudeSessionId(dbSessionId, detectedSessionId).catch((error: unknown) => {
+              console.error("Failed to save Claude session ID:", error);
+            });
+          }
+        }
+      }
+    }
+
     async function setup(): Promise<void> {
       try {
         /* Subscribe to pty:data events only for PTYs spawned without a Channel
@@ -221,32 +251,24 @@ export function WorkspaceTerminalView({ workspace, isActiveTab }: WorkspaceTermi
         if (!hasChannelRef.current) {
           const dataUnsub = await listen<string>(`pty:data:${ptyId}`, (event) => {
             if (!mounted) return;
-            terminalRef.current?.write(event.payload);
-
-            const { agents, permissions, claudeSessionId: detectedSessionId } = detectorRef.current.feed(event.payload);
-            for (const agent of agents) {
-              handleAgentDetected(agent, workspace.slug);
-            }
-            for (const perm of permissions) {
-              setPendingPermission(perm);
-            }
-
-            if (detectedSessionId) {
-              const sessionState = useSessionStore.getState();
-              const floorId = sessionState.activeFloorId;
-              if (floorId) {
-                const floor = sessionState.floors[floorId];
-                const dbSessionId = floor?.session?.id;
-                if (dbSessionId) {
-                  updateClaudeSessionId(dbSessionId, detectedSessionId).catch((error: unknown) => {
-                    console.error("Failed to save Claude session ID:", error);
-                  });
-                }
-              }
+            if (pending) {
+              pending.push(event.payload);
+              return;
             }
+            handleOutput(event.payload);
           });
           if (!mounted) { dataUnsub(); return; }
           unlistenData = dataUnsub;
+
+          /* Rehydrate with the PTY's history (e.g. after a reload or reopening the tab), then
+           * write what streamed meanwhile. History isn't fed to the detector, so old prompts
+           * don't pop up again. */
+          const scrollback = await readPtyScrollback(ptyId!).catch(() => "");
+          if (!mounted) return;
+          terminalRef.current?.write(scrollback);
+          const unseen = unseenOutput(scrollback, pending.join(""));
+          pending = null;
+          if (unseen) handleOutput(unseen);
         }
 
         /* Exit event — always via Tauri events (one-shot signal, not streaming). */
@@ -0,0 +1,48 @@
+/* Database insights hook — loads insights computed from the ELVES database for a filter. */
+
+import { useCallback, useEffect, useState } from "react";
+import { loadDbInsights } from "@/lib/tauri";
+import type { DbInsights, InsightsFilter } from "@/types/insights";
+
+interface UseDbInsightsResult {
+  /** Insights for the current filter, or null while loading. */
+  readonly data: DbInsights | null;
+  /** Whether the data is being loaded. */
+  readonly isLoading: boolean;
+  /** Error message if the load failed. */
+  readonly error: string | null;
+  /** Reload insights for the current filter. */
+  readonly reload: () => Promise<void>;
+}
+
+/**
+ * Loads database insights for the filter, reloading whenever the filter changes.
+ */
+export function useDbInsights(filter: InsightsFilter): UseDbInsightsResult {
+  const [data, setData] = useState<DbInsights | null>(null);
+  const [isLoading, setIsLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const { since, until, projectId } = filter;
+
+  const reload = useCallback(async (): Promise<void> => {
+    setIsLoading(true);
+    setError(null);
+    try {
+      const result = await loadDbInsights({ since, until, projectId });
+      setData(result);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      console.error("Failed to load database insights:", message);
+      setError(message);
+      setData(null);
+    } finally {
+      setIsLoading(false);
+    }
+  }, [since, until, projectId]);
+
+  useEffect(() => {
+    void reload();
+  }, [reload]);
+
+  return { data, isLoading, error, reload };
+}
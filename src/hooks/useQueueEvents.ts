@@ -0,0 +1,102 @@
+/* Task queue event listener — gives queued tasks a floor when the dispatcher starts them. */
+
+import { useEffect } from "react";
+import { useSessionStore } from "@/stores/session";
+import { useToastStore } from "@/stores/toast";
+import { subscribeSafe } from "@/hooks/useSessionEvents";
+import { generateElf } from "@/lib/elf-names";
+import type { Runtime } from "@/types/elf";
+import type { TaskPlan } from "@/types/session";
+import type { QueueTaskFailedPayload, QueueTaskStartedPayload } from "@/types/queue";
+
+/** Parse a queued task's JSON-encoded plan, ignoring malformed plans. */
+function parsePlan(plan: string | null): TaskPlan | undefined {
+  if (!plan) return undefined;
+  try {
+    return JSON.parse(plan) as TaskPlan;
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * Global subscriber for task queue events. Mount once, alongside useSessionEvents.
+ *
+ * When a queued task starts, its session gets a new floor so useSessionEvents can route
+ * the agent's events there. The user stays on their current floor and gets a toast to
+ * jump to the new one. Tasks that fail to start are reported with an error toast.
+ */
+export function useQueueEvents(): void {
+  useEffect(() => {
+    const cleanups: Array<() => void> = [];
+
+    cleanups.push(
+      subscribeSafe<QueueTaskStartedPayload>("queue:task_started", (data) => {
+        const store = useSessionStore.getState();
+        if (store.getFloorBySessionId(data.sessionId)) return;
+
+        const previousFloorId = store.activeFloorId;
+        const floorId = store.createFloor(data.task.slice(0, 30) || "Queued task");
+        const plan = parsePlan(data.plan);
+        const runtime = data.runtime as Runtime;
+
+        store.startSessionOnFloor(floorId, {
+          id: data.sessionId,
+          projectId: data.projectId,
+          task: data.task,
+          runtime,
+          plan,
+        });
+        if (data.ptyId) {
+          store.setFloorPtyId(floorId, data.ptyId);
+        }
+
+        const personality = generateElf();
+        store.addElfToFloor(floorId, {
+          id: `elf-${data.sessionId}`,
+          sessionId: data.sessionId,
+          name: personality.name,
+          role: plan?.roles[0]?.name ?? null,
+          avatar: personality.avatar,
+          color: personality.color,
+          quirk: personality.quirk,
+          runtime,
+          status: "working",
+          spawnedAt: Date.now(),
+          finishedAt: null,
+          parentElfId: null,
+          toolsUsed: [],
+        });
+
+        /* Stay on the floor the user was looking at */
+        if (previousFloorId && previousFloorId !== floorId) {
+          store.switchFloor(previousFloorId);
+        }
+
+        useToastStore.getState().addToast({
+          message: `Queued task started: ${data.task.slice(0, 40)}`,
+          variant: "info",
+          duration: 5000,
+          action: {
+            label: "VIEW",
+            onClick: () => useSessionStore.getState().switchFloor(floorId),
+          },
+        });
+      }),
+    );
+
+    cleanups.push(
+      subscribeSafe<QueueTaskFailedPayload>("queue:task_failed", (data) => {
+        useToastStore.getState().addToast({
+          message: `Queued task failed to start: ${data.error}`,
+          variant: "error",
+          duration: 6000,
+        });
+      }),
+    );
+
+    return () => {
+      cleanups.forEach((cleanup) => cleanup());
+    };
+  }, []);
+}
@@ -0,0 +1,105 @@
+/* Resume session hook — reopens a recorded session in a new floor with `claude --resume`. */
+
+import { useCallback } from "react";
+import { useUiStore } from "@/stores/ui";
+import { useSessionStore } from "@/stores/session";
+import { useWorkspaceStore } from "@/stores/workspace";
+import { startTaskPty } from "@/lib/tauri";
+import { generateElf, getStatusMessage } from "@/lib/elf-names";
+import type { Session } from "@/types/session";
+
+/**
+ * Returns a callback that resumes a session in an interactive PTY on a fresh floor.
+ * The session keeps its ELVES ID (the backend reopens its row), so its history stays in one place.
+ * `workingDir` overrides the project root, e.g. to resume inside the session's worktree.
+ * Resolves to false when the session cannot be resumed or the PTY fails to start.
+ */
+export function useResumeSession(): (session: Session, workingDir?: string) => Promise<boolean> {
+  const createFloor = useSessionStore((state) => state.createFloor);
+  const startSessionOnStore = useSessionStore((state) => state.startSession);
+  const addElf = useSessionStore((state) => state.addElf);
+  const addEvent = useSessionStore((state) => state.addEvent);
+  const setFloorPtyId = useSessionStore((state) => state.setFloorPtyId);
+  const setActiveView = useUiStore((state) => state.setActiveView);
+
+  return useCallback(
+    async (session: Session, workingDir?: string): Promise<boolean> => {
+      if (!session.claudeSessionId) return false;
+
+      try {
+        /* Create a fresh floor for the resumed session */
+        const floorId = createFloor(session.task.slice(0, 30) || "Resume");
+
+        const { sessionId, ptyId } = await startTaskPty(
+          session.projectId,
+          "Resuming session...",
+          session.runtime,
+          workingDir,
+          { resumeSessionId: session.claudeSessionId },
+          undefined,
+          session.id,
+        );
+
+        setFloorPtyId(floorId, ptyId);
+
+        /* Wire PTY to workspace store so WorkspaceTerminalView can render it */
+        const wsSlug = `resume-${sessionId.slice(0, 8)}`;
+        const wsStore = useWorkspaceStore.getState();
+        wsStore.addWorkspace({
+          slug: wsSlug,
+          path: workingDir ?? "",
+          branch: "",
+          status: "active",
+          filesChanged: 0,
+          lastModified: new Date().toISOString(),
+        });
+        wsStore.setPtyId(wsSlug, ptyId);
+        wsStore.openWorkspace(wsSlug);
+
+        /* Start session on the new floor */
+        startSessionOnStore({
+          id: sessionId,
+          projectId: session.projectId,
+          task: session.task,
+          runtime: session.runtime,
+          plan: session.plan ?? undefined,
+        });
+
+        const personality = generateElf();
+        addElf({
+          id: `elf-${sessionId}`,
+          sessionId,
+          name: personality.name,
+          role: "Worker",
+          avatar: personality.avatar,
+          color: personality.color,
+          quirk: personality.quirk,
+          runtime: session.runtime,
+          status: "working",
+          spawnedAt: Date.now(),
+          finishedAt: null,
+          parentElfId: null,
+          toolsUsed: [],
+        });
+
+        addEvent({
+          id: `event-resume-${Date.now()}`,
+          timestamp: Date.now(),
+          elfId: `elf-${sessionId}`,
+          elfName: personality.name,
+          runtime: session.runtime,
+          type: "spawn",
+          payload: { role: "Worker" },
+          funnyStatus: getStatusMessage(personality.name, "working"),
+        });
+
+        setActiveView("workspace");
+        return true;
+      } catch (error) {
+        console.error("Failed to resume session:", error);
+        return false;
+      }
+    },
+    [createFloor, startSessionOnStore, addElf, addEvent, setFloorPtyId, setActiveView],
+  );
+}
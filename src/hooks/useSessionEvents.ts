@@ -14,14 +14,18 @@ import { useToastStore } from "@/stores/toast";
 import { onEvent, extractSessionMemories } from "@/lib/tauri";
 import { generateElf, getStatusMessage } from "@/lib/elf-names";
 import { playSound } from "@/lib/sounds";
-import type { ElfEventType, ElfStatus } from "@/types/elf";
+import { agentEventToElfStatus, agentEventToFeed } from "@/lib/agent-events";
+import type { AgentEvent, ElfStatus } from "@/types/elf";
+import type { FailureReason } from "@/types/session";
+import type { BudgetScope } from "@/types/budget";
 
-/** Payload shape for `elf:event` Tauri events emitted by the Rust backend. */
+/** Payload shape for `elf:event` Tauri events: the typed events parsed from one agent output line. */
 interface ElfEventPayload {
   readonly sessionId: string;
-  readonly eventType: string;
-  readonly payload: Record<string, unknown>;
+  readonly elfId?: string | null;
+  readonly events: readonly AgentEvent[];
   readonly timestamp: number;
+  readonly runtime?: string | null;
 }
 
 /** Payload shape for `session:completed` Tauri events emitted when the Claude process exits. */
@@ -29,129 +33,86 @@ interface SessionCompletedPayload {
   readonly sessionId: string;
 }
 
-/** Payload shape for `session:cancelled` Tauri events emitted when the user stops a task. */
-interface SessionCancelledPayload {
+/** Payload shape for `session:failed` Tauri events emitted when the agent process fails. */
+interface SessionFailedPayload {
   readonly sessionId: string;
+  readonly reason: FailureReason;
+  readonly exitCode: number | null;
+  readonly message: string;
 }
 
-/** A parsed sub-event extracted from a single Claude stream-json line. */
-interface ParsedEvent {
-  readonly type: ElfEventType;
-  readonly payload: Record<string, unknown>;
+/** Payload shape for `session:retrying` Tauri events emitted before a failed run is retried. */
+interface SessionRetryingPayload {
+  readonly sessionId: string;
+  readonly attempt: number;
+  readonly maxAttempts: number;
+  readonly reason: FailureReason;
+  readonly delaySecs: number;
+  /** Whether the retry resumes the Claude conversation instead of restarting. */
+  readonly resume: boolean;
+  readonly message: string;
 }
 
-/**
- * Parse a raw Claude stream-json event into one or more typed ElfEvents.
- *
- * Claude's `--verbose --output-format stream-json` emits structured events:
- * - `assistant` -> message.content[] with text, tool_use, and thinking blocks
- * - `user` -> message.content[] with tool_result blocks
- * - `result` -> final output with cost/token data
- * - `system` -> initialization metadata (skipped)
- *
- * A single `assistant` event may produce multiple parsed events (e.g. text + tool_use).
- */
-function parseClaudePayload(
-  eventType: string,
-  rawPayload: Record<string, unknown>,
-): readonly ParsedEvent[] {
-  if (eventType === "assistant") {
-    const message = rawPayload.message as Record<string, unknown> | undefined;
-    if (!message) return [{ type: "output", payload: rawPayload }];
-
-    const contentBlocks = (message.content as Array<Record<string, unknown>>) ?? [];
-    const results: ParsedEvent[] = [];
-
-    for (const block of contentBlocks) {
-      if (block.type === "tool_use") {
-        results.push({
-          type: "tool_call",
-          payload: { tool: block.name, input: block.input, toolUseId: block.id },
-        });
-      } else if (block.type === "thinking") {
-        const text = String(block.thinking ?? "");
-        if (text.trim()) {
-          results.push({ type: "thinking", payload: { text } });
-        }
-      } else if (block.type === "text") {
-        const text = String(block.text ?? "");
-        if (text.trim()) {
-          results.push({ type: "output", payload: { text } });
-        }
-      }
-    }
+/** User-facing label for each failure reason. */
+const FAILURE_LABEL: Record<FailureReason, string> = {
+  auth: "Authentication failed",
+  rate_limit: "Rate limited",
+  budget_exceeded: "Budget exceeded",
+  crash: "Agent crashed",
+  killed: "Agent was killed",
+};
 
-    return results.length > 0 ? results : [];
-  }
+/** Which watchdog limit a session ran past, or "idle" for a stall warning. */
+type WatchdogReason = "wall_clock" | "idle" | "events" | "tool_calls";
 
-  if (eventType === "user") {
-    const message = rawPayload.message as Record<string, unknown> | undefined;
-    if (!message) return [{ type: "tool_result", payload: rawPayload }];
+/** Payload shape for `session:stalled` Tauri events emitted by the session watchdog. */
+interface SessionStalledPayload {
+  readonly sessionId: string;
+  readonly reason: WatchdogReason;
+  /** Seconds without activity, for stall warnings. */
+  readonly idleSecs?: number;
+  /** True when a limit was exceeded and the session is being stopped. */
+  readonly stopping: boolean;
+}
 
-    const contentBlocks = (message.content as Array<Record<string, unknown>>) ?? [];
-    const results: ParsedEvent[] = [];
+/** Payload shape for `session:timed_out` Tauri events emitted once the watchdog stopped a session. */
+interface SessionTimedOutPayload {
+  readonly sessionId: string;
+  readonly reason: WatchdogReason;
+  readonly summary: string;
+}
 
-    for (const block of contentBlocks) {
-      if (block.type === "tool_result") {
-        const content = String(block.content ?? "").slice(0, 300);
-        results.push({
-          type: "tool_result",
-          payload: { result: content, toolUseId: block.tool_use_id },
-        });
-      }
-    }
+/** User-facing label for each watchdog limit. */
+const WATCHDOG_LABEL: Record<WatchdogReason, string> = {
+  wall_clock: "Maximum run time reached",
+  idle: "No activity",
+  events: "Too many events",
+  tool_calls: "Too many tool calls",
+};
 
-    return results.length > 0 ? results : [];
-  }
-
-  if (eventType === "result") {
-    /* The result text duplicates the final assistant text block already emitted above.
-     * Only emit a task_update with cost metadata to avoid duplicate output in the card view. */
-    const cost = rawPayload.cost_usd as number | undefined;
-    return [{
-      type: "task_update",
-      payload: {
-        status: "completed",
-        isFinal: true,
-        cost: cost ?? 0,
-        message: "Session finished",
-      },
-    }];
-  }
-
-  /* Skip system init events — they're metadata, not interesting for the feed */
-  if (eventType === "system") return [];
-
-  /* Legacy/direct types from older Claude versions or non-JSON lines */
-  if (eventType === "tool_use") return [{ type: "tool_call", payload: rawPayload }];
-  if (eventType === "tool_result") return [{ type: "tool_result", payload: rawPayload }];
-  if (eventType === "thinking") return [{ type: "thinking", payload: rawPayload }];
-
-  /* Default: wrap as output if there's any text content */
-  const text = rawPayload.text as string | undefined;
-  if (text) return [{ type: "output", payload: { text } }];
-
-  return [{ type: "output", payload: rawPayload }];
+/** Payload shape for `budget:warning` Tauri events emitted as spend approaches a cap. */
+interface BudgetWarningPayload {
+  readonly sessionId: string;
+  readonly projectId: string;
+  readonly scope: BudgetScope;
+  /** Fraction of the cap passed, e.g. 0.8. */
+  readonly threshold: number;
+  readonly spentUsd: number;
+  readonly limitUsd: number;
 }
 
-/** Maps raw Claude event types to elf statuses for real-time card updates. */
-function eventTypeToElfStatus(eventType: string): ElfStatus | null {
-  switch (eventType) {
-    case "assistant":
-      return "working";
-    case "user":
-      return "working";
-    case "thinking":
-      return "thinking";
-    case "tool_use":
-    case "tool_result":
-      return "working";
-    case "output":
-    case "result":
-      return "working";
-    default:
-      return null;
-  }
+/** User-facing label for each budget cap. */
+const BUDGET_LABEL: Record<BudgetScope, string> = {
+  session: "session budget",
+  project_daily: "project daily budget",
+  project_monthly: "project monthly budget",
+  global_daily: "daily budget",
+  global_monthly: "monthly budget",
+};
+
+/** Payload shape for `session:cancelled` Tauri events emitted when the user stops a task. */
+interface SessionCancelledPayload {
+  readonly sessionId: string;
 }
 
 /**
@@ -164,7 +125,7 @@ function eventTypeToElfStatus(eventType: string): ElfStatus | null {
  * This prevents the classic StrictMode double-subscription race where cleanup runs
  * before async `.then()` callbacks populate the cleanup array.
  */
-function subscribeSafe<T>(
+export function subscribeSafe<T>(
   eventName: string,
   handler: (data: T) => void,
 ): () => void {
@@ -237,9 +198,13 @@ export function useSessionEvents(): void {
         }
         if (!targetElf) return;
 
-        /* Parse the raw Claude event into one or more typed ElfEvents */
-        const parsed = parseClaudePayload(data.eventType, data.payload);
-        for (const entry of parsed) {
+        /* Map each typed agent event onto the feed */
+        let newStatus: ElfStatus | null = null;
+        for (const agentEvent of data.events) {
+          newStatus = agentEventToElfStatus(agentEvent) ?? newStatus;
+          const entry = agentEventToFeed(agentEvent);
+          if (!entry) continue;
+
           store.addEventToFloor(floorId, {
             id: `event-${entry.type}-${data.timestamp}-${Math.random().toString(36).slice(2, 6)}`,
             timestamp: data.timestamp * 1000,
@@ -327,8 +292,7 @@ export function useSessionEvents(): void {
           }
         }
 
-        /* Update elf status based on raw event type */
-        const newStatus = eventTypeToElfStatus(data.eventType);
+        /* Update elf status based on the latest typed event */
         if (newStatus && targetElf.status !== newStatus) {
           store.updateElfStatusOnFloor(floorId, targetElf.id, newStatus);
         }
@@ -432,6 +396,141 @@ export function useSessionEvents(): void {
       }),
     );
 
+    /* Listen for session failure (agent exited with an error, was killed, or crashed) */
+    cleanups.push(
+      subscribeSafe<SessionFailedPayload>("session:failed", (data) => {
+        const store = useSessionStore.getState();
+        const floorId = store.getFloorBySessionId(data.sessionId);
+        if (!floorId) return;
+
+        const floor = store.floors[floorId];
+        if (!floor || floor.session?.status === "failed") return;
+
+        const label = FAILURE_LABEL[data.reason] ?? "Session failed";
+        store.updateAllElfStatusOnFloor(floorId, "error");
+        store.addEventToFloor(floorId, {
+          id: `event-failed-${Date.now()}`,
+          timestamp: Date.now(),
+          elfId: "system",
+          elfName: "System",
+          runtime: floor.session?.runtime ?? "claude-code",
+          type: "error",
+          payload: { message: `${label}: ${data.message}`, reason: data.reason, exitCode: data.exitCode },
+          funnyStatus: label,
+        });
+        store.endSessionOnFloor(floorId, "failed");
+
+        const floorLabel = floor.label || "another floor";
+        useToastStore.getState().addToast({
+          message: store.activeFloorId !== floorId ? `${label} on "${floorLabel}"` : label,
+          variant: "error",
+          duration: 6000,
+        });
+      }),
+    );
+
+    /* Listen for automatic retries (run failed, project retry policy will relaunch it) */
+    cleanups.push(
+      subscribeSafe<SessionRetryingPayload>("session:retrying", (data) => {
+        const store = useSessionStore.getState();
+        const floorId = store.getFloorBySessionId(data.sessionId);
+        if (!floorId) return;
+
+        const floor = store.floors[floorId];
+        if (!floor) return;
+
+        const label = FAILURE_LABEL[data.reason] ?? "Run failed";
+        const how = data.resume ? "resuming" : "restarting";
+        store.updateAllElfStatusOnFloor(floorId, "waiting");
+        store.addEventToFloor(floorId, {
+          id: `event-retry-${data.attempt}-${Date.now()}`,
+          timestamp: Date.now(),
+          elfId: "system",
+          elfName: "System",
+          runtime: floor.session?.runtime ?? "claude-code",
+          type: "task_update",
+          payload: {
+            status: "retrying",
+            message: `${label} — ${how} in ${data.delaySecs}s (attempt ${data.attempt} of ${data.maxAttempts})`,
+            reason: data.reason,
+            attempt: data.attempt,
+          },
+          funnyStatus: `${label}, trying again soon`,
+        });
+      }),
+    );
+
+    /* Listen for watchdog stall warnings and limit breaches */
+    cleanups.push(
+      subscribeSafe<SessionStalledPayload>("session:stalled", (data) => {
+        const store = useSessionStore.getState();
+        const floorId = store.getFloorBySessionId(data.sessionId);
+        if (!floorId) return;
+
+        const floor = store.floors[floorId];
+        if (!floor) return;
+
+        const label = WATCHDOG_LABEL[data.reason] ?? "Session stalled";
+        const message = data.stopping
+          ? `${label} — stopping the session`
+          : `No activity for ${Math.round((data.idleSecs ?? 0) / 60)} min`;
+        store.addEventToFloor(floorId, {
+          id: `event-stalled-${Date.now()}`,
+          timestamp: Date.now(),
+          elfId: "system",
+          elfName: "System",
+          runtime: floor.session?.runtime ?? "claude-code",
+          type: "task_update",
+          payload: { status: "stalled", message, reason: data.reason },
+          funnyStatus: data.stopping ? "The watchdog is stepping in" : "Things have gone quiet",
+        });
+
+        const floorLabel = floor.label || "another floor";
+        useToastStore.getState().addToast({
+          message: store.activeFloorId !== floorId ? `${message} on "${floorLabel}"` : message,
+          variant: "warning",
+          duration: 6000,
+        });
+      }),
+    );
+
+    /* Listen for sessions the watchdog stopped */
+    cleanups.push(
+      subscribeSafe<SessionTimedOutPayload>("session:timed_out", (data) => {
+        const store = useSessionStore.getState();
+        const floorId = store.getFloorBySessionId(data.sessionId);
+        if (!floorId) return;
+
+        const floor = store.floors[floorId];
+        if (!floor || floor.session?.status === "timed_out") return;
+
+        store.updateAllElfStatusOnFloor(floorId, "done");
+        store.addEventToFloor(floorId, {
+          id: `event-timed-out-${Date.now()}`,
+          timestamp: Date.now(),
+          elfId: "system",
+          elfName: "System",
+          runtime: floor.session?.runtime ?? "claude-code",
+          type: "error",
+          payload: { message: data.summary, reason: data.reason },
+          funnyStatus: "Time's up",
+        });
+        store.endSessionOnFloor(floorId, "timed_out");
+      }),
+    );
+
+    /* Listen for spend approaching a budget cap; reaching one fails the session via session:failed */
+    cleanups.push(
+      subscribeSafe<BudgetWarningPayload>("budget:warning", (data) => {
+        const label = BUDGET_LABEL[data.scope] ?? "budget";
+        useToastStore.getState().addToast({
+          message: `Spent $${data.spentUsd.toFixed(2)} of the $${data.limitUsd.toFixed(2)} ${label} (${Math.round(data.threshold * 100)}%)`,
+          variant: "warning",
+          duration: 6000,
+        });
+      }),
+    );
+
     /* Listen for session cancellation (user stopped the task) */
     cleanups.push(
       subscribeSafe<SessionCancelledPayload>("session:cancelled", (data) => {
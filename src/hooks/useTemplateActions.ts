@@ -77,11 +77,13 @@ export function useTemplateActions(): {
     [removeTemplate],
   );
 
-  /** Load a specific template by ID. Returns the full template or null on error. */
+  /** Load a specific template by ID. Returns the full template or null on error.
+   *  The plan is tagged with the template ID, so sessions started from it record it. */
   const handleLoadTemplate = useCallback(
     async (templateId: string): Promise<Template | null> => {
       try {
-        return await invokeLoadTemplate(templateId);
+        const template = await invokeLoadTemplate(templateId);
+        return { ...template, plan: { ...template.plan, templateId: template.id } };
       } catch (error) {
         console.error("Failed to load template:", error);
         return null;
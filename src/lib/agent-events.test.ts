@@ -0,0 +1,42 @@
+/* Tests for typed agent event helpers. */
+
+import { describe, it, expect } from "vitest";
+import { agentEventToFeed, agentEventToElfStatus, parseStoredAgentEvent } from "./agent-events";
+
+describe("agentEventToFeed", () => {
+  it("maps tool calls with the tool name and id", () => {
+    const entry = agentEventToFeed({ kind: "tool_call", id: "t1", name: "Agent", input: { description: "x" } });
+    expect(entry).toEqual({
+      type: "tool_call",
+      payload: { tool: "Agent", input: { description: "x" }, toolUseId: "t1" },
+    });
+  });
+
+  it("maps text to output and file edits to file changes", () => {
+    expect(agentEventToFeed({ kind: "text", text: "hi" })?.type).toBe("output");
+    expect(agentEventToFeed({ kind: "file_edit", path: "a.rs" })?.payload).toEqual({ path: "a.rs" });
+  });
+
+  it("skips metadata-only kinds", () => {
+    expect(agentEventToFeed({ kind: "init", sessionId: "s", model: null })).toBeNull();
+    expect(
+      agentEventToFeed({ kind: "usage", inputTokens: 1, outputTokens: 2, cacheReadTokens: 0, cacheWriteTokens: 0 }),
+    ).toBeNull();
+  });
+});
+
+describe("agentEventToElfStatus", () => {
+  it("derives statuses from kinds", () => {
+    expect(agentEventToElfStatus({ kind: "thinking", text: "hmm" })).toBe("thinking");
+    expect(agentEventToElfStatus({ kind: "tool_result", id: null, isError: false, content: "" })).toBe("working");
+    expect(agentEventToElfStatus({ kind: "error", message: "boom" })).toBeNull();
+  });
+});
+
+describe("parseStoredAgentEvent", () => {
+  it("parses typed rows and ignores raw ones", () => {
+    expect(parseStoredAgentEvent("text", '{"kind":"text","text":"hi"}')).toEqual({ kind: "text", text: "hi" });
+    expect(parseStoredAgentEvent(null, '{"type":"system"}')).toBeNull();
+    expect(parseStoredAgentEvent("text", "not json")).toBeNull();
+  });
+});
@@ -0,0 +1,78 @@
+/* Agent event helpers — map typed AgentEvents from the backend onto feed events and elf statuses. */
+
+import type { AgentEvent, ElfEventType, ElfStatus } from "@/types/elf";
+
+/** A feed entry derived from one typed agent event. */
+export interface FeedEntry {
+  readonly type: ElfEventType;
+  readonly payload: Record<string, unknown>;
+}
+
+/**
+ * Map a typed agent event onto the activity feed.
+ * Returns null for metadata-only kinds (init, usage) that have no feed representation.
+ */
+export function agentEventToFeed(event: AgentEvent): FeedEntry | null {
+  switch (event.kind) {
+    case "thinking":
+      return { type: "thinking", payload: { text: event.text } };
+    case "text":
+      return { type: "output", payload: { text: event.text } };
+    case "tool_call":
+      return {
+        type: "tool_call",
+        payload: { tool: event.name, input: event.input, toolUseId: event.id },
+      };
+    case "tool_result":
+      return {
+        type: "tool_result",
+        payload: { result: event.content.slice(0, 300), toolUseId: event.id, isError: event.isError },
+      };
+    case "file_edit":
+      return { type: "file_change", payload: { path: event.path } };
+    case "result":
+      /* The result text duplicates the final assistant text already in the feed,
+       * so only surface the outcome and cost. */
+      return {
+        type: "task_update",
+        payload: {
+          status: "completed",
+          isFinal: true,
+          cost: event.costUsd ?? 0,
+          message: event.isError ? "Session ended with an error" : "Session finished",
+        },
+      };
+    case "error":
+      return { type: "error", payload: { message: event.message } };
+    case "init":
+    case "usage":
+      return null;
+  }
+}
+
+/** Elf status implied by a typed agent event, or null when it implies no change. */
+export function agentEventToElfStatus(event: AgentEvent): ElfStatus | null {
+  switch (event.kind) {
+    case "thinking":
+      return "thinking";
+    case "text":
+    case "tool_call":
+    case "tool_result":
+    case "file_edit":
+    case "result":
+      return "working";
+    default:
+      return null;
+  }
+}
+
+/** Parse a stored event row's payload as a typed AgentEvent. Returns null for raw rows (no kind). */
+export function parseStoredAgentEvent(kind: string | null, payload: string): AgentEvent | null {
+  if (!kind) return null;
+  try {
+    const parsed = JSON.parse(payload) as AgentEvent;
+    return parsed.kind === kind ? parsed : null;
+  } catch {
+    return null;
+  }
+}
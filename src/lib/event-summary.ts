@@ -7,8 +7,12 @@ export const EVENT_TYPE_COLOR: Record<string, string> = {
   tool_call: "#FF8B3D",
   tool_result: "#6BCB77",
   output: "#FFD93D",
+  text: "#FFD93D",
   result: "#FFD93D",
+  file_edit: "#FFE4B5",
   error: "#FF6B6B",
+  stderr: "#FF9F9F",
+  retry: "#FFB86B",
   spawn: "#E0C3FC",
   task_update: "#B8E6D0",
 };
@@ -30,18 +34,26 @@ export function summarizeEventPayload(eventType: string, payloadStr: string): st
         return `${tool}(...)`;
       }
       case "tool_result": {
-        const output = (payload.output ?? payload.result ?? "") as string;
+        const output = (payload.content ?? payload.output ?? payload.result ?? "") as string;
         return typeof output === "string"
           ? output.slice(0, 150)
           : JSON.stringify(output).slice(0, 150);
       }
       case "output":
+      case "text":
       case "result":
         return typeof payload.text === "string"
           ? payload.text.slice(0, 150)
           : typeof payload.result === "string"
             ? (payload.result as string).slice(0, 150)
             : JSON.stringify(payload).slice(0, 150);
+      case "file_edit":
+        return typeof payload.path === "string" ? `Edited ${payload.path}` : "Edited a file";
+      case "usage":
+        return `${String(payload.inputTokens ?? 0)} in / ${String(payload.outputTokens ?? 0)} out tokens`;
+      case "stderr":
+      case "retry":
+        return typeof payload.text === "string" ? payload.text.slice(0, 150) : "";
       case "error":
         return typeof payload.message === "string"
           ? payload.message
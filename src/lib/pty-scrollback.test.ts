@@ -0,0 +1,19 @@
+/* Tests for PTY scrollback rehydration. */
+
+import { describe, it, expect } from "vitest";
+import { unseenOutput } from "./pty-scrollback";
+
+describe("unseenOutput", () => {
+  it("drops output already in the scrollback", () => {
+    expect(unseenOutput("$ ls\r\nsrc\r\n", "src\r\n")).toBe("");
+  });
+
+  it("keeps output streamed after the scrollback was read", () => {
+    expect(unseenOutput("$ ls\r\nsrc\r\n", "src\r\n$ ")).toBe("$ ");
+  });
+
+  it("keeps everything when nothing overlaps", () => {
+    expect(unseenOutput("$ ls\r\n", "done")).toBe("done");
+    expect(unseenOutput("", "done")).toBe("done");
+  });
+});
@@ -0,0 +1,17 @@
+/* PTY scrollback rehydration — merges a PTY's server-side scrollback with output streamed
+ * while it was being read, so a reattached terminal shows its history exactly once. */
+
+/**
+ * The part of `pending` not already at the end of `scrollback`.
+ *
+ * Output that arrives after subscribing but before the scrollback is read is both in the
+ * scrollback and streamed; only what follows the overlap still needs writing.
+ */
+export function unseenOutput(scrollback: string, pending: string): string {
+  for (let overlap = Math.min(scrollback.length, pending.length); overlap > 0; overlap--) {
+    if (scrollback.endsWith(pending.slice(0, overlap))) {
+      return pending.slice(overlap);
+    }
+  }
+  return pending;
+}
@@ -4,17 +4,20 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import type { RuntimeInfo } from "@/types/runtime";
 import type { Project } from "@/types/project";
-import type { Session, TaskPlan } from "@/types/session";
-import type { MemoryEntry, ExtractionResult } from "@/types/memory";
+import type { ExternalClaudeSession, InterruptedSession, Session, SessionAttempt, SessionRecording, SessionSearchHit, SessionSearchQuery, SessionUsage, TaskPlan } from "@/types/session";
+import type { MemoryEntry, ExtractionResult, MemoryPinRequest } from "@/types/memory";
 import type { Skill } from "@/types/skill";
-import type { McpServer, McpCatalogItem, McpImportResult } from "@/types/mcp";
+import type { McpServer, McpCatalogItem, McpImportResult, McpServerInventory, McpResourceContent, McpPromptResult, McpToolCallResult, McpTransport, McpSyncTarget, McpSyncPreview, McpSyncResult } from "@/types/mcp";
 import type { Template } from "@/types/template";
+import type { SecretsStatus } from "@/types/secrets";
 import type { ClaudeDiscovery, ClaudeSpawnOptions } from "@/types/claude";
 import type { FileEntry } from "@/types/filesystem";
 import type { SkillSource, SkillUpdateInfo, SkillSearchResultV2, CatalogSkillItem, RemoteSkillResult } from "@/types/skill-registry";
 import type { GitBranchInfo, GitCommit } from "@/types/git";
 import type { GitState, WorktreeInfo } from "@/types/git-state";
-import type { WorkspaceInfo, WorkspaceDiff, ProjectConfig, ProjectTopology, MultiRepoWorkspace } from "@/types/workspace";
+import type { WorkspaceInfo, WorkspaceDiff, ProjectConfig, ProjectTopology, MultiRepoWorkspace, WatchdogLimits } from "@/types/workspace";
+import type { QueuedTask } from "@/types/queue";
+import type { BudgetStatus } from "@/types/budget";
 
 /** Detect available AI runtimes (Claude Code, Codex) on the system */
 export async function detectRuntimes(): Promise<RuntimeInfo> {
@@ -57,6 +60,51 @@ export async function listSessions(projectId: string): Promise<Session[]> {
   return invoke<Session[]>("list_sessions", { projectId });
 }
 
+/** List a session's run attempts, first first. Retried sessions have more than one. */
+export async function listSessionAttempts(sessionId: string): Promise<SessionAttempt[]> {
+  return invoke<SessionAttempt[]>("list_session_attempts", { sessionId });
+}
+
+/** A session's usage and cost per model (and per elf in team runs), which add up to its totals. */
+export async function getSessionUsage(sessionId: string): Promise<SessionUsage[]> {
+  return invoke<SessionUsage[]>("get_session_usage", { sessionId });
+}
+
+/** A session's terminal recordings, which replays embed. */
+export async function listSessionRecordings(sessionId: string): Promise<SessionRecording[]> {
+  return invoke<SessionRecording[]>("list_session_recordings", { sessionId });
+}
+
+/** Override the watchdog limits of a running session. Returns false if it is not running. */
+export async function setSessionLimits(sessionId: string, limits: WatchdogLimits): Promise<boolean> {
+  return invoke<boolean>("set_session_limits", { sessionId, limits });
+}
+
+/** List sessions interrupted by the last app exit, most recent first. */
+export async function listInterruptedSessions(): Promise<InterruptedSession[]> {
+  return invoke<InterruptedSession[]>("list_interrupted_sessions");
+}
+
+/** Stop offering an interrupted session for resume; it is recorded as failed. */
+export async function dismissInterruptedSession(sessionId: string): Promise<boolean> {
+  return invoke<boolean>("dismiss_interrupted_session", { sessionId });
+}
+
+/** Full-text search across session tasks, summaries, and event content, ranked best first. */
+export async function searchSessions(search: SessionSearchQuery): Promise<SessionSearchHit[]> {
+  return invoke<SessionSearchHit[]>("search_sessions", { search });
+}
+
+/** List the Claude Code sessions run in a project's directory, newest first. */
+export async function listExternalClaudeSessions(projectPath: string): Promise<ExternalClaudeSession[]> {
+  return invoke<ExternalClaudeSession[]>("list_external_claude_sessions", { projectPath });
+}
+
+/** Import a Claude Code session as a completed ELVES session (or return the existing one). */
+export async function importClaudeSession(projectId: string, claudeSessionId: string): Promise<Session> {
+  return invoke<Session>("import_claude_session", { projectId, claudeSessionId });
+}
+
 /** Event row from the database, used for session history detail view. */
 export interface SessionEvent {
   readonly id: number;
@@ -66,6 +114,8 @@ export interface SessionEvent {
   readonly payload: string;
   readonly funnyStatus: string | null;
   readonly timestamp: number;
+  /** Typed event kind when `payload` is a serialized AgentEvent; null for raw rows. */
+  readonly kind: string | null;
 }
 
 /** List all events for a session, ordered chronologically. */
@@ -73,6 +123,44 @@ export async function listSessionEvents(sessionId: string): Promise<SessionEvent
   return invoke<SessionEvent[]>("list_session_events", { sessionId });
 }
 
+/** One page of session events; pass `nextAfterId` back as `afterId` for the next page. */
+export interface SessionEventPage {
+  readonly events: SessionEvent[];
+  readonly nextAfterId: number | null;
+}
+
+/** Event count for one event type within a session. */
+export interface SessionEventTypeCount {
+  readonly eventType: string;
+  readonly count: number;
+}
+
+/** Total event count and per-type histogram for a session. */
+export interface SessionEventStats {
+  readonly total: number;
+  readonly byType: SessionEventTypeCount[];
+}
+
+/** List one page of a session's events after the `afterId` cursor, optionally filtered by type. */
+export async function listSessionEventsPage(
+  sessionId: string,
+  afterId?: number | null,
+  limit?: number,
+  eventTypes?: readonly string[],
+): Promise<SessionEventPage> {
+  return invoke<SessionEventPage>("list_session_events_page", {
+    sessionId,
+    afterId: afterId ?? null,
+    limit: limit ?? null,
+    eventTypes: eventTypes ?? null,
+  });
+}
+
+/** Count a session's events, in total and per event type. */
+export async function getSessionEventStats(sessionId: string): Promise<SessionEventStats> {
+  return invoke<SessionEventStats>("get_session_event_stats", { sessionId });
+}
+
 /** Mark a session as completed (or failed/cancelled) in the DB.
  * Called from the frontend when a PTY exits so the session status persists beyond in-memory state. */
 export async function completeSession(
@@ -140,7 +228,8 @@ export interface StartTaskPtyResult {
 }
 
 /** Start a task in PTY-first mode — creates session, spawns elf, launches Claude in an interactive PTY.
- * Returns both sessionId (for event routing) and ptyId (for xterm.js wiring). */
+ * Returns both sessionId (for event routing) and ptyId (for xterm.js wiring).
+ * When resuming, `elvesSessionId` reopens that recorded session instead of running untracked. */
 export async function startTaskPty(
   projectId: string,
   task: string,
@@ -148,9 +237,10 @@ export async function startTaskPty(
   workingDir?: string,
   spawnOptions?: ClaudeSpawnOptions,
   worktreeSlug?: string,
+  elvesSessionId?: string,
 ): Promise<StartTaskPtyResult> {
   const options = spawnOptions ? JSON.stringify(spawnOptions) : undefined;
-  return invoke<StartTaskPtyResult>("start_task_pty", { projectId, task, runtime, workingDir, options, worktreeSlug });
+  return invoke<StartTaskPtyResult>("start_task_pty", { projectId, task, runtime, workingDir, options, worktreeSlug, elvesSessionId });
 }
 
 /** Stop a running task. Returns true if a process was killed. */
@@ -206,11 +296,75 @@ export async function startTeamTaskPty(
   return invoke<StartTeamTaskPtyResult>("start_team_task_pty", { projectId, task, plan, options, workingDir, worktreeSlug });
 }
 
+/** A line of PTY scrollback containing the search query. */
+export interface ScrollbackMatch {
+  /** Index of the line in the scrollback, oldest first. */
+  readonly line: number;
+  /** The line as displayed, without escape sequences. */
+  readonly text: string;
+}
+
+/** A PTY's recent output, escape sequences included, for rehydrating a reattached terminal. */
+export async function readPtyScrollback(ptyId: string): Promise<string> {
+  return invoke<string>("read_pty_scrollback", { ptyId });
+}
+
+/** Search a PTY's recent output; returns the most recent matching lines, oldest first. */
+export async function searchPtyScrollback(ptyId: string, query: string, limit?: number): Promise<ScrollbackMatch[]> {
+  return invoke<ScrollbackMatch[]>("search_pty_scrollback", { ptyId, query, limit: limit ?? null });
+}
+
 /** Stop a team task. Kills all agent processes for the session. */
 export async function stopTeamTask(sessionId: string): Promise<boolean> {
   return invoke<boolean>("stop_team_task", { sessionId });
 }
 
+/** Options for enqueueTask. */
+export interface EnqueueTaskOptions {
+  readonly workingDir?: string;
+  readonly spawnOptions?: ClaudeSpawnOptions;
+  readonly worktreeSlug?: string;
+  /** Team plan; the task starts as a team task when set. */
+  readonly plan?: TaskPlan;
+  /** Higher-priority tasks are queued ahead of lower ones. Defaults to 0. */
+  readonly priority?: number;
+  /** Start a solo task in an interactive PTY instead of print mode. */
+  readonly pty?: boolean;
+}
+
+/** Queue a task to start once the concurrency caps allow, possibly right away.
+ * The dispatcher emits `queue:task_started` with the session ID when it starts. */
+export async function enqueueTask(
+  projectId: string,
+  task: string,
+  runtime: string,
+  queueOptions: EnqueueTaskOptions = {},
+): Promise<QueuedTask> {
+  const { workingDir, spawnOptions, worktreeSlug, plan, priority, pty } = queueOptions;
+  const options = spawnOptions ? JSON.stringify(spawnOptions) : undefined;
+  return invoke<QueuedTask>("enqueue_task", { projectId, task, runtime, workingDir, options, worktreeSlug, plan, priority, pty });
+}
+
+/** List tasks waiting to start, in start order, optionally for one project. */
+export async function listTaskQueue(projectId?: string): Promise<QueuedTask[]> {
+  return invoke<QueuedTask[]>("list_task_queue", { projectId });
+}
+
+/** Move the given waiting tasks to the front of the queue, in the given order. */
+export async function reorderQueue(ids: readonly string[]): Promise<void> {
+  return invoke<void>("reorder_queue", { ids });
+}
+
+/** Remove a waiting task from the queue. Returns false if it already started. */
+export async function cancelQueued(id: string): Promise<boolean> {
+  return invoke<boolean>("cancel_queued", { id });
+}
+
+/** Spend today and this month against a project's caps and the app-wide caps. */
+export async function getBudgetStatus(projectId: string): Promise<BudgetStatus> {
+  return invoke<BudgetStatus>("get_budget_status", { projectId });
+}
+
 /** Transition a session from non-interactive print mode to interactive terminal.
  * Kills the --print process and marks the session so the backend suppresses
  * the false session:completed event. The frontend then spawns a PTY terminal
@@ -266,6 +420,16 @@ export async function searchMemories(projectId: string, query: string): Promise<
   return invoke<MemoryEntry[]>("search_memories", { projectId, query });
 }
 
+/** List pending requests from agents (via the ELVES MCP server) to pin a memory. */
+export async function listMemoryPinRequests(projectId?: string): Promise<MemoryPinRequest[]> {
+  return invoke<MemoryPinRequest[]>("list_memory_pin_requests", { projectId });
+}
+
+/** Approve (pins the memory) or dismiss an agent's pin request. */
+export async function resolveMemoryPinRequest(id: number, approve: boolean): Promise<boolean> {
+  return invoke<boolean>("resolve_memory_pin_request", { id, approve });
+}
+
 /** Run relevance decay on all non-pinned memories. Returns count decayed. */
 export async function decayMemories(): Promise<number> {
   return invoke<number>("decay_memories");
@@ -418,6 +582,18 @@ export async function addMcpServer(
   return invoke<McpServer>("add_mcp_server", { id, name, command, args, env, scope });
 }
 
+/** Add a remote MCP server reached over Streamable HTTP or legacy SSE. Headers is a JSON object string. */
+export async function addRemoteMcpServer(
+  name: string,
+  transport: Exclude<McpTransport, "stdio">,
+  url: string,
+  headers?: string,
+  scope?: string,
+): Promise<McpServer> {
+  const id = `mcp-${Date.now()}-${Math.random().toString(36).slice(2, 9)}`;
+  return invoke<McpServer>("add_mcp_server", { id, name, command: url, scope, transport, url, headers });
+}
+
 /** Toggle an MCP server enabled/disabled. */
 export async function toggleMcpServer(id: string, enabled: boolean): Promise<boolean> {
   return invoke<boolean>("toggle_mcp_server", { id, enabled });
@@ -433,6 +609,24 @@ export async function importMcpFromClaude(): Promise<McpImportResult> {
   return invoke<McpImportResult>("import_mcp_from_claude");
 }
 
+/** Compare ELVES MCP servers with Claude Code, .mcp.json, or Codex config without changing anything. */
+export async function previewMcpSync(
+  target: McpSyncTarget,
+  projectId?: string,
+): Promise<McpSyncPreview> {
+  return invoke<McpSyncPreview>("preview_mcp_sync", { target, projectId });
+}
+
+/** Apply a sync for the selected server names — "import" into ELVES or "export" to the external file. */
+export async function applyMcpSync(
+  target: McpSyncTarget,
+  direction: "import" | "export",
+  names: string[],
+  projectId?: string,
+): Promise<McpSyncResult> {
+  return invoke<McpSyncResult>("apply_mcp_sync", { target, projectId, direction, names });
+}
+
 /** Load the curated MCP server catalog. Returns a hardcoded list — no network dependency. */
 export async function loadMcpCatalog(): Promise<McpCatalogItem[]> {
   return invoke<McpCatalogItem[]>("load_mcp_catalog");
@@ -449,11 +643,74 @@ export interface McpTool {
   readonly description: string | null;
 }
 
-/** Spawn an MCP server and query its available tools via JSON-RPC. Times out after 5s. */
+/** Spawn an MCP server and query its available tools via JSON-RPC. Times out after 10s. */
 export async function listMcpTools(id: string): Promise<McpTool[]> {
   return invoke<McpTool[]>("list_mcp_tools", { id });
 }
 
+/** Spawn an MCP server and list its capabilities, tools, resources, templates, and prompts. */
+export async function inspectMcpServer(id: string): Promise<McpServerInventory> {
+  return invoke<McpServerInventory>("inspect_mcp_server", { id });
+}
+
+/** Read one resource from an MCP server by URI. */
+export async function readMcpResource(id: string, uri: string): Promise<McpResourceContent[]> {
+  return invoke<McpResourceContent[]>("read_mcp_resource", { id, uri });
+}
+
+/** Render a prompt from an MCP server with the given arguments. */
+export async function getMcpPrompt(
+  id: string,
+  name: string,
+  args?: Record<string, string>,
+): Promise<McpPromptResult> {
+  return invoke<McpPromptResult>("get_mcp_prompt", { id, name, arguments: args });
+}
+
+/** Call a tool on an MCP server. Tool failures resolve with `isError` set. Times out after 60s. */
+export async function callMcpTool(
+  id: string,
+  name: string,
+  args?: Record<string, unknown>,
+): Promise<McpToolCallResult> {
+  return invoke<McpToolCallResult>("call_mcp_tool", { id, name, arguments: args });
+}
+
+/* ── Secrets commands ────────────────────────────────────────── */
+
+/** Report whether the secrets store is unlocked and which secret names it holds. */
+export async function getSecretsStatus(): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("get_secrets_status");
+}
+
+/** Unlock a passphrase-protected secrets store. On first use this sets the passphrase. */
+export async function unlockSecrets(passphrase: string): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("unlock_secrets", { passphrase });
+}
+
+/** Drop decrypted secret values from memory until the store is unlocked again. */
+export async function lockSecrets(): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("lock_secrets");
+}
+
+/** Add or replace a secret. Reference it from MCP configs as `secret://<name>`. */
+export async function setSecret(name: string, value: string): Promise<void> {
+  return invoke<void>("set_secret", { name, value });
+}
+
+/** Delete a secret. Returns true if it existed. */
+export async function deleteSecret(name: string): Promise<boolean> {
+  return invoke<boolean>("delete_secret", { name });
+}
+
+/**
+ * Move plaintext MCP credentials into the secrets store. Without `keys`, only
+ * credential-looking env vars and headers are moved. Returns how many moved.
+ */
+export async function migrateMcpSecrets(keys?: string[]): Promise<number> {
+  return invoke<number>("migrate_mcp_secrets", { keys });
+}
+
 /* ── Template commands ───────────────────────────────────────── */
 
 /** List all templates (built-in + custom). */
@@ -778,13 +1035,58 @@ export async function checkHomebrewUpdate(): Promise<string | null> {
 
 /* ── Insights commands ────────────────────────────────────────── */
 
-import type { InsightsData } from "@/types/insights";
+import type { DbInsights, InsightsData, InsightsFilter } from "@/types/insights";
 
 /** Load aggregated usage insights from Claude Code telemetry files and ELVES sessions DB. */
 export async function loadInsights(): Promise<InsightsData> {
   return invoke<InsightsData>("load_insights");
 }
 
+/** Compute insights from the ELVES database, filtered by date range and project. */
+export async function loadDbInsights(filter: InsightsFilter): Promise<DbInsights> {
+  return invoke<DbInsights>("load_db_insights", { filter });
+}
+
+/* ── Maintenance commands ─────────────────────────────────────── */
+
+import type { CompactionReport, DatabaseStats, VacuumReport } from "@/types/maintenance";
+import type { AppSettings } from "@/types/settings";
+
+/** Pin or unpin a session. Pinned sessions are exempt from event retention. */
+export async function setSessionPinned(sessionId: string, pinned: boolean): Promise<void> {
+  return invoke<void>("set_session_pinned", { sessionId, pinned });
+}
+
+/** Database size per table and per project. */
+export async function getDatabaseStats(): Promise<DatabaseStats> {
+  return invoke<DatabaseStats>("database_stats");
+}
+
+/** Checkpoint the WAL and VACUUM the database to reclaim free space. */
+export async function vacuumDatabase(): Promise<VacuumReport> {
+  return invoke<VacuumReport>("vacuum_database");
+}
+
+/** Refresh the query planner statistics. */
+export async function analyzeDatabase(): Promise<void> {
+  return invoke<void>("analyze_database");
+}
+
+/** Compact events older than `retentionDays` (default: the configured retention period). */
+export async function compactSessionEvents(retentionDays?: number): Promise<CompactionReport> {
+  return invoke<CompactionReport>("compact_session_events", { retentionDays: retentionDays ?? null });
+}
+
+/** Read the app-wide settings from ~/.elves/settings.json. */
+export async function getAppSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>("get_app_settings");
+}
+
+/** Replace the app-wide settings. */
+export async function updateAppSettings(settings: AppSettings): Promise<void> {
+  return invoke<void>("update_app_settings", { settings });
+}
+
 /* ── Event subscription ──────────────────────────────────────── */
 
 /** Subscribe to a Tauri event. Returns an unsubscribe function. */
@@ -14,6 +14,9 @@ function createTestServer(overrides?: Partial<McpServer>): McpServer {
     scope: "global",
     enabled: true,
     lastHealthCheck: null,
+    transport: "stdio",
+    url: null,
+    headers: "{}",
     ...overrides,
   };
 }
@@ -29,6 +29,7 @@ export function insightsData(overrides: Partial<InsightsData> = {}): InsightsDat
     topLanguages: [],
     featureAdoption: { taskAgent: 0, mcp: 0, webSearch: 0, webFetch: 0, total: 0 },
     recentSessions: [],
+    runtimes: [],
     reportHtml: null,
     ...overrides,
   };
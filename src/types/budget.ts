@@ -0,0 +1,27 @@
+/* Budget types — spending caps per session, per project per day and month, and across all projects. */
+
+/** Spending caps in USD; a null cap is not enforced. Days and months are UTC. */
+export interface BudgetLimits {
+  readonly enabled: boolean;
+  /** Most a single session may spend. */
+  readonly sessionUsd: number | null;
+  readonly dailyUsd: number | null;
+  readonly monthlyUsd: number | null;
+  /** Fractions of a cap (e.g. 0.8) at which a warning is shown, once per period. */
+  readonly warnAt: readonly number[];
+}
+
+/** Which cap a budget warning or stop is about. */
+export type BudgetScope = "session" | "project_daily" | "project_monthly" | "global_daily" | "global_monthly";
+
+/** A project's caps, the app-wide caps, and what has been spent against them. */
+export interface BudgetStatus {
+  readonly project: BudgetLimits;
+  readonly global: BudgetLimits;
+  readonly projectDayUsd: number;
+  readonly projectMonthUsd: number;
+  readonly globalDayUsd: number;
+  readonly globalMonthUsd: number;
+  /** How much a new session may spend, or null if no cap applies. */
+  readonly remainingUsd: number | null;
+}
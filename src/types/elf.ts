@@ -59,6 +59,56 @@ export interface ElfEvent {
   readonly funnyStatus?: string;
 }
 
+/** Kinds of typed agent events produced by the runtime adapters. */
+export type AgentEventKind =
+  | "init"
+  | "thinking"
+  | "text"
+  | "tool_call"
+  | "tool_result"
+  | "file_edit"
+  | "usage"
+  | "result"
+  | "error";
+
+/** A typed agent event — mirrors the Rust `ElfEvent` enum, tagged by `kind`.
+ * Emitted in `elf:event` and stored as the payload of event rows that have a `kind`. */
+export type AgentEvent =
+  | { readonly kind: "init"; readonly sessionId: string | null; readonly model: string | null }
+  | { readonly kind: "thinking"; readonly text: string }
+  | { readonly kind: "text"; readonly text: string }
+  | {
+      readonly kind: "tool_call";
+      readonly id: string | null;
+      readonly name: string;
+      readonly input: unknown;
+    }
+  | {
+      readonly kind: "tool_result";
+      readonly id: string | null;
+      readonly isError: boolean;
+      readonly content: string;
+    }
+  | { readonly kind: "file_edit"; readonly path: string }
+  | {
+      readonly kind: "usage";
+      readonly inputTokens: number;
+      readonly outputTokens: number;
+      readonly cacheReadTokens: number;
+      readonly cacheWriteTokens: number;
+      /** Set when the runtime breaks usage down by model. */
+      readonly model?: string;
+    }
+  | {
+      readonly kind: "result";
+      readonly text: string | null;
+      readonly isError: boolean;
+      readonly costUsd: number | null;
+      readonly durationMs: number | null;
+      readonly sessionId: string | null;
+    }
+  | { readonly kind: "error"; readonly message: string };
+
 /** Personality profile assigned to each elf on spawn */
 export interface ElfPersonality {
   readonly name: string;
@@ -1,4 +1,7 @@
-/* Insights types — aggregated usage analytics from Claude Code telemetry files. */
+/* Insights types — aggregated usage analytics from Claude Code telemetry files and Codex
+ * rollout logs, and from the ELVES database. */
+
+import type { Runtime } from "./elf";
 
 /** A single day's activity from stats-cache. */
 export interface DailyActivityEntry {
@@ -24,6 +27,8 @@ export interface OutcomeEntry {
 /** Per-model token breakdown from stats-cache. */
 export interface ModelUsageEntry {
   readonly model: string;
+  /** Runtime the model was used with. */
+  readonly runtime: Runtime;
   readonly inputTokens: number;
   readonly outputTokens: number;
   readonly cacheReadTokens: number;
@@ -34,6 +39,7 @@ export interface ModelUsageEntry {
 /** Per-project aggregated summary. */
 export interface ProjectSummary {
   readonly name: string;
+  readonly runtime: Runtime;
   readonly sessions: number;
   readonly linesAdded: number;
   readonly commits: number;
@@ -45,6 +51,7 @@ export interface ProjectSummary {
 export interface SessionSummary {
   readonly sessionId: string;
   readonly project: string;
+  readonly runtime: Runtime;
   readonly startTime: string;
   readonly durationMinutes: number;
   readonly firstPrompt: string;
@@ -55,6 +62,17 @@ export interface SessionSummary {
   readonly commits: number;
 }
 
+/** Totals of one runtime, for comparing Claude Code with Codex. */
+export interface RuntimeSummary {
+  readonly runtime: Runtime;
+  readonly sessions: number;
+  readonly messages: number;
+  readonly inputTokens: number;
+  readonly outputTokens: number;
+  readonly durationMinutes: number;
+  readonly toolCalls: number;
+}
+
 /** Feature adoption stats. */
 export interface FeatureAdoption {
   readonly taskAgent: number;
@@ -106,6 +124,76 @@ export interface InsightsData {
   /* Sessions */
   readonly recentSessions: readonly SessionSummary[];
 
+  /* Runtimes */
+  readonly runtimes: readonly RuntimeSummary[];
+
   /* Report */
   readonly reportHtml: string | null;
 }
+
+/* ── Database insights ─────────────────────────────────────────── */
+
+/** Which records database insights cover. Bounds are Unix seconds; `until` is exclusive. */
+export interface InsightsFilter {
+  readonly since?: number;
+  readonly until?: number;
+  readonly projectId?: string;
+}
+
+/** How finished sessions ended. */
+export interface OutcomeRates {
+  readonly completed: number;
+  /** Sessions that failed, errored or timed out. */
+  readonly failed: number;
+  /** Sessions cancelled or interrupted. */
+  readonly stopped: number;
+  /** Completed sessions as a fraction of completed and failed ones. */
+  readonly successRate: number;
+}
+
+/** Sessions, tokens and cost of the sessions started on one UTC day. */
+export interface DailyUsage {
+  readonly date: string;
+  readonly sessions: number;
+  readonly tokens: number;
+  readonly costUsd: number;
+}
+
+/** Average duration of finished sessions started from one template. */
+export interface TemplateDuration {
+  readonly templateId: string;
+  readonly name: string;
+  readonly sessions: number;
+  readonly avgDurationSecs: number;
+}
+
+/** Memories added on one UTC day, and the total by its end. */
+export interface MemoryGrowth {
+  readonly date: string;
+  readonly added: number;
+  readonly total: number;
+}
+
+/** Workspaces sessions ran in, and how many were merged or opened as a PR. */
+export interface WorkspaceShipRate {
+  readonly workspaces: number;
+  readonly shipped: number;
+  readonly shipRate: number;
+}
+
+/** Insights computed from the ELVES database. */
+export interface DbInsights {
+  readonly totalSessions: number;
+  readonly totalTokens: number;
+  readonly totalCostUsd: number;
+  readonly sessionsByProject: readonly NamedCount[];
+  readonly sessionsByRuntime: readonly NamedCount[];
+  readonly sessionsByStatus: readonly NamedCount[];
+  readonly outcomes: OutcomeRates;
+  readonly failureReasons: readonly NamedCount[];
+  readonly dailyUsage: readonly DailyUsage[];
+  readonly templateDurations: readonly TemplateDuration[];
+  readonly topRoles: readonly NamedCount[];
+  readonly memoryGrowth: readonly MemoryGrowth[];
+  readonly workspaces: WorkspaceShipRate;
+}
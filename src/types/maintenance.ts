@@ -0,0 +1,42 @@
+/* Database maintenance types — size statistics and results of retention, VACUUM, and ANALYZE. */
+
+/** Outcome of one event retention pass. */
+export interface CompactionReport {
+  readonly sessionsCompacted: number;
+  readonly eventsDeleted: number;
+  /** Tool calls whose input was dropped, keeping name and id. */
+  readonly eventsTrimmed: number;
+}
+
+/** Space used by one table, including its indexes. */
+export interface TableSize {
+  readonly name: string;
+  readonly bytes: number;
+}
+
+/** Session and event volume for one project. */
+export interface ProjectSize {
+  readonly projectId: string;
+  /** Null when the project no longer exists. */
+  readonly projectName: string | null;
+  readonly sessions: number;
+  readonly events: number;
+  readonly eventBytes: number;
+}
+
+/** Overall database size, broken down per table and per project. */
+export interface DatabaseStats {
+  readonly fileBytes: number;
+  /** Changes not yet checkpointed into the main file. */
+  readonly walBytes: number;
+  /** Space reclaimable with VACUUM. */
+  readonly freeBytes: number;
+  readonly tables: readonly TableSize[];
+  readonly projects: readonly ProjectSize[];
+}
+
+/** Database size before and after a VACUUM. */
+export interface VacuumReport {
+  readonly bytesBefore: number;
+  readonly bytesAfter: number;
+}
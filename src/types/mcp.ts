@@ -3,6 +3,9 @@
 /** Scope for an MCP server: global or project-specific. */
 export type McpScope = "global" | "project";
 
+/** Wire transport: a spawned process, Streamable HTTP, or legacy HTTP+SSE. */
+export type McpTransport = "stdio" | "http" | "sse";
+
 /** A configured MCP server with connection details and health status. */
 export interface McpServer {
   readonly id: string;
@@ -13,6 +16,11 @@ export interface McpServer {
   readonly scope: McpScope;
   readonly enabled: boolean;
   readonly lastHealthCheck: number | null;
+  readonly transport: McpTransport;
+  /** Server URL for http/sse transports, null for stdio. */
+  readonly url: string | null;
+  /** JSON object of HTTP headers; values may reference `${VAR}`. */
+  readonly headers: string;
 }
 
 /** Parameters for adding a new MCP server. */
@@ -22,6 +30,9 @@ export interface NewMcpServer {
   readonly args?: string[];
   readonly env?: Record<string, string>;
   readonly scope?: McpScope;
+  readonly transport?: McpTransport;
+  readonly url?: string;
+  readonly headers?: Record<string, string>;
 }
 
 /** A curated MCP server entry from the built-in catalog. */
@@ -41,3 +52,139 @@ export interface McpImportResult {
   readonly imported: number;
   readonly scanned: number;
 }
+
+/** Capabilities a server advertised in its `initialize` response. */
+export interface McpServerCapabilities {
+  readonly tools: boolean;
+  readonly resources: boolean;
+  readonly resourcesSubscribe: boolean;
+  readonly prompts: boolean;
+  readonly logging: boolean;
+  readonly listChanged: string[];
+}
+
+/** Server identity and negotiated protocol from the MCP handshake. */
+export interface McpServerInfo {
+  readonly name: string | null;
+  readonly version: string | null;
+  readonly protocolVersion: string | null;
+  readonly instructions: string | null;
+  readonly capabilities: McpServerCapabilities;
+}
+
+/** A concrete resource exposed by an MCP server. */
+export interface McpResource {
+  readonly uri: string;
+  readonly name: string;
+  readonly description: string | null;
+  readonly mimeType: string | null;
+}
+
+/** A parameterized resource (RFC 6570 URI template) exposed by an MCP server. */
+export interface McpResourceTemplate {
+  readonly uriTemplate: string;
+  readonly name: string;
+  readonly description: string | null;
+  readonly mimeType: string | null;
+}
+
+/** One argument accepted by an MCP prompt. */
+export interface McpPromptArgument {
+  readonly name: string;
+  readonly description: string | null;
+  readonly required: boolean;
+}
+
+/** A prompt template exposed by an MCP server. */
+export interface McpPrompt {
+  readonly name: string;
+  readonly description: string | null;
+  readonly arguments: McpPromptArgument[];
+}
+
+/** Content returned from reading a resource. Exactly one of text/blob is set. */
+export interface McpResourceContent {
+  readonly uri: string;
+  readonly mimeType: string | null;
+  readonly text: string | null;
+  readonly blob: string | null;
+}
+
+/** One rendered prompt message. */
+export interface McpPromptMessage {
+  readonly role: string;
+  readonly text: string | null;
+  readonly content: unknown;
+}
+
+/** A prompt rendered with arguments. */
+export interface McpPromptResult {
+  readonly description: string | null;
+  readonly messages: McpPromptMessage[];
+}
+
+/** Everything an MCP server offers, collected in one handshake. */
+export interface McpServerInventory {
+  readonly server: McpServerInfo;
+  readonly tools: { readonly name: string; readonly description: string | null }[];
+  readonly resources: McpResource[];
+  readonly resourceTemplates: McpResourceTemplate[];
+  readonly prompts: McpPrompt[];
+}
+
+/** Result of calling a tool with `tools/call`. */
+export interface McpToolCallResult {
+  readonly content: unknown[];
+  /** True when the tool ran but reported a failure. */
+  readonly isError: boolean;
+  /** All text content blocks joined with newlines. */
+  readonly text: string;
+  readonly structuredContent?: unknown;
+}
+
+/** External config file that ELVES can sync MCP servers with. */
+export type McpSyncTarget = "claude" | "claude-local" | "mcp-json" | "codex";
+
+/** How a server compares between ELVES and an external config. */
+export type McpSyncStatus = "elves_only" | "external_only" | "drift" | "in_sync";
+
+/** Normalized server definition used in sync diffs. */
+export interface McpServerSpec {
+  readonly transport: McpTransport;
+  readonly command: string;
+  readonly args: string[];
+  readonly env: Record<string, string>;
+  readonly url: string | null;
+  readonly headers: Record<string, string>;
+}
+
+/** One field that differs between ELVES and the external config. */
+export interface McpFieldChange {
+  readonly field: string;
+  readonly elves: unknown;
+  readonly external: unknown;
+}
+
+/** One server row in a sync preview. */
+export interface McpSyncEntry {
+  readonly name: string;
+  readonly status: McpSyncStatus;
+  readonly elves: McpServerSpec | null;
+  readonly external: McpServerSpec | null;
+  readonly changes: McpFieldChange[];
+}
+
+/** Diff between ELVES servers in one scope and an external config file. */
+export interface McpSyncPreview {
+  readonly target: McpSyncTarget;
+  readonly path: string;
+  readonly scope: string;
+  readonly entries: McpSyncEntry[];
+}
+
+/** Outcome of applying a sync. */
+export interface McpSyncResult {
+  readonly created: string[];
+  readonly updated: string[];
+  readonly skipped: string[];
+}
@@ -48,3 +48,19 @@ export interface ExtractionResult {
   readonly sessionSummary: string;
   readonly eventsProcessed: number;
 }
+
+/** Status of an agent's request to pin a memory. */
+export type PinRequestStatus = "pending" | "approved" | "dismissed";
+
+/** A request, made by an agent through the ELVES MCP server, for the user to pin a memory. */
+export interface MemoryPinRequest {
+  readonly id: number;
+  readonly memoryId: number;
+  readonly sessionId: string | null;
+  readonly reason: string | null;
+  readonly status: PinRequestStatus;
+  readonly createdAt: number;
+  readonly resolvedAt: number | null;
+  /** Content of the memory the request refers to. */
+  readonly content: string;
+}
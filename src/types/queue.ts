@@ -0,0 +1,50 @@
+/* Task queue types — tasks waiting for the dispatcher to start them within the concurrency caps. */
+
+/** Lifecycle of a queued task. Only "queued" tasks are listed in the queue. */
+export type QueuedTaskStatus = "queued" | "started" | "cancelled" | "failed";
+
+/** A task in the persistent task queue. */
+export interface QueuedTask {
+  readonly id: string;
+  readonly projectId: string;
+  readonly task: string;
+  readonly runtime: string;
+  /** Directory to run in, e.g. a worktree; null runs in the project root. */
+  readonly workingDir: string | null;
+  readonly worktreeSlug: string | null;
+  /** JSON-encoded spawn options. */
+  readonly options: string | null;
+  /** JSON-encoded team plan, for team tasks. */
+  readonly plan: string | null;
+  /** Starts in an interactive PTY rather than print mode. */
+  readonly pty: boolean;
+  readonly priority: number;
+  readonly position: number;
+  readonly status: QueuedTaskStatus;
+  /** Session the task started as, once started. */
+  readonly sessionId: string | null;
+  /** Why the task failed to start, or why a waiting task is held back (e.g. a spending cap). */
+  readonly error: string | null;
+  readonly createdAt: number;
+  readonly startedAt: number | null;
+}
+
+/** Payload of `queue:task_started`, emitted when the dispatcher starts a queued task. */
+export interface QueueTaskStartedPayload {
+  readonly queueId: string;
+  readonly sessionId: string;
+  readonly projectId: string;
+  readonly task: string;
+  readonly runtime: string;
+  /** JSON-encoded team plan, for team tasks. */
+  readonly plan: string | null;
+  /** PTY the task runs in, for tasks queued in PTY mode. */
+  readonly ptyId: string | null;
+}
+
+/** Payload of `queue:task_failed`, emitted when a queued task could not be started. */
+export interface QueueTaskFailedPayload {
+  readonly queueId: string;
+  readonly task: string;
+  readonly error: string;
+}
@@ -0,0 +1,13 @@
+/* Secrets store types — lock state and secret names reported by the Rust secrets commands. */
+
+/** Where the key that encrypts the secrets file comes from. */
+export type SecretsKeySource = "keyring" | "passphrase";
+
+/** Lock state and contents (names only) of the secrets store. Values never cross IPC. */
+export interface SecretsStatus {
+  readonly unlocked: boolean;
+  readonly keySource: SecretsKeySource;
+  /** False until the first secret (or passphrase) is saved. */
+  readonly initialized: boolean;
+  readonly names: string[];
+}
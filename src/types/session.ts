@@ -3,7 +3,10 @@
 import type { Runtime } from "./elf";
 
 /** Status of a task session */
-export type SessionStatus = "active" | "completed" | "failed" | "cancelled";
+export type SessionStatus = "active" | "completed" | "failed" | "interrupted" | "timed_out" | "cancelled";
+
+/** Why a "failed" session failed, as classified from its exit status and stderr. */
+export type FailureReason = "auth" | "rate_limit" | "budget_exceeded" | "crash" | "killed";
 
 /** Task complexity classification from the analyzer */
 export type TaskComplexity = "solo" | "team";
@@ -40,6 +43,8 @@ export interface TaskPlan {
   readonly taskGraph: readonly TaskNode[];
   readonly runtimeRecommendation: string;
   readonly estimatedDuration: string;
+  /** Template the plan was loaded from, recorded on the session for insights. */
+  readonly templateId?: string;
 }
 
 /** A single task execution session */
@@ -58,6 +63,103 @@ export interface Session {
   readonly summary: string | null;
   /** Claude Code session ID for --resume support (null if not a Claude Code session). */
   readonly claudeSessionId: string | null;
+  /** Pinned sessions keep their full event log regardless of retention. */
+  readonly pinned: boolean;
+  /** Unix seconds when retention last compacted this session's events, or null. */
+  readonly compactedAt: number | null;
+  /** Exit code of the agent process, once it has exited normally. */
+  readonly exitCode: number | null;
+  /** Set when status is "failed". */
+  readonly failureReason: FailureReason | null;
+  /** Current attempt number; above 1 once a failed run has been retried. */
+  readonly attempt: number;
+  /** Template the session's plan was loaded from, if any. */
+  readonly templateId?: string | null;
+}
+
+/** One attempt at running a session's task. */
+export interface SessionAttempt {
+  readonly sessionId: string;
+  readonly attempt: number;
+  readonly startedAt: number;
+  readonly endedAt: number | null;
+  readonly exitCode: number | null;
+  readonly failureReason: FailureReason | null;
+  /** Claude session ID this attempt resumed, or null if it restarted the task. */
+  readonly resumedFrom: string | null;
+}
+
+/** A session's token usage and cost for one model, and one elf in team runs. */
+export interface SessionUsage {
+  readonly sessionId: string;
+  /** Elf the usage is attributed to in team runs, null for solo runs. */
+  readonly elfId: string | null;
+  readonly model: string;
+  readonly inputTokens: number;
+  readonly outputTokens: number;
+  readonly cacheReadTokens: number;
+  readonly cacheWriteTokens: number;
+  readonly costUsd: number;
+}
+
+/** A Claude Code session found in a project's transcripts, possibly started outside ELVES. */
+export interface ExternalClaudeSession {
+  /** Claude's session ID, as used with `claude --resume`. */
+  readonly sessionId: string;
+  readonly firstPrompt: string | null;
+  readonly startedAt: number | null;
+  readonly endedAt: number | null;
+  readonly model: string | null;
+  readonly messageCount: number;
+  readonly inputTokens: number;
+  readonly outputTokens: number;
+  readonly cacheReadTokens: number;
+  readonly cacheWriteTokens: number;
+  readonly costUsd: number;
+  /** The ELVES session that ran or imported it, if any. */
+  readonly elvesSessionId: string | null;
+}
+
+/** A terminal recording (asciicast v2 file) of one of a session's PTY runs. */
+export interface SessionRecording {
+  readonly id: number;
+  readonly sessionId: string;
+  /** Elf the recorded PTY ran as in team runs, null for solo runs. */
+  readonly elfId: string | null;
+  /** Absolute path of the `.cast` file. */
+  readonly path: string;
+  readonly createdAt: number;
+}
+
+/** Query text and optional filters for full-text session search. */
+export interface SessionSearchQuery {
+  readonly query: string;
+  readonly projectId?: string | null;
+  readonly runtime?: Runtime | null;
+  readonly status?: SessionStatus | null;
+  /** Unix seconds; only sessions started at or after this time. */
+  readonly startedAfter?: number | null;
+  /** Unix seconds; only sessions started at or before this time. */
+  readonly startedBefore?: number | null;
+  readonly limit?: number | null;
+}
+
+/** A highlighted excerpt of one matching event. Matched terms are wrapped in `<mark>`. */
+export interface EventSnippet {
+  readonly eventId: number;
+  readonly snippet: string;
+}
+
+/** A session matching a full-text search. */
+export interface SessionSearchHit {
+  readonly session: Session;
+  /** Relevance, higher is better. */
+  readonly score: number;
+  /** Highlighted task or summary excerpt, when either matched. */
+  readonly snippet: string | null;
+  readonly eventSnippets: readonly EventSnippet[];
+  /** Matching event ids in chronological order. */
+  readonly matchingEventIds: readonly number[];
 }
 
 /**
@@ -86,3 +188,14 @@ export interface PlanTask {
   readonly dependsOn: readonly string[];
   readonly status: TaskNodeStatus;
 }
+
+/** A session left running when the app last exited, as offered for resume on startup. */
+export interface InterruptedSession {
+  readonly session: Session;
+  readonly projectName: string;
+  /** Directory the agent ran in: the session's worktree, or the project root. */
+  readonly workingDir: string;
+  readonly workspaceExists: boolean;
+  /** True when the session has a Claude session ID, ran on Claude Code, and its workspace exists. */
+  readonly resumable: boolean;
+}
@@ -0,0 +1,64 @@
+/* App-wide settings types — mirror the Rust AppSettings stored at ~/.elves/settings.json. */
+
+import type { BudgetLimits } from "@/types/budget";
+
+/** Event retention: compact old events of finished, unpinned sessions at startup. */
+export interface RetentionSettings {
+  readonly enabled: boolean;
+  /** How many days sessions keep their full event log. */
+  readonly fullEventDays: number;
+}
+
+/** Graceful stop: SIGINT, then SIGTERM after the grace period, then SIGKILL. */
+export interface StopSettings {
+  /** How long an interrupted agent gets to write its final result and exit. */
+  readonly gracePeriodSecs: number;
+  /** How long a terminated agent gets before it is killed. */
+  readonly terminateTimeoutSecs: number;
+}
+
+/** Task queue concurrency: queued tasks wait while this many sessions are active. */
+export interface QueueSettings {
+  readonly maxConcurrentSessions: number;
+}
+
+/** USD per million tokens of each kind. */
+export interface ModelPrice {
+  readonly input: number;
+  readonly output: number;
+  readonly cacheRead: number;
+  readonly cacheWrite: number;
+}
+
+/** Prices sessions are costed with. Models match the longest key their name starts with. */
+export interface PricingTable {
+  /** Prices by model name prefix, taking precedence over the built-in prices. */
+  readonly models: Readonly<Record<string, ModelPrice>>;
+  /** Price of models matching no entry. */
+  readonly fallback: ModelPrice;
+}
+
+/** Terminal recording: agent PTY runs are saved as asciicast files and embedded in replays. */
+export interface RecordingSettings {
+  readonly enabled: boolean;
+}
+
+/** Terminal scrollback: each PTY keeps its last `maxBytes` of output for reattaching views. */
+export interface ScrollbackSettings {
+  readonly maxBytes: number;
+}
+
+/** App-wide settings. */
+export interface AppSettings {
+  readonly retention: RetentionSettings;
+  readonly stop: StopSettings;
+  readonly queue: QueueSettings;
+  /** Caps across all projects; the session cap applies where a project sets none. */
+  readonly budget: BudgetLimits;
+  /** Per-model prices overriding the built-in ones. */
+  readonly pricing: PricingTable;
+  /** Whether agent terminals are recorded. */
+  readonly recording: RecordingSettings;
+  /** How much output each terminal keeps. */
+  readonly scrollback: ScrollbackSettings;
+}
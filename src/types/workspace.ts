@@ -1,5 +1,8 @@
 /* Workspace types — worktree-based workspace model for the ELVES redesign. */
 
+import type { BudgetLimits } from "@/types/budget";
+import type { FailureReason } from "@/types/session";
+
 /** A workspace represents a git worktree with an associated elf session. */
 export interface WorkspaceInfo {
   readonly slug: string;
@@ -38,6 +41,46 @@ export interface ProjectConfig {
   readonly defaultRuntime: string;
   readonly mcpServers: readonly McpServerEntry[];
   readonly memoryEnabled: boolean;
+  readonly mcpGateway: McpGatewayConfig;
+  readonly retry: RetryPolicy;
+  readonly watchdog: WatchdogLimits;
+  /** Most sessions of this project the task queue runs at once; null leaves only the app-wide cap. */
+  readonly maxConcurrentSessions: number | null;
+  /** Spending caps for this project's sessions, per day, and per month. */
+  readonly budget: BudgetLimits;
+}
+
+/** Automatic retry of failed print-mode runs; resumes the Claude conversation when possible. */
+export interface RetryPolicy {
+  readonly enabled: boolean;
+  /** Total attempts, including the first run. */
+  readonly maxAttempts: number;
+  readonly initialBackoffSecs: number;
+  readonly backoffMultiplier: number;
+  readonly maxBackoffSecs: number;
+  /** Failure classes worth retrying. */
+  readonly retryOn: readonly FailureReason[];
+}
+
+/** Session watchdog limits; a null limit is not enforced. Sessions past a limit are stopped and marked timed out. */
+export interface WatchdogLimits {
+  readonly enabled: boolean;
+  readonly maxWallClockSecs: number | null;
+  /** Longest a session may go without new events or terminal output. */
+  readonly maxIdleSecs: number | null;
+  /** Quiet time before a stall warning, ahead of `maxIdleSecs`. */
+  readonly stallWarningSecs: number | null;
+  readonly maxEvents: number | null;
+  readonly maxToolCalls: number | null;
+}
+
+/** MCP gateway settings: audit agent tool calls and apply allow/deny lists. */
+export interface McpGatewayConfig {
+  readonly enabled: boolean;
+  /** Tool names or `server/tool` patterns (`*` wildcard). Empty allows everything. */
+  readonly allow: readonly string[];
+  /** Patterns that are always refused, even if allowed. */
+  readonly deny: readonly string[];
 }
 
 export interface McpServerEntry {